@@ -0,0 +1,248 @@
+use crate::constants;
+use crate::database::{self, journal, AppState};
+use crate::models::GameArtwork;
+use crate::services::artwork;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+use tracing::info;
+
+pub(crate) fn artwork_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir não encontrado: {}", e))?;
+    Ok(dir.join(constants::ARTWORK_DIR))
+}
+
+fn map_artwork_row(row: &rusqlite::Row) -> rusqlite::Result<GameArtwork> {
+    Ok(GameArtwork {
+        game_id: row.get(0)?,
+        kind: row.get(1)?,
+        file_path: row.get(2)?,
+        thumb_path: row.get(3)?,
+        width: row.get(4)?,
+        height: row.get(5)?,
+        size_bytes: row.get::<_, i64>(6)? as u64,
+        created_at: row.get(7)?,
+    })
+}
+
+/// Artes de um jogo, ou de todos quando `game_id` é None
+pub(crate) fn fetch_artwork(
+    conn: &Connection,
+    game_id: Option<&str>,
+) -> Result<Vec<GameArtwork>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT game_id, kind, file_path, thumb_path, width, height, size_bytes, created_at
+             FROM game_artwork
+             WHERE ?1 IS NULL OR game_id = ?1
+             ORDER BY game_id, kind",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![game_id], map_artwork_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+/// Remove o registro da arte do jogo; `kind` None remove todas. Os arquivos ficam até a
+/// exclusão sair do diário (`journal::purge`), então desfazê-la traz a arte de volta.
+pub(crate) fn remove_artwork(
+    conn: &Connection,
+    game_id: &str,
+    kind: Option<&str>,
+) -> Result<i32, String> {
+    conn.execute(
+        "DELETE FROM game_artwork WHERE game_id = ?1 AND (?2 IS NULL OR kind = ?2)",
+        params![game_id, kind],
+    )
+    .map(|removed| removed as i32)
+    .map_err(|e| e.to_string())
+}
+
+/// Valida e grava a arte, substituindo a anterior do mesmo tipo
+pub(crate) fn save_artwork(
+    conn: &Connection,
+    dir: &Path,
+    game_id: &str,
+    kind: &str,
+    bytes: &[u8],
+) -> Result<GameArtwork, String> {
+    // Valida antes de apagar a arte atual, para não perder a anterior com um arquivo ruim
+    let validated = artwork::validate(kind, bytes)?;
+    remove_artwork(conn, game_id, Some(kind))?;
+
+    let stored = artwork::store_artwork(dir, game_id, kind, bytes, &validated)?;
+    let file_path = stored.file_path.to_string_lossy().to_string();
+    let thumb_path = stored
+        .thumb_path
+        .as_ref()
+        .map(|p| p.to_string_lossy().to_string());
+
+    conn.execute(
+        "INSERT OR REPLACE INTO game_artwork (game_id, kind, file_path, thumb_path, width, height, size_bytes, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, CURRENT_TIMESTAMP)",
+        params![
+            game_id,
+            kind,
+            file_path,
+            thumb_path,
+            stored.width,
+            stored.height,
+            stored.size_bytes as i64
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.query_row(
+        "SELECT game_id, kind, file_path, thumb_path, width, height, size_bytes, created_at
+         FROM game_artwork WHERE game_id = ?1 AND kind = ?2",
+        params![game_id, kind],
+        map_artwork_row,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Passa as artes do jogo duplicado para o principal, sem sobrescrever as que ele já tem
+pub(crate) fn move_artwork(conn: &Connection, from_id: &str, to_id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE OR IGNORE game_artwork SET game_id = ?2 WHERE game_id = ?1",
+        params![from_id, to_id],
+    )
+    .map_err(|e| e.to_string())?;
+    remove_artwork(conn, from_id, None)?;
+    Ok(())
+}
+
+/// Importa uma imagem local como capa, banner (hero) ou logo do jogo
+#[tauri::command]
+pub fn import_game_artwork(
+    app: AppHandle,
+    state: State<AppState>,
+    game_id: String,
+    kind: String,
+    file_path: String,
+) -> Result<GameArtwork, String> {
+    let size = fs::metadata(&file_path)
+        .map_err(|e| format!("Erro ao ler arquivo: {}", e))?
+        .len();
+    if size > constants::MAX_ARTWORK_BYTES {
+        return Err(format!(
+            "Imagem muito grande (máximo de {} MB)",
+            constants::MAX_ARTWORK_BYTES / 1024 / 1024
+        ));
+    }
+    let bytes = fs::read(&file_path).map_err(|e| format!("Erro ao ler arquivo: {}", e))?;
+
+    let dir = artwork_dir(&app)?;
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    let exists: Option<String> = conn
+        .query_row(
+            "SELECT id FROM games WHERE id = ?1",
+            params![game_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if exists.is_none() {
+        return Err("Jogo não encontrado".to_string());
+    }
+
+    // Um lote só: desfazer a importação traz de volta a arte substituída
+    let saved = database::in_transaction(&conn, |conn| {
+        journal::journaled(conn, "import_artwork", None, |conn| {
+            save_artwork(conn, &dir, &game_id, &kind, &bytes)
+        })
+    })?;
+    info!(
+        "Arte '{}' importada para {} ({}x{})",
+        kind, game_id, saved.width, saved.height
+    );
+    Ok(saved)
+}
+
+#[tauri::command]
+pub fn get_game_artwork(
+    state: State<AppState>,
+    game_id: String,
+) -> Result<Vec<GameArtwork>, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    fetch_artwork(&conn, Some(&game_id))
+}
+
+/// Remove a arte do tipo informado; a capa volta a ser a do cache/URL
+#[tauri::command]
+pub fn remove_game_artwork(
+    state: State<AppState>,
+    game_id: String,
+    kind: String,
+) -> Result<(), String> {
+    if !artwork::is_valid_kind(&kind) {
+        return Err(format!("Tipo de arte inválido: {}", kind));
+    }
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    remove_artwork(&conn, &game_id, Some(&kind))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{memory_db, temp_dir};
+    use image::{ImageFormat, Rgb, RgbImage};
+    use std::io::Cursor;
+
+    fn cover(shade: u8) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        RgbImage::from_pixel(300, 450, Rgb([shade, 0, 0]))
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    fn import(conn: &Connection, dir: &Path, bytes: &[u8]) -> GameArtwork {
+        database::in_transaction(conn, |conn| {
+            journal::journaled(conn, "import_artwork", None, |conn| {
+                save_artwork(conn, dir, "g", constants::ARTWORK_KIND_COVER, bytes)
+            })
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn replaced_artwork_comes_back_on_undo_until_the_trash_is_emptied() {
+        let dir = temp_dir("artwork_undo");
+        let conn = memory_db();
+        conn.execute("INSERT INTO games (id, name) VALUES ('g', 'Hades')", [])
+            .unwrap();
+
+        let first = import(&conn, &dir, &cover(10));
+        let second = import(&conn, &dir, &cover(200));
+        assert_ne!(first.file_path, second.file_path);
+        assert!(Path::new(&first.file_path).exists());
+
+        database::in_transaction(&conn, journal::undo_last).unwrap();
+        let current = fetch_artwork(&conn, Some("g")).unwrap();
+        assert_eq!(current.len(), 1);
+        assert_eq!(current[0].file_path, first.file_path);
+        assert!(Path::new(&first.file_path).exists());
+
+        // Excluída e fora do diário, a arte deixa o disco
+        remove_artwork(&conn, "g", None).unwrap();
+        assert!(Path::new(&first.file_path).exists());
+        journal::empty_trash(&conn).unwrap();
+        assert!(!Path::new(&first.file_path).exists());
+        assert!(!Path::new(first.thumb_path.as_deref().unwrap()).exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,9 +1,25 @@
-use crate::database::AppState;
-use crate::models::{Game, WishlistGame};
-use std::fs;
-use tauri::{AppHandle, State};
+use crate::commands::wishlist;
+use crate::constants;
+use crate::database::{self, journal, AppState};
+use crate::models::{BackupProgress, Game, WishlistGame};
+use crate::security::{PassphraseKey, PassphraseReader, PassphraseWriter};
+use crate::services::backup_archive::{
+    self, ArchiveInput, ArchiveWriter, BackupManifest, FileInfo, TableInfo,
+};
+use crate::services::covers;
+use crate::services::restore::{RestoreMode, RowAction, RowComparer, TableChanges};
+use crate::{security, storage};
+use rusqlite::{Connection, OpenFlags};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager, State};
+use zip::ZipArchive;
+use tracing::{info, warn};
 
 #[derive(serde::Serialize, serde::Deserialize)]
+/// Backup v1 (JSON com as tabelas principais); lido apenas na importação
 pub struct BackupData {
     pub version: u32,
     pub date: String,
@@ -11,70 +27,982 @@ pub struct BackupData {
     pub wishlist_game: Vec<WishlistGame>,
 }
 
+/// Tabelas que guardam caminhos de imagens e a pasta (no app_data_dir) onde os arquivos ficam
+const FILE_TABLES: &[(&str, &str)] = &[
+    ("cover_cache", constants::COVER_CACHE_DIR),
+    ("game_artwork", constants::ARTWORK_DIR),
+];
+const FILE_COLUMNS: &[&str] = &["file_path", "thumb_path"];
+
+/// Tabelas preenchidas por um backup v1
+const V1_TABLES: &[&str] = &["games", "wishlist", "game_ownership"];
+
+fn app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir não encontrado: {}", e))
+}
+
+fn file_dir_for(table: &str) -> Option<&'static str> {
+    FILE_TABLES
+        .iter()
+        .find(|(name, _)| *name == table)
+        .map(|(_, dir)| *dir)
+}
+
+/// Nome da entrada do arquivo para a imagem em `path` (só o nome do arquivo é aproveitado:
+/// o backup pode vir de outra máquina)
+fn file_entry(dir: &str, path: &str) -> Option<String> {
+    Path::new(path)
+        .file_name()
+        .map(|name| format!("{}/{}", dir, name.to_string_lossy()))
+}
+
+/// Linha que descreve esta máquina: não entra no backup nem é restaurada (ver `LOCAL_SETTINGS`)
+fn is_local_row(table: &str, columns: &[String], row: &[serde_json::Value]) -> bool {
+    table == "app_settings"
+        && columns
+            .iter()
+            .position(|c| c == "key")
+            .and_then(|index| row.get(index))
+            .and_then(|v| v.as_str())
+            .is_some_and(|key| constants::LOCAL_SETTINGS.contains(&key))
+}
+
+/// Condição SQL das linhas locais que a restauração pode substituir ou apagar
+fn library_rows_filter(table: &str) -> String {
+    if table != "app_settings" {
+        return "1".to_string();
+    }
+    let keys = constants::LOCAL_SETTINGS
+        .iter()
+        .map(|key| format!("'{}'", key))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("key NOT IN ({})", keys)
+}
+
+fn count_library_rows(conn: &Connection, table: &str) -> Result<usize, String> {
+    conn.query_row(
+        &format!("SELECT COUNT(*) FROM \"{}\" WHERE {}", table, library_rows_filter(table)),
+        [],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count as usize)
+    .map_err(|e| e.to_string())
+}
+
+/// Caminho temporário único ao lado de `dir`
+fn temp_path(dir: &Path, label: &str) -> PathBuf {
+    dir.join(format!(
+        "playlite-{}-{}-{}.tmp",
+        label,
+        std::process::id(),
+        chrono::Local::now().timestamp_millis()
+    ))
+}
+
+/// Emite o progresso a cada `BACKUP_PROGRESS_INTERVAL` linhas e ao fim de cada tabela
+struct Progress<'a> {
+    app: Option<&'a AppHandle>,
+    operation: &'static str,
+}
+
+impl Progress<'_> {
+    fn silent() -> Self {
+        Progress {
+            app: None,
+            operation: "",
+        }
+    }
+
+    // `is_multiple_of` só existe a partir do Rust 1.87
+    #[allow(clippy::manual_is_multiple_of)]
+    fn report(&self, table: &str, done: usize, total: usize) {
+        let Some(app) = self.app else {
+            return;
+        };
+        if done % constants::BACKUP_PROGRESS_INTERVAL != 0 && done != total {
+            return;
+        }
+        let event = BackupProgress {
+            operation: self.operation.to_string(),
+            table: table.to_string(),
+            done,
+            total,
+        };
+        if let Err(e) = app.emit(constants::EVENT_BACKUP_PROGRESS, &event) {
+            warn!("Falha ao emitir progresso do backup: {}", e);
+        }
+    }
+}
+
+/// Segunda conexão, só de leitura, ao arquivo do banco aberto: a exportação e a
+/// pré-visualização não seguram o Mutex enquanto percorrem a biblioteca
+pub(crate) fn open_reader(state: &AppState) -> Result<Connection, String> {
+    let path = {
+        let conn = state.db.lock().map_err(|_| "Falha no Mutex")?;
+        conn.path()
+            .filter(|p| !p.is_empty())
+            .map(String::from)
+            .ok_or_else(|| "Banco de dados sem arquivo".to_string())?
+    };
+    Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Linha de uma tabela de arquivos que pode entrar no backup: o arquivo principal precisa
+/// ter sido gravado no arquivo
+fn has_main_file(
+    dir: &str,
+    columns: &[String],
+    row: &[serde_json::Value],
+    files: &dyn Fn(&str) -> bool,
+) -> bool {
+    columns.iter().enumerate().all(|(index, column)| {
+        if column != "file_path" {
+            return true;
+        }
+        match row.get(index).and_then(|v| v.as_str()) {
+            Some(path) => file_entry(dir, path).is_some_and(|entry| files(&entry)),
+            None => true,
+        }
+    })
+}
+
+/// Grava as imagens referenciadas pela tabela, cada uma uma vez.
+/// Imagens que sumiram do disco ficam de fora (e a linha também, se for o arquivo principal).
+fn write_table_files<W: Write + Seek>(
+    conn: &Connection,
+    archive: &mut ArchiveWriter<W>,
+    table: &str,
+    dir: &str,
+    columns: &[String],
+) -> Result<(), String> {
+    let file_indexes: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| FILE_COLUMNS.contains(&c.as_str()))
+        .map(|(i, _)| i)
+        .collect();
+
+    database::for_each_row(conn, table, columns, |row| {
+        for &index in &file_indexes {
+            let Some(path) = row.get(index).and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let Some(entry_name) = file_entry(dir, path) else {
+                continue;
+            };
+            if archive.contains(&entry_name) {
+                continue;
+            }
+            match File::open(path) {
+                Ok(mut file) => archive.write_entry(&entry_name, false, |w| {
+                    io::copy(&mut file, w)
+                        .map(|_| ())
+                        .map_err(|e| format!("Erro ao copiar imagem: {}", e))
+                })?,
+                Err(e) => warn!("Imagem ignorada no backup ({}): {}", path, e),
+            }
+        }
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+/// Grava a tabela como uma lista JSON, linha a linha; retorna quantas linhas entraram
+fn write_table_rows<W: Write + Seek>(
+    conn: &Connection,
+    archive: &mut ArchiveWriter<W>,
+    table: &str,
+    columns: &[String],
+    progress: &Progress,
+) -> Result<usize, String> {
+    let total = database::count_rows(conn, table)?;
+    let dir = file_dir_for(table);
+    // As entradas de imagem já gravadas decidem quais linhas de arquivos entram
+    let files: HashSet<String> = match dir {
+        Some(dir) => archive
+            .entries()
+            .filter(|name| name.starts_with(&format!("{}/", dir)))
+            .cloned()
+            .collect(),
+        None => HashSet::new(),
+    };
+
+    let mut written = 0;
+    archive.write_entry(&backup_archive::table_entry(table), true, |w| {
+        w.write_all(b"[").map_err(|e| e.to_string())?;
+        let mut read = 0;
+        database::for_each_row(conn, table, columns, |row| {
+            read += 1;
+            progress.report(table, read, total);
+            if let Some(dir) = dir {
+                if !has_main_file(dir, columns, &row, &|entry| files.contains(entry)) {
+                    return Ok(());
+                }
+            }
+            if is_local_row(table, columns, &row) {
+                return Ok(());
+            }
+            if written > 0 {
+                w.write_all(b",").map_err(|e| e.to_string())?;
+            }
+            serde_json::to_writer(&mut *w, &row).map_err(|e| e.to_string())?;
+            written += 1;
+            Ok(())
+        })?;
+        w.write_all(b"]").map_err(|e| e.to_string())
+    })?;
+
+    Ok(written)
+}
+
+/// Grava o backup v2 de `conn` em `path`: tabelas da biblioteca (`BACKUP_TABLES`, ou só `only`),
+/// imagens e, se houver, as chaves de API. Tudo é lido em uma transação de leitura para o arquivo ficar consistente.
+fn write_backup<W: Write + Seek>(
+    conn: &Connection,
+    mut archive: ArchiveWriter<W>,
+    only: Option<&[&str]>,
+    secrets: Option<&BTreeMap<String, String>>,
+    progress: &Progress,
+) -> Result<(BackupManifest, W), String> {
+    conn.execute("BEGIN TRANSACTION", [])
+        .map_err(|e| e.to_string())?;
+
+    let result = (|| {
+        let existing = database::list_tables(conn)?;
+        for table in database::BACKUP_TABLES {
+            if !existing.iter().any(|name| name == table)
+                || only.is_some_and(|only| !only.contains(table))
+            {
+                continue;
+            }
+            let table = table.to_string();
+            let columns = database::table_columns(conn, &table)?;
+            if let Some(dir) = file_dir_for(&table) {
+                write_table_files(conn, &mut archive, &table, dir, &columns)?;
+            }
+            let rows = write_table_rows(conn, &mut archive, &table, &columns, progress)?;
+            archive.add_table(TableInfo {
+                name: table,
+                columns,
+                rows,
+            });
+        }
+
+        if let Some(secrets) = secrets {
+            archive.write_entry(constants::BACKUP_SECRETS_FILE, true, |w| {
+                serde_json::to_writer(w, secrets).map_err(|e| e.to_string())
+            })?;
+        }
+
+        archive.finish()
+    })();
+
+    conn.execute("COMMIT", []).map_err(|e| e.to_string())?;
+    result
+}
+
+/// Chaves de API salvas no armazenamento seguro (só as preenchidas)
+fn read_secrets(app: &AppHandle) -> Result<BTreeMap<String, String>, String> {
+    let mut secrets = BTreeMap::new();
+    for key in storage::list_supported_keys() {
+        let value = storage::get_secret(app, key)?;
+        if !value.is_empty() {
+            secrets.insert(key.to_string(), value);
+        }
+    }
+    Ok(secrets)
+}
+
+/// Grava as chaves de API do backup. Nos modos que preservam dados locais,
+/// só preenche as chaves que ainda não existem nesta máquina.
+fn restore_secrets(
+    app: &AppHandle,
+    secrets: &BTreeMap<String, String>,
+    mode: RestoreMode,
+) -> Result<usize, String> {
+    let supported = storage::list_supported_keys();
+    let mut restored = 0;
+
+    for (key, value) in secrets {
+        if !supported.contains(&key.as_str()) || value.trim().is_empty() {
+            continue;
+        }
+        let keep_local = matches!(mode, RestoreMode::Merge | RestoreMode::Additive)
+            && !storage::get_secret(app, key)?.is_empty();
+        if keep_local {
+            continue;
+        }
+        storage::set_secret(app, key, value.trim())?;
+        restored += 1;
+    }
+
+    Ok(restored)
+}
+
+/// Exporta o backup v2: tabelas da biblioteca, imagens em cache, artes e configurações.
+/// Com `passphrase`, o arquivo é encriptado com uma chave derivada da senha e pode levar
+/// também as chaves de API (`include_secrets`); sem senha, elas nunca entram no arquivo.
+/// As linhas vão direto do banco para o arquivo, sem carregar a biblioteca em memória;
+/// o progresso é emitido no evento `backup-progress`.
 #[tauri::command]
 pub async fn export_database(
-    _app: AppHandle,
+    app: AppHandle,
     state: State<'_, AppState>,
     file_path: String,
+    passphrase: Option<String>,
+    include_secrets: Option<bool>,
 ) -> Result<(), String> {
-    // Buscar dados em um único lock
-    let (games, wishlist_game) = {
-        let conn = state.db.lock().map_err(|_| "Falha no Mutex")?;
+    let passphrase = passphrase.filter(|p| !p.is_empty());
+    let include_secrets = include_secrets.unwrap_or(false);
 
-        // Inicia transação READ para consistência
-        conn.execute("BEGIN TRANSACTION", [])
-            .map_err(|e| e.to_string())?;
+    if let Some(p) = &passphrase {
+        if p.chars().count() < constants::MIN_BACKUP_PASSPHRASE_LEN {
+            return Err(format!(
+                "A senha do backup deve ter pelo menos {} caracteres",
+                constants::MIN_BACKUP_PASSPHRASE_LEN
+            ));
+        }
+    } else if include_secrets {
+        return Err(
+            "As chaves de API só podem ser incluídas em backups protegidos por senha".to_string(),
+        );
+    }
 
-        let games = fetch_games(&conn)?;
-        let wishlist_game = fetch_wishlist(&conn)?;
+    let secrets = if include_secrets {
+        Some(read_secrets(&app)?)
+    } else {
+        None
+    };
 
-        conn.execute("COMMIT", []).map_err(|e| e.to_string())?;
+    let conn = open_reader(&state)?;
+    let target = PathBuf::from(&file_path);
+    // O arquivo de destino só é substituído quando a exportação termina sem erros
+    let partial = PathBuf::from(format!("{}.partial", file_path));
+    let manifest = BackupManifest::new(&app.package_info().version.to_string());
+    let progress = Progress {
+        app: Some(&app),
+        operation: "export",
+    };
 
-        (games, wishlist_game)
-    }; // Lock liberado aqui
+    let result = match &passphrase {
+        None => ArchiveWriter::create(&partial, manifest).and_then(|archive| {
+            write_backup(&conn, archive, None, secrets.as_ref(), &progress).map(|(manifest, _)| manifest)
+        }),
+        // O zip passa direto pela criptografia: nada do conteúdo vai em claro para o disco
+        Some(p) => encrypted_file(&partial, p).and_then(|encryptor| {
+            let archive = ArchiveWriter::streaming(encryptor, manifest);
+            let (manifest, tail) = write_backup(&conn, archive, None, secrets.as_ref(), &progress)?;
+            tail.into_inner()
+                .map_err(|e| format!("Erro ao salvar arquivo: {}", e))?
+                .finish()?;
+            Ok(manifest)
+        }),
+    };
 
-    let backup = BackupData {
-        version: 1,
-        date: chrono::Local::now().to_rfc3339(),
-        games,
-        wishlist_game,
+    let manifest = match result {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
     };
+    fs::rename(&partial, &target).map_err(|e| format!("Erro ao salvar arquivo: {}", e))?;
 
-    let json = serde_json::to_string_pretty(&backup).map_err(|e| e.to_string())?;
-    fs::write(file_path, json).map_err(|e| format!("Erro ao salvar arquivo: {}", e))?;
+    info!(
+        "Backup exportado: {} tabelas, {} entradas",
+        manifest.tables.len(),
+        manifest.files.len()
+    );
 
     Ok(())
 }
 
-#[tauri::command]
-pub async fn import_database(
-    state: State<'_, AppState>,
-    file_path: String,
-) -> Result<String, String> {
-    let content = fs::read_to_string(file_path).map_err(|e| e.to_string())?;
+fn encrypted_file(path: &Path, passphrase: &str) -> Result<PassphraseWriter<BufWriter<File>>, String> {
+    let file = File::create(path).map_err(|e| format!("Erro ao salvar arquivo: {}", e))?;
+    PassphraseWriter::new(BufWriter::new(file), passphrase)
+}
+
+/// Backup pronto para leitura como zip. Backups encriptados são decifrados bloco a bloco
+/// durante a leitura; backups v1 são convertidos em um arquivo temporário, apagado quando a
+/// fonte sai de escopo.
+struct BackupSource {
+    path: PathBuf,
+    temporary: bool,
+    /// Chave derivada da senha. Chaves de API só são aceitas de arquivos encriptados.
+    key: Option<PassphraseKey>,
+}
+
+impl BackupSource {
+    fn encrypted(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Abre um novo leitor do zip
+    fn open(&self) -> Result<ArchiveInput, String> {
+        let file = File::open(&self.path).map_err(|e| e.to_string())?;
+        Ok(match &self.key {
+            Some(key) => Box::new(PassphraseReader::new(BufReader::new(file), key)?),
+            None => Box::new(BufReader::new(file)),
+        })
+    }
+}
+
+impl Drop for BackupSource {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// O que cada modo de restauração faria com o backup
+#[derive(serde::Serialize)]
+pub struct RestorePreview {
+    pub version: u32,
+    pub created_at: String,
+    /// Nomes das chaves de API incluídas no backup
+    pub secrets: Vec<String>,
+    pub modes: Vec<ModePreview>,
+}
+
+#[derive(serde::Serialize)]
+pub struct ModePreview {
+    pub mode: RestoreMode,
+    pub added: usize,
+    pub updated: usize,
+    pub deleted: usize,
+    pub tables: Vec<TableChanges>,
+}
+
+fn open_source(
+    data_dir: &Path,
+    file_path: &str,
+    passphrase: Option<&str>,
+) -> Result<BackupSource, String> {
+    let mut magic = [0u8; 8];
+    let read = File::open(file_path)
+        .and_then(|mut f| f.read(&mut magic))
+        .map_err(|e| e.to_string())?;
+    let magic = &magic[..read];
+
+    if security::is_passphrase_encrypted(magic) {
+        let passphrase = passphrase
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| "Backup protegido por senha: informe a senha".to_string())?;
+
+        let mut file = File::open(file_path).map_err(|e| e.to_string())?;
+        let source = BackupSource {
+            path: PathBuf::from(file_path),
+            temporary: false,
+            key: Some(PassphraseKey::read(&mut file, passphrase)?),
+        };
+
+        // Confere todos os blocos antes de ler qualquer coisa do arquivo
+        let mut reader = source.open()?;
+        io::copy(&mut reader, &mut io::sink()).map_err(|e| e.to_string())?;
+
+        let mut magic = [0u8; 4];
+        reader.seek(io::SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+        let read = reader.read(&mut magic).map_err(|e| e.to_string())?;
+        if !backup_archive::is_archive(&magic[..read]) {
+            return Err("Arquivo de backup inválido: conteúdo encriptado desconhecido".to_string());
+        }
+        return Ok(source);
+    }
+
+    if backup_archive::is_archive(magic) {
+        return Ok(BackupSource {
+            path: PathBuf::from(file_path),
+            temporary: false,
+            key: None,
+        });
+    }
+
+    // Exceção ao streaming: backups v1 são um único JSON que as versões antigas montavam
+    // inteiro em memória, então cabem nela. São lidos de uma vez e convertidos para v2.
+    let content = fs::read(file_path).map_err(|e| e.to_string())?;
     let backup: BackupData =
-        serde_json::from_str(&content).map_err(|_| "Arquivo de backup inválido".to_string())?;
+        serde_json::from_slice(&content).map_err(|_| "Arquivo de backup inválido".to_string())?;
 
     // Validação de versão
     if backup.version != 1 {
         return Err(format!("Versão de backup incompatível: {}", backup.version));
     }
 
-    let conn = state.db.lock().map_err(|_| "Falha no Mutex")?;
+    stage_v1(data_dir, &backup)
+}
+
+/// Converte o backup v1 carregando-o em um banco temporário em memória com o esquema atual
+/// e exportando-o no formato v2
+fn stage_v1(data_dir: &Path, backup: &BackupData) -> Result<BackupSource, String> {
+    let staging = Connection::open_in_memory().map_err(|e| e.to_string())?;
+    database::create_schema(&staging)?;
 
-    // Transação única para todas as operações
-    conn.execute("BEGIN IMMEDIATE TRANSACTION", [])
+    let source = BackupSource {
+        path: temp_path(data_dir, "v1"),
+        temporary: true,
+        key: None,
+    };
+
+    let mut manifest = BackupManifest::new("");
+    manifest.version = backup.version;
+    manifest.created_at = backup.date.clone();
+
+    // Só as tabelas que o v1 carrega: as demais não podem ser apagadas no modo replace
+    database::in_transaction(&staging, |conn| import_v1(conn, backup))?;
+    let archive = ArchiveWriter::create(&source.path, manifest)?;
+    write_backup(&staging, archive, Some(V1_TABLES), None, &Progress::silent())?;
+
+    Ok(source)
+}
+
+/// Chaves de API do arquivo, lidas só de backups encriptados
+fn read_backup_secrets<R: Read + Seek>(
+    source: &BackupSource,
+    manifest: &BackupManifest,
+    zip: &mut ZipArchive<R>,
+) -> Result<BTreeMap<String, String>, String> {
+    if !manifest.files.contains_key(constants::BACKUP_SECRETS_FILE) {
+        return Ok(BTreeMap::new());
+    }
+    if !source.encrypted() {
+        warn!("Chaves de API ignoradas: backup sem senha");
+        return Ok(BTreeMap::new());
+    }
+
+    match backup_archive::read_entry(zip, constants::BACKUP_SECRETS_FILE)? {
+        Some(bytes) => serde_json::from_slice(&bytes)
+            .map_err(|e| format!("Arquivo de backup inválido: chaves ilegíveis ({})", e)),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+/// Chave que identifica a linha entre bancos diferentes
+fn row_key_columns(conn: &Connection, table: &str) -> Result<Vec<String>, String> {
+    // O ID autoincremento das cópias muda de um banco para outro
+    if table == "game_ownership" {
+        return Ok(["game_id", "platform", "external_id"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect());
+    }
+
+    let keys = database::primary_key_columns(conn, table)?;
+    if keys.is_empty() {
+        database::table_columns(conn, table)
+    } else {
+        Ok(keys)
+    }
+}
+
+/// Compara as linhas de uma tabela do backup com as locais e conta o que cada modo faria
+fn scan_table<R: Read + Seek>(
+    conn: &Connection,
+    zip: &mut ZipArchive<R>,
+    info: &TableInfo,
+    modes: &[RestoreMode],
+    progress: &Progress,
+) -> Result<Vec<TableChanges>, String> {
+    let local_columns = database::table_columns(conn, &info.name)?;
+    let keys = row_key_columns(conn, &info.name)?;
+    let comparer = RowComparer::new(&local_columns, &info.columns, FILE_COLUMNS);
+    let mut lookup =
+        database::RowLookup::new(conn, &info.name, &local_columns, &keys, &info.columns)?;
+    let mut changes: Vec<TableChanges> = modes.iter().map(|_| TableChanges::new(&info.name)).collect();
+
+    let entry = zip
+        .by_name(&backup_archive::table_entry(&info.name))
+        .map_err(|e| e.to_string())?;
+    let mut done = 0;
+    backup_archive::for_each_row(entry, |row| {
+        done += 1;
+        progress.report(&info.name, done, info.rows);
+        if is_local_row(&info.name, &info.columns, &row) {
+            return Ok(());
+        }
+        let local = lookup.find(&row)?;
+        let state = comparer.compare(&row, local.as_deref());
+        for (mode, changes) in modes.iter().zip(changes.iter_mut()) {
+            changes.record(*mode, state);
+        }
+        Ok(())
+    })?;
+
+    let local_rows = count_library_rows(conn, &info.name)?;
+    for (mode, changes) in modes.iter().zip(changes.iter_mut()) {
+        changes.finish(*mode, local_rows);
+    }
+    Ok(changes)
+}
+
+/// Regrava as imagens da linha na pasta de dados e aponta a linha para os novos caminhos.
+/// None quando o arquivo principal não está no backup (a linha é descartada).
+fn restore_row_files<R: Read + Seek>(
+    target_dir: &Path,
+    dir: &str,
+    columns: &[String],
+    mut row: Vec<serde_json::Value>,
+    files: &BTreeMap<String, FileInfo>,
+    images: &mut ZipArchive<R>,
+) -> Result<Option<Vec<serde_json::Value>>, String> {
+    for (index, column) in columns.iter().enumerate() {
+        if !FILE_COLUMNS.contains(&column.as_str()) {
+            continue;
+        }
+        let Some(path) = row.get(index).and_then(|v| v.as_str()) else {
+            continue;
+        };
+        // Só entradas conferidas pelo manifesto são copiadas
+        let entry_name = file_entry(dir, path).filter(|name| files.contains_key(name));
+
+        match entry_name {
+            Some(entry_name) => {
+                let file_name = Path::new(&entry_name).file_name().unwrap_or_default();
+                let target = target_dir.join(file_name);
+                let mut entry = images.by_name(&entry_name).map_err(|e| e.to_string())?;
+                let mut file = File::create(&target)
+                    .map_err(|e| format!("Erro ao restaurar imagem: {}", e))?;
+                io::copy(&mut entry, &mut file)
+                    .map_err(|e| format!("Erro ao restaurar imagem: {}", e))?;
+                row[index] = target.to_string_lossy().to_string().into();
+            }
+            None if column == "file_path" => return Ok(None),
+            None => row[index] = serde_json::Value::Null,
+        }
+    }
+    Ok(Some(row))
+}
+
+/// Arquivo aberto para restauração: um leitor para as tabelas e outro para copiar as
+/// imagens enquanto a tabela é lida
+struct BackupReader {
+    manifest: BackupManifest,
+    tables: ZipArchive<ArchiveInput>,
+    images: ZipArchive<ArchiveInput>,
+}
+
+/// Aplica uma tabela do backup no modo escolhido, lendo as linhas em fluxo
+fn restore_table(
+    conn: &Connection,
+    data_dir: &Path,
+    reader: &mut BackupReader,
+    info: &TableInfo,
+    mode: RestoreMode,
+    progress: &Progress,
+) -> Result<TableChanges, String> {
+    let BackupReader {
+        manifest,
+        tables: zip,
+        images,
+    } = reader;
+
+    // No modo replace a contagem precisa ser feita antes de apagar a tabela
+    let mut changes = if mode == RestoreMode::Replace {
+        let changes = scan_table(conn, zip, info, &[mode], &Progress::silent())?
+            .pop()
+            .unwrap_or_default();
+        conn.execute(
+            &format!("DELETE FROM \"{}\" WHERE {}", info.name, library_rows_filter(&info.name)),
+            [],
+        )
         .map_err(|e| e.to_string())?;
+        changes
+    } else {
+        TableChanges::new(&info.name)
+    };
 
+    let file_dir = file_dir_for(&info.name);
+    let target_dir = file_dir.map(|dir| data_dir.join(dir));
+    if let Some(target_dir) = &target_dir {
+        fs::create_dir_all(target_dir).map_err(|e| format!("Erro ao criar pasta: {}", e))?;
+    }
+
+    let local_columns = database::table_columns(conn, &info.name)?;
+    let keys = row_key_columns(conn, &info.name)?;
+    let comparer = RowComparer::new(&local_columns, &info.columns, FILE_COLUMNS);
+    let mut lookup =
+        database::RowLookup::new(conn, &info.name, &local_columns, &keys, &info.columns)?;
+    let mut writer = database::RowWriter::new(conn, &info.name, &info.columns)?;
+
+    let entry = zip
+        .by_name(&backup_archive::table_entry(&info.name))
+        .map_err(|e| e.to_string())?;
+    let mut done = 0;
+    backup_archive::for_each_row(entry, |row| {
+        done += 1;
+        progress.report(&info.name, done, info.rows);
+        if is_local_row(&info.name, &info.columns, &row) {
+            return Ok(());
+        }
+
+        let action = if mode == RestoreMode::Replace {
+            RowAction::Replace
+        } else {
+            let local = lookup.find(&row)?;
+            changes.record(mode, comparer.compare(&row, local.as_deref()))
+        };
+        if action == RowAction::Keep {
+            return Ok(());
+        }
+
+        let row = match (file_dir, &target_dir) {
+            (Some(dir), Some(target_dir)) => {
+                match restore_row_files(target_dir, dir, &info.columns, row, &manifest.files, images)?
+                {
+                    Some(row) => row,
+                    None => return Ok(()),
+                }
+            }
+            _ => row,
+        };
+        writer.write(&row, action == RowAction::Replace)
+    })?;
+
+    Ok(changes)
+}
+
+/// Tabelas da biblioteca presentes no backup que existem nesta versão do aplicativo.
+/// Estado de máquina gravado por versões antigas (instalações, mesclagens...) é ignorado.
+fn restorable_tables(conn: &Connection, manifest: &BackupManifest) -> Result<Vec<TableInfo>, String> {
+    let existing = database::list_tables(conn)?;
+    Ok(manifest
+        .tables
+        .iter()
+        .filter(|info| {
+            let known = existing.contains(&info.name)
+                && database::BACKUP_TABLES.contains(&info.name.as_str());
+            if !known {
+                warn!("Tabela '{}' do backup ignorada", info.name);
+            }
+            known
+        })
+        .cloned()
+        .collect())
+}
+
+/// Aplica as tabelas do backup em uma transação, como um lote do diário
+fn restore_backup(
+    conn: &Connection,
+    data_dir: &Path,
+    reader: &mut BackupReader,
+    mode: RestoreMode,
+    description: Option<&str>,
+    progress: &Progress,
+) -> Result<Vec<TableChanges>, String> {
+    let mut orphan_covers = Vec::new();
+    let changes = database::in_transaction(conn, |conn| {
+        journal::journaled(conn, "restore", description, |conn| {
+            let mut changes = Vec::new();
+            for info in restorable_tables(conn, &reader.manifest)? {
+                changes.push(restore_table(conn, data_dir, reader, &info, mode, progress)?);
+            }
+            if mode == RestoreMode::Replace {
+                orphan_covers = remove_orphan_rows(conn, &mut changes)?;
+            }
+            database::backfill_ownership(conn).map_err(|e| e.to_string())?;
+            Ok(changes)
+        })
+    })?;
+
+    // Capas em cache são descartáveis: os arquivos saem só depois do commit
+    covers::remove_files(&orphan_covers);
+    Ok(changes)
+}
+
+/// Tabelas com uma linha por jogo (`game_id`), que perdem o sentido quando o jogo não existe
+const GAME_ROW_TABLES: &[&str] = &[
+    "game_ownership",
+    "game_external_ids",
+    "game_installs",
+    "game_metadata",
+    "metadata_sources",
+    "metadata_misses",
+    "game_artwork",
+    "cover_cache",
+];
+
+/// No modo replace, apaga o que ficou apontando para jogos que não estão no backup (tabelas
+/// ausentes em backups antigos não são apagadas) e soma as linhas em `changes`.
+/// Retorna os arquivos das capas em cache dessas linhas.
+fn remove_orphan_rows(
+    conn: &Connection,
+    changes: &mut Vec<TableChanges>,
+) -> Result<Vec<Option<String>>, String> {
+    const ORPHANS: &str = "game_id NOT IN (SELECT id FROM games)";
+
+    let mut stmt = conn
+        .prepare(&format!("SELECT file_path, thumb_path FROM cover_cache WHERE {ORPHANS}"))
+        .map_err(|e| e.to_string())?;
+    let cover_files = stmt
+        .query_map([], |row| Ok([row.get::<_, Option<String>>(0)?, row.get(1)?]))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .flatten()
+        .collect();
+
+    for table in GAME_ROW_TABLES {
+        let removed = conn
+            .execute(&format!("DELETE FROM \"{table}\" WHERE {ORPHANS}"), [])
+            .map_err(|e| e.to_string())?;
+        if removed == 0 {
+            continue;
+        }
+        match changes.iter_mut().find(|t| t.table == *table) {
+            Some(table_changes) => table_changes.deleted += removed,
+            None => changes.push(TableChanges {
+                deleted: removed,
+                ..TableChanges::new(table)
+            }),
+        }
+    }
+
+    Ok(cover_files)
+}
+
+/// Mostra o que cada modo de restauração adicionaria, atualizaria e apagaria, sem gravar nada
+#[tauri::command]
+pub async fn preview_import(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    file_path: String,
+    passphrase: Option<String>,
+) -> Result<RestorePreview, String> {
+    let source = open_source(&app_data_dir(&app)?, &file_path, passphrase.as_deref())?;
+    let (manifest, mut zip) = backup_archive::open_archive(source.open()?)?;
+    let secrets = read_backup_secrets(&source, &manifest, &mut zip)?;
+
+    let conn = open_reader(&state)?;
+    let progress = Progress {
+        app: Some(&app),
+        operation: "preview",
+    };
+
+    let mut tables: Vec<Vec<TableChanges>> = RestoreMode::ALL.iter().map(|_| Vec::new()).collect();
+    conn.execute("BEGIN TRANSACTION", [])
+        .map_err(|e| e.to_string())?;
+    let scanned = (|| {
+        for info in restorable_tables(&conn, &manifest)? {
+            let changes = scan_table(&conn, &mut zip, &info, &RestoreMode::ALL, &progress)?;
+            for (mode_tables, changes) in tables.iter_mut().zip(changes) {
+                mode_tables.push(changes);
+            }
+        }
+        Ok::<(), String>(())
+    })();
+    conn.execute("COMMIT", []).map_err(|e| e.to_string())?;
+    scanned?;
+
+    let modes = RestoreMode::ALL
+        .iter()
+        .zip(tables)
+        .map(|(mode, tables)| ModePreview {
+            mode: *mode,
+            added: tables.iter().map(|t| t.added).sum(),
+            updated: tables.iter().map(|t| t.updated).sum(),
+            deleted: tables.iter().map(|t| t.deleted).sum(),
+            tables,
+        })
+        .collect();
+
+    Ok(RestorePreview {
+        version: manifest.version,
+        created_at: manifest.created_at,
+        secrets: secrets.into_keys().collect(),
+        modes,
+    })
+}
+
+/// Restaura um backup v2 (zip) ou v1 (JSON) no modo escolhido. Sem `mode`, grava todas as
+/// linhas do backup por cima das locais (modo "overwrite", o comportamento original).
+/// As tabelas são lidas em fluxo do arquivo; o progresso é emitido no evento `backup-progress`.
+#[tauri::command]
+pub async fn import_database(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    file_path: String,
+    mode: Option<String>,
+    passphrase: Option<String>,
+) -> Result<String, String> {
+    let mode = match mode.as_deref() {
+        Some(value) => RestoreMode::parse(value)?,
+        None => RestoreMode::Overwrite,
+    };
+
+    let data_dir = app_data_dir(&app)?;
+    let source = open_source(&data_dir, &file_path, passphrase.as_deref())?;
+    let (manifest, mut zip) = backup_archive::open_archive(source.open()?)?;
+    let secrets = read_backup_secrets(&source, &manifest, &mut zip)?;
+    let mut reader = BackupReader {
+        manifest,
+        tables: zip,
+        images: backup_archive::reopen_archive(source.open()?)?,
+    };
+    let progress = Progress {
+        app: Some(&app),
+        operation: "import",
+    };
+
+    // A restauração inteira vira um lote do diário: `undo_last` volta a biblioteca ao estado anterior
+    let description = Path::new(&file_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string());
+
+    let changes = {
+        let conn = state.db.lock().map_err(|_| "Falha no Mutex")?;
+        restore_backup(&conn, &data_dir, &mut reader, mode, description.as_deref(), &progress)?
+    };
+
+    // As chaves ficam no banco de segredos, fora da transação da biblioteca
+    let restored_secrets = restore_secrets(&app, &secrets, mode)?;
+    if restored_secrets > 0 {
+        info!("{} chaves de API restauradas do backup", restored_secrets);
+    }
+
+    info!(
+        "Backup restaurado ({:?}): {} adicionadas, {} atualizadas, {} removidas",
+        mode,
+        changes.iter().map(|t| t.added).sum::<usize>(),
+        changes.iter().map(|t| t.updated).sum::<usize>(),
+        changes.iter().map(|t| t.deleted).sum::<usize>()
+    );
+
+    let counts = |table: &str| {
+        changes
+            .iter()
+            .find(|t| t.table == table)
+            .map(|t| (t.added, t.updated, t.deleted))
+            .unwrap_or_default()
+    };
+    let (games_added, games_updated, games_deleted) = counts("games");
+    let (wishlist_added, wishlist_updated, wishlist_deleted) = counts("wishlist");
+
+    Ok(format!(
+        "Backup restaurado! Jogos: {} novos, {} atualizados, {} removidos. Lista de desejos: {} novos, {} atualizados, {} removidos.",
+        games_added, games_updated, games_deleted, wishlist_added, wishlist_updated, wishlist_deleted
+    ))
+}
+
+/// Grava os modelos de um backup v1 nas tabelas atuais (no banco temporário da conversão)
+fn import_v1(conn: &Connection, backup: &BackupData) -> Result<(), String> {
     // Usa prepared statements para melhor performance
     let mut game_stmt = conn.prepare(
-        "INSERT OR REPLACE INTO games (id, name, genre, platform, cover_url, playtime, rating, favorite)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
+        "INSERT OR REPLACE INTO games (id, name, genre, platform, cover_url, playtime, rating, favorite, last_played, app_type, parent_game_id, rawg_id, parent_app_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)"
     ).map_err(|e| e.to_string())?;
 
     let mut wishlist_stmt = conn.prepare(
-        "INSERT OR REPLACE INTO wishlist (id, name, cover_url, store_url, current_price, lowest_price, on_sale, localized_price, localized_currency, steam_app_id, added_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"
+        "INSERT OR REPLACE INTO wishlist (id, name, cover_url, store_url, current_price, lowest_price, on_sale, localized_price, localized_currency, steam_app_id, added_at, archived_at, purchased_game_id, source, steam_priority, priority, notes, tags, sort_position, discount_percent, release_date, release_date_text, coming_soon, app_type, parent_app_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25)"
     ).map_err(|e| e.to_string())?;
 
     for game in &backup.games {
@@ -87,7 +1015,12 @@ pub async fn import_database(
                 game.cover_url,
                 game.playtime,
                 game.rating,
-                game.favorite
+                game.favorite,
+                game.last_played,
+                game.app_type,
+                game.parent_game_id,
+                game.rawg_id,
+                game.parent_app_id
             ])
             .map_err(|e| e.to_string())?;
     }
@@ -105,68 +1038,359 @@ pub async fn import_database(
                 item.localized_price,
                 item.localized_currency,
                 item.steam_app_id,
-                item.added_at
+                item.added_at,
+                item.archived_at,
+                item.purchased_game_id,
+                item.source,
+                item.steam_priority,
+                item.priority,
+                item.notes,
+                wishlist::join_tags(&item.tags),
+                item.sort_position,
+                item.discount_percent,
+                item.release_date,
+                item.release_date_text,
+                item.coming_soon,
+                item.app_type,
+                item.parent_app_id
             ])
             .map_err(|e| e.to_string())?;
     }
 
-    conn.execute("COMMIT", []).map_err(|e| e.to_string())?;
+    // Backups v1 não têm cópias: cada jogo recebe a da plataforma principal
+    database::backfill_ownership(conn).map_err(|e| e.to_string())?;
 
-    Ok(format!(
-        "Backup restaurado! {} jogos e {} itens da lista de desejos.",
-        backup.games.len(),
-        backup.wishlist_game.len()
-    ))
+    Ok(())
 }
 
-// Funções auxiliares
-fn fetch_games(conn: &rusqlite::Connection) -> Result<Vec<Game>, String> {
-    let mut stmt = conn
-        .prepare("SELECT * FROM games")
-        .map_err(|e| e.to_string())?;
-    let game_iter = stmt
-        .query_map([], |row| {
-            Ok(Game {
-                id: row.get("id")?,
-                name: row.get("name")?,
-                genre: row.get("genre")?,
-                platform: row.get("platform")?,
-                cover_url: row.get("cover_url")?,
-                playtime: row.get("playtime")?,
-                rating: row.get("rating").unwrap_or(None),
-                favorite: row.get("favorite").unwrap_or(false),
-            })
-        })
-        .map_err(|e| e.to_string())?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{memory_db, peak_allocation, temp_dir};
+    use std::io::Write;
 
-    game_iter
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())
-}
+    /// Biblioteca com dados do usuário e estado que pertence só a esta máquina
+    fn library() -> Connection {
+        let conn = memory_db();
+        conn.execute_batch(
+            "INSERT INTO games (id, name, platform, playtime) VALUES ('hades', 'Hades', 'Steam', 30);
+             INSERT INTO wishlist (id, name) VALUES ('celeste', 'Celeste');
+             INSERT INTO game_ownership (game_id, platform, external_id) VALUES ('hades', 'Steam', '1145360');
+             INSERT INTO game_installs (game_id, install_path) VALUES ('hades', 'C:/Games/Hades');
+             INSERT INTO game_merges (primary_id, snapshot) VALUES ('hades', '{}');
+             INSERT INTO metadata_misses (game_id) VALUES ('hades');
+             INSERT INTO app_settings (key, value) VALUES
+               ('base_currency', 'BRL'), ('sync_device_id', 'origem'), ('snapshot_interval_hours', '6');",
+        )
+        .unwrap();
+        conn
+    }
 
-fn fetch_wishlist(conn: &rusqlite::Connection) -> Result<Vec<WishlistGame>, String> {
-    let mut stmt = conn
-        .prepare("SELECT * FROM wishlist")
-        .map_err(|e| e.to_string())?;
-    let wishlist_iter = stmt
-        .query_map([], |row| {
-            Ok(WishlistGame {
-                id: row.get("id")?,
-                name: row.get("name")?,
-                cover_url: row.get("cover_url")?,
-                store_url: row.get("store_url")?,
-                current_price: row.get("current_price")?,
-                lowest_price: row.get("lowest_price")?,
-                on_sale: row.get("on_sale")?,
-                localized_price: row.get("localized_price")?,
-                localized_currency: row.get("localized_currency")?,
-                steam_app_id: row.get("steam_app_id")?,
-                added_at: row.get("added_at")?,
+    fn export(conn: &Connection, path: &Path) -> BackupManifest {
+        let archive = ArchiveWriter::create(path, BackupManifest::new("test")).unwrap();
+        write_backup(conn, archive, None, None, &Progress::silent()).unwrap().0
+    }
+
+    fn open(dir: &Path, path: &Path) -> Result<(BackupSource, BackupReader), String> {
+        let source = open_source(dir, &path.to_string_lossy(), None)?;
+        let (manifest, tables) = backup_archive::open_archive(source.open()?)?;
+        let images = backup_archive::reopen_archive(source.open()?)?;
+        Ok((
+            source,
+            BackupReader {
+                manifest,
+                tables,
+                images,
+            },
+        ))
+    }
+
+    fn open_error(dir: &Path, path: &Path) -> String {
+        match open(dir, path) {
+            Ok(_) => panic!("o backup deveria ser rejeitado"),
+            Err(e) => e,
+        }
+    }
+
+    fn setting(conn: &Connection, key: &str) -> Option<String> {
+        database::get_setting(conn, key).unwrap()
+    }
+
+    #[test]
+    fn v2_round_trip_keeps_machine_state_out() {
+        let dir = temp_dir("backup_v2");
+        let path = dir.join("biblioteca.zip");
+
+        let manifest = export(&library(), &path);
+        let tables: Vec<&str> = manifest.tables.iter().map(|t| t.name.as_str()).collect();
+        assert!(tables.contains(&"games") && tables.contains(&"app_settings"));
+        for local in ["game_installs", "game_merges", "metadata_misses", "sync_tombstones"] {
+            assert!(!tables.contains(&local), "{local} não deveria ir para o backup");
+        }
+        let settings = manifest.tables.iter().find(|t| t.name == "app_settings").unwrap();
+        assert_eq!(settings.rows, 1);
+
+        let target = memory_db();
+        target
+            .execute_batch(
+                "INSERT INTO games (id, name) VALUES ('outro', 'Outro');
+                 INSERT INTO app_settings (key, value) VALUES ('sync_device_id', 'destino'), ('snapshot_interval_hours', '48');",
+            )
+            .unwrap();
+        let (_source, mut reader) = open(&dir, &path).unwrap();
+        let changes =
+            restore_backup(&target, &dir, &mut reader, RestoreMode::Replace, None, &Progress::silent())
+                .unwrap();
+
+        let games = changes.iter().find(|t| t.table == "games").unwrap();
+        assert_eq!((games.added, games.deleted), (1, 1));
+        assert_eq!(database::count_rows(&target, "games").unwrap(), 1);
+        assert_eq!(database::count_rows(&target, "wishlist").unwrap(), 1);
+        assert_eq!(setting(&target, "base_currency").as_deref(), Some("BRL"));
+        assert_eq!(setting(&target, "sync_device_id").as_deref(), Some("destino"));
+        assert_eq!(setting(&target, "snapshot_interval_hours").as_deref(), Some("48"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn encrypted_backup_is_never_written_in_plain() {
+        let dir = temp_dir("backup_encrypted");
+        let data_dir = dir.join("dados");
+        fs::create_dir_all(&data_dir).unwrap();
+        let path = dir.join("protegido.zip");
+
+        // Linhas suficientes para vários blocos da criptografia e várias entradas no zip
+        let source = library();
+        source
+            .execute_batch(
+                "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < 2000)
+                 INSERT INTO games (id, name, platform) SELECT 'jogo-' || x, 'Jogo ' || x, 'Steam' FROM n;",
+            )
+            .unwrap();
+        let secrets = BTreeMap::from([("rawg_api_key".to_string(), "segredo-rawg".to_string())]);
+
+        let encryptor = encrypted_file(&path, "senha forte").unwrap();
+        let archive = ArchiveWriter::streaming(encryptor, BackupManifest::new("test"));
+        let (_, tail) =
+            write_backup(&source, archive, None, Some(&secrets), &Progress::silent()).unwrap();
+        tail.into_inner().unwrap().finish().unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        assert!(security::is_passphrase_encrypted(&bytes));
+        for plain in [&b"segredo-rawg"[..], b"Hades", constants::BACKUP_MANIFEST_FILE.as_bytes()] {
+            assert!(!bytes.windows(plain.len()).any(|w| w == plain));
+        }
+
+        let wrong = open_source(&data_dir, &path.to_string_lossy(), Some("errada"));
+        assert!(wrong.err().unwrap().contains("incorreta"));
+
+        let source = open_source(&data_dir, &path.to_string_lossy(), Some("senha forte")).unwrap();
+        // Nenhuma cópia decifrada vai para o disco
+        assert_eq!(fs::read_dir(&data_dir).unwrap().count(), 0);
+        assert_eq!(source.path, path);
+
+        let (manifest, mut tables) = backup_archive::open_archive(source.open().unwrap()).unwrap();
+        let restored_secrets = read_backup_secrets(&source, &manifest, &mut tables).unwrap();
+        assert_eq!(restored_secrets, secrets);
+
+        let mut reader = BackupReader {
+            manifest,
+            tables,
+            images: backup_archive::reopen_archive(source.open().unwrap()).unwrap(),
+        };
+        let target = memory_db();
+        restore_backup(&target, &data_dir, &mut reader, RestoreMode::Replace, None, &Progress::silent())
+            .unwrap();
+        assert_eq!(database::count_rows(&target, "games").unwrap(), 2001);
+
+        let mut tampered = bytes.clone();
+        let middle = tampered.len() / 2;
+        tampered[middle] ^= 0x01;
+        fs::write(&path, &tampered).unwrap();
+        let error = open_source(&data_dir, &path.to_string_lossy(), Some("senha forte"));
+        assert!(error.err().unwrap().contains("adulterado"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn restore_keeps_this_device_sync_identity() {
+        let dir = temp_dir("backup_sync");
+        let path = dir.join("outro.zip");
+        let source = library();
+        source
+            .execute_batch(
+                "INSERT INTO app_settings (key, value) VALUES ('sync_cursor', '{\"b\":3}');
+                 DELETE FROM wishlist;",
+            )
+            .unwrap();
+        assert_eq!(database::count_rows(&source, "sync_tombstones").unwrap(), 1);
+        export(&source, &path);
+
+        let target = memory_db();
+        target
+            .execute_batch(
+                "INSERT INTO app_settings (key, value) VALUES ('sync_device_id', 'destino'), ('sync_cursor', '{\"a\":1}');",
+            )
+            .unwrap();
+        for mode in [RestoreMode::Overwrite, RestoreMode::Replace] {
+            let (_source, mut reader) = open(&dir, &path).unwrap();
+            restore_backup(&target, &dir, &mut reader, mode, None, &Progress::silent()).unwrap();
+            assert_eq!(setting(&target, "sync_device_id").as_deref(), Some("destino"));
+            assert_eq!(setting(&target, "sync_cursor").as_deref(), Some("{\"a\":1}"));
+            // As lápides do aparelho de origem não chegam aqui
+            assert_eq!(database::count_rows(&target, "sync_tombstones").unwrap(), 0);
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn large_v2_backup_streams_rows() {
+        const ROWS: usize = 100_000;
+        // Bem abaixo dos ~26 MB de linhas em JSON: nenhuma tabela pode ficar inteira na memória
+        const LIMIT: usize = 8 * 1024 * 1024;
+
+        let dir = temp_dir("backup_large");
+        let path = dir.join("grande.zip");
+        let source = memory_db();
+        source
+            .execute_batch(&format!(
+                "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < {ROWS})
+                 INSERT INTO games (id, name, platform, playtime)
+                 SELECT 'jogo-' || x, 'Jogo ' || x || ' ' || hex(randomblob(100)), 'Steam', x FROM n;"
+            ))
+            .unwrap();
+
+        let (manifest, export_peak) = peak_allocation(|| export(&source, &path));
+        assert_eq!(manifest.tables.iter().find(|t| t.name == "games").unwrap().rows, ROWS);
+        assert!(export_peak < LIMIT, "exportação alocou {export_peak} bytes");
+
+        let target = memory_db();
+        let (_source, mut reader) = open(&dir, &path).unwrap();
+        let (changes, restore_peak) = peak_allocation(|| {
+            restore_backup(&target, &dir, &mut reader, RestoreMode::Replace, None, &Progress::silent())
+                .unwrap()
+        });
+        assert_eq!(changes.iter().find(|t| t.table == "games").unwrap().added, ROWS);
+        assert!(restore_peak < LIMIT, "restauração alocou {restore_peak} bytes");
+        assert_eq!(database::count_rows(&target, "games").unwrap(), ROWS);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn v1_json_is_converted_and_restored() {
+        let dir = temp_dir("backup_v1");
+        let path = dir.join("antigo.json");
+        let backup = serde_json::json!({
+            "version": 1,
+            "date": "2024-01-01T00:00:00",
+            "games": [{"id": "hades", "name": "Hades", "platform": "Steam", "playtime": 30,
+                       "rating": 5, "favorite": true}],
+            "wishlist_game": [{"id": "celeste", "name": "Celeste", "on_sale": false}]
+        });
+        fs::write(&path, backup.to_string()).unwrap();
+
+        let target = memory_db();
+        let (_source, mut reader) = open(&dir, &path).unwrap();
+        assert_eq!(reader.manifest.version, 1);
+        restore_backup(&target, &dir, &mut reader, RestoreMode::Overwrite, None, &Progress::silent())
+            .unwrap();
+
+        let (name, rating, favorite): (String, i32, bool) = target
+            .query_row("SELECT name, rating, favorite FROM games WHERE id = 'hades'", [], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
             })
-        })
-        .map_err(|e| e.to_string())?;
+            .unwrap();
+        assert_eq!((name.as_str(), rating, favorite), ("Hades", 5, true));
+        assert_eq!(database::count_rows(&target, "wishlist").unwrap(), 1);
+        // Backups v1 não tinham cópias: a plataforma principal vira uma
+        assert_eq!(database::count_rows(&target, "game_ownership").unwrap(), 1);
 
-    wishlist_iter
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())
+        // No modo replace, o que apontava para jogos fora do backup sai junto com eles
+        target
+            .execute_batch(
+                "INSERT INTO games (id, name) VALUES ('outro', 'Outro');
+                 INSERT INTO game_installs (game_id, install_path) VALUES ('outro', 'D:/Outro'), ('hades', 'D:/Hades');
+                 INSERT INTO game_external_ids (source, external_id, game_id) VALUES ('epic', 'x', 'outro');
+                 INSERT INTO cover_cache (game_id, status) VALUES ('outro', 'missing');",
+            )
+            .unwrap();
+        let (_source, mut reader) = open(&dir, &path).unwrap();
+        let changes =
+            restore_backup(&target, &dir, &mut reader, RestoreMode::Replace, None, &Progress::silent())
+                .unwrap();
+        let deleted = |table: &str| changes.iter().find(|t| t.table == table).map(|t| t.deleted);
+        assert_eq!(deleted("games"), Some(1));
+        assert_eq!(deleted("game_installs"), Some(1));
+        assert_eq!(deleted("game_external_ids"), Some(1));
+        assert_eq!(deleted("cover_cache"), Some(1));
+        let installs: Vec<String> = target
+            .prepare("SELECT game_id FROM game_installs")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(installs, vec!["hades"]);
+
+        fs::write(&path, r#"{"version": 2, "date": "", "games": [], "wishlist_game": []}"#).unwrap();
+        assert!(open_error(&dir, &path).contains("incompatível"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// Copia o zip trocando o conteúdo de uma entrada, sem atualizar o manifesto
+    fn tamper(from: &Path, to: &Path, name: &str, content: &[u8]) {
+        let mut original = ZipArchive::new(File::open(from).unwrap()).unwrap();
+        let mut copy = zip::ZipWriter::new(File::create(to).unwrap());
+        for index in 0..original.len() {
+            let mut entry = original.by_index(index).unwrap();
+            let entry_name = entry.name().to_string();
+            copy.start_file(&entry_name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            if entry_name == name {
+                copy.write_all(content).unwrap();
+            } else {
+                io::copy(&mut entry, &mut copy).unwrap();
+            }
+        }
+        copy.finish().unwrap();
+    }
+
+    #[test]
+    fn checksum_mismatch_is_rejected() {
+        let dir = temp_dir("backup_checksum");
+        let path = dir.join("biblioteca.zip");
+        let tampered = dir.join("adulterado.zip");
+        export(&library(), &path);
+
+        tamper(
+            &path,
+            &tampered,
+            &backup_archive::table_entry("games"),
+            br#"[["hades","Outro nome",null,null,null,0,null,0,0,null,null,null,null,null,null]]"#,
+        );
+
+        let error = open_error(&dir, &tampered);
+        assert!(error.contains("checksum"), "{error}");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn newer_schema_is_rejected() {
+        let dir = temp_dir("backup_schema");
+        let path = dir.join("futuro.zip");
+
+        let mut manifest = BackupManifest::new("99.0.0");
+        manifest.schema_version = constants::DB_SCHEMA_VERSION + 1;
+        let archive = ArchiveWriter::create(&path, manifest).unwrap();
+        write_backup(&library(), archive, None, None, &Progress::silent()).unwrap();
+
+        let error = open_error(&dir, &path);
+        assert!(error.contains("incompatível"), "{error}");
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,321 @@
+use crate::constants;
+use crate::database::{self, AppState};
+use crate::services::covers;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+#[derive(serde::Serialize)]
+pub struct CoverCacheSummary {
+    pub downloaded: i32,
+    /// Jogos sem nenhuma arte disponível (todas as URLs deram 404)
+    pub missing: i32,
+    pub failed: i32,
+    pub evicted: i32,
+    pub total_bytes: u64,
+    pub errors: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct CoverCacheStats {
+    pub cached: i32,
+    pub missing: i32,
+    pub total_bytes: u64,
+    pub max_bytes: u64,
+    pub directory: String,
+}
+
+fn cache_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir não encontrado: {}", e))?;
+    Ok(dir.join(constants::COVER_CACHE_DIR))
+}
+
+fn max_cache_bytes(conn: &Connection) -> Result<u64, String> {
+    let mb = database::get_setting(conn, constants::SETTING_COVER_CACHE_MAX_MB)?
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(constants::DEFAULT_COVER_CACHE_MAX_MB);
+    Ok(mb * 1024 * 1024)
+}
+
+fn total_cache_bytes(conn: &Connection) -> Result<u64, String> {
+    conn.query_row(
+        "SELECT IFNULL(SUM(size_bytes), 0) FROM cover_cache WHERE status = 'ok'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|total| total as u64)
+    .map_err(|e| e.to_string())
+}
+
+/// Remove a capa do jogo do cache (arquivos e registro)
+pub(crate) fn invalidate_cover(conn: &Connection, game_id: &str) -> Result<(), String> {
+    let paths: Option<(Option<String>, Option<String>)> = conn
+        .query_row(
+            "SELECT file_path, thumb_path FROM cover_cache WHERE game_id = ?1",
+            params![game_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    if let Some((file, thumb)) = paths {
+        covers::remove_files(&[file, thumb]);
+        conn.execute("DELETE FROM cover_cache WHERE game_id = ?1", params![game_id])
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Remove capas até o cache caber no limite: primeiro as de jogos excluídos, depois as de jogos
+/// não favoritos jogados há mais tempo, e por último as baixadas há mais tempo.
+fn evict_to_limit(conn: &Connection, max_bytes: u64) -> Result<i32, String> {
+    let mut total = total_cache_bytes(conn)?;
+    if total <= max_bytes {
+        return Ok(0);
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT c.game_id, c.file_path, c.thumb_path, c.size_bytes
+             FROM cover_cache c
+             LEFT JOIN games g ON g.id = c.game_id
+             WHERE c.status = 'ok'
+             ORDER BY g.id IS NOT NULL, IFNULL(g.favorite, 0), g.last_played IS NOT NULL,
+                      g.last_played, c.fetched_at",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let candidates = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut evicted = 0;
+    for (game_id, file, thumb, size) in candidates {
+        if total <= max_bytes {
+            break;
+        }
+        covers::remove_files(&[file, thumb]);
+        conn.execute("DELETE FROM cover_cache WHERE game_id = ?1", params![game_id])
+            .map_err(|e| e.to_string())?;
+        total = total.saturating_sub(size as u64);
+        evicted += 1;
+    }
+
+    Ok(evicted)
+}
+
+/// Baixa as capas que ainda não estão no cache (ou todas, com `force`)
+async fn download_covers(
+    app: &AppHandle,
+    state: &AppState,
+    game_ids: Option<Vec<String>>,
+    force: bool,
+) -> Result<CoverCacheSummary, String> {
+    let dir = cache_dir(app)?;
+
+    let pending = {
+        let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT g.id, g.name, g.cover_url,
+                        (SELECT o.external_id FROM game_ownership o
+                          WHERE o.game_id = g.id AND o.platform = ?1 AND o.external_id IS NOT NULL
+                          ORDER BY o.id LIMIT 1),
+                        m.background_image
+                 FROM games g
+                 LEFT JOIN game_metadata m ON m.game_id = g.id
+                 WHERE ?2 OR g.id NOT IN (SELECT game_id FROM cover_cache)",
+            )
+            .map_err(|e| e.to_string())?;
+
+        let rows = stmt
+            .query_map(params![constants::DEFAULT_PLATFORM_STEAM, force], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        match &game_ids {
+            Some(ids) => rows.into_iter().filter(|r| ids.contains(&r.0)).collect(),
+            None => rows,
+        }
+    };
+
+    let mut summary = CoverCacheSummary {
+        downloaded: 0,
+        missing: 0,
+        failed: 0,
+        evicted: 0,
+        total_bytes: 0,
+        errors: Vec::new(),
+    };
+
+    for (game_id, name, cover_url, steam_id, background) in pending {
+        let urls = covers::candidate_urls(
+            cover_url.as_deref(),
+            steam_id.and_then(|id| id.parse().ok()),
+            background.as_deref(),
+        );
+
+        match covers::download_first(&urls).await {
+            Ok(Some((url, bytes))) => {
+                // A extensão pode mudar entre downloads: apaga os arquivos antigos antes
+                {
+                    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+                    invalidate_cover(&conn, &game_id)?;
+                }
+
+                // Decodificar e redimensionar trava a thread: fica fora do runtime assíncrono
+                let stored = {
+                    let (dir, game_id) = (dir.clone(), game_id.clone());
+                    tokio::task::spawn_blocking(move || covers::store_cover(&dir, &game_id, &url, &bytes))
+                        .await
+                        .map_err(|e| e.to_string())
+                        .and_then(|result| result)
+                };
+                let stored = match stored {
+                    Ok(stored) => stored,
+                    Err(e) => {
+                        summary.failed += 1;
+                        summary.errors.push(format!("{} ({})", name, e));
+                        continue;
+                    }
+                };
+
+                let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+                conn.execute(
+                    "INSERT OR REPLACE INTO cover_cache (game_id, source_url, file_path, thumb_path, size_bytes, status, fetched_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, 'ok', CURRENT_TIMESTAMP)",
+                    params![
+                        game_id,
+                        stored.source_url,
+                        stored.file_path.to_string_lossy(),
+                        stored.thumb_path.to_string_lossy(),
+                        stored.size_bytes as i64
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+                summary.downloaded += 1;
+            }
+            Ok(None) => {
+                let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+                invalidate_cover(&conn, &game_id)?;
+                conn.execute(
+                    "INSERT OR REPLACE INTO cover_cache (game_id, status, fetched_at)
+                     VALUES (?1, 'missing', CURRENT_TIMESTAMP)",
+                    params![game_id],
+                )
+                .map_err(|e| e.to_string())?;
+                summary.missing += 1;
+            }
+            Err(e) => {
+                // Erro de rede: não marca como ausente para tentar de novo depois
+                warn!("Falha ao baixar capa de '{}': {}", name, e);
+                summary.failed += 1;
+                summary.errors.push(format!("{} ({})", name, e));
+            }
+        }
+    }
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    summary.evicted = evict_to_limit(&conn, max_cache_bytes(&conn)?)?;
+    summary.total_bytes = total_cache_bytes(&conn)?;
+
+    info!(
+        "Cache de capas: {} baixadas, {} sem arte, {} falhas, {} removidas",
+        summary.downloaded, summary.missing, summary.failed, summary.evicted
+    );
+
+    Ok(summary)
+}
+
+/// Baixa para o cache local as capas ainda não armazenadas. `game_ids` limita a alguns jogos.
+#[tauri::command]
+pub async fn cache_covers(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    game_ids: Option<Vec<String>>,
+) -> Result<CoverCacheSummary, String> {
+    download_covers(&app, &state, game_ids, false).await
+}
+
+/// Baixa novamente todas as capas, inclusive as já armazenadas e as marcadas como ausentes
+#[tauri::command]
+pub async fn redownload_all_covers(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<CoverCacheSummary, String> {
+    download_covers(&app, &state, None, true).await
+}
+
+#[tauri::command]
+pub fn get_cover_cache_stats(
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<CoverCacheStats, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    let (cached, missing): (i32, i32) = conn
+        .query_row(
+            "SELECT IFNULL(SUM(status = 'ok'), 0), IFNULL(SUM(status = 'missing'), 0) FROM cover_cache",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(CoverCacheStats {
+        cached,
+        missing,
+        total_bytes: total_cache_bytes(&conn)?,
+        max_bytes: max_cache_bytes(&conn)?,
+        directory: cache_dir(&app)?.to_string_lossy().to_string(),
+    })
+}
+
+/// Define o tamanho máximo do cache (em MB) e remove o excedente
+#[tauri::command]
+pub fn set_cover_cache_limit(state: State<AppState>, max_mb: u64) -> Result<i32, String> {
+    if max_mb == 0 {
+        return Err("O limite do cache deve ser maior que zero".to_string());
+    }
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    database::set_setting(
+        &conn,
+        constants::SETTING_COVER_CACHE_MAX_MB,
+        &max_mb.to_string(),
+    )?;
+
+    evict_to_limit(&conn, max_mb * 1024 * 1024)
+}
+
+/// Apaga todas as capas do cache
+#[tauri::command]
+pub fn clear_cover_cache(state: State<AppState>) -> Result<i32, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    let removed = evict_to_limit(&conn, 0)?;
+    conn.execute("DELETE FROM cover_cache", [])
+        .map_err(|e| e.to_string())?;
+    Ok(removed)
+}
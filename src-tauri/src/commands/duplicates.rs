@@ -0,0 +1,655 @@
+use crate::commands::{artwork, covers};
+use crate::commands::ownership::fetch_ownership;
+use crate::constants;
+use crate::database::{self, journal, AppState};
+use crate::models::{Game, GameArtwork, GameOwnership};
+use crate::services::duplicates::{self, DuplicateCluster};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::State;
+use tracing::info;
+
+/// Estado anterior de uma mesclagem, guardado em `game_merges` para permitir desfazer
+#[derive(Serialize, Deserialize)]
+struct MergeSnapshot {
+    primary_before: Game,
+    merged: Vec<Game>,
+    /// game_id -> ID na RAWG de todos os jogos envolvidos
+    rawg_ids: HashMap<String, i32>,
+    /// (source, external_id, game_id original)
+    external_ids: Vec<(String, String, String)>,
+    /// (game_id, install_path, size_on_disk, last_updated)
+    installs: Vec<(String, String, i64, Option<String>)>,
+    /// Cópias (`game_ownership`) de todos os jogos envolvidos antes da mesclagem
+    ownership: Vec<GameOwnership>,
+    /// (DLC, jogo base original) das DLCs que apontavam para um jogo mesclado
+    children: Vec<(String, String)>,
+    /// Artes dos jogos mesclados, para voltarem a eles ao desfazer
+    artwork: Vec<GameArtwork>,
+}
+
+#[derive(Serialize)]
+pub struct MergeRecord {
+    pub id: i64,
+    pub primary_id: String,
+    pub merged_names: Vec<String>,
+    pub created_at: String,
+    pub undone: bool,
+}
+
+#[derive(Serialize)]
+pub struct MergeResult {
+    pub merge_id: i64,
+    pub game: Game,
+}
+
+fn load_games(conn: &Connection) -> Result<Vec<Game>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM games", database::GAME_COLUMNS))
+        .map_err(|e| e.to_string())?;
+    let games = stmt
+        .query_map([], database::map_game_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(games)
+}
+
+fn load_game(conn: &Connection, id: &str) -> Result<Game, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM games WHERE id = ?1", database::GAME_COLUMNS),
+        params![id],
+        database::map_game_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Jogo não encontrado: {}", id))
+}
+
+fn load_rawg_ids(conn: &Connection) -> Result<HashMap<String, i32>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, rawg_id FROM games WHERE rawg_id IS NOT NULL")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+fn load_external_ids(conn: &Connection) -> Result<Vec<(String, String, String)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT game_id, source, external_id FROM game_external_ids")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+/// Lista grupos de jogos que parecem ser o mesmo título, com a confiança e os motivos
+#[tauri::command]
+pub fn find_duplicate_games(state: State<AppState>) -> Result<Vec<DuplicateCluster>, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    let games = load_games(&conn)?;
+    let external_ids = load_external_ids(&conn)?;
+    let rawg_ids = load_rawg_ids(&conn)?;
+
+    Ok(duplicates::find_clusters(games, &external_ids, &rawg_ids))
+}
+
+fn is_known_genre(genre: &Option<String>) -> bool {
+    genre
+        .as_deref()
+        .map(|g| !g.trim().is_empty() && g != constants::DEFAULT_GENRE)
+        .unwrap_or(false)
+}
+
+/// Mescla `duplicate_ids` em `primary_id`: soma o tempo jogado, mantém os melhores metadados,
+/// registra as plataformas de cada cópia e remove as linhas duplicadas.
+#[tauri::command]
+pub fn merge_games(
+    state: State<AppState>,
+    primary_id: String,
+    duplicate_ids: Vec<String>,
+) -> Result<MergeResult, String> {
+    if duplicate_ids.is_empty() {
+        return Err("Selecione ao menos um jogo para mesclar".to_string());
+    }
+    if duplicate_ids.contains(&primary_id) {
+        return Err("O jogo principal não pode estar entre os duplicados".to_string());
+    }
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    let result = database::in_transaction(&conn, |conn| {
+        journal::journaled(conn, "merge_games", Some(&primary_id), |conn| {
+            merge_in_transaction(conn, &primary_id, &duplicate_ids)
+        })
+    })?;
+
+    info!(
+        "Mesclagem {}: {} jogos unidos em '{}'",
+        result.merge_id,
+        duplicate_ids.len(),
+        primary_id
+    );
+    Ok(result)
+}
+
+fn merge_in_transaction(
+    conn: &Connection,
+    primary_id: &str,
+    duplicate_ids: &[String],
+) -> Result<MergeResult, String> {
+    let primary = load_game(conn, primary_id)?;
+    let merged: Vec<Game> = duplicate_ids
+        .iter()
+        .map(|id| load_game(conn, id))
+        .collect::<Result<_, _>>()?;
+
+    let involved: Vec<&str> = std::iter::once(primary_id)
+        .chain(duplicate_ids.iter().map(|s| s.as_str()))
+        .collect();
+
+    let all_rawg = load_rawg_ids(conn)?;
+    let rawg_ids: HashMap<String, i32> = all_rawg
+        .into_iter()
+        .filter(|(id, _)| involved.contains(&id.as_str()))
+        .collect();
+
+    let external_ids: Vec<(String, String, String)> = load_external_ids(conn)?
+        .into_iter()
+        .filter(|(game_id, _, _)| involved.contains(&game_id.as_str()))
+        .map(|(game_id, source, external_id)| (source, external_id, game_id))
+        .collect();
+
+    let mut installs = Vec::new();
+    for id in &involved {
+        let install = conn
+            .query_row(
+                "SELECT install_path, size_on_disk, last_updated FROM game_installs WHERE game_id = ?1",
+                params![id],
+                |row| Ok((id.to_string(), row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        installs.extend(install);
+    }
+
+    // Guarda as cópias antes de mexer nelas; o desfazer restaura exatamente este estado
+    let ownership: Vec<GameOwnership> = fetch_ownership(conn, None)?
+        .into_iter()
+        .filter(|o| involved.contains(&o.game_id.as_str()))
+        .collect();
+
+    // Todo jogo envolvido precisa ter sua cópia registrada antes de ser transferida
+    for game in std::iter::once(&primary).chain(merged.iter()) {
+        let external_id = external_ids
+            .iter()
+            .find(|(_, _, game_id)| *game_id == game.id)
+            .map(|(source, ext, _)| duplicates::canonical_external_key(source, ext));
+
+        database::record_ownership(
+            conn,
+            &game.id,
+            game.platform.as_deref(),
+            external_id.as_deref(),
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    let mut children: Vec<(String, String)> = Vec::new();
+    for id in duplicate_ids {
+        let mut stmt = conn
+            .prepare("SELECT id FROM games WHERE parent_game_id = ?1 AND id != ?2")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![id, primary_id], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        children.extend(rows.into_iter().map(|child| (child, id.clone())));
+    }
+
+    let mut merged_artwork = Vec::new();
+    for id in duplicate_ids {
+        merged_artwork.extend(artwork::fetch_artwork(conn, Some(id))?);
+    }
+
+    // Combina os dados: soma de tempo, melhor nota, metadados mais completos
+    let playtime = std::iter::once(&primary)
+        .chain(merged.iter())
+        .map(|g| g.playtime)
+        .sum::<i32>()
+        .min(constants::MAX_PLAYTIME);
+    let rating = std::iter::once(&primary)
+        .chain(merged.iter())
+        .filter_map(|g| g.rating)
+        .max();
+    let favorite = primary.favorite || merged.iter().any(|g| g.favorite);
+    let installed = primary.installed || merged.iter().any(|g| g.installed);
+    let last_played = std::iter::once(&primary)
+        .chain(merged.iter())
+        .filter_map(|g| g.last_played.clone())
+        .max();
+    let genre = if is_known_genre(&primary.genre) {
+        primary.genre.clone()
+    } else {
+        merged
+            .iter()
+            .map(|g| g.genre.clone())
+            .find(is_known_genre)
+            .unwrap_or_else(|| primary.genre.clone())
+    };
+    let cover_url = primary
+        .cover_url
+        .clone()
+        .or_else(|| merged.iter().find_map(|g| g.cover_url.clone()));
+    let rawg_id = rawg_ids
+        .get(primary_id)
+        .or_else(|| duplicate_ids.iter().find_map(|id| rawg_ids.get(id)))
+        .copied();
+
+    for id in duplicate_ids {
+        conn.execute(
+            "UPDATE game_external_ids SET game_id = ?1 WHERE game_id = ?2",
+            params![primary_id, id],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE OR IGNORE game_installs SET game_id = ?1 WHERE game_id = ?2",
+            params![primary_id, id],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM game_installs WHERE game_id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE OR IGNORE game_ownership SET game_id = ?1 WHERE game_id = ?2",
+            params![primary_id, id],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM game_ownership WHERE game_id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        covers::invalidate_cover(conn, id)?;
+        artwork::move_artwork(conn, id, primary_id)?;
+        // Metadados das fontes são cache: o principal mantém os seus (ou busca de novo);
+        // só as substituições manuais do usuário passam para o principal
+        conn.execute("DELETE FROM game_metadata WHERE game_id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE OR IGNORE metadata_sources SET game_id = ?1 WHERE game_id = ?2 AND provider = 'local'",
+            params![primary_id, id],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM metadata_sources WHERE game_id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        // DLCs do jogo removido passam para o principal
+        conn.execute(
+            "UPDATE games SET parent_game_id = CASE WHEN id = ?1 THEN NULL ELSE ?1 END
+             WHERE parent_game_id = ?2",
+            params![primary_id, id],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM games WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+    }
+
+    conn.execute(
+        "UPDATE games SET genre = ?1, cover_url = ?2, playtime = ?3, rating = ?4, favorite = ?5,
+                installed = ?6, last_played = ?7, rawg_id = ?8
+         WHERE id = ?9",
+        params![
+            genre,
+            cover_url,
+            playtime,
+            rating,
+            favorite,
+            installed,
+            last_played,
+            rawg_id,
+            primary_id
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let snapshot = MergeSnapshot {
+        primary_before: primary,
+        merged,
+        rawg_ids,
+        external_ids,
+        installs,
+        ownership,
+        children,
+        artwork: merged_artwork,
+    };
+    let snapshot_json = serde_json::to_string(&snapshot).map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO game_merges (primary_id, snapshot) VALUES (?1, ?2)",
+        params![primary_id, snapshot_json],
+    )
+    .map_err(|e| e.to_string())?;
+    let merge_id = conn.last_insert_rowid();
+
+    let mut game = load_game(conn, primary_id)?;
+    database::attach_platforms(conn, std::slice::from_mut(&mut game))?;
+
+    Ok(MergeResult { merge_id, game })
+}
+
+/// Desfaz uma mesclagem: restaura o jogo principal e recria as linhas removidas
+#[tauri::command]
+pub fn undo_merge(state: State<AppState>, merge_id: i64) -> Result<String, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    let restored = database::in_transaction(&conn, |conn| {
+        let primary_id: Option<String> = conn
+            .query_row(
+                "SELECT primary_id FROM game_merges WHERE id = ?1",
+                params![merge_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+
+        journal::journaled(conn, "undo_merge", primary_id.as_deref(), |conn| {
+            undo_merge_in_transaction(conn, merge_id)
+        })
+    })?;
+
+    Ok(format!("Mesclagem desfeita! {} jogos restaurados.", restored))
+}
+
+/// Retorna quantos jogos mesclados voltaram para a biblioteca
+fn undo_merge_in_transaction(conn: &Connection, merge_id: i64) -> Result<usize, String> {
+    let (snapshot_json, undone): (String, bool) = conn
+        .query_row(
+            "SELECT snapshot, undone FROM game_merges WHERE id = ?1",
+            params![merge_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or("Mesclagem não encontrada")?;
+
+    if undone {
+        return Err("Esta mesclagem já foi desfeita".to_string());
+    }
+
+    let snapshot: MergeSnapshot =
+        serde_json::from_str(&snapshot_json).map_err(|e| e.to_string())?;
+
+    // Se um ID removido voltou a existir (ex: re-importação), desfazer criaria conflito
+    for game in &snapshot.merged {
+        let exists: bool = conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM games WHERE id = ?1)",
+                params![game.id],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if exists {
+            return Err(format!(
+                "Não é possível desfazer: o jogo '{}' já existe novamente na biblioteca",
+                game.name
+            ));
+        }
+    }
+
+    restore_snapshot(conn, &snapshot)
+        .map_err(|e| format!("Erro ao desfazer mesclagem: {}", e))?;
+    conn.execute(
+        "UPDATE game_merges SET undone = 1 WHERE id = ?1",
+        params![merge_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(snapshot.merged.len())
+}
+
+fn restore_snapshot(conn: &Connection, snapshot: &MergeSnapshot) -> Result<(), rusqlite::Error> {
+    let primary = &snapshot.primary_before;
+
+    for game in std::iter::once(primary).chain(snapshot.merged.iter()) {
+        conn.execute(
+            "INSERT OR REPLACE INTO games (id, name, genre, platform, cover_url, playtime, rating, favorite, installed, last_played, rawg_id, app_type, parent_game_id, parent_app_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![
+                game.id,
+                game.name,
+                game.genre,
+                game.platform,
+                game.cover_url,
+                game.playtime,
+                game.rating,
+                game.favorite,
+                game.installed,
+                game.last_played,
+                snapshot.rawg_ids.get(&game.id),
+                game.app_type,
+                game.parent_game_id,
+                game.parent_app_id
+            ],
+        )?;
+    }
+
+    for (child, parent) in &snapshot.children {
+        conn.execute(
+            "UPDATE games SET parent_game_id = ?1 WHERE id = ?2",
+            params![parent, child],
+        )?;
+    }
+
+    for (source, external_id, game_id) in &snapshot.external_ids {
+        conn.execute(
+            "UPDATE game_external_ids SET game_id = ?1 WHERE source = ?2 AND external_id = ?3",
+            params![game_id, source, external_id],
+        )?;
+    }
+
+    for game in std::iter::once(primary).chain(snapshot.merged.iter()) {
+        conn.execute(
+            "DELETE FROM game_ownership WHERE game_id = ?1",
+            params![game.id],
+        )?;
+    }
+    for copy in &snapshot.ownership {
+        conn.execute(
+            "INSERT OR REPLACE INTO game_ownership (id, game_id, platform, external_id, purchase_date, price_paid, currency, edition)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                copy.id,
+                copy.game_id,
+                copy.platform,
+                copy.external_id,
+                copy.purchase_date,
+                copy.price_paid,
+                copy.currency,
+                copy.edition
+            ],
+        )?;
+    }
+
+    // Artes que passaram para o principal voltam; as que ele já tinha do mesmo tipo foram
+    // descartadas e voltam enquanto o arquivo ainda existir
+    for art in &snapshot.artwork {
+        let moved_back = conn.execute(
+            "UPDATE game_artwork SET game_id = ?1 WHERE game_id = ?2 AND kind = ?3 AND file_path = ?4",
+            params![art.game_id, primary.id, art.kind, art.file_path],
+        )?;
+        if moved_back == 0 && std::path::Path::new(&art.file_path).exists() {
+            conn.execute(
+                "INSERT OR IGNORE INTO game_artwork (game_id, kind, file_path, thumb_path, width, height, size_bytes, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    art.game_id,
+                    art.kind,
+                    art.file_path,
+                    art.thumb_path,
+                    art.width,
+                    art.height,
+                    art.size_bytes as i64,
+                    art.created_at
+                ],
+            )?;
+        }
+    }
+
+    conn.execute(
+        "DELETE FROM game_installs WHERE game_id = ?1",
+        params![primary.id],
+    )?;
+    for (game_id, path, size, updated) in &snapshot.installs {
+        conn.execute(
+            "INSERT OR REPLACE INTO game_installs (game_id, install_path, size_on_disk, last_updated)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![game_id, path, size, updated],
+        )?;
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_merge_history(state: State<AppState>) -> Result<Vec<MergeRecord>, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    let mut stmt = conn
+        .prepare("SELECT id, primary_id, snapshot, created_at, undone FROM game_merges ORDER BY id DESC")
+        .map_err(|e| e.to_string())?;
+
+    let records = stmt
+        .query_map([], |row| {
+            let snapshot: String = row.get(2)?;
+            let merged_names = serde_json::from_str::<MergeSnapshot>(&snapshot)
+                .map(|s| s.merged.into_iter().map(|g| g.name).collect())
+                .unwrap_or_default();
+
+            Ok(MergeRecord {
+                id: row.get(0)?,
+                primary_id: row.get(1)?,
+                merged_names,
+                created_at: row.get(3)?,
+                undone: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{memory_db, temp_dir};
+    use std::fs;
+    use std::path::Path;
+
+    fn copies(conn: &Connection) -> Vec<(String, String)> {
+        conn.prepare("SELECT game_id, platform FROM game_ownership ORDER BY game_id, platform")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn merge_and_undo_restore_every_copy() {
+        let conn = memory_db();
+        conn.execute_batch(
+            "INSERT INTO games (id, name, platform, playtime) VALUES ('a', 'Hades', 'Steam', 10);
+             INSERT INTO games (id, name, platform, playtime) VALUES ('b', 'Hades', 'Epic Games', 5);
+             INSERT INTO game_ownership (game_id, platform) VALUES ('a', 'Steam');
+             INSERT INTO game_ownership (game_id, platform, external_id, price_paid) VALUES ('b', 'Epic Games', NULL, 19.9);",
+        )
+        .unwrap();
+
+        let result = merge_in_transaction(&conn, "a", &["b".to_string()]).unwrap();
+        assert_eq!(result.game.playtime, 15);
+        assert_eq!(
+            copies(&conn),
+            vec![
+                ("a".to_string(), "Epic Games".to_string()),
+                ("a".to_string(), "Steam".to_string())
+            ]
+        );
+
+        assert_eq!(undo_merge_in_transaction(&conn, result.merge_id).unwrap(), 1);
+        assert_eq!(
+            copies(&conn),
+            vec![
+                ("a".to_string(), "Steam".to_string()),
+                ("b".to_string(), "Epic Games".to_string())
+            ]
+        );
+        let price: Option<f64> = conn
+            .query_row(
+                "SELECT price_paid FROM game_ownership WHERE game_id = 'b'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(price, Some(19.9));
+
+        assert!(undo_merge_in_transaction(&conn, result.merge_id).is_err());
+    }
+
+    fn artwork_of(conn: &Connection) -> Vec<(String, String, String)> {
+        conn.prepare("SELECT game_id, kind, file_path FROM game_artwork ORDER BY game_id, kind")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn undo_moves_artwork_back_to_the_restored_game() {
+        // A capa do duplicado perde para a do principal: o registro sai, o arquivo fica
+        let dir = temp_dir("merge_artwork");
+        let dropped = dir.join("b_cover.png").to_string_lossy().to_string();
+        fs::write(&dropped, b"png").unwrap();
+
+        let conn = memory_db();
+        conn.execute_batch(
+            "INSERT INTO games (id, name, platform) VALUES ('a', 'Hades', 'Steam'), ('b', 'Hades', 'Epic Games');
+             INSERT INTO game_ownership (game_id, platform) VALUES ('a', 'Steam'), ('b', 'Epic Games');
+             INSERT INTO game_artwork (game_id, kind, file_path, width, height, size_bytes) VALUES
+               ('a', 'cover', 'a_cover.png', 600, 900, 10),
+               ('b', 'hero', 'b_hero.png', 1920, 620, 20);",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO game_artwork (game_id, kind, file_path, width, height, size_bytes)
+             VALUES ('b', 'cover', ?1, 600, 900, 3)",
+            params![dropped],
+        )
+        .unwrap();
+
+        let result = merge_in_transaction(&conn, "a", &["b".to_string()]).unwrap();
+        assert_eq!(artwork_of(&conn).len(), 2);
+        assert!(artwork_of(&conn).iter().all(|(game, _, _)| game == "a"));
+        assert!(Path::new(&dropped).exists());
+
+        undo_merge_in_transaction(&conn, result.merge_id).unwrap();
+        assert_eq!(
+            artwork_of(&conn),
+            vec![
+                ("a".to_string(), "cover".to_string(), "a_cover.png".to_string()),
+                ("b".to_string(), "cover".to_string(), dropped.clone()),
+                ("b".to_string(), "hero".to_string(), "b_hero.png".to_string())
+            ]
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
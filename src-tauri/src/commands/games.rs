@@ -1,18 +1,17 @@
+use crate::commands::{covers, purchases};
 use crate::constants;
-use crate::database::AppState;
+use crate::database::{self, journal, AppState};
 use crate::models;
-use rusqlite::params;
+use rusqlite::{params, Connection, OptionalExtension};
 use tauri::State;
 use url::Url;
 
-#[tauri::command]
-pub fn add_game(
-    state: State<AppState>,
-    id: String,
-    name: String,
-    genre: Option<String>,
-    platform: Option<String>,
-    cover_url: Option<String>,
+/// Regras de validação de um jogo, compartilhadas entre `add_game` e as importações em lote
+pub(crate) fn validate_game_input(
+    name: &str,
+    genre: Option<&str>,
+    platform: Option<&str>,
+    cover_url: Option<&str>,
     playtime: Option<i32>,
     rating: Option<i32>,
 ) -> Result<(), String> {
@@ -27,7 +26,7 @@ pub fn add_game(
         ));
     }
 
-    if let Some(ref url_str) = cover_url {
+    if let Some(url_str) = cover_url {
         if url_str.len() > constants::MAX_URL_LENGTH {
             return Err(format!(
                 "URL da capa muito longa (máximo {} caracteres)",
@@ -40,7 +39,7 @@ pub fn add_game(
         }
     }
 
-    if let Some(ref g) = genre {
+    if let Some(g) = genre {
         if g.len() > constants::MAX_GENRE_LENGTH {
             return Err(format!(
                 "Gênero muito longo (máximo {} caracteres)",
@@ -49,7 +48,7 @@ pub fn add_game(
         }
     }
 
-    if let Some(ref p) = platform {
+    if let Some(p) = platform {
         if p.len() > constants::MAX_PLATFORM_LENGTH {
             return Err(format!(
                 "Plataforma muito longa (máximo {} caracteres)",
@@ -80,12 +79,62 @@ pub fn add_game(
         }
     }
 
+    Ok(())
+}
+
+#[tauri::command]
+pub fn add_game(
+    state: State<AppState>,
+    id: String,
+    name: String,
+    genre: Option<String>,
+    platform: Option<String>,
+    cover_url: Option<String>,
+    playtime: Option<i32>,
+    rating: Option<i32>,
+) -> Result<(), String> {
+    validate_game_input(
+        &name,
+        genre.as_deref(),
+        platform.as_deref(),
+        cover_url.as_deref(),
+        playtime,
+        rating,
+    )?;
+
     let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
 
+    insert_game(
+        &conn,
+        &NewGame {
+            id: &id,
+            name: &name,
+            genre: genre.as_deref(),
+            platform: platform.as_deref(),
+            cover_url: cover_url.as_deref(),
+            playtime,
+            rating,
+        },
+    )
+}
+
+/// Jogo adicionado manualmente, já validado
+struct NewGame<'a> {
+    id: &'a str,
+    name: &'a str,
+    genre: Option<&'a str>,
+    platform: Option<&'a str>,
+    cover_url: Option<&'a str>,
+    playtime: Option<i32>,
+    rating: Option<i32>,
+}
+
+/// Grava o jogo, a cópia e o preço vindo da lista de desejos em um único lote do diário
+fn insert_game(conn: &Connection, game: &NewGame) -> Result<(), String> {
     let exists: bool = conn
         .query_row(
             "SELECT EXISTS(SELECT 1 FROM games WHERE id = ?1)",
-            params![id],
+            params![game.id],
             |row| row.get(0),
         )
         .map_err(|e| format!("Erro ao verificar duplicata: {}", e))?;
@@ -94,42 +143,69 @@ pub fn add_game(
         return Err("Já existe um jogo com este ID".to_string());
     }
 
-    conn.execute(
-            "INSERT INTO games (id, name, genre, platform, cover_url, playtime, rating) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            params![id, name, genre, platform, cover_url, playtime, rating],
-        )
+    database::in_transaction(conn, |conn| {
+        journal::journaled(conn, "add_game", Some(game.name), |conn| {
+            conn.execute(
+                "INSERT INTO games (id, name, genre, platform, cover_url, playtime, rating) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![game.id, game.name, game.genre, game.platform, game.cover_url, game.playtime, game.rating],
+            )
             .map_err(|e| e.to_string())?;
 
-    Ok(())
+            database::record_ownership(conn, game.id, game.platform, None)
+                .map_err(|e| e.to_string())?;
+
+            // Jogo que estava na lista de desejos: registra o último preço visto como preço pago
+            purchases::fill_purchase_from_wishlist(conn, game.id, game.id)
+                .map_err(|e| e.to_string())?;
+
+            Ok(())
+        })
+    })
 }
 
 #[tauri::command]
-pub fn get_games(state: State<AppState>) -> Result<Vec<models::Game>, String> {
+pub fn get_games(
+    state: State<AppState>,
+    games_only: Option<bool>,
+    developer: Option<String>,
+    publisher: Option<String>,
+    min_metacritic: Option<i32>,
+) -> Result<Vec<models::Game>, String> {
     let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
 
+    // `games_only` esconde DLCs, trilhas sonoras e ferramentas;
+    // os demais filtros usam os metadados da RAWG salvos em `game_metadata`
     let mut stmt = conn
-        .prepare(
-            "SELECT id, name, genre, platform, cover_url, playtime, rating, favorite FROM games",
-        )
+        .prepare(&format!(
+            "SELECT {} FROM games
+             WHERE (?1 = 0 OR {})
+               AND (?2 IS NULL OR id IN (SELECT m.game_id FROM game_metadata m, json_each(m.developers) d
+                                         WHERE d.value = ?2 COLLATE NOCASE))
+               AND (?3 IS NULL OR id IN (SELECT m.game_id FROM game_metadata m, json_each(m.publishers) p
+                                         WHERE p.value = ?3 COLLATE NOCASE))
+               AND (?4 IS NULL OR id IN (SELECT game_id FROM game_metadata WHERE metacritic >= ?4))",
+            database::GAME_COLUMNS,
+            database::GAMES_ONLY_FILTER
+        ))
         .map_err(|e| e.to_string())?;
 
-    let games = stmt
-        .query_map([], |row| {
-            Ok(models::Game {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                genre: row.get(2)?,
-                platform: row.get(3)?,
-                cover_url: row.get(4)?,
-                playtime: row.get(5)?,
-                rating: row.get(6)?,
-                favorite: row.get(7)?,
-            })
-        })
+    let mut games = stmt
+        .query_map(
+            params![
+                games_only.unwrap_or(false),
+                developer.filter(|d| !d.trim().is_empty()),
+                publisher.filter(|p| !p.trim().is_empty()),
+                min_metacritic
+            ],
+            database::map_game_row,
+        )
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
 
+    database::attach_platforms(&conn, &mut games)?;
+    database::attach_cached_covers(&conn, &mut games)?;
+
     Ok(games)
 }
 
@@ -150,10 +226,43 @@ pub fn toggle_favorite(state: State<AppState>, id: String) -> Result<(), String>
 pub fn delete_game(state: State<AppState>, id: String) -> Result<(), String> {
     let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
 
-    conn.execute("DELETE FROM games WHERE id = ?1", params![id])
+    let name: Option<String> = conn
+        .query_row("SELECT name FROM games WHERE id = ?1", params![id], |row| row.get(0))
+        .optional()
         .map_err(|e| e.to_string())?;
 
-    Ok(())
+    database::in_transaction(&conn, |conn| {
+        // Tudo em um lote do diário: o jogo vai para a lixeira e volta com cópias, metadados e artes
+        journal::journaled(conn, "delete_game", name.as_deref(), |conn| {
+            // DLCs do jogo removido deixam de estar aninhadas
+            conn.execute(
+                "UPDATE games SET parent_game_id = NULL WHERE parent_game_id = ?1",
+                params![id],
+            )
+            .map_err(|e| e.to_string())?;
+
+            // Remove também as cópias, IDs externos, metadados, dados de instalação e artes do jogo.
+            // Os arquivos das artes ficam até o jogo sair da lixeira.
+            for table_sql in [
+                "DELETE FROM game_ownership WHERE game_id = ?1",
+                "DELETE FROM game_external_ids WHERE game_id = ?1",
+                "DELETE FROM game_installs WHERE game_id = ?1",
+                "DELETE FROM game_metadata WHERE game_id = ?1",
+                "DELETE FROM metadata_sources WHERE game_id = ?1",
+                "DELETE FROM metadata_misses WHERE game_id = ?1",
+                "DELETE FROM game_artwork WHERE game_id = ?1",
+                "DELETE FROM games WHERE id = ?1",
+            ] {
+                conn.execute(table_sql, params![id])
+                    .map_err(|e| e.to_string())?;
+            }
+
+            Ok(())
+        })?;
+
+        // Por último: a capa em cache é apagada do disco e pode ser baixada de novo
+        covers::invalidate_cover(conn, &id)
+    })
 }
 
 #[tauri::command]
@@ -202,11 +311,223 @@ pub fn update_game(
 
     let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
 
-    conn.execute(
-            "UPDATE games SET name = ?1, genre = ?2, platform = ?3, cover_url = ?4, playtime = ?5, rating = ?6 WHERE id = ?7",
-            params![name, genre, platform, cover_url, playtime, rating, id],
+    // Capa trocada: a versão em cache deixa de valer
+    let cover_changed: bool = conn
+        .query_row(
+            "SELECT IFNULL(cover_url, '') != IFNULL(?1, '') FROM games WHERE id = ?2",
+            params![cover_url, id],
+            |row| row.get(0),
         )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .unwrap_or(false);
+
+    database::in_transaction(&conn, |conn| {
+        journal::journaled(conn, "update_game", Some(&name), |conn| {
+            if let Some(new_platform) = platform.as_deref().filter(|p| !p.trim().is_empty()) {
+                update_platform_copy(conn, &id, new_platform.trim()).map_err(|e| e.to_string())?;
+            }
+
+            conn.execute(
+                "UPDATE games SET name = ?1, genre = ?2, platform = ?3, cover_url = ?4, playtime = ?5, rating = ?6 WHERE id = ?7",
+                params![name, genre, platform, cover_url, playtime, rating, id],
+            )
+            .map_err(|e| e.to_string())?;
+
+            Ok(())
+        })?;
+
+        // Por último: a capa trocada sai do cache e é baixada de novo
+        if cover_changed {
+            covers::invalidate_cover(conn, &id)?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Trocar a plataforma de um jogo corrige a cópia correspondente (a da plataforma antiga,
+/// ou a única cópia). Sem cópia correspondente, registra uma nova.
+fn update_platform_copy(conn: &Connection, id: &str, new_platform: &str) -> rusqlite::Result<()> {
+    let old_platform: Option<String> = conn
+        .query_row("SELECT platform FROM games WHERE id = ?1", params![id], |row| {
+            row.get(0)
+        })
+        .optional()?
+        .flatten();
+
+    let already_owned: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM game_ownership WHERE game_id = ?1 AND platform = ?2)",
+        params![id, new_platform],
+        |row| row.get(0),
+    )?;
+    if already_owned {
+        return Ok(());
+    }
+
+    let updated = conn.execute(
+        "UPDATE game_ownership SET platform = ?1
+         WHERE id = (
+             SELECT id FROM game_ownership
+             WHERE game_id = ?2
+               AND (platform = ?3 OR (SELECT COUNT(*) FROM game_ownership WHERE game_id = ?2) = 1)
+             ORDER BY id LIMIT 1
+         )",
+        params![new_platform, id, old_platform],
+    )?;
+    if updated == 0 {
+        database::record_ownership(conn, id, Some(new_platform), None)?;
+    }
+
+    Ok(())
+}
+
+/// Define (ou remove, com `parent_id` None) o jogo base de uma DLC
+#[tauri::command]
+pub fn set_game_parent(
+    state: State<AppState>,
+    game_id: String,
+    parent_id: Option<String>,
+) -> Result<(), String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    if let Some(parent) = &parent_id {
+        if parent == &game_id {
+            return Err("Um jogo não pode ser DLC de si mesmo".to_string());
+        }
+
+        let grandparent: Option<String> = conn
+            .query_row(
+                "SELECT parent_game_id FROM games WHERE id = ?1",
+                params![parent],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or("Jogo base não encontrado")?;
+        if grandparent.is_some() {
+            return Err("O jogo base escolhido já é uma DLC".to_string());
+        }
+
+        let has_children: bool = conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM games WHERE parent_game_id = ?1)",
+                params![game_id],
+                |row| row.get(0),
+            )
             .map_err(|e| e.to_string())?;
+        if has_children {
+            return Err("Este jogo possui DLCs e não pode ser aninhado".to_string());
+        }
+    }
+
+    let updated = conn
+        .execute(
+            "UPDATE games SET parent_game_id = ?1,
+                    app_type = CASE WHEN ?1 IS NOT NULL AND app_type IS NULL THEN ?3 ELSE app_type END
+             WHERE id = ?2",
+            params![parent_id, game_id, constants::STEAM_APP_TYPE_DLC],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err("Jogo não encontrado".to_string());
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::memory_db;
+
+    fn copies(conn: &Connection) -> Vec<String> {
+        conn.prepare("SELECT platform FROM game_ownership WHERE game_id = 'hades' ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn platform_change_updates_the_matching_copy() {
+        let conn = memory_db();
+        conn.execute_batch(
+            "INSERT INTO games (id, name, platform) VALUES ('hades', 'Hades', 'Steam');
+             INSERT INTO game_ownership (game_id, platform) VALUES ('hades', 'Steam');
+             INSERT INTO game_ownership (game_id, platform) VALUES ('hades', 'Nintendo Switch');",
+        )
+        .unwrap();
+
+        update_platform_copy(&conn, "hades", "Epic Games").unwrap();
+        assert_eq!(copies(&conn), vec!["Epic Games", "Nintendo Switch"]);
+
+        // Plataforma que já tem cópia: nada muda
+        update_platform_copy(&conn, "hades", "Nintendo Switch").unwrap();
+        assert_eq!(copies(&conn), vec!["Epic Games", "Nintendo Switch"]);
+    }
+
+    #[test]
+    fn single_copy_follows_the_platform() {
+        let conn = memory_db();
+        conn.execute_batch(
+            "INSERT INTO games (id, name, platform) VALUES ('hades', 'Hades', NULL);
+             INSERT INTO game_ownership (game_id, platform) VALUES ('hades', 'Manual');",
+        )
+        .unwrap();
+
+        update_platform_copy(&conn, "hades", "Steam").unwrap();
+        assert_eq!(copies(&conn), vec!["Steam"]);
+    }
+
+    #[test]
+    fn unmatched_platform_change_adds_a_copy() {
+        let conn = memory_db();
+        conn.execute_batch(
+            "INSERT INTO games (id, name, platform) VALUES ('hades', 'Hades', 'PlayStation 5');
+             INSERT INTO game_ownership (game_id, platform) VALUES ('hades', 'Steam');
+             INSERT INTO game_ownership (game_id, platform) VALUES ('hades', 'Nintendo Switch');",
+        )
+        .unwrap();
+
+        update_platform_copy(&conn, "hades", "Epic Games").unwrap();
+        assert_eq!(copies(&conn), vec!["Steam", "Nintendo Switch", "Epic Games"]);
+    }
+
+    #[test]
+    fn added_game_is_undone_with_its_copy() {
+        let conn = memory_db();
+        conn.execute(
+            "INSERT INTO wishlist (id, name, localized_price, localized_currency)
+             VALUES ('hades', 'Hades', 49.99, 'BRL')",
+            [],
+        )
+        .unwrap();
+
+        insert_game(
+            &conn,
+            &NewGame {
+                id: "hades",
+                name: "Hades",
+                genre: None,
+                platform: Some("Steam"),
+                cover_url: None,
+                playtime: None,
+                rating: None,
+            },
+        )
+        .unwrap();
+        let price: Option<f64> = conn
+            .query_row("SELECT price_paid FROM game_ownership WHERE game_id = 'hades'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(price, Some(49.99));
+
+        let undone = journal::undo_last(&conn).unwrap();
+        assert_eq!(undone.operation, "add_game");
+        assert_eq!(database::count_rows(&conn, "games").unwrap(), 0);
+        assert!(copies(&conn).is_empty());
+    }
+}
@@ -0,0 +1,591 @@
+use crate::commands::games::validate_game_input;
+use crate::commands::integrations::ImportSummary;
+use crate::constants;
+use crate::database::{self, journal, AppState};
+use crate::services::duplicates;
+use crate::services::importers::generic::{self, ColumnMapping};
+use crate::services::importers::playnite::{PlayniteImporter, UnmappedField};
+use crate::services::importers::{self, gog, heroic, lutris, ImportedGame, LibraryImporter};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use tauri::State;
+use tracing::{info, warn};
+
+/// Importa a biblioteca de um launcher local.
+/// `source`: "lutris" (caminho do pga.db), "heroic" (pasta de configuração)
+/// ou "gog" (galaxy-2.0.db ou JSON exportado da conta).
+#[tauri::command]
+pub fn import_launcher_library(
+    state: State<AppState>,
+    source: String,
+    path: Option<String>,
+) -> Result<ImportSummary, String> {
+    let importer: Box<dyn LibraryImporter> = match source.as_str() {
+        "lutris" => Box::new(lutris::LutrisImporter::new(path)?),
+        "heroic" => Box::new(heroic::HeroicImporter::new(path)?),
+        "gog" => Box::new(gog::GogImporter::new(path)?),
+        other => return Err(format!("Fonte de importação desconhecida: {}", other)),
+    };
+
+    let games = importer.load()?;
+    info!("{} jogos encontrados na fonte '{}'", games.len(), importer.source());
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    save_imported_games(&conn, importer.source(), &games)
+}
+
+#[derive(Serialize)]
+pub struct PlayniteImportReport {
+    pub summary: ImportSummary,
+    /// Campos do Playnite sem correspondência na biblioteca (status, notas, desenvolvedores...)
+    pub unmapped: Vec<UnmappedField>,
+}
+
+/// Importa uma biblioteca do Playnite exportada em JSON e informa o que não pôde ser mapeado
+#[tauri::command]
+pub fn import_playnite_library(
+    state: State<AppState>,
+    file_path: String,
+) -> Result<PlayniteImportReport, String> {
+    let importer = PlayniteImporter::new(file_path)?;
+    let (games, unmapped) = importer.load_with_report()?;
+    info!(
+        "{} jogos encontrados no Playnite ({} campos não mapeados)",
+        games.len(),
+        unmapped.len()
+    );
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    let summary = save_imported_games(&conn, importer.source(), &games)?;
+
+    Ok(PlayniteImportReport { summary, unmapped })
+}
+
+#[derive(Serialize)]
+pub struct PreviewRow {
+    pub row: usize,
+    pub name: String,
+    pub platform: String,
+    pub playtime: i32,
+    pub rating: Option<i32>,
+    /// Jogo já existente na biblioteca (None quando a duplicata é dentro do próprio arquivo)
+    pub existing_id: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct InvalidRow {
+    pub row: usize,
+    pub name: Option<String>,
+    pub reason: String,
+}
+
+/// Relatório de simulação (dry-run) de uma importação por arquivo
+#[derive(Serialize)]
+pub struct FileImportPreview {
+    pub total_rows: usize,
+    pub new_games: Vec<PreviewRow>,
+    pub duplicates: Vec<PreviewRow>,
+    pub invalid: Vec<InvalidRow>,
+}
+
+#[tauri::command]
+pub fn list_import_presets() -> Vec<String> {
+    generic::preset_names()
+        .into_iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Simula a importação de um CSV/JSON sem gravar nada
+#[tauri::command]
+pub fn preview_file_import(
+    state: State<AppState>,
+    file_path: String,
+    preset: Option<String>,
+    mapping: Option<ColumnMapping>,
+) -> Result<FileImportPreview, String> {
+    let (source, records, mapping) = load_file(&file_path, preset, mapping)?;
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    let (preview, _) = analyze_records(&conn, &source, &records, &mapping)?;
+    Ok(preview)
+}
+
+/// Importa um CSV/JSON gravando apenas as linhas novas e válidas, em uma única transação
+#[tauri::command]
+pub fn import_file_library(
+    state: State<AppState>,
+    file_path: String,
+    preset: Option<String>,
+    mapping: Option<ColumnMapping>,
+) -> Result<ImportSummary, String> {
+    let (source, records, mapping) = load_file(&file_path, preset, mapping)?;
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    let (preview, new_games) = analyze_records(&conn, &source, &records, &mapping)?;
+
+    let mut summary = save_imported_games(&conn, &source, &new_games)?;
+
+    summary.total_processed = preview.total_rows as i32;
+    summary.error_count += preview.invalid.len() as i32;
+    summary.errors.extend(
+        preview
+            .invalid
+            .iter()
+            .map(|r| format!("Linha {}: {}", r.row, r.reason)),
+    );
+    summary.message = format!(
+        "Importação concluída! {} novos jogos, {} duplicados ignorados, {} inválidos.",
+        new_games.len(),
+        preview.duplicates.len(),
+        preview.invalid.len()
+    );
+
+    Ok(summary)
+}
+
+/// Lê o arquivo e resolve o mapeamento (explícito tem prioridade sobre o preset)
+fn load_file(
+    file_path: &str,
+    preset: Option<String>,
+    mapping: Option<ColumnMapping>,
+) -> Result<(String, Vec<generic::Record>, ColumnMapping), String> {
+    let (source, mapping) = match (mapping, preset) {
+        (Some(mapping), preset) => (preset.unwrap_or_else(|| "file".to_string()), mapping),
+        (None, Some(preset)) => {
+            let mapping = generic::preset(&preset)
+                .ok_or_else(|| format!("Preset de importação desconhecido: {}", preset))?;
+            (preset.to_lowercase(), mapping)
+        }
+        (None, None) => return Err("Informe um mapeamento de colunas ou um preset".to_string()),
+    };
+
+    if mapping.name.trim().is_empty() {
+        return Err("O mapeamento precisa indicar a coluna do nome".to_string());
+    }
+
+    let content = fs::read_to_string(file_path).map_err(|e| format!("Erro ao ler arquivo: {}", e))?;
+
+    let is_csv = Path::new(file_path)
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("csv"))
+        .unwrap_or(false);
+
+    let records = if is_csv {
+        generic::parse_csv(&content)?
+    } else {
+        generic::parse_json(&content)?
+    };
+
+    Ok((source, records, mapping))
+}
+
+/// Classifica cada linha em nova, duplicada ou inválida (com o motivo)
+fn analyze_records(
+    conn: &Connection,
+    source: &str,
+    records: &[generic::Record],
+    mapping: &ColumnMapping,
+) -> Result<(FileImportPreview, Vec<ImportedGame>), String> {
+    let mut preview = FileImportPreview {
+        total_rows: records.len(),
+        new_games: Vec::new(),
+        duplicates: Vec::new(),
+        invalid: Vec::new(),
+    };
+    let mut new_games = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+
+    for (i, record) in records.iter().enumerate() {
+        let row = i + 1;
+
+        let game = match generic::map_record(record, mapping).and_then(|game| {
+            validate_game_input(
+                &game.name,
+                game.genre.as_deref(),
+                Some(&game.platform),
+                game.cover_url.as_deref(),
+                Some(game.playtime),
+                game.rating,
+            )?;
+            Ok(game)
+        }) {
+            Ok(game) => game,
+            Err(reason) => {
+                let name = record.get(&mapping.name).filter(|n| !n.trim().is_empty());
+                preview.invalid.push(InvalidRow {
+                    row,
+                    name: name.cloned(),
+                    reason,
+                });
+                continue;
+            }
+        };
+
+        let existing_id = find_existing_game(conn, source, &game)?;
+        let duplicated_in_file = !seen.insert(game.external_id.clone());
+
+        let preview_row = PreviewRow {
+            row,
+            name: game.name.clone(),
+            platform: game.platform.clone(),
+            playtime: game.playtime,
+            rating: game.rating,
+            existing_id: existing_id.clone(),
+        };
+
+        if existing_id.is_some() || duplicated_in_file {
+            preview.duplicates.push(preview_row);
+        } else {
+            preview.new_games.push(preview_row);
+            new_games.push(game);
+        }
+    }
+
+    Ok((preview, new_games))
+}
+
+/// Procura o jogo pelo ID externo da mesma fonte ou, na falta dele, pelo nome
+fn find_existing_game(
+    conn: &Connection,
+    source: &str,
+    game: &ImportedGame,
+) -> Result<Option<String>, String> {
+    let by_external = mapped_game_id(conn, source, &game.external_id).map_err(|e| e.to_string())?;
+
+    if by_external.is_some() {
+        return Ok(by_external);
+    }
+
+    conn.query_row(
+        "SELECT id FROM games WHERE name = ?1 COLLATE NOCASE LIMIT 1",
+        params![game.name],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Grava jogos importados em uma única transação.
+/// Jogos já importados da mesma fonte (mesmo ID externo) são atualizados em vez de duplicados.
+pub(crate) fn save_imported_games(
+    conn: &Connection,
+    source: &str,
+    games: &[ImportedGame],
+) -> Result<ImportSummary, String> {
+    // A importação inteira é desfeita de uma vez pelo diário
+    let (inserted, updated, errors) = database::in_transaction(conn, |conn| {
+        journal::journaled(conn, "import", Some(source), |conn| {
+            let mut inserted = 0;
+            let mut updated = 0;
+            let mut errors = Vec::new();
+
+            for game in games {
+                if game.name.trim().is_empty() || game.name.len() > constants::MAX_NAME_LENGTH {
+                    errors.push(format!("{} (nome inválido)", game.external_id));
+                    continue;
+                }
+
+                match upsert_imported_game(conn, source, game) {
+                    Ok(true) => inserted += 1,
+                    Ok(false) => updated += 1,
+                    Err(e) => {
+                        warn!("Erro ao importar '{}': {}", game.name, e);
+                        errors.push(format!("{} ({})", game.name, e));
+                    }
+                }
+            }
+
+            Ok((inserted, updated, errors))
+        })
+    })?;
+
+    info!(
+        "Importação '{}': {} novos, {} atualizados, {} erros",
+        source,
+        inserted,
+        updated,
+        errors.len()
+    );
+
+    Ok(ImportSummary {
+        success_count: inserted + updated,
+        error_count: errors.len() as i32,
+        total_processed: games.len() as i32,
+        message: format!(
+            "Importação concluída! {} novos jogos adicionados, {} atualizados.",
+            inserted, updated
+        ),
+        errors,
+    })
+}
+
+/// Jogo já vinculado ao ID externo. Jogos da Steam vindos de outro launcher são
+/// procurados pela cópia da Steam, como no importador dela.
+fn mapped_game_id(
+    conn: &Connection,
+    source: &str,
+    external_id: &str,
+) -> Result<Option<String>, rusqlite::Error> {
+    if let Some(app_id) = importers::steam_app_id(external_id) {
+        if let Some(game_id) =
+            database::owned_game_id(conn, constants::DEFAULT_PLATFORM_STEAM, app_id)?
+        {
+            return Ok(Some(game_id));
+        }
+    }
+
+    let (mapped_source, mapped_id) = importers::external_key(source, external_id);
+    conn.prepare_cached(
+        "SELECT m.game_id FROM game_external_ids m
+         JOIN games g ON g.id = m.game_id
+         WHERE m.source = ?1 AND m.external_id = ?2",
+    )?
+    .query_row(params![mapped_source, mapped_id], |row| row.get(0))
+    .optional()
+}
+
+/// Retorna `true` se o jogo foi inserido e `false` se já existia e foi atualizado
+fn upsert_imported_game(
+    conn: &Connection,
+    source: &str,
+    game: &ImportedGame,
+) -> Result<bool, rusqlite::Error> {
+    let (mapped_source, mapped_id) = importers::external_key(source, &game.external_id);
+    let steam_app = importers::steam_app_id(&game.external_id);
+    // A cópia da Steam usa o AppID puro, como o importador da Steam
+    let (platform, external_key) = match steam_app {
+        Some(app_id) => (constants::DEFAULT_PLATFORM_STEAM, app_id.to_string()),
+        None => (
+            game.platform.as_str(),
+            duplicates::canonical_external_key(mapped_source, mapped_id),
+        ),
+    };
+
+    let existing = mapped_game_id(conn, source, &game.external_id)?;
+
+    if let Some(game_id) = existing {
+        // Mantém edições do usuário; só avança o tempo jogado e preenche lacunas
+        conn.execute(
+            "UPDATE games SET playtime = MAX(IFNULL(playtime, 0), ?1),
+                    cover_url = COALESCE(cover_url, ?2),
+                    rating = COALESCE(rating, ?6),
+                    installed = ?3,
+                    last_played = COALESCE(?4, last_played)
+             WHERE id = ?5",
+            params![
+                game.playtime,
+                game.cover_url,
+                game.installed,
+                game.last_played,
+                game_id,
+                game.rating
+            ],
+        )?;
+        database::record_ownership(conn, &game_id, Some(platform), Some(&external_key))?;
+        return Ok(false);
+    }
+
+    let game_id = match steam_app {
+        Some(app_id) => app_id.to_string(),
+        None => importers::game_id_for(mapped_source, mapped_id),
+    };
+
+    // A linha pode já existir sem o vínculo em `game_external_ids` (importação antiga)
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO games (id, name, genre, platform, cover_url, playtime, rating, favorite, installed, last_played)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            game_id,
+            game.name,
+            game.genre.as_deref().unwrap_or(constants::DEFAULT_GENRE),
+            game.platform,
+            game.cover_url,
+            game.playtime,
+            game.rating,
+            game.favorite,
+            game.installed,
+            game.last_played
+        ],
+    )?;
+
+    conn.execute(
+        "INSERT OR REPLACE INTO game_external_ids (source, external_id, game_id) VALUES (?1, ?2, ?3)",
+        params![mapped_source, mapped_id, game_id],
+    )?;
+
+    database::record_ownership(conn, &game_id, Some(platform), Some(&external_key))?;
+
+    Ok(inserted > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::memory_db;
+
+    fn imported(external_id: &str, name: &str) -> ImportedGame {
+        ImportedGame {
+            external_id: external_id.to_string(),
+            name: name.to_string(),
+            platform: constants::PLATFORM_LUTRIS.to_string(),
+            playtime: 2,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn counts_only_rows_actually_inserted() {
+        let conn = memory_db();
+        let games = vec![imported("celeste", "Celeste"), imported("quake", "Quake")];
+
+        let first = save_imported_games(&conn, "lutris", &games).unwrap();
+        assert!(first.message.contains("2 novos jogos adicionados, 0 atualizados"));
+
+        let second = save_imported_games(&conn, "lutris", &games).unwrap();
+        assert!(second.message.contains("0 novos jogos adicionados, 2 atualizados"));
+    }
+
+    #[test]
+    fn existing_row_without_mapping_is_not_counted_as_new() {
+        let conn = memory_db();
+        conn.execute(
+            "INSERT INTO games (id, name) VALUES ('lutris:celeste', 'Celeste')",
+            [],
+        )
+        .unwrap();
+
+        let summary =
+            save_imported_games(&conn, "lutris", &[imported("celeste", "Celeste")]).unwrap();
+        assert!(summary.message.contains("0 novos jogos adicionados, 1 atualizados"));
+
+        let mapped: String = conn
+            .query_row(
+                "SELECT game_id FROM game_external_ids WHERE source = 'lutris' AND external_id = 'celeste'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(mapped, "lutris:celeste");
+    }
+
+    #[test]
+    fn gog_and_heroic_share_the_gog_product() {
+        let conn = memory_db();
+        let gog_game = ImportedGame {
+            platform: constants::PLATFORM_GOG.to_string(),
+            ..imported("1207658924", "Unreal Tournament 2004")
+        };
+        let heroic_game = ImportedGame {
+            platform: constants::PLATFORM_GOG.to_string(),
+            installed: true,
+            ..imported("gog:1207658924", "Unreal Tournament 2004 Editor's Choice Edition")
+        };
+
+        save_imported_games(&conn, "gog", std::slice::from_ref(&gog_game)).unwrap();
+        let summary = save_imported_games(&conn, "heroic", std::slice::from_ref(&heroic_game)).unwrap();
+        assert!(summary.message.contains("0 novos jogos adicionados, 1 atualizados"));
+
+        let (games, installed): (i64, bool) = conn
+            .query_row("SELECT COUNT(*), MAX(installed) FROM games", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(games, 1);
+        assert!(installed);
+
+        // Na ordem inversa, o jogo do Heroic já nasce com o ID do produto GOG
+        let conn = memory_db();
+        save_imported_games(&conn, "heroic", &[heroic_game]).unwrap();
+        save_imported_games(&conn, "gog", &[gog_game]).unwrap();
+
+        let ids: Vec<String> = conn
+            .prepare("SELECT id FROM games")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(ids, vec!["gog:1207658924"]);
+    }
+
+    #[test]
+    fn preview_classifies_new_duplicate_and_invalid_rows() {
+        let conn = memory_db();
+        conn.execute("INSERT INTO games (id, name) VALUES ('c', 'Celeste')", [])
+            .unwrap();
+
+        let csv = "Game,Platform,Rating\n\
+                   Hades,PC,4\n\
+                   celeste,Switch,5\n\
+                   Hades,PC,4\n\
+                   ,PC,3\n\
+                   !!!,PC,3\n\
+                   Quake,PC,ótimo\n";
+        let records = generic::parse_csv(csv).unwrap();
+        let mapping = generic::preset("backloggd").unwrap();
+        let (preview, new_games) = analyze_records(&conn, "backloggd", &records, &mapping).unwrap();
+
+        assert_eq!(preview.total_rows, 6);
+        let rows = |list: &[PreviewRow]| list.iter().map(|r| r.row).collect::<Vec<_>>();
+        assert_eq!(rows(&preview.new_games), vec![1]);
+        assert_eq!(new_games.len(), 1);
+        assert_eq!(new_games[0].external_id, "hades");
+        // Já na biblioteca (pelo nome) e repetida no próprio arquivo
+        assert_eq!(rows(&preview.duplicates), vec![2, 3]);
+        assert_eq!(preview.duplicates[0].existing_id.as_deref(), Some("c"));
+        assert_eq!(preview.duplicates[1].existing_id, None);
+
+        let invalid: Vec<(usize, Option<&str>)> = preview
+            .invalid
+            .iter()
+            .map(|r| (r.row, r.name.as_deref()))
+            .collect();
+        assert_eq!(invalid, vec![(4, None), (5, Some("!!!")), (6, Some("Quake"))]);
+    }
+
+    #[test]
+    fn steam_games_from_playnite_reuse_the_steam_copy() {
+        let conn = memory_db();
+        conn.execute(
+            "INSERT INTO games (id, name, platform) VALUES ('620', 'Portal 2', 'Steam')",
+            [],
+        )
+        .unwrap();
+        database::record_ownership(&conn, "620", Some(constants::DEFAULT_PLATFORM_STEAM), Some("620"))
+            .unwrap();
+
+        let steam_game = |app_id: &str, name: &str| ImportedGame {
+            platform: constants::DEFAULT_PLATFORM_STEAM.to_string(),
+            ..imported(&format!("steam:{}", app_id), name)
+        };
+        let summary = save_imported_games(
+            &conn,
+            "playnite",
+            &[steam_game("620", "Portal 2"), steam_game("400", "Portal")],
+        )
+        .unwrap();
+        assert!(summary.message.contains("1 novos jogos adicionados, 1 atualizados"));
+
+        // O jogo novo nasce com o AppID como ID, e o importador da Steam o encontra
+        let copies: Vec<(String, String)> = conn
+            .prepare("SELECT game_id, external_id FROM game_ownership ORDER BY game_id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            copies,
+            vec![
+                ("400".to_string(), "400".to_string()),
+                ("620".to_string(), "620".to_string())
+            ]
+        );
+        assert_eq!(
+            database::owned_game_id(&conn, constants::DEFAULT_PLATFORM_STEAM, "400").unwrap(),
+            Some("400".to_string())
+        );
+    }
+}
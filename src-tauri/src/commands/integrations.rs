@@ -1,11 +1,16 @@
+use crate::commands::{metadata, purchases, wishlist};
 use crate::constants;
 use crate::database::AppState;
-use crate::services::{rawg, steam};
+use crate::database::{self, journal};
+use crate::models::SteamClassifyProgress;
+use crate::services::metadata::{MetadataProvider, RawgProvider, PROVIDER_RAWG};
+use crate::services::{rawg, steam, steam_local};
 use crate::storage;
-use tracing::{info, error};
-use rusqlite::params;
+use tracing::{info, error, warn};
+use rusqlite::{params, OptionalExtension};
+use std::path::PathBuf;
 use std::time::Duration;
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Emitter, State};
 use tokio::time::sleep;
 use crate::constants::STEAM_RATE_LIMIT_MS;
 
@@ -20,9 +25,11 @@ pub struct ImportSummary {
 
 #[tauri::command]
 pub async fn import_steam_library(
+    app: AppHandle,
     state: State<'_, AppState>,
     api_key: String,
     steam_id: String,
+    skip_non_games: Option<bool>,
 ) -> Result<String, String> {
     let steam_games = steam::list_steam_games(&api_key, &steam_id).await?;
 
@@ -30,98 +37,456 @@ pub async fn import_steam_library(
         return Ok("Nenhum jogo encontrado na sua biblioteca Steam.".to_string());
     }
 
-    println!("{} jogos encontrados na Steam", steam_games.len());
+    info!("{} jogos encontrados na Steam", steam_games.len());
 
-    let mut games_to_insert = Vec::new();
+    // Grava a biblioteca primeiro; o tipo dos apps novos é consultado depois, um por vez
+    let (inserted_ids, skipped, archived) = {
+        let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
 
-    for game in steam_games {
-        let cover_url = format!(
-            "{}/steam/apps/{}/library_600x900.jpg",
-            constants::STEAM_CDN_URL,
-            game.appid
-        );
+        database::in_transaction(&conn, |conn| {
+            journal::journaled(conn, "import", Some(constants::DEFAULT_PLATFORM_STEAM), |conn| {
+                let mut inserted_ids = Vec::new();
+                let mut skipped = 0;
+
+                for game in &steam_games {
+                    let id = game.appid.to_string();
+
+                    // AppID já mesclado em outro jogo da biblioteca: não recria a linha duplicada
+                    let owner = database::owned_game_id(conn, constants::DEFAULT_PLATFORM_STEAM, &id)
+                        .map_err(|e| format!("Erro ao consultar cópias Steam: {}", e))?;
+                    if owner.is_some_and(|owner| owner != id) {
+                        skipped += 1;
+                        continue;
+                    }
+
+                    let cover_url = format!(
+                        "{}/steam/apps/{}/library_600x900.jpg",
+                        constants::STEAM_CDN_URL,
+                        game.appid
+                    );
+                    let playtime_hours = (game.playtime_forever as f32 / 60.0).round() as i32;
+
+                    match conn.execute(
+                        "INSERT OR IGNORE INTO games (id, name, genre, platform, cover_url, playtime, rating)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        params![
+                            id,
+                            game.name,
+                            constants::DEFAULT_GENRE,
+                            constants::DEFAULT_PLATFORM_STEAM,
+                            cover_url,
+                            playtime_hours,
+                            None::<i32>
+                        ],
+                    ) {
+                        Ok(rows) => {
+                            if rows > 0 {
+                                if let Err(e) = purchases::fill_purchase_from_wishlist(conn, &id, &id) {
+                                    warn!("Erro ao preencher compra de '{}': {}", game.name, e);
+                                }
+                                inserted_ids.push(id.clone());
+                            } else {
+                                skipped += 1;
+                            }
+                            if let Err(e) = database::record_ownership(
+                                conn,
+                                &id,
+                                Some(constants::DEFAULT_PLATFORM_STEAM),
+                                Some(&id),
+                            ) {
+                                warn!("Erro ao registrar cópia Steam de '{}': {}", game.name, e);
+                            }
+                        }
+                        Err(e) => {
+                            warn!("Erro ao inserir jogo '{}': {}", game.name, e);
+                        }
+                    }
+                }
+
+                // Jogos da lista de desejos que agora estão na biblioteca Steam foram comprados
+                let archived = wishlist::archive_owned_wishlist_items(conn).unwrap_or_else(|e| {
+                    warn!("Erro ao arquivar itens comprados da wishlist: {}", e);
+                    0
+                });
+
+                Ok((inserted_ids, skipped, archived))
+            })
+        })?
+    };
+
+    // Tipo e jogo base dos apps novos (e dos que ficaram sem tipo antes); as DLCs são
+    // vinculadas ao jogo base no fim, quando toda a biblioteca já está gravada
+    let classified = classify_pending(&app, state.inner(), false).await?;
+
+    // `skip_non_games` tira da biblioteca os apps novos que não são jogos (trilhas sonoras,
+    // ferramentas...); eles ficam na lixeira e podem ser restaurados
+    let non_games = if skip_non_games.unwrap_or(false) && !inserted_ids.is_empty() {
+        let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+        database::in_transaction(&conn, |conn| {
+            journal::journaled(conn, "import", Some("Apps da Steam que não são jogos"), |conn| {
+                remove_non_games(conn, &inserted_ids)
+            })
+        })?
+    } else {
+        0
+    };
 
-        let playtime_hours = (game.playtime_forever as f32 / 60.0).round() as i32;
+    let count = inserted_ids.len() - non_games;
+    info!(
+        "Import completado: {} inseridos, {} já existiam, {} apps ignorados, {} itens da wishlist arquivados, {} tipos não consultados",
+        count, skipped, non_games, archived, classified.errors
+    );
 
-        games_to_insert.push((
-            game.appid.to_string(),
-            game.name.clone(),
-            constants::DEFAULT_GENRE.to_string(),
-            constants::DEFAULT_PLATFORM_STEAM.to_string(),
-            cover_url,
-            playtime_hours,
+    if archived > 0 {
+        return Ok(format!(
+            "Importação concluída! {} novos jogos adicionados. {} jogos da lista de desejos foram marcados como comprados.",
+            count, archived
         ));
     }
 
-    let count = {
+    Ok(format!(
+        "Importação concluída! {} novos jogos adicionados.",
+        count
+    ))
+}
+
+/// Remove, dentre os jogos informados, os apps de tipos ignorados na importação com suas cópias
+fn remove_non_games(conn: &rusqlite::Connection, ids: &[String]) -> Result<usize, String> {
+    let mut removed = 0;
+    for id in ids {
+        let app_type: Option<String> = conn
+            .query_row("SELECT app_type FROM games WHERE id = ?1", params![id], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?
+            .flatten();
+        if !app_type.is_some_and(|t| constants::SKIPPED_STEAM_APP_TYPES.contains(&t.as_str())) {
+            continue;
+        }
+
+        conn.execute("DELETE FROM game_ownership WHERE game_id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM games WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Aponta para o jogo base as DLCs ainda soltas cujo `parent_app_id` já está na biblioteca
+/// (pela cópia Steam, então também encontra o jogo base depois de uma mesclagem)
+pub(crate) fn relink_dlcs(conn: &rusqlite::Connection) -> rusqlite::Result<usize> {
+    conn.execute(
+        "UPDATE games SET parent_game_id = (
+             SELECT o.game_id FROM game_ownership o
+             WHERE o.platform = ?1 AND o.external_id = CAST(games.parent_app_id AS TEXT)
+               AND o.game_id != games.id
+             LIMIT 1)
+         WHERE parent_app_id IS NOT NULL AND parent_game_id IS NULL
+           AND EXISTS (
+             SELECT 1 FROM game_ownership o
+             WHERE o.platform = ?1 AND o.external_id = CAST(games.parent_app_id AS TEXT)
+               AND o.game_id != games.id)",
+        params![constants::DEFAULT_PLATFORM_STEAM],
+    )
+}
+
+#[derive(serde::Serialize)]
+pub struct ClassifySummary {
+    pub games_classified: i32,
+    pub wishlist_classified: i32,
+    pub dlcs_linked: i32,
+    pub errors: i32,
+    pub message: String,
+}
+
+/// Consulta na loja o tipo (jogo, DLC, trilha sonora...) dos apps Steam ainda não classificados
+/// e aninha as DLCs sob o jogo base quando ele está na biblioteca.
+#[tauri::command]
+pub async fn classify_steam_apps(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ClassifySummary, String> {
+    classify_pending(&app, state.inner(), true).await
+}
+
+/// Classifica os jogos da biblioteca (e, se pedido, os itens da lista de desejos) ainda sem tipo,
+/// um app por vez no limite de requisições da loja, emitindo o progresso no evento
+/// `steam-classify-progress`. No fim, vincula as DLCs cujo jogo base está na biblioteca.
+async fn classify_pending(
+    app: &AppHandle,
+    state: &AppState,
+    include_wishlist: bool,
+) -> Result<ClassifySummary, String> {
+    // (tabela, id da linha, AppID)
+    let pending: Vec<(&str, String, u32)> = {
         let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
 
-        // Inicia transação
-        conn.execute("BEGIN TRANSACTION", [])
-            .map_err(|e| format!("Erro ao iniciar transação: {}", e))?;
-
-        let mut inserted = 0;
-        let mut skipped = 0;
-
-        for (id, name, genre, platform, cover_url, playtime) in games_to_insert {
-            match conn.execute(
-                "INSERT OR IGNORE INTO games (id, name, genre, platform, cover_url, playtime, rating)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-                params![id, name, genre, platform, cover_url, playtime, None::<i32>],
-            ) {
-                Ok(rows) => {
-                    if rows > 0 {
-                        inserted += rows;
-                    } else {
-                        skipped += 1;
-                    }
-                }
-                Err(e) => {
-                    eprintln!("[WARN] Erro ao inserir jogo '{}': {}", name, e);
+        let mut stmt = conn
+            .prepare(
+                "SELECT g.id, o.external_id FROM games g
+                 JOIN game_ownership o ON o.game_id = g.id
+                 WHERE o.platform = ?1 AND o.external_id IS NOT NULL
+                   AND (g.app_type IS NULL
+                        OR (g.app_type = 'dlc' AND g.parent_app_id IS NULL AND g.parent_game_id IS NULL))",
+            )
+            .map_err(|e| e.to_string())?;
+        let games = stmt
+            .query_map(params![constants::DEFAULT_PLATFORM_STEAM], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        let mut pending: Vec<_> = games
+            .into_iter()
+            .filter_map(|(id, app_id)| app_id.parse::<u32>().ok().map(|app_id| ("games", id, app_id)))
+            .collect();
+
+        if include_wishlist {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, steam_app_id FROM wishlist
+                     WHERE app_type IS NULL AND steam_app_id IS NOT NULL",
+                )
+                .map_err(|e| e.to_string())?;
+            let wishlist_items = stmt
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)?)))
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            pending.extend(
+                wishlist_items
+                    .into_iter()
+                    .map(|(id, app_id)| ("wishlist", id, app_id as u32)),
+            );
+        }
+
+        pending
+    };
+
+    let mut games_classified = 0;
+    let mut wishlist_classified = 0;
+    let mut errors = 0;
+    let total = pending.len();
+
+    for (done, (table, id, app_id)) in pending.into_iter().enumerate() {
+        match steam::fetch_app_type(app_id).await {
+            Ok(Some(info)) => {
+                let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+                conn.execute(
+                    &format!("UPDATE {table} SET app_type = ?1, parent_app_id = ?2 WHERE id = ?3"),
+                    params![info.app_type, info.parent_app_id, id],
+                )
+                .map_err(|e| e.to_string())?;
+                if table == "games" {
+                    games_classified += 1;
+                } else {
+                    wishlist_classified += 1;
                 }
             }
+            Ok(None) => {}
+            Err(e) => {
+                error!("Erro ao consultar tipo do app {}: {}", app_id, e);
+                errors += 1;
+            }
         }
 
-        conn.execute("COMMIT", []).map_err(|e| {
-            let _ = conn.execute("ROLLBACK", []);
-            format!("Erro ao commitar transação: {}", e)
-        })?;
+        let event = SteamClassifyProgress { done: done + 1, total };
+        if let Err(e) = app.emit(constants::EVENT_STEAM_CLASSIFY_PROGRESS, &event) {
+            warn!("Falha ao emitir progresso da classificação: {}", e);
+        }
 
-        println!(
-            "Import completado: {} inseridos, {} já existiam",
-            inserted, skipped
-        );
+        sleep(Duration::from_millis(STEAM_RATE_LIMIT_MS)).await;
+    }
 
-        inserted
+    // Inclui DLCs classificadas antes cujo jogo base chegou depois
+    let dlcs_linked = {
+        let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+        relink_dlcs(&conn).map_err(|e| e.to_string())? as i32
     };
 
-    Ok(format!(
-        "Importação concluída! {} novos jogos adicionados.",
-        count
-    ))
+    info!(
+        "Classificação Steam: {} jogos, {} itens da wishlist, {} DLCs vinculadas",
+        games_classified, wishlist_classified, dlcs_linked
+    );
+
+    Ok(ClassifySummary {
+        games_classified,
+        wishlist_classified,
+        dlcs_linked,
+        errors,
+        message: format!(
+            "{} jogos e {} itens da lista de desejos classificados. {} DLCs vinculadas ao jogo base.",
+            games_classified, wishlist_classified, dlcs_linked
+        ),
+    })
+}
+
+#[derive(serde::Serialize)]
+pub struct LocalScanSummary {
+    pub steam_root: String,
+    pub installed_count: i32,
+    pub new_games: i32,
+    pub uninstalled_count: i32,
+    pub games: Vec<steam_local::InstalledGame>,
 }
 
+/// Varre a instalação local da Steam (sem API key) e marca os jogos instalados na biblioteca.
+/// Jogos ainda não existentes são adicionados; jogos Steam que sumiram do disco são desmarcados.
 #[tauri::command]
-pub async fn enrich_library(state: State<'_, AppState>) -> Result<ImportSummary, String> {
+pub fn scan_local_steam_library(
+    state: State<AppState>,
+    steam_root: Option<String>,
+) -> Result<LocalScanSummary, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    // Ordem: parâmetro explícito -> configuração salva -> detecção automática
+    let root = match steam_root.filter(|r| !r.trim().is_empty()) {
+        Some(r) => PathBuf::from(r.trim()),
+        None => match database::get_setting(&conn, constants::SETTING_STEAM_ROOT)? {
+            Some(saved) => PathBuf::from(saved),
+            None => steam_local::detect_steam_root()
+                .ok_or("Instalação da Steam não encontrada. Informe o caminho manualmente.")?,
+        },
+    };
+
+    let installed = steam_local::scan_installed_games(&root)?;
+    info!(
+        "Scanner local: {} jogos instalados encontrados em {:?}",
+        installed.len(),
+        root
+    );
+
+    let (new_games, uninstalled_count) = database::in_transaction(&conn, |conn| {
+        journal::journaled(conn, "scan_local_steam_library", None, |conn| {
+            save_local_scan(conn, &installed)
+                .map_err(|e| format!("Erro ao salvar jogos instalados: {}", e))
+        })
+    })?;
+
+    Ok(LocalScanSummary {
+        steam_root: root.to_string_lossy().to_string(),
+        installed_count: installed.len() as i32,
+        new_games,
+        uninstalled_count,
+        games: installed,
+    })
+}
+
+/// Grava o resultado da varredura; retorna os jogos novos e os que deixaram de estar instalados
+fn save_local_scan(
+    conn: &rusqlite::Connection,
+    installed: &[steam_local::InstalledGame],
+) -> rusqlite::Result<(i32, i32)> {
+    // Só os jogos com cópia na Steam: a instalação de outras lojas vem de outros importadores
+    let steam_owned = "SELECT game_id FROM game_ownership WHERE platform = ?1";
+
+    let previously_installed: Vec<String> = conn
+        .prepare(&format!(
+            "SELECT id FROM games WHERE installed = 1 AND id IN ({})",
+            steam_owned
+        ))?
+        .query_map([constants::DEFAULT_PLATFORM_STEAM], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Reinicia o estado para refletir exatamente o que está no disco agora
+    conn.execute(
+        &format!(
+            "UPDATE games SET installed = 0 WHERE installed = 1 AND id IN ({})",
+            steam_owned
+        ),
+        [constants::DEFAULT_PLATFORM_STEAM],
+    )?;
+    conn.execute(
+        &format!("DELETE FROM game_installs WHERE game_id IN ({})", steam_owned),
+        [constants::DEFAULT_PLATFORM_STEAM],
+    )?;
+
+    let mut new_games = 0;
+    let mut installed_ids = Vec::new();
+
+    for game in installed {
+        let appid = game.appid.to_string();
+        let id = database::owned_game_id(conn, constants::DEFAULT_PLATFORM_STEAM, &appid)?
+            .unwrap_or(appid);
+        let cover_url = format!(
+            "{}/steam/apps/{}/library_600x900.jpg",
+            constants::STEAM_CDN_URL,
+            game.appid
+        );
+
+        new_games += conn.execute(
+            "INSERT OR IGNORE INTO games (id, name, genre, platform, cover_url, playtime, rating)
+             VALUES (?1, ?2, ?3, ?4, ?5, 0, NULL)",
+            params![
+                id,
+                game.name,
+                constants::DEFAULT_GENRE,
+                constants::DEFAULT_PLATFORM_STEAM,
+                cover_url
+            ],
+        )? as i32;
+
+        database::record_ownership(
+            conn,
+            &id,
+            Some(constants::DEFAULT_PLATFORM_STEAM),
+            Some(&game.appid.to_string()),
+        )?;
+        conn.execute("UPDATE games SET installed = 1 WHERE id = ?1", params![id])?;
+        conn.execute(
+            "INSERT OR REPLACE INTO game_installs (game_id, install_path, size_on_disk, last_updated, scanned_at)
+             VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP)",
+            params![id, game.install_path, game.size_on_disk, game.last_updated],
+        )?;
+        installed_ids.push(id);
+    }
+
+    let uninstalled = previously_installed
+        .iter()
+        .filter(|id| !installed_ids.contains(id))
+        .count() as i32;
+
+    // Um jogo base instalado agora pode ser o que faltava para DLCs já importadas
+    relink_dlcs(conn)?;
+
+    Ok((new_games, uninstalled))
+}
+
+/// Enriquece os jogos sem gênero ou sem metadados usando todas as fontes (loja Steam, RAWG e
+/// substituições locais). Jogos de qualquer plataforma entram, inclusive os adicionados à mão.
+#[tauri::command]
+pub async fn enrich_library(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ImportSummary, String> {
     info!("Iniciando processo de enriquecimento de biblioteca...");
 
-    let games_to_update = {
+    let (games_to_update, providers) = {
         let conn = state.db.lock().map_err(|_| "Mutex error")?;
         let mut stmt = conn
-            .prepare("SELECT id, name FROM games WHERE genre = ?1 AND platform = ?2")
+            .prepare(&format!(
+                "SELECT id, name FROM games
+                 WHERE (genre IS NULL OR genre = '' OR genre = ?1
+                        OR id NOT IN (SELECT game_id FROM game_metadata))
+                   AND id NOT IN (SELECT game_id FROM metadata_misses
+                                  WHERE missed_at > datetime('now', ?2))
+                   AND {}",
+                database::GAMES_ONLY_FILTER
+            ))
             .map_err(|e| e.to_string())?;
 
-        let mut rows = stmt
-            .query([constants::DEFAULT_GENRE, constants::DEFAULT_PLATFORM_STEAM])
+        let retry_after = format!("-{} days", constants::METADATA_MISS_RETRY_DAYS);
+        let games = stmt
+            .query_map(params![constants::DEFAULT_GENRE, retry_after], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
             .map_err(|e| e.to_string())?;
 
-        let mut games = Vec::new();
-        while let Some(row) = rows.next().map_err(|e| e.to_string())? {
-            let id: String = row.get(0).map_err(|e| e.to_string())?;
-            let name: String = row.get(1).map_err(|e| e.to_string())?;
-            games.push((id, name));
-        }
-        games
+        (games, metadata::build_providers(&app_handle, &conn)?)
     };
 
     let total = games_to_update.len();
@@ -137,46 +502,44 @@ pub async fn enrich_library(state: State<'_, AppState>) -> Result<ImportSummary,
 
     info!("Encontrados {} jogos com metadados pendentes.", total);
 
-    let mut batch_updates: Vec<(String, String)> = Vec::new();
     let mut success_count = 0;
     let mut failed_games = Vec::new();
 
-    // Loop de processamento
-    for (i, (id_str, name)) in games_to_update.iter().enumerate() {
-        if let Ok(app_id) = id_str.parse::<u32>() {
-            match steam::fetch_game_metadata(app_id).await {
-                Ok(metadata) => {
-                    batch_updates.push((id_str.clone(), metadata.genre.clone()));
-                    // Log menos verboso no console, detalhado no arquivo
-                    info!("Metadata OK ({}/{}): {} -> {}", i+1, total, name, metadata.genre);
-                    success_count += 1;
-                }
-                Err(e) => {
-                    error!("Falha metadata ({}/{}): {} - Erro: {}", i+1, total, name, e);
-                    failed_games.push(format!("{} ({})", name, e));
-                }
+    for (i, (id, name)) in games_to_update.iter().enumerate() {
+        let outcome = metadata::enrich_game(&state, &providers, id).await;
+
+        // Só "nenhuma fonte encontrou" fica registrado; erros de rede tentam de novo na próxima vez
+        if let Ok(found) = &outcome {
+            let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+            let sql = if found.is_some() {
+                "DELETE FROM metadata_misses WHERE game_id = ?1"
+            } else {
+                "INSERT OR REPLACE INTO metadata_misses (game_id, missed_at) VALUES (?1, CURRENT_TIMESTAMP)"
+            };
+            if let Err(e) = conn.execute(sql, params![id]) {
+                warn!("Erro ao registrar busca de metadados de '{}': {}", name, e);
             }
-
-            sleep(Duration::from_millis(STEAM_RATE_LIMIT_MS)).await;
         }
-    }
-
-    // Salvar no Banco
-    if !batch_updates.is_empty() {
-        let conn = state.db.lock().map_err(|_| "Mutex error ao salvar")?;
-        conn.execute("BEGIN TRANSACTION", []).map_err(|e| e.to_string())?;
 
-        for (id, genre) in batch_updates {
-            let _ = conn.execute(
-                "UPDATE games SET genre = ?1 WHERE id = ?2",
-                rusqlite::params![genre, id],
-            );
+        match outcome {
+            Ok(Some(meta)) => {
+                info!("Metadata OK ({}/{}): {} ({} campos)", i + 1, total, name, meta.sources.len());
+                success_count += 1;
+            }
+            Ok(None) => {
+                failed_games.push(format!("{} (nenhuma fonte encontrou o jogo)", name));
+            }
+            Err(e) => {
+                error!("Falha metadata ({}/{}): {} - Erro: {}", i + 1, total, name, e);
+                failed_games.push(format!("{} ({})", name, e));
+            }
         }
 
-        conn.execute("COMMIT", []).map_err(|e| e.to_string())?;
-        info!("Processamento concluído: {} sucessos e {} falhas.", success_count, failed_games.len());
+        sleep(Duration::from_millis(STEAM_RATE_LIMIT_MS)).await;
     }
 
+    info!("Processamento concluído: {} sucessos e {} falhas.", success_count, failed_games.len());
+
     let summary = ImportSummary {
         success_count,
         error_count: failed_games.len() as i32,
@@ -188,14 +551,19 @@ pub async fn enrich_library(state: State<'_, AppState>) -> Result<ImportSummary,
     Ok(summary)
 }
 
-fn get_api_key(app_handle: &tauri::AppHandle) -> Result<String, String> {
+pub(crate) fn get_api_key(app_handle: &tauri::AppHandle) -> Result<String, String> {
     storage::get_secret(app_handle, "rawg_api_key")
 }
 
+/// Detalhes do jogo na RAWG. Com `game_id`, usa o ID da RAWG já associado ao jogo ou
+/// resolve pela busca e salva o ID para as próximas consultas.
 #[tauri::command]
 pub async fn fetch_game_details(
     app_handle: AppHandle,
+    state: State<'_, AppState>,
     query: String,
+    game_id: Option<String>,
+    year: Option<i32>,
 ) -> Result<rawg::GameDetails, String> {
     let api_key = get_api_key(&app_handle)?;
 
@@ -203,7 +571,146 @@ pub async fn fetch_game_details(
         return Err("API Key da RAWG não configurada.".to_string());
     }
 
-    rawg::fetch_game_details(&api_key, query).await
+    let (stored_id, platform, year) = match &game_id {
+        Some(id) => {
+            let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+            let (stored_id, platform) = conn
+                .query_row(
+                    "SELECT rawg_id, platform FROM games WHERE id = ?1",
+                    params![id],
+                    |row| Ok((row.get::<_, Option<i32>>(0)?, row.get::<_, Option<String>>(1)?)),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?
+                .unwrap_or((None, None));
+            let year = match year {
+                Some(year) => Some(year),
+                None => metadata::stored_release_year(&conn, id).map_err(|e| e.to_string())?,
+            };
+            (stored_id, platform, year)
+        }
+        None => (None, None, year),
+    };
+
+    if let Some(rawg_id) = stored_id {
+        return rawg::fetch_game_details(&api_key, rawg_id).await;
+    }
+
+    let hints = rawg::MatchHints { year, platform };
+    let best = rawg::find_best_match(&api_key, &query, &hints)
+        .await?
+        .ok_or("Jogo não encontrado na RAWG")?;
+
+    info!("RAWG: '{}' associado a '{}' (#{}, {:.2})", query, best.name, best.id, best.score);
+
+    if let Some(id) = &game_id {
+        let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+        // Não sobrescreve uma escolha manual feita enquanto a busca rodava
+        conn.execute(
+            "UPDATE games SET rawg_id = ?1 WHERE id = ?2 AND rawg_id IS NULL",
+            params![best.id, id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    rawg::fetch_game_details(&api_key, best.id).await
+}
+
+/// Candidatos da RAWG para o usuário escolher manualmente, do mais para o menos provável.
+/// Com `game_id`, o ano de lançamento já conhecido (Steam ou substituição local) desempata.
+#[tauri::command]
+pub async fn search_rawg_matches(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    query: String,
+    year: Option<i32>,
+    platform: Option<String>,
+    game_id: Option<String>,
+) -> Result<Vec<rawg::RawgMatch>, String> {
+    let api_key = get_api_key(&app_handle)?;
+
+    if api_key.is_empty() {
+        return Err("API Key da RAWG não configurada.".to_string());
+    }
+
+    let year = match (year, &game_id) {
+        (None, Some(id)) => {
+            let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+            metadata::stored_release_year(&conn, id).map_err(|e| e.to_string())?
+        }
+        (year, _) => year,
+    };
+
+    let candidates = rawg::search_games(&api_key, &query).await?;
+    Ok(rawg::rank_candidates(
+        &query,
+        candidates,
+        &rawg::MatchHints { year, platform },
+    ))
+}
+
+/// Corrige manualmente o jogo da RAWG associado. `None` limpa a associação (nova busca automática).
+/// O registro antigo da RAWG sai junto e os metadados são mesclados de novo sem ele; com um
+/// novo ID, os dados dele são buscados em seguida.
+#[tauri::command]
+pub async fn set_game_rawg_id(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    game_id: String,
+    rawg_id: Option<i32>,
+) -> Result<(), String> {
+    {
+        let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+        database::in_transaction(&conn, |conn| clear_rawg_match(conn, &game_id, rawg_id))?;
+    }
+
+    let Some(rawg_id) = rawg_id else {
+        return Ok(());
+    };
+    let api_key = get_api_key(&app_handle).unwrap_or_default();
+    if api_key.is_empty() {
+        return Ok(());
+    }
+
+    let providers: Vec<Box<dyn MetadataProvider>> = vec![Box::new(RawgProvider { api_key })];
+    if let Err(e) = metadata::enrich_game(&state, &providers, &game_id).await {
+        warn!("Erro ao buscar o jogo #{} da RAWG para '{}': {}", rawg_id, game_id, e);
+    }
+
+    Ok(())
+}
+
+/// Troca o ID da RAWG do jogo e descarta o que veio da associação anterior
+fn clear_rawg_match(
+    conn: &rusqlite::Connection,
+    game_id: &str,
+    rawg_id: Option<i32>,
+) -> Result<(), String> {
+    let updated = conn
+        .execute(
+            "UPDATE games SET rawg_id = ?1 WHERE id = ?2",
+            params![rawg_id, game_id],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err("Jogo não encontrado".to_string());
+    }
+
+    conn.execute(
+        "DELETE FROM metadata_sources WHERE game_id = ?1 AND provider = ?2",
+        params![game_id, PROVIDER_RAWG],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM metadata_misses WHERE game_id = ?1", params![game_id])
+        .map_err(|e| e.to_string())?;
+
+    // Sem outras fontes não há o que mesclar: os metadados antigos eram só da RAWG
+    if metadata::remerge(conn, game_id)?.is_none() {
+        conn.execute("DELETE FROM game_metadata WHERE game_id = ?1", params![game_id])
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
 }
 
 #[tauri::command]
@@ -216,3 +723,103 @@ pub async fn get_trending_games(app_handle: AppHandle) -> Result<Vec<rawg::RawgG
 pub async fn get_upcoming_games(api_key: String) -> Result<Vec<rawg::RawgGame>, String> {
     rawg::fetch_upcoming_games(&api_key).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::memory_db;
+
+    fn parent_of(conn: &rusqlite::Connection, id: &str) -> Option<String> {
+        conn.query_row("SELECT parent_game_id FROM games WHERE id = ?1", params![id], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn dlc_imported_before_its_base_game_is_linked_when_the_base_arrives() {
+        let conn = memory_db();
+        conn.execute(
+            "INSERT INTO games (id, name, app_type, parent_app_id) VALUES ('2', 'Expansão', 'dlc', 1)",
+            [],
+        )
+        .unwrap();
+        database::record_ownership(&conn, "2", Some(constants::DEFAULT_PLATFORM_STEAM), Some("2"))
+            .unwrap();
+
+        assert_eq!(relink_dlcs(&conn).unwrap(), 0);
+        assert_eq!(parent_of(&conn, "2"), None);
+
+        // Jogo base mesclado em outro: o vínculo segue a cópia Steam
+        conn.execute("INSERT INTO games (id, name) VALUES ('base', 'Jogo')", [])
+            .unwrap();
+        database::record_ownership(&conn, "base", Some(constants::DEFAULT_PLATFORM_STEAM), Some("1"))
+            .unwrap();
+
+        assert_eq!(relink_dlcs(&conn).unwrap(), 1);
+        assert_eq!(parent_of(&conn, "2").as_deref(), Some("base"));
+    }
+
+    fn metadata_of(conn: &rusqlite::Connection, id: &str) -> Option<(Option<i32>, Option<String>)> {
+        conn.query_row(
+            "SELECT rawg_id, description FROM game_metadata WHERE game_id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .unwrap()
+    }
+
+    #[test]
+    fn changing_the_rawg_match_drops_the_old_rawg_data() {
+        let conn = memory_db();
+        conn.execute_batch(
+            r#"INSERT INTO games (id, name, rawg_id) VALUES ('g', 'Hades', 10), ('h', 'Celeste', 20);
+               INSERT INTO metadata_sources (game_id, provider, external_id, data) VALUES
+                 ('g', 'rawg', '10', '{"description": "Outro jogo", "website": "https://errado"}'),
+                 ('g', 'steam', '1145360', '{"description": "Roguelike"}'),
+                 ('h', 'rawg', '20', '{"description": "Plataforma"}');
+               INSERT INTO game_metadata (game_id, rawg_id, description) VALUES
+                 ('g', 10, 'Outro jogo'), ('h', 20, 'Plataforma');
+               INSERT INTO metadata_misses (game_id) VALUES ('g');"#,
+        )
+        .unwrap();
+
+        database::in_transaction(&conn, |conn| clear_rawg_match(conn, "g", Some(11))).unwrap();
+        database::in_transaction(&conn, |conn| clear_rawg_match(conn, "h", None)).unwrap();
+
+        let providers: Vec<String> = conn
+            .prepare("SELECT provider FROM metadata_sources ORDER BY game_id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(providers, vec!["steam"]);
+        assert_eq!(metadata_of(&conn, "g"), Some((None, Some("Roguelike".to_string()))));
+        assert_eq!(metadata_of(&conn, "h"), None);
+        assert_eq!(database::count_rows(&conn, "metadata_misses").unwrap(), 0);
+
+        let rawg_id: Option<i32> = conn
+            .query_row("SELECT rawg_id FROM games WHERE id = 'g'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rawg_id, Some(11));
+        assert!(database::in_transaction(&conn, |conn| clear_rawg_match(conn, "x", None)).is_err());
+    }
+
+    #[test]
+    fn manual_links_are_kept() {
+        let conn = memory_db();
+        conn.execute_batch(
+            "INSERT INTO games (id, name) VALUES ('1', 'Jogo'), ('outro', 'Outro');
+             INSERT INTO games (id, name, app_type, parent_app_id, parent_game_id)
+             VALUES ('2', 'Expansão', 'dlc', 1, 'outro');",
+        )
+        .unwrap();
+        database::record_ownership(&conn, "1", Some(constants::DEFAULT_PLATFORM_STEAM), Some("1"))
+            .unwrap();
+
+        assert_eq!(relink_dlcs(&conn).unwrap(), 0);
+        assert_eq!(parent_of(&conn, "2").as_deref(), Some("outro"));
+    }
+}
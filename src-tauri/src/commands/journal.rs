@@ -0,0 +1,150 @@
+use crate::commands::backup;
+use crate::constants;
+use crate::database::{self, journal, AppState};
+use crate::models::{JournalEntry, TrashItem, UndoResult};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct JournalSettings {
+    /// Dias que as alterações (e os itens da lixeira) ficam guardadas
+    pub retention_days: u32,
+}
+
+/// Alterações mais recentes primeiro, paginadas
+#[tauri::command]
+pub fn get_journal(
+    state: State<AppState>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> Result<Vec<JournalEntry>, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    journal::list_entries(&conn, limit.unwrap_or(100), offset.unwrap_or(0))
+}
+
+/// Desfaz a última operação registrada (um lote inteiro, como uma importação, ou uma alteração avulsa)
+#[tauri::command]
+pub fn undo_last(state: State<AppState>) -> Result<UndoResult, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    let result = database::in_transaction(&conn, journal::undo_last)?;
+
+    info!(
+        "Desfeito: {} ({} alterações)",
+        result.description.as_deref().unwrap_or(&result.operation),
+        result.changes
+    );
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub fn list_trash(state: State<AppState>) -> Result<Vec<TrashItem>, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    journal::list_trash(&conn)
+}
+
+/// Restaura um item da lixeira; retorna quantas linhas voltaram
+#[tauri::command]
+pub fn restore_from_trash(state: State<AppState>, entry_id: i64) -> Result<usize, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    database::in_transaction(&conn, |conn| journal::restore_from_trash(conn, entry_id))
+}
+
+/// Torna definitivas as exclusões da lixeira (não podem mais ser desfeitas)
+#[tauri::command]
+pub fn empty_trash(state: State<AppState>) -> Result<usize, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    let removed = journal::empty_trash(&conn)?;
+    info!("Lixeira esvaziada: {} entradas do diário removidas", removed);
+    Ok(removed)
+}
+
+/// Exporta o diário inteiro em JSON, para investigar problemas de sincronização ou importação
+#[tauri::command]
+pub fn export_journal(state: State<AppState>, file_path: String) -> Result<usize, String> {
+    let conn = backup::open_reader(&state)?;
+    let target = PathBuf::from(&file_path);
+    let partial = PathBuf::from(format!("{}.partial", file_path));
+
+    let result = (|| {
+        let file = File::create(&partial).map_err(|e| format!("Erro ao salvar arquivo: {}", e))?;
+        let mut writer = BufWriter::new(file);
+        let write_err = |e: std::io::Error| format!("Erro ao salvar arquivo: {}", e);
+
+        // Lista JSON gravada entrada por entrada
+        writer.write_all(b"[").map_err(write_err)?;
+        let mut first = true;
+        let count = journal::for_each_entry(&conn, |entry| {
+            if !first {
+                writer.write_all(b",\n").map_err(write_err)?;
+            }
+            first = false;
+            serde_json::to_writer(&mut writer, &entry).map_err(|e| e.to_string())
+        })?;
+        writer.write_all(b"]\n").map_err(write_err)?;
+        writer.flush().map_err(write_err)?;
+        Ok::<usize, String>(count)
+    })();
+
+    let count = match result {
+        Ok(count) => count,
+        Err(e) => {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+    };
+    fs::rename(&partial, &target).map_err(|e| format!("Erro ao salvar arquivo: {}", e))?;
+
+    info!("Diário de alterações exportado: {} entradas", count);
+    Ok(count)
+}
+
+#[tauri::command]
+pub fn get_journal_settings(state: State<AppState>) -> Result<JournalSettings, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    Ok(JournalSettings {
+        retention_days: journal::retention_days(&conn),
+    })
+}
+
+#[tauri::command]
+pub fn set_journal_settings(
+    state: State<AppState>,
+    settings: JournalSettings,
+) -> Result<(), String> {
+    if settings.retention_days == 0 {
+        return Err("Guarde as alterações por pelo menos 1 dia".to_string());
+    }
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    database::set_setting(
+        &conn,
+        constants::SETTING_JOURNAL_RETENTION_DAYS,
+        &settings.retention_days.to_string(),
+    )
+}
+
+/// Apaga periodicamente as entradas do diário mais antigas que a retenção
+pub(crate) fn start_purge_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(constants::JOURNAL_PURGE_INTERVAL_SECS)).await;
+
+            let result = {
+                let state = app.state::<AppState>();
+                let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex".to_string());
+                conn.and_then(|conn| journal::purge_expired(&conn, journal::retention_days(&conn)))
+            };
+
+            match result {
+                Ok(0) => {}
+                Ok(purged) => info!("Diário de alterações: {} entradas expiradas removidas", purged),
+                Err(e) => warn!("Limpeza do diário de alterações falhou: {}", e),
+            }
+        }
+    });
+}
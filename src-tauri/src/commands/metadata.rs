@@ -0,0 +1,484 @@
+use crate::commands::integrations;
+use crate::constants;
+use crate::database::{self, journal, AppState};
+use crate::models::GameMetadata;
+use crate::services::metadata::{
+    self, GameRef, LocalOverrideProvider, MergePolicy, MetadataFields, MetadataProvider,
+    RawgProvider, SteamStoreProvider,
+};
+use crate::services::rawg;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::{AppHandle, State};
+use tracing::{info, warn};
+
+const METADATA_COLUMNS: &str = "game_id, rawg_id, description, website, metacritic, release_date, background_image, developers, publishers, tags, sources, updated_at";
+
+/// Registro salvo de uma fonte de metadados para um jogo
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetadataSource {
+    pub game_id: String,
+    pub provider: String,
+    pub external_id: Option<String>,
+    pub fields: MetadataFields,
+    #[serde(default)]
+    pub fetched_at: Option<String>,
+}
+
+fn parse_json<T: serde::de::DeserializeOwned + Default>(raw: Option<String>) -> T {
+    raw.and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn map_metadata_row(row: &rusqlite::Row) -> rusqlite::Result<GameMetadata> {
+    Ok(GameMetadata {
+        game_id: row.get(0)?,
+        rawg_id: row.get(1)?,
+        description: row.get(2)?,
+        website: row.get(3)?,
+        metacritic: row.get(4)?,
+        release_date: row.get(5)?,
+        background_image: row.get(6)?,
+        developers: parse_json(row.get(7)?),
+        publishers: parse_json(row.get(8)?),
+        tags: parse_json(row.get(9)?),
+        sources: parse_json(row.get(10)?),
+        updated_at: row.get(11)?,
+    })
+}
+
+fn map_source_row(row: &rusqlite::Row) -> rusqlite::Result<MetadataSource> {
+    Ok(MetadataSource {
+        game_id: row.get(0)?,
+        provider: row.get(1)?,
+        external_id: row.get(2)?,
+        fields: parse_json(row.get(3)?),
+        fetched_at: row.get(4)?,
+    })
+}
+
+pub(crate) fn load_metadata(
+    conn: &Connection,
+    game_id: &str,
+) -> Result<Option<GameMetadata>, String> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM game_metadata WHERE game_id = ?1",
+            METADATA_COLUMNS
+        ),
+        params![game_id],
+        map_metadata_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Registros das fontes; de um jogo ou de todos quando `game_id` é None
+pub(crate) fn fetch_sources(
+    conn: &Connection,
+    game_id: Option<&str>,
+) -> Result<Vec<MetadataSource>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT game_id, provider, external_id, data, fetched_at FROM metadata_sources
+             WHERE ?1 IS NULL OR game_id = ?1 ORDER BY game_id, provider",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![game_id], map_source_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+pub(crate) fn save_metadata(conn: &Connection, meta: &GameMetadata) -> rusqlite::Result<()> {
+    let to_json = |list: &Vec<String>| serde_json::to_string(list).unwrap_or_else(|_| "[]".into());
+
+    conn.execute(
+        "INSERT OR REPLACE INTO game_metadata
+            (game_id, rawg_id, description, website, metacritic, release_date, background_image,
+             developers, publishers, tags, sources, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, COALESCE(?12, CURRENT_TIMESTAMP))",
+        params![
+            meta.game_id,
+            meta.rawg_id,
+            meta.description,
+            meta.website,
+            meta.metacritic,
+            meta.release_date,
+            meta.background_image,
+            to_json(&meta.developers),
+            to_json(&meta.publishers),
+            to_json(&meta.tags),
+            serde_json::to_string(&meta.sources).unwrap_or_else(|_| "{}".into()),
+            meta.updated_at
+        ],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn save_source(conn: &Connection, source: &MetadataSource) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO metadata_sources (game_id, provider, external_id, data, fetched_at)
+         VALUES (?1, ?2, ?3, ?4, COALESCE(?5, CURRENT_TIMESTAMP))",
+        params![
+            source.game_id,
+            source.provider,
+            source.external_id,
+            serde_json::to_string(&source.fields).unwrap_or_else(|_| "{}".into()),
+            source.fetched_at
+        ],
+    )?;
+    Ok(())
+}
+
+fn load_policy(conn: &Connection) -> Result<MergePolicy, String> {
+    Ok(database::get_setting(conn, constants::SETTING_METADATA_PRIORITY)?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+fn load_game_ref(conn: &Connection, game_id: &str) -> Result<GameRef, String> {
+    let (name, platform, rawg_id) = conn
+        .query_row(
+            "SELECT name, platform, rawg_id FROM games WHERE id = ?1",
+            params![game_id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<i32>>(2)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or("Jogo não encontrado")?;
+
+    let steam_app_id: Option<String> = conn
+        .query_row(
+            "SELECT external_id FROM game_ownership
+             WHERE game_id = ?1 AND platform = ?2 AND external_id IS NOT NULL
+             ORDER BY id LIMIT 1",
+            params![game_id, constants::DEFAULT_PLATFORM_STEAM],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    Ok(GameRef {
+        id: game_id.to_string(),
+        name,
+        platform,
+        steam_app_id: steam_app_id.and_then(|id| id.parse().ok()),
+        rawg_id,
+        release_year: stored_release_year(conn, game_id).map_err(|e| e.to_string())?,
+    })
+}
+
+/// Ano de lançamento já conhecido por uma fonte que não é a RAWG (substituição local ou Steam)
+pub(crate) fn stored_release_year(conn: &Connection, game_id: &str) -> rusqlite::Result<Option<i32>> {
+    let date: Option<String> = conn
+        .query_row(
+            "SELECT json_extract(data, '$.release_date') FROM metadata_sources
+             WHERE game_id = ?1 AND provider != ?2
+               AND json_extract(data, '$.release_date') IS NOT NULL
+             ORDER BY provider = ?3 DESC LIMIT 1",
+            params![game_id, metadata::PROVIDER_RAWG, metadata::PROVIDER_LOCAL],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(date.as_deref().and_then(rawg::release_year))
+}
+
+/// Mescla os registros salvos das fontes e grava o resultado. Gênero e capa vão para a tabela
+/// `games` apenas quando estão vazios lá ou quando vêm de uma substituição local.
+pub(crate) fn remerge(conn: &Connection, game_id: &str) -> Result<Option<GameMetadata>, String> {
+    let sources = fetch_sources(conn, Some(game_id))?;
+    if sources.is_empty() {
+        return Ok(None);
+    }
+
+    let rawg_id = sources
+        .iter()
+        .find(|s| s.provider == metadata::PROVIDER_RAWG)
+        .and_then(|s| s.external_id.as_deref())
+        .and_then(|id| id.parse::<i32>().ok());
+    let records: BTreeMap<String, MetadataFields> = sources
+        .into_iter()
+        .map(|s| (s.provider, s.fields))
+        .collect();
+
+    let (merged, provenance) = metadata::merge(&records, &load_policy(conn)?);
+    let is_local = |field: &str| {
+        provenance.get(field).map(String::as_str) == Some(metadata::PROVIDER_LOCAL)
+    };
+
+    if let Some(genre) = &merged.genre {
+        conn.execute(
+            "UPDATE games SET genre = ?1
+             WHERE id = ?2 AND (?3 OR genre IS NULL OR genre = '' OR genre = ?4)",
+            params![genre, game_id, is_local("genre"), constants::DEFAULT_GENRE],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    if let Some(cover) = &merged.cover_url {
+        conn.execute(
+            "UPDATE games SET cover_url = ?1
+             WHERE id = ?2 AND (?3 OR cover_url IS NULL OR cover_url = '')",
+            params![cover, game_id, is_local("cover_url")],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    let meta = GameMetadata {
+        game_id: game_id.to_string(),
+        rawg_id,
+        description: merged.description,
+        website: merged.website,
+        metacritic: merged.metacritic,
+        release_date: merged.release_date,
+        background_image: merged.background_image,
+        developers: merged.developers,
+        publishers: merged.publishers,
+        tags: merged.tags,
+        sources: provenance,
+        updated_at: None,
+    };
+    save_metadata(conn, &meta).map_err(|e| e.to_string())?;
+
+    load_metadata(conn, game_id)
+}
+
+/// Fontes disponíveis: substituições locais, loja Steam e RAWG (se houver API key)
+pub(crate) fn build_providers(
+    app_handle: &AppHandle,
+    conn: &Connection,
+) -> Result<Vec<Box<dyn MetadataProvider>>, String> {
+    let overrides = fetch_sources(conn, None)?
+        .into_iter()
+        .filter(|s| s.provider == metadata::PROVIDER_LOCAL)
+        .map(|s| (s.game_id, s.fields))
+        .collect();
+
+    let mut providers: Vec<Box<dyn MetadataProvider>> = vec![
+        Box::new(LocalOverrideProvider { overrides }),
+        Box::new(SteamStoreProvider),
+    ];
+
+    let api_key = integrations::get_api_key(app_handle).unwrap_or_default();
+    if !api_key.is_empty() {
+        providers.push(Box::new(RawgProvider { api_key }));
+    }
+
+    Ok(providers)
+}
+
+/// Consulta todas as fontes para o jogo, salva os registros e mescla.
+/// Falhas de uma fonte não impedem as demais; None se nenhuma fonte conhece o jogo.
+pub(crate) async fn enrich_game(
+    state: &AppState,
+    providers: &[Box<dyn MetadataProvider>],
+    game_id: &str,
+) -> Result<Option<GameMetadata>, String> {
+    let mut game = {
+        let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+        load_game_ref(&conn, game_id)?
+    };
+
+    let mut last_error = None;
+    for provider in providers {
+        match provider.fetch(&game).await {
+            Ok(Some(record)) => {
+                // A data da Steam chega antes da busca na RAWG e ajuda a escolher o resultado
+                if provider.name() != metadata::PROVIDER_RAWG && game.release_year.is_none() {
+                    game.release_year = record.fields.release_date.as_deref().and_then(rawg::release_year);
+                }
+
+                let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+                save_source(
+                    &conn,
+                    &MetadataSource {
+                        game_id: game.id.clone(),
+                        provider: provider.name().to_string(),
+                        external_id: record.external_id.clone(),
+                        fields: record.fields,
+                        fetched_at: None,
+                    },
+                )
+                .map_err(|e| e.to_string())?;
+
+                // Guarda o jogo encontrado na RAWG sem sobrescrever uma escolha manual
+                if provider.name() == metadata::PROVIDER_RAWG {
+                    if let Some(rawg_id) = record.external_id.and_then(|id| id.parse::<i32>().ok()) {
+                        conn.execute(
+                            "UPDATE games SET rawg_id = ?1 WHERE id = ?2 AND rawg_id IS NULL",
+                            params![rawg_id, game.id],
+                        )
+                        .map_err(|e| e.to_string())?;
+                    }
+                }
+            }
+            Ok(None) => {}
+            Err(e) => {
+                warn!("Fonte '{}' falhou para '{}': {}", provider.name(), game.name, e);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    match remerge(&conn, game_id)? {
+        Some(meta) => Ok(Some(meta)),
+        None => match last_error {
+            Some(e) => Err(e),
+            None => Ok(None),
+        },
+    }
+}
+
+/// Metadados salvos do jogo; consulta as fontes quando ainda não existem ou com `refresh`
+#[tauri::command]
+pub async fn get_game_metadata(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    game_id: String,
+    refresh: Option<bool>,
+) -> Result<Option<GameMetadata>, String> {
+    let providers = {
+        let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+        if !refresh.unwrap_or(false) {
+            if let Some(meta) = load_metadata(&conn, &game_id)? {
+                return Ok(Some(meta));
+            }
+        }
+        build_providers(&app_handle, &conn)?
+    };
+
+    enrich_game(&state, &providers, &game_id).await
+}
+
+/// Registros de cada fonte para o jogo (para conferir de onde veio cada campo)
+#[tauri::command]
+pub fn get_metadata_sources(
+    state: State<AppState>,
+    game_id: String,
+) -> Result<Vec<MetadataSource>, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    fetch_sources(&conn, Some(&game_id))
+}
+
+/// Substitui manualmente um campo (fonte "local", sempre com prioridade máxima por padrão).
+/// `value` None ou vazio remove a substituição.
+#[tauri::command]
+pub fn set_metadata_override(
+    state: State<AppState>,
+    game_id: String,
+    field: String,
+    value: Option<String>,
+) -> Result<Option<GameMetadata>, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    let exists: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM games WHERE id = ?1)",
+            params![game_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if !exists {
+        return Err("Jogo não encontrado".to_string());
+    }
+
+    let mut local = fetch_sources(&conn, Some(&game_id))?
+        .into_iter()
+        .find(|s| s.provider == metadata::PROVIDER_LOCAL)
+        .map(|s| s.fields)
+        .unwrap_or_default();
+    local.set_from_text(&field, value.as_deref())?;
+
+    if local.is_empty() {
+        conn.execute(
+            "DELETE FROM metadata_sources WHERE game_id = ?1 AND provider = ?2",
+            params![game_id, metadata::PROVIDER_LOCAL],
+        )
+        .map_err(|e| e.to_string())?;
+    } else {
+        save_source(
+            &conn,
+            &MetadataSource {
+                game_id: game_id.clone(),
+                provider: metadata::PROVIDER_LOCAL.to_string(),
+                external_id: None,
+                fields: local,
+                fetched_at: None,
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    remerge(&conn, &game_id)
+}
+
+#[tauri::command]
+pub fn get_metadata_priority(state: State<AppState>) -> Result<MergePolicy, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    load_policy(&conn)
+}
+
+/// Define a prioridade das fontes e mescla novamente os metadados já salvos (sem rede)
+#[tauri::command]
+pub fn set_metadata_priority(state: State<AppState>, policy: MergePolicy) -> Result<i32, String> {
+    policy.validate()?;
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    let json = serde_json::to_string(&policy).map_err(|e| e.to_string())?;
+    database::set_setting(&conn, constants::SETTING_METADATA_PRIORITY, &json)?;
+
+    let mut stmt = conn
+        .prepare("SELECT DISTINCT game_id FROM metadata_sources")
+        .map_err(|e| e.to_string())?;
+    let game_ids = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    database::in_transaction(&conn, |conn| {
+        journal::journaled(conn, "set_metadata_priority", None, |conn| {
+            for game_id in &game_ids {
+                remerge(conn, game_id)?;
+            }
+            Ok(())
+        })
+    })?;
+
+    info!("Prioridade de metadados atualizada ({} jogos mesclados)", game_ids.len());
+
+    Ok(game_ids.len() as i32)
+}
+
+/// Desenvolvedoras presentes nos metadados salvos (para o filtro da biblioteca)
+#[tauri::command]
+pub fn get_metadata_developers(state: State<AppState>) -> Result<Vec<String>, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT DISTINCT d.value FROM game_metadata m, json_each(m.developers) d
+             ORDER BY d.value COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let developers = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(developers)
+}
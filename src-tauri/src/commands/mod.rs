@@ -1,6 +1,16 @@
+pub mod artwork;
+pub mod covers;
+pub mod duplicates;
 pub mod games;
+pub mod imports;
 pub mod integrations;
+pub mod metadata;
+pub mod ownership;
+pub mod purchases;
 pub mod settings;
+pub mod snapshots;
+pub mod sync;
+pub mod journal;
 pub mod wishlist;
 pub mod recommendations;
 pub mod backup;
@@ -0,0 +1,239 @@
+use crate::constants;
+use crate::database::AppState;
+use crate::models::{GameOwnership, OwnershipInput};
+use rusqlite::{params, Connection, OptionalExtension};
+use tauri::State;
+
+const OWNERSHIP_COLUMNS: &str =
+    "id, game_id, platform, external_id, purchase_date, price_paid, currency, edition";
+
+fn map_ownership_row(row: &rusqlite::Row) -> rusqlite::Result<GameOwnership> {
+    Ok(GameOwnership {
+        id: row.get(0)?,
+        game_id: row.get(1)?,
+        platform: row.get(2)?,
+        external_id: row.get(3)?,
+        purchase_date: row.get(4)?,
+        price_paid: row.get(5)?,
+        currency: row.get(6)?,
+        edition: row.get(7)?,
+    })
+}
+
+/// Lista as cópias de um jogo, ou de todos os jogos quando `game_id` é None
+pub(crate) fn fetch_ownership(
+    conn: &Connection,
+    game_id: Option<&str>,
+) -> Result<Vec<GameOwnership>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM game_ownership WHERE ?1 IS NULL OR game_id = ?1 ORDER BY game_id, id",
+            OWNERSHIP_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![game_id], map_ownership_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+/// Normaliza um código de moeda ISO 4217 ("brl" -> "BRL")
+pub(crate) fn normalize_currency(code: &str) -> Result<String, String> {
+    let code = code.trim().to_uppercase();
+    if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(format!("Moeda inválida: '{}' (use o código de 3 letras, ex: BRL)", code));
+    }
+    Ok(code)
+}
+
+fn validate_ownership(copy: &mut OwnershipInput) -> Result<(), String> {
+    copy.platform = copy.platform.trim().to_string();
+    if copy.platform.is_empty() {
+        return Err("Plataforma não pode ser vazia".to_string());
+    }
+    if copy.platform.len() > constants::MAX_PLATFORM_LENGTH {
+        return Err(format!(
+            "Plataforma muito longa (máximo {} caracteres)",
+            constants::MAX_PLATFORM_LENGTH
+        ));
+    }
+    if let Some(date) = &copy.purchase_date {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| "Data de compra inválida (use AAAA-MM-DD)".to_string())?;
+    }
+    if let Some(price) = copy.price_paid {
+        if !price.is_finite() || price < 0.0 {
+            return Err("Preço pago não pode ser negativo".to_string());
+        }
+    }
+    copy.currency = match copy.currency.as_deref().filter(|c| !c.trim().is_empty()) {
+        Some(code) => Some(normalize_currency(code)?),
+        None => None,
+    };
+    if let Some(e) = &copy.edition {
+        if e.len() > constants::MAX_NAME_LENGTH {
+            return Err(format!(
+                "Edição muito longa (máximo {} caracteres)",
+                constants::MAX_NAME_LENGTH
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Mantém `games.platform` apontando para uma plataforma que o jogo ainda possui
+fn sync_primary_platform(conn: &Connection, game_id: &str) -> Result<(), String> {
+    let first: Option<String> = conn
+        .query_row(
+            "SELECT platform FROM game_ownership WHERE game_id = ?1 ORDER BY id LIMIT 1",
+            params![game_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    if let Some(platform) = first {
+        conn.execute(
+            "UPDATE games SET platform = ?1
+             WHERE id = ?2 AND (platform IS NULL OR platform NOT IN
+                 (SELECT platform FROM game_ownership WHERE game_id = ?2))",
+            params![platform, game_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_game_ownership(
+    state: State<AppState>,
+    game_id: String,
+) -> Result<Vec<GameOwnership>, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    fetch_ownership(&conn, Some(&game_id))
+}
+
+#[tauri::command]
+pub fn add_game_ownership(
+    state: State<AppState>,
+    game_id: String,
+    mut copy: OwnershipInput,
+) -> Result<i64, String> {
+    validate_ownership(&mut copy)?;
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    let exists: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM games WHERE id = ?1)",
+            params![game_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if !exists {
+        return Err("Jogo não encontrado".to_string());
+    }
+
+    conn.execute(
+        "INSERT INTO game_ownership (game_id, platform, external_id, purchase_date, price_paid, currency, edition)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            game_id,
+            copy.platform,
+            copy.external_id,
+            copy.purchase_date,
+            copy.price_paid,
+            copy.currency,
+            copy.edition
+        ],
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            "Esta cópia já está registrada".to_string()
+        }
+        other => other.to_string(),
+    })?;
+    let id = conn.last_insert_rowid();
+
+    sync_primary_platform(&conn, &game_id)?;
+
+    Ok(id)
+}
+
+#[tauri::command]
+pub fn update_game_ownership(
+    state: State<AppState>,
+    id: i64,
+    mut copy: OwnershipInput,
+) -> Result<(), String> {
+    validate_ownership(&mut copy)?;
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    let game_id: String = conn
+        .query_row(
+            "SELECT game_id FROM game_ownership WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or("Cópia não encontrada")?;
+
+    conn.execute(
+        "UPDATE game_ownership SET platform = ?1, external_id = ?2, purchase_date = ?3, price_paid = ?4,
+                currency = ?5, edition = ?6
+         WHERE id = ?7",
+        params![
+            copy.platform,
+            copy.external_id,
+            copy.purchase_date,
+            copy.price_paid,
+            copy.currency,
+            copy.edition,
+            id
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    sync_primary_platform(&conn, &game_id)
+}
+
+/// Remove uma cópia. A última cópia de um jogo não pode ser removida (exclua o jogo).
+#[tauri::command]
+pub fn remove_game_ownership(state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    let game_id: String = conn
+        .query_row(
+            "SELECT game_id FROM game_ownership WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or("Cópia não encontrada")?;
+
+    let copies: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM game_ownership WHERE game_id = ?1",
+            params![game_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if copies <= 1 {
+        return Err("O jogo precisa ter ao menos uma plataforma".to_string());
+    }
+
+    conn.execute("DELETE FROM game_ownership WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    sync_primary_platform(&conn, &game_id)
+}
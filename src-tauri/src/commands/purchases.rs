@@ -0,0 +1,289 @@
+use crate::commands::ownership::normalize_currency;
+use crate::constants;
+use crate::database::{self, AppState};
+use crate::models::CurrencyRate;
+use crate::services::spending::{self, Converter, Purchase, SpendingReport};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+use tauri::State;
+use tracing::info;
+
+/// Moeda base configurada (padrão: BRL)
+pub(crate) fn base_currency(conn: &Connection) -> Result<String, String> {
+    Ok(database::get_setting(conn, constants::SETTING_BASE_CURRENCY)?
+        .unwrap_or_else(|| constants::DEFAULT_BASE_CURRENCY.to_string()))
+}
+
+fn load_rates(conn: &Connection) -> Result<Vec<CurrencyRate>, String> {
+    let mut stmt = conn
+        .prepare("SELECT currency, rate, updated_at FROM currency_rates ORDER BY currency")
+        .map_err(|e| e.to_string())?;
+    let rates = stmt
+        .query_map([], |row| {
+            Ok(CurrencyRate {
+                currency: row.get(0)?,
+                rate: row.get(1)?,
+                updated_at: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rates)
+}
+
+/// Preenche o preço pago da cópia do jogo com o último preço visto na lista de desejos.
+/// `app_key` é o ID do item na wishlist ou o AppID da Steam. Não sobrescreve dados já informados.
+pub(crate) fn fill_purchase_from_wishlist(
+    conn: &Connection,
+    game_id: &str,
+    app_key: &str,
+) -> rusqlite::Result<bool> {
+    let price: Option<(Option<f64>, Option<String>)> = conn
+        .query_row(
+            "SELECT localized_price, localized_currency FROM wishlist
+             WHERE id = ?1 OR CAST(steam_app_id AS TEXT) = ?1
+             LIMIT 1",
+            params![app_key],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    let Some((Some(price), currency)) = price else {
+        return Ok(false);
+    };
+
+    // Prefere a cópia da Steam (origem dos preços da wishlist); senão, a primeira cópia
+    let updated = conn.execute(
+        "UPDATE game_ownership
+            SET price_paid = ?1, currency = ?2, purchase_date = COALESCE(purchase_date, date('now'))
+          WHERE id = (SELECT id FROM game_ownership WHERE game_id = ?3
+                      ORDER BY platform = ?4 DESC, id LIMIT 1)
+            AND price_paid IS NULL",
+        params![price, currency, game_id, constants::DEFAULT_PLATFORM_STEAM],
+    )?;
+
+    Ok(updated > 0)
+}
+
+/// Relatório de gastos: total por ano e plataforma, custo por hora e gasto com jogos nunca jogados
+#[tauri::command]
+pub fn get_spending_report(state: State<AppState>) -> Result<SpendingReport, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    let converter = Converter {
+        base: base_currency(&conn)?,
+        rates: load_rates(&conn)?
+            .into_iter()
+            .map(|r| (r.currency, r.rate))
+            .collect::<HashMap<_, _>>(),
+    };
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT o.game_id, g.name, o.platform, o.purchase_date, o.price_paid, o.currency,
+                    IFNULL(g.playtime, 0)
+             FROM game_ownership o
+             JOIN games g ON g.id = o.game_id
+             WHERE o.price_paid IS NOT NULL",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let purchases = stmt
+        .query_map([], |row| {
+            Ok(Purchase {
+                game_id: row.get(0)?,
+                name: row.get(1)?,
+                platform: row.get(2)?,
+                purchase_date: row.get(3)?,
+                price: row.get(4)?,
+                currency: row.get(5)?,
+                playtime: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(spending::build_report(&purchases, &converter))
+}
+
+#[tauri::command]
+pub fn get_currency_rates(state: State<AppState>) -> Result<Vec<CurrencyRate>, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    load_rates(&conn)
+}
+
+/// Define quanto vale 1 unidade de `currency` na moeda base
+#[tauri::command]
+pub fn set_currency_rate(
+    state: State<AppState>,
+    currency: String,
+    rate: f64,
+) -> Result<(), String> {
+    let currency = normalize_currency(&currency)?;
+    if !rate.is_finite() || rate <= 0.0 {
+        return Err("A cotação deve ser maior que zero".to_string());
+    }
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    if currency == base_currency(&conn)? {
+        return Err("A moeda base não precisa de cotação".to_string());
+    }
+
+    conn.execute(
+        "INSERT OR REPLACE INTO currency_rates (currency, rate, updated_at) VALUES (?1, ?2, CURRENT_TIMESTAMP)",
+        params![currency, rate],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_currency_rate(state: State<AppState>, currency: String) -> Result<(), String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    conn.execute(
+        "DELETE FROM currency_rates WHERE currency = ?1",
+        params![currency.trim().to_uppercase()],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Troca a moeda base. Compras sem moeda ficam com a base antiga, para o histórico não mudar.
+/// As cotações são convertidas para a nova base quando a cotação dela é conhecida; caso
+/// contrário, só são descartadas com `discard_rates`.
+#[tauri::command]
+pub fn set_base_currency(
+    state: State<AppState>,
+    currency: String,
+    discard_rates: Option<bool>,
+) -> Result<(), String> {
+    let currency = normalize_currency(&currency)?;
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    database::in_transaction(&conn, |conn| {
+        change_base_currency(conn, &currency, discard_rates.unwrap_or(false))
+    })
+}
+
+fn change_base_currency(conn: &Connection, currency: &str, discard_rates: bool) -> Result<(), String> {
+    let old_base = base_currency(conn)?;
+    if currency == old_base {
+        return Ok(());
+    }
+
+    let new_base_rate: Option<f64> = conn
+        .query_row(
+            "SELECT rate FROM currency_rates WHERE currency = ?1",
+            params![currency],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    match new_base_rate {
+        Some(rate) => {
+            // 1 X = r(X) na base antiga = r(X) / r(nova) na nova base
+            conn.execute(
+                "UPDATE currency_rates SET rate = rate / ?1, updated_at = CURRENT_TIMESTAMP",
+                params![rate],
+            )
+            .map_err(|e| e.to_string())?;
+            conn.execute(
+                "DELETE FROM currency_rates WHERE currency = ?1",
+                params![currency],
+            )
+            .map_err(|e| e.to_string())?;
+            conn.execute(
+                "INSERT OR REPLACE INTO currency_rates (currency, rate, updated_at) VALUES (?1, ?2, CURRENT_TIMESTAMP)",
+                params![old_base, 1.0 / rate],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        None => {
+            let rates = database::count_rows(conn, "currency_rates")?;
+            if rates > 0 && !discard_rates {
+                return Err(format!(
+                    "Informe a cotação de {} antes de trocar a moeda base, ou confirme o descarte das {} cotações atuais",
+                    currency, rates
+                ));
+            }
+            conn.execute("DELETE FROM currency_rates", [])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    let stamped = conn
+        .execute(
+            "UPDATE game_ownership SET currency = ?1 WHERE currency IS NULL AND price_paid IS NOT NULL",
+            params![old_base],
+        )
+        .map_err(|e| e.to_string())?;
+    database::set_setting(conn, constants::SETTING_BASE_CURRENCY, currency)?;
+
+    info!(
+        "Moeda base alterada de {} para {} ({} compras mantidas em {})",
+        old_base, currency, stamped, old_base
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::memory_db;
+
+    fn rates(conn: &Connection) -> Vec<(String, f64)> {
+        load_rates(conn)
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.currency, (r.rate * 1000.0).round() / 1000.0))
+            .collect()
+    }
+
+    #[test]
+    fn keeps_history_and_rebases_rates() {
+        let conn = memory_db();
+        conn.execute_batch(
+            "INSERT INTO games (id, name) VALUES ('g', 'Hades');
+             INSERT INTO game_ownership (game_id, platform, price_paid, currency) VALUES ('g', 'Steam', 50, NULL);
+             INSERT INTO game_ownership (game_id, platform, price_paid, currency) VALUES ('g', 'GOG', 10, 'USD');
+             INSERT INTO currency_rates (currency, rate) VALUES ('USD', 5.0), ('EUR', 6.0);",
+        )
+        .unwrap();
+
+        change_base_currency(&conn, "USD", false).unwrap();
+
+        assert_eq!(base_currency(&conn).unwrap(), "USD");
+        assert_eq!(rates(&conn), vec![("BRL".to_string(), 0.2), ("EUR".to_string(), 1.2)]);
+
+        let currency: Option<String> = conn
+            .query_row(
+                "SELECT currency FROM game_ownership WHERE platform = 'Steam'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(currency.as_deref(), Some("BRL"));
+    }
+
+    #[test]
+    fn unknown_rate_needs_confirmation_to_discard() {
+        let conn = memory_db();
+        conn.execute("INSERT INTO currency_rates (currency, rate) VALUES ('USD', 5.0)", [])
+            .unwrap();
+
+        assert!(change_base_currency(&conn, "JPY", false).is_err());
+        assert_eq!(base_currency(&conn).unwrap(), "BRL");
+
+        change_base_currency(&conn, "JPY", true).unwrap();
+        assert_eq!(base_currency(&conn).unwrap(), "JPY");
+        assert!(rates(&conn).is_empty());
+    }
+}
@@ -1,6 +1,7 @@
+use crate::database::{self, AppState};
 use crate::storage;
 use serde::Serialize;
-use tauri::AppHandle;
+use tauri::{AppHandle, State};
 
 #[derive(Serialize)]
 pub struct KeysBatch {
@@ -86,3 +87,17 @@ pub fn list_secrets() -> Result<Vec<String>, String> {
         .map(|s| s.to_string())
         .collect())
 }
+
+// === Configurações não sensíveis (ficam no library.db, sem criptografia) ===
+
+#[tauri::command]
+pub fn get_app_setting(state: State<AppState>, key: String) -> Result<Option<String>, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    database::get_setting(&conn, &key)
+}
+
+#[tauri::command]
+pub fn set_app_setting(state: State<AppState>, key: String, value: String) -> Result<(), String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    database::set_setting(&conn, &key, &value)
+}
@@ -0,0 +1,305 @@
+use crate::constants;
+use crate::database::{self, AppState};
+use crate::services::snapshots::{self, SnapshotInfo};
+use rusqlite::{Connection, MAIN_DB};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tracing::{error, info, warn};
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct SnapshotSettings {
+    pub interval_hours: u64,
+    pub keep_daily: usize,
+    pub keep_weekly: usize,
+}
+
+#[derive(serde::Serialize)]
+pub struct DatabaseHealth {
+    pub ok: bool,
+    /// Resultado do `PRAGMA quick_check` quando o banco tem problemas
+    pub problem: Option<String>,
+    /// Cópia íntegra mais recente, oferecida para restauração
+    pub latest_good_snapshot: Option<SnapshotInfo>,
+}
+
+fn snapshot_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir não encontrado: {}", e))?;
+    Ok(dir.join(constants::SNAPSHOT_DIR))
+}
+
+fn load_settings(conn: &Connection) -> SnapshotSettings {
+    // Na primeira execução a cópia roda antes do `init_db` criar a tabela de configurações
+    let read = |key: &str| database::get_setting(conn, key).ok().flatten();
+
+    SnapshotSettings {
+        interval_hours: read(constants::SETTING_SNAPSHOT_INTERVAL_HOURS)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(constants::DEFAULT_SNAPSHOT_INTERVAL_HOURS),
+        keep_daily: read(constants::SETTING_SNAPSHOT_KEEP_DAILY)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(constants::DEFAULT_SNAPSHOT_KEEP_DAILY),
+        keep_weekly: read(constants::SETTING_SNAPSHOT_KEEP_WEEKLY)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(constants::DEFAULT_SNAPSHOT_KEEP_WEEKLY),
+    }
+}
+
+/// Copia o banco e, com `rotate`, apaga as cópias fora da rotação.
+/// Um banco corrompido nunca é copiado, para não empurrar as cópias boas para fora da rotação.
+fn take_snapshot(conn: &Connection, dir: &Path, rotate: bool) -> Result<SnapshotInfo, String> {
+    if let Some(problem) = snapshots::check_integrity(conn)? {
+        return Err(format!("Banco corrompido, cópia cancelada: {}", problem));
+    }
+
+    let snapshot = snapshots::create_snapshot(conn, dir)?;
+
+    if rotate {
+        let settings = load_settings(conn);
+        let removed = snapshots::rotate(dir, settings.keep_daily, settings.keep_weekly, &[])?;
+        if removed > 0 {
+            info!("{} cópias antigas do banco removidas", removed);
+        }
+    }
+
+    Ok(snapshot)
+}
+
+/// Cópia feita ao abrir o aplicativo, antes das migrações do `init_db`
+pub(crate) fn run_startup(app: &AppHandle) {
+    let result = snapshot_dir(app).and_then(|dir| {
+        let state = app.state::<AppState>();
+        let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex".to_string())?;
+        take_snapshot(&conn, &dir, true)
+    });
+
+    match result {
+        Ok(snapshot) => info!("Cópia do banco criada: {}", snapshot.file_name),
+        Err(e) => error!("Cópia do banco na inicialização falhou: {}", e),
+    }
+}
+
+/// Confere periodicamente se a última cópia é mais antiga que o intervalo configurado
+pub(crate) fn start_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(constants::SNAPSHOT_CHECK_INTERVAL_SECS)).await;
+
+            let result = snapshot_dir(&app).and_then(|dir| {
+                let state = app.state::<AppState>();
+                let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex".to_string())?;
+
+                let settings = load_settings(&conn);
+                let latest = snapshots::list_snapshots(&dir)?.into_iter().next();
+                let due = latest.is_none_or(|s| {
+                    let age = chrono::Local::now().naive_local() - s.created_at;
+                    age.num_hours() >= settings.interval_hours as i64
+                });
+
+                if due {
+                    take_snapshot(&conn, &dir, true).map(Some)
+                } else {
+                    Ok(None)
+                }
+            });
+
+            match result {
+                Ok(Some(snapshot)) => info!("Cópia agendada do banco criada: {}", snapshot.file_name),
+                Ok(None) => {}
+                Err(e) => warn!("Cópia agendada do banco falhou: {}", e),
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn list_snapshots(app: AppHandle) -> Result<Vec<SnapshotInfo>, String> {
+    snapshots::list_snapshots(&snapshot_dir(&app)?)
+}
+
+/// Cria uma cópia do banco na hora, fora do agendamento
+#[tauri::command]
+pub fn create_snapshot(app: AppHandle, state: State<AppState>) -> Result<SnapshotInfo, String> {
+    let dir = snapshot_dir(&app)?;
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    take_snapshot(&conn, &dir, true)
+}
+
+/// Substitui o banco atual pela cópia escolhida. Antes, o banco atual (se íntegro) também
+/// é copiado, para que a restauração possa ser desfeita.
+#[tauri::command]
+pub fn restore_snapshot(
+    app: AppHandle,
+    state: State<AppState>,
+    file_name: String,
+) -> Result<String, String> {
+    let dir = snapshot_dir(&app)?;
+    let mut conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    let snapshot = restore_from(&mut conn, &dir, &file_name)?;
+
+    info!("Banco restaurado da cópia {}", snapshot.file_name);
+    Ok(format!("Banco restaurado da cópia de {}", snapshot.created_at.format("%d/%m/%Y %H:%M")))
+}
+
+/// Restaura a cópia `file_name` da pasta no banco aberto. A cópia restaurada e a cópia de
+/// segurança ficam fora da rotação feita em seguida, mesmo sendo do mesmo dia que outras.
+fn restore_from(conn: &mut Connection, dir: &Path, file_name: &str) -> Result<SnapshotInfo, String> {
+    let snapshot = snapshots::list_snapshots(dir)?
+        .into_iter()
+        .find(|s| s.file_name == file_name)
+        .ok_or_else(|| "Cópia não encontrada".to_string())?;
+
+    snapshots::verify_snapshot(Path::new(&snapshot.path))?;
+
+    let safety = match take_snapshot(conn, dir, false) {
+        Ok(safety) => {
+            info!("Banco atual copiado antes da restauração: {}", safety.file_name);
+            Some(safety.file_name)
+        }
+        Err(e) => {
+            warn!("Banco atual não copiado antes da restauração: {}", e);
+            None
+        }
+    };
+
+    conn.restore(
+        MAIN_DB,
+        &snapshot.path,
+        None::<fn(rusqlite::backup::Progress)>,
+    )
+    .map_err(|e| format!("Erro ao restaurar cópia: {}", e))?;
+
+    // Cópias antigas podem ser de um esquema anterior
+    database::create_schema(conn)?;
+
+    let settings = load_settings(conn);
+    let mut protected = vec![snapshot.file_name.as_str()];
+    protected.extend(safety.as_deref());
+    snapshots::rotate(dir, settings.keep_daily, settings.keep_weekly, &protected)?;
+
+    Ok(snapshot)
+}
+
+/// Verifica a integridade do banco; quando há problema, indica a cópia íntegra mais recente
+#[tauri::command]
+pub fn check_database_health(
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<DatabaseHealth, String> {
+    let problem = {
+        let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+        // Um banco ilegível também conta como corrompido
+        snapshots::check_integrity(&conn).unwrap_or_else(Some)
+    };
+
+    let Some(problem) = problem else {
+        return Ok(DatabaseHealth {
+            ok: true,
+            problem: None,
+            latest_good_snapshot: None,
+        });
+    };
+
+    error!("Banco com problemas de integridade: {}", problem);
+
+    let latest_good_snapshot = snapshots::list_snapshots(&snapshot_dir(&app)?)?
+        .into_iter()
+        .find(|s| snapshots::verify_snapshot(Path::new(&s.path)).is_ok());
+
+    Ok(DatabaseHealth {
+        ok: false,
+        problem: Some(problem),
+        latest_good_snapshot,
+    })
+}
+
+#[tauri::command]
+pub fn get_snapshot_settings(state: State<AppState>) -> Result<SnapshotSettings, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    Ok(load_settings(&conn))
+}
+
+#[tauri::command]
+pub fn set_snapshot_settings(
+    state: State<AppState>,
+    settings: SnapshotSettings,
+) -> Result<(), String> {
+    if settings.interval_hours == 0 {
+        return Err("O intervalo entre cópias deve ser de pelo menos 1 hora".to_string());
+    }
+    if settings.keep_daily == 0 {
+        return Err("Mantenha pelo menos uma cópia diária".to_string());
+    }
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    database::set_setting(
+        &conn,
+        constants::SETTING_SNAPSHOT_INTERVAL_HOURS,
+        &settings.interval_hours.to_string(),
+    )?;
+    database::set_setting(
+        &conn,
+        constants::SETTING_SNAPSHOT_KEEP_DAILY,
+        &settings.keep_daily.to_string(),
+    )?;
+    database::set_setting(
+        &conn,
+        constants::SETTING_SNAPSHOT_KEEP_WEEKLY,
+        &settings.keep_weekly.to_string(),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{memory_db, temp_dir};
+
+    fn game_count(conn: &Connection) -> usize {
+        database::count_rows(conn, "games").unwrap()
+    }
+
+    #[test]
+    fn restored_and_safety_copies_survive_rotation() {
+        let dir = temp_dir("snapshots_restore");
+        let mut conn = memory_db();
+        database::set_setting(&conn, constants::SETTING_SNAPSHOT_KEEP_DAILY, "1").unwrap();
+        database::set_setting(&conn, constants::SETTING_SNAPSHOT_KEEP_WEEKLY, "1").unwrap();
+
+        conn.execute("INSERT INTO games (id, name) VALUES ('hades', 'Hades')", [])
+            .unwrap();
+        let original = take_snapshot(&conn, &dir, false).unwrap();
+
+        // Cópias mais novas do mesmo dia deixam a original fora das recentes e da diária
+        conn.execute("DELETE FROM games", []).unwrap();
+        for _ in 0..constants::SNAPSHOT_KEEP_RECENT {
+            take_snapshot(&conn, &dir, false).unwrap();
+        }
+        let names = |dir: &Path| -> Vec<String> {
+            snapshots::list_snapshots(dir)
+                .unwrap()
+                .into_iter()
+                .map(|s| s.file_name)
+                .collect()
+        };
+        let before = names(&dir);
+
+        restore_from(&mut conn, &dir, &original.file_name).unwrap();
+        assert_eq!(game_count(&conn), 1);
+
+        let after = names(&dir);
+        assert!(after.contains(&original.file_name));
+        // A cópia de segurança, a única nova, guarda o banco de antes da restauração
+        let safety: Vec<&String> = after.iter().filter(|name| !before.contains(name)).collect();
+        assert_eq!(safety.len(), 1);
+        let safety_conn = Connection::open(dir.join(safety[0])).unwrap();
+        assert_eq!(game_count(&safety_conn), 0);
+        // A rotação continua valendo para as demais
+        assert_eq!(after.len(), constants::SNAPSHOT_KEEP_RECENT + 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
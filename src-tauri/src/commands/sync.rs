@@ -0,0 +1,614 @@
+use crate::constants;
+use crate::database::{self, journal, AppState};
+use crate::services::restore::parse_timestamp;
+use crate::services::sync::{
+    self, Change, ChangeSet, FolderTransport, HttpTransport, LocalState, Resolution, SyncCursor,
+    SyncTransport,
+};
+use crate::storage;
+use rand::RngCore;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+use tracing::{info, warn};
+
+#[derive(serde::Serialize)]
+pub struct SyncSettings {
+    pub device_id: String,
+    /// "folder", "http" ou None (sincronização desativada)
+    pub transport: Option<String>,
+    pub folder_path: Option<String>,
+    pub server_url: Option<String>,
+    pub has_token: bool,
+    pub last_sync: Option<String>,
+}
+
+#[derive(serde::Serialize, Default)]
+pub struct SyncReport {
+    /// Alterações locais enviadas
+    pub pushed: usize,
+    /// Alterações recebidas dos outros dispositivos
+    pub received: usize,
+    pub applied: usize,
+    pub deleted: usize,
+    /// Alterações recebidas em que a versão local, mais recente, foi mantida
+    pub kept_local: usize,
+    /// Linhas alteradas aqui e em outro dispositivo desde a última sincronização
+    pub conflicts: usize,
+}
+
+/// ID deste dispositivo, gerado na primeira sincronização
+fn device_id(conn: &Connection) -> Result<String, String> {
+    if let Some(id) = database::get_setting(conn, constants::SETTING_SYNC_DEVICE_ID)? {
+        return Ok(id);
+    }
+    let mut bytes = [0u8; 16];
+    rand::rng().fill_bytes(&mut bytes);
+    let id: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    database::set_setting(conn, constants::SETTING_SYNC_DEVICE_ID, &id)?;
+    Ok(id)
+}
+
+fn load_settings(app: &AppHandle, conn: &Connection) -> Result<SyncSettings, String> {
+    Ok(SyncSettings {
+        device_id: device_id(conn)?,
+        transport: database::get_setting(conn, constants::SETTING_SYNC_TRANSPORT)?,
+        folder_path: database::get_setting(conn, constants::SETTING_SYNC_FOLDER)?,
+        server_url: database::get_setting(conn, constants::SETTING_SYNC_SERVER_URL)?,
+        has_token: !storage::get_secret(app, constants::SECRET_SYNC_TOKEN)?.is_empty(),
+        last_sync: database::get_setting(conn, constants::SETTING_SYNC_LAST_RUN)?,
+    })
+}
+
+fn transport_for(app: &AppHandle, settings: &SyncSettings) -> Result<Box<dyn SyncTransport>, String> {
+    match settings.transport.as_deref() {
+        Some(constants::SYNC_TRANSPORT_FOLDER) => {
+            let root = settings
+                .folder_path
+                .clone()
+                .ok_or("Pasta de sincronização não configurada")?;
+            Ok(Box::new(FolderTransport {
+                root: PathBuf::from(root),
+            }))
+        }
+        Some(constants::SYNC_TRANSPORT_HTTP) => {
+            let url = settings
+                .server_url
+                .clone()
+                .ok_or("Servidor de sincronização não configurado")?;
+            let token = storage::get_secret(app, constants::SECRET_SYNC_TOKEN)?;
+            Ok(Box::new(HttpTransport {
+                url,
+                token: Some(token).filter(|t| !t.is_empty()),
+            }))
+        }
+        _ => Err("Sincronização desativada".to_string()),
+    }
+}
+
+fn key_columns(table: &str) -> Option<Vec<String>> {
+    database::SYNCED_TABLES
+        .iter()
+        .find(|(name, _)| *name == table)
+        .map(|(_, keys)| keys.iter().map(|k| k.to_string()).collect())
+}
+
+/// Colunas enviadas da tabela, mais `updated_at` no fim
+fn synced_columns(conn: &Connection, table: &str) -> Result<Vec<String>, String> {
+    let mut columns: Vec<String> = database::table_columns(conn, table)?
+        .into_iter()
+        .filter(|c| sync::is_synced_column(table, c))
+        .collect();
+    columns.push("updated_at".to_string());
+    Ok(columns)
+}
+
+fn is_after(value: Option<&str>, since: Option<&str>) -> bool {
+    let Some(since) = since.and_then(|s| parse_timestamp(&Value::from(s))) else {
+        return true;
+    };
+    value
+        .and_then(|v| parse_timestamp(&Value::from(v)))
+        .is_some_and(|v| v >= since)
+}
+
+/// Linhas alteradas e excluídas desde `since` (tudo, na primeira sincronização)
+fn collect_changes(conn: &Connection, since: Option<&str>) -> Result<Vec<Change>, String> {
+    let mut changes = Vec::new();
+
+    for (table, keys) in database::SYNCED_TABLES {
+        let columns = synced_columns(conn, table)?;
+        let key_indexes: Vec<usize> = keys
+            .iter()
+            .filter_map(|k| columns.iter().position(|c| c == k))
+            .collect();
+
+        database::for_each_row(conn, table, &columns, |mut values| {
+            let modified_at = values.pop().and_then(|v| v.as_str().map(String::from));
+            if !is_after(modified_at.as_deref(), since) {
+                return Ok(());
+            }
+            changes.push(Change {
+                table: table.to_string(),
+                key: key_indexes.iter().map(|&i| values[i].clone()).collect(),
+                modified_at,
+                row: Some(columns.iter().cloned().zip(values).collect()),
+            });
+            Ok(())
+        })?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT row_key, deleted_at FROM sync_tombstones
+                 WHERE table_name = ?1 AND (?2 IS NULL OR deleted_at >= ?2)",
+            )
+            .map_err(|e| e.to_string())?;
+        let tombstones = stmt
+            .query_map(params![table, since], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        for (row_key, deleted_at) in tombstones {
+            let Ok(key) = serde_json::from_str::<Vec<Value>>(&row_key) else {
+                continue;
+            };
+            changes.push(Change {
+                table: table.to_string(),
+                key,
+                modified_at: Some(deleted_at),
+                row: None,
+            });
+        }
+    }
+
+    Ok(changes)
+}
+
+fn key_condition(keys: &[String]) -> String {
+    keys.iter()
+        .enumerate()
+        .map(|(i, k)| format!("\"{}\" IS ?{}", k, i + 1))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+fn local_state(conn: &Connection, change: &Change, keys: &[String]) -> Result<LocalState, String> {
+    let columns = synced_columns(conn, &change.table)?;
+    let mut lookup = database::RowLookup::new(conn, &change.table, &columns, keys, keys)?;
+
+    if let Some(mut values) = lookup.find(&change.key)? {
+        let modified_at = values.pop().and_then(|v| v.as_str().map(String::from));
+        return Ok(LocalState::Row {
+            modified_at,
+            row: columns.into_iter().zip(values).collect(),
+        });
+    }
+
+    let deleted_at: Option<String> = conn
+        .query_row(
+            "SELECT deleted_at FROM sync_tombstones WHERE table_name = ?1 AND row_key = ?2",
+            params![change.table, sync::row_key(&change.key)],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    Ok(match deleted_at {
+        Some(deleted_at) => LocalState::Deleted { deleted_at },
+        None => LocalState::Missing,
+    })
+}
+
+/// Grava a alteração vencedora. A data da alteração original é mantida na linha e na lápide,
+/// para que a mesma alteração não pareça nova aqui.
+fn apply_change(
+    conn: &Connection,
+    change: &Change,
+    keys: &[String],
+    local: &LocalState,
+) -> Result<(), String> {
+    let key_values: Vec<_> = change.key.iter().map(database::json_to_sql).collect();
+
+    let Some(row) = &change.row else {
+        conn.execute(
+            &format!("DELETE FROM \"{}\" WHERE {}", change.table, key_condition(keys)),
+            rusqlite::params_from_iter(key_values),
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO sync_tombstones (table_name, row_key, deleted_at)
+             VALUES (?1, ?2, IFNULL(?3, CURRENT_TIMESTAMP))
+             ON CONFLICT(table_name, row_key) DO UPDATE SET deleted_at = excluded.deleted_at",
+            params![change.table, sync::row_key(&change.key), change.modified_at],
+        )
+        .map_err(|e| e.to_string())?;
+        return Ok(());
+    };
+
+    // Só as colunas que existem nesta versão do aplicativo
+    let existing = database::table_columns(conn, &change.table)?;
+    let mut columns: Vec<&str> = row
+        .keys()
+        .map(String::as_str)
+        .filter(|c| existing.iter().any(|e| e == c) && sync::is_synced_column(&change.table, c))
+        .collect();
+    let mut values: Vec<_> = columns.iter().map(|c| database::json_to_sql(&row[*c])).collect();
+    columns.push("updated_at");
+    values.push(database::json_to_sql(
+        &change.modified_at.clone().map(Value::from).unwrap_or(Value::Null),
+    ));
+
+    if matches!(local, LocalState::Row { .. }) {
+        let assignments = columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| format!("\"{}\" = ?{}", c, i + 1))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let condition = keys
+            .iter()
+            .enumerate()
+            .map(|(i, k)| format!("\"{}\" IS ?{}", k, columns.len() + i + 1))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        values.extend(key_values);
+        conn.execute(
+            &format!(
+                "UPDATE \"{}\" SET {} WHERE {}",
+                change.table, assignments, condition
+            ),
+            rusqlite::params_from_iter(values),
+        )
+        .map_err(|e| e.to_string())?;
+    } else {
+        let column_list = columns
+            .iter()
+            .map(|c| format!("\"{}\"", c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let placeholders = (1..=columns.len())
+            .map(|i| format!("?{}", i))
+            .collect::<Vec<_>>()
+            .join(", ");
+        conn.execute(
+            &format!(
+                "INSERT INTO \"{}\" ({}) VALUES ({})",
+                change.table, column_list, placeholders
+            ),
+            rusqlite::params_from_iter(values),
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+fn local_modified(local: &LocalState) -> Option<&str> {
+    match local {
+        LocalState::Missing => None,
+        LocalState::Row { modified_at, .. } => modified_at.as_deref(),
+        LocalState::Deleted { deleted_at } => Some(deleted_at),
+    }
+}
+
+/// Aplica os conjuntos recebidos. `last_run` separa as alterações locais feitas desde a
+/// sincronização anterior, contadas como conflito (uma vez por linha) quando ela mudou lá também.
+fn apply_sets(
+    conn: &Connection,
+    device_id: &str,
+    sets: &[ChangeSet],
+    last_run: Option<&str>,
+    report: &mut SyncReport,
+) -> Result<(), String> {
+    let mut conflicts = HashSet::new();
+
+    for set in sets {
+        if set.device_id == device_id {
+            continue;
+        }
+        if let Err(e) = set.validate() {
+            warn!("Conjunto de {} ignorado: {}", set.device_id, e);
+            continue;
+        }
+
+        for change in &set.changes {
+            let Some(keys) = key_columns(&change.table) else {
+                warn!("Tabela '{}' recebida na sincronização ignorada", change.table);
+                continue;
+            };
+            if keys.len() != change.key.len() {
+                continue;
+            }
+            report.received += 1;
+
+            let local = local_state(conn, change, &keys)?;
+            let resolution = sync::resolve(change, &local);
+            if resolution != Resolution::Unchanged
+                && last_run.is_some()
+                && is_after(local_modified(&local), last_run)
+            {
+                conflicts.insert((change.table.clone(), sync::row_key(&change.key)));
+            }
+
+            match resolution {
+                Resolution::Apply => {
+                    apply_change(conn, change, &keys, &local)?;
+                    if change.row.is_some() {
+                        report.applied += 1;
+                    } else {
+                        report.deleted += 1;
+                    }
+                }
+                Resolution::Keep => report.kept_local += 1,
+                Resolution::Unchanged => {}
+            }
+        }
+    }
+
+    report.conflicts = conflicts.len();
+    database::backfill_ownership(conn).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn load_cursor(conn: &Connection) -> Result<SyncCursor, String> {
+    Ok(database::get_setting(conn, constants::SETTING_SYNC_CURSOR)?
+        .and_then(|v| serde_json::from_str(&v).ok())
+        .unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn get_sync_settings(app: AppHandle, state: State<AppState>) -> Result<SyncSettings, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    load_settings(&app, &conn)
+}
+
+/// Configura o destino da sincronização (`transport` "folder" ou "http"; vazio desativa).
+/// Trocar de destino recomeça do zero: tudo é enviado e tudo é lido de novo.
+/// `token` vazio remove o token do servidor.
+#[tauri::command]
+pub fn set_sync_settings(
+    app: AppHandle,
+    state: State<AppState>,
+    transport: Option<String>,
+    folder_path: Option<String>,
+    server_url: Option<String>,
+    token: Option<String>,
+) -> Result<SyncSettings, String> {
+    let transport = transport.filter(|t| !t.trim().is_empty());
+    let folder_path = folder_path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    let server_url = server_url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+
+    match transport.as_deref() {
+        None => {}
+        Some(constants::SYNC_TRANSPORT_FOLDER) => match &folder_path {
+            Some(path) if Path::new(path).is_dir() => {}
+            _ => return Err("Escolha uma pasta existente para a sincronização".to_string()),
+        },
+        Some(constants::SYNC_TRANSPORT_HTTP) => match &server_url {
+            Some(url) if url.starts_with("http://") || url.starts_with("https://") => {}
+            _ => return Err("Informe a URL do servidor (http:// ou https://)".to_string()),
+        },
+        Some(other) => return Err(format!("Destino de sincronização inválido: {}", other)),
+    }
+
+    if let Some(token) = token {
+        if token.trim().is_empty() {
+            storage::delete_secret(&app, constants::SECRET_SYNC_TOKEN)?;
+        } else {
+            storage::set_secret(&app, constants::SECRET_SYNC_TOKEN, token.trim())?;
+        }
+    }
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    let current = load_settings(&app, &conn)?;
+    let changed = current.transport != transport
+        || current.folder_path != folder_path
+        || current.server_url != server_url;
+
+    database::in_transaction(&conn, |conn| {
+        for (key, value) in [
+            (constants::SETTING_SYNC_TRANSPORT, &transport),
+            (constants::SETTING_SYNC_FOLDER, &folder_path),
+            (constants::SETTING_SYNC_SERVER_URL, &server_url),
+        ] {
+            match value {
+                Some(value) => database::set_setting(conn, key, value)?,
+                None => {
+                    conn.execute("DELETE FROM app_settings WHERE key = ?1", params![key])
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+        }
+        if changed {
+            conn.execute(
+                "DELETE FROM app_settings WHERE key IN (?1, ?2, ?3)",
+                params![
+                    constants::SETTING_SYNC_CURSOR,
+                    constants::SETTING_SYNC_LAST_PUSH,
+                    constants::SETTING_SYNC_LAST_RUN
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    })?;
+
+    load_settings(&app, &conn)
+}
+
+/// Envia as alterações locais desde o último envio e aplica as dos outros dispositivos
+#[tauri::command]
+pub async fn sync_now(app: AppHandle, state: State<'_, AppState>) -> Result<SyncReport, String> {
+    let transport = {
+        let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+        let settings = load_settings(&app, &conn)?;
+        transport_for(&app, &settings)?
+    };
+    run_sync(&state.db, transport.as_ref()).await
+}
+
+/// Uma rodada de sincronização com o destino: envia, busca e aplica. O banco só fica
+/// bloqueado entre as etapas de rede.
+async fn run_sync(db: &Mutex<Connection>, transport: &dyn SyncTransport) -> Result<SyncReport, String> {
+    let (device_id, changes, cursor, now, last_run) = {
+        let conn = db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+        // Marca antes de coletar: o que mudar durante o envio entra na próxima rodada
+        let now: String = conn
+            .query_row("SELECT CURRENT_TIMESTAMP", [], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        let since = database::get_setting(&conn, constants::SETTING_SYNC_LAST_PUSH)?;
+        let changes = collect_changes(&conn, since.as_deref())?;
+
+        (
+            device_id(&conn)?,
+            changes,
+            load_cursor(&conn)?,
+            now,
+            database::get_setting(&conn, constants::SETTING_SYNC_LAST_RUN)?,
+        )
+    }; // Lock liberado aqui
+
+    let mut report = SyncReport {
+        pushed: changes.len(),
+        ..Default::default()
+    };
+
+    for chunk in changes.chunks(constants::SYNC_MAX_CHANGES_PER_SET) {
+        transport
+            .push(&ChangeSet::new(&device_id, chunk.to_vec()))
+            .await?;
+    }
+    {
+        let conn = db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+        database::set_setting(&conn, constants::SETTING_SYNC_LAST_PUSH, &now)?;
+    }
+
+    let (sets, cursor) = transport.pull(&device_id, &cursor).await?;
+
+    let conn = db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    database::in_transaction(&conn, |conn| {
+        journal::journaled(conn, "sync", None, |conn| {
+            apply_sets(conn, &device_id, &sets, last_run.as_deref(), &mut report)
+        })?;
+        let cursor = serde_json::to_string(&cursor).map_err(|e| e.to_string())?;
+        database::set_setting(conn, constants::SETTING_SYNC_CURSOR, &cursor)?;
+        database::set_setting(conn, constants::SETTING_SYNC_LAST_RUN, &now)
+    })?;
+
+    info!(
+        "Sincronização: {} enviadas, {} recebidas, {} aplicadas, {} excluídas, {} conflitos",
+        report.pushed, report.received, report.applied, report.deleted, report.conflicts
+    );
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{memory_db, serve_http, temp_dir};
+    use std::sync::Arc;
+
+    fn rating(db: &Mutex<Connection>, id: &str) -> Option<i32> {
+        db.lock()
+            .unwrap()
+            .query_row("SELECT rating FROM games WHERE id = ?1", [id], |row| row.get(0))
+            .optional()
+            .unwrap()
+    }
+
+    /// Dois dispositivos sincronizando pelo mesmo destino: criação, edição e exclusão
+    async fn round_trip(first: &dyn SyncTransport, second: &dyn SyncTransport) {
+        let a = Mutex::new(memory_db());
+        let b = Mutex::new(memory_db());
+        a.lock()
+            .unwrap()
+            .execute_batch(
+                "INSERT INTO games (id, name, rating) VALUES ('hades', 'Hades', 3), ('celeste', 'Celeste', 4);",
+            )
+            .unwrap();
+
+        let report = run_sync(&a, first).await.unwrap();
+        assert_eq!((report.pushed, report.received), (2, 0));
+        let report = run_sync(&b, second).await.unwrap();
+        assert_eq!(report.applied, 2);
+        assert_eq!(rating(&b, "hades"), Some(3));
+
+        // Edição com data posterior e exclusão no segundo dispositivo
+        b.lock()
+            .unwrap()
+            .execute_batch(
+                "UPDATE games SET rating = 5, updated_at = '2099-01-01 00:00:00' WHERE id = 'hades';
+                 DELETE FROM games WHERE id = 'celeste';",
+            )
+            .unwrap();
+        run_sync(&b, second).await.unwrap();
+        let report = run_sync(&a, first).await.unwrap();
+        assert_eq!(report.deleted, 1);
+        assert_eq!(rating(&a, "hades"), Some(5));
+        assert_eq!(rating(&a, "celeste"), None);
+
+        // Mais uma rodada não desfaz nada: os dois ficam com a mesma biblioteca
+        let report = run_sync(&b, second).await.unwrap();
+        assert_eq!((report.applied, report.deleted), (0, 0));
+        assert_eq!(rating(&b, "hades"), Some(5));
+        for db in [&a, &b] {
+            let conn = db.lock().unwrap();
+            assert_eq!(database::count_rows(&conn, "games").unwrap(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn folder_transport_syncs_two_devices() {
+        let root = temp_dir("sync_folder");
+        let transport = || FolderTransport { root: root.clone() };
+        round_trip(&transport(), &transport()).await;
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    /// Servidor de sincronização mínimo, com o protocolo de docs/sync.md
+    fn sync_server() -> String {
+        let sets: Arc<Mutex<Vec<ChangeSet>>> = Arc::default();
+        serve_http(move |request| {
+            let mut sets = sets.lock().unwrap();
+            let (path, query) = request.path.split_once('?').unwrap_or((&request.path, ""));
+            assert_eq!(path, "/changesets");
+            if request.method == "POST" {
+                sets.push(serde_json::from_slice(&request.body).unwrap());
+                return (200, "{}".to_string());
+            }
+
+            let param = |name: &str| {
+                query
+                    .split('&')
+                    .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+                    .unwrap_or_default()
+            };
+            let after: usize = param("after").parse().unwrap();
+            let changesets: Vec<&ChangeSet> = sets
+                .iter()
+                .skip(after)
+                .filter(|set| set.device_id != param("exclude"))
+                .collect();
+            let body = serde_json::json!({ "cursor": sets.len(), "changesets": changesets });
+            (200, body.to_string())
+        })
+    }
+
+    #[tokio::test]
+    async fn http_transport_syncs_two_devices() {
+        let url = sync_server();
+        let transport = || HttpTransport {
+            url: url.clone(),
+            token: None,
+        };
+        round_trip(&transport(), &transport()).await;
+    }
+}
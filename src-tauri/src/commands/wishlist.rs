@@ -1,11 +1,17 @@
-use crate::database::AppState;
-use crate::models::WishlistGame;
+use crate::commands::ownership::normalize_currency;
+use crate::commands::purchases;
+use crate::constants;
+use crate::database::{self, journal, AppState};
+use crate::models::{WishlistGame, WishlistReleaseEvent};
 use crate::services::steam::{self, StoreSearchItem};
-use rusqlite::params;
+use crate::storage;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashMap;
 use std::time::Duration;
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 use tokio::time::sleep;
-use tracing::{info, error};
+use tracing::{error, info, warn};
 
 #[tauri::command]
 pub async fn search_wishlist_game(query: String) -> Result<Vec<StoreSearchItem>, String> {
@@ -13,8 +19,8 @@ pub async fn search_wishlist_game(query: String) -> Result<Vec<StoreSearchItem>,
 }
 
 #[tauri::command]
-pub fn add_to_wishlist(
-    state: State<AppState>,
+pub async fn add_to_wishlist(
+    state: State<'_, AppState>,
     id: String,
     name: String,
     cover_url: Option<String>,
@@ -24,70 +30,450 @@ pub fn add_to_wishlist(
 ) -> Result<String, String> {
     info!("Tentando adicionar à Wishlist: ID={}, Nome={}, SteamID={:?}", id, name, steam_app_id);
 
-    let conn = state.db.lock().map_err(|e| {
-        error!("Erro de Mutex na Wishlist: {}", e);
-        "Falha interna ao acessar banco".to_string()
-    })?;
+    {
+        let conn = state.db.lock().map_err(|e| {
+            error!("Erro de Mutex na Wishlist: {}", e);
+            "Falha interna ao acessar banco".to_string()
+        })?;
 
-    // Tenta inserir e loga o resultado exato
-    match conn.execute(
-        "INSERT OR REPLACE INTO wishlist (id, name, cover_url, store_url, current_price, steam_app_id, added_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, CURRENT_TIMESTAMP)",
-        params![id, name, cover_url, store_url, current_price, steam_app_id],
-    ) {
-        Ok(_) => {
-            info!("Sucesso: Jogo {} adicionado à wishlist.", name);
-            Ok("Jogo adicionado à lista de desejos!".to_string())
-        },
-        Err(e) => {
+        // Tenta inserir e loga o resultado exato
+        if let Err(e) = conn.execute(
+            "INSERT OR REPLACE INTO wishlist (id, name, cover_url, store_url, current_price, steam_app_id, added_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, CURRENT_TIMESTAMP)",
+            params![id, name, cover_url, store_url, current_price, steam_app_id],
+        ) {
             error!("Erro SQL ao adicionar {}: {:?}", name, e);
-            Err(format!("Erro de banco de dados: {}", e))
+            return Err(format!("Erro de banco de dados: {}", e));
+        }
+    }
+    info!("Sucesso: Jogo {} adicionado à wishlist.", name);
+
+    // Tipo do app (DLC, demo...) e jogo base; sem eles o item só fica sem classificação
+    if let Some(app_id) = steam_app_id {
+        match steam::fetch_app_type(app_id as u32).await {
+            Ok(Some(info)) => {
+                let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+                if let Err(e) = conn.execute(
+                    "UPDATE wishlist SET app_type = ?1, parent_app_id = ?2 WHERE id = ?3",
+                    params![info.app_type, info.parent_app_id, id],
+                ) {
+                    warn!("Erro ao salvar o tipo do app {}: {}", app_id, e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Erro ao buscar o tipo do app {}: {}", app_id, e),
         }
     }
+
+    Ok("Jogo adicionado à lista de desejos!".to_string())
 }
 
 #[tauri::command]
 pub fn remove_from_wishlist(state: State<AppState>, id: String) -> Result<String, String> {
     let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
 
-    conn.execute("DELETE FROM wishlist WHERE id = ?1", params![id])
+    let name: Option<String> = conn
+        .query_row("SELECT name FROM wishlist WHERE id = ?1", params![id], |row| row.get(0))
+        .optional()
         .map_err(|e| e.to_string())?;
 
+    // O item vai para a lixeira e pode ser restaurado
+    journal::journaled(&conn, "remove_from_wishlist", name.as_deref(), |conn| {
+        conn.execute("DELETE FROM wishlist WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())
+    })?;
+
     Ok("Jogo removido da lista de desejos.".to_string())
 }
 
+const WISHLIST_COLUMNS: &str = "id, name, cover_url, store_url, current_price, lowest_price, on_sale, localized_price, localized_currency, steam_app_id, added_at, archived_at, purchased_game_id, source, steam_priority, priority, notes, tags, sort_position, discount_percent, release_date, release_date_text, coming_soon, app_type, parent_app_id";
+
+/// Tags são guardadas como texto separado por vírgulas (como `games.genre`)
+pub(crate) fn parse_tags(raw: Option<String>) -> Vec<String> {
+    raw.map(|t| {
+        t.split(',')
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+pub(crate) fn join_tags(tags: &[String]) -> Option<String> {
+    if tags.is_empty() {
+        None
+    } else {
+        Some(tags.join(", "))
+    }
+}
+
+fn map_wishlist_row(row: &rusqlite::Row) -> rusqlite::Result<WishlistGame> {
+    Ok(WishlistGame {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        cover_url: row.get(2)?,
+        store_url: row.get(3)?,
+        current_price: row.get(4)?,
+        lowest_price: row.get(5)?,
+        on_sale: row.get(6)?,
+        localized_price: row.get(7)?,
+        localized_currency: row.get(8)?,
+        steam_app_id: row.get(9)?,
+        added_at: row.get(10)?,
+        archived_at: row.get(11)?,
+        purchased_game_id: row.get(12)?,
+        source: row.get(13)?,
+        steam_priority: row.get(14)?,
+        priority: row.get(15)?,
+        notes: row.get(16)?,
+        tags: parse_tags(row.get(17)?),
+        sort_position: row.get(18)?,
+        discount_percent: row.get(19)?,
+        release_date: row.get(20)?,
+        release_date_text: row.get(21)?,
+        coming_soon: row.get::<_, Option<bool>>(22)?.unwrap_or(false),
+        app_type: row.get(23)?,
+        parent_app_id: row.get(24)?,
+    })
+}
+
+/// Cláusula ORDER BY de cada ordenação disponível para a lista ativa
+fn wishlist_order(sort_by: Option<&str>) -> Result<&'static str, String> {
+    match sort_by.unwrap_or("manual") {
+        // Ordem manual; sem posição, segue a ordem da Steam e depois os mais recentes
+        "manual" => Ok("sort_position IS NULL, sort_position, steam_priority IS NULL, steam_priority, added_at DESC"),
+        "added" => Ok("added_at DESC"),
+        "discount" => Ok("IFNULL(discount_percent, 0) DESC, name COLLATE NOCASE"),
+        "price" => Ok("COALESCE(localized_price, current_price) IS NULL, COALESCE(localized_price, current_price), name COLLATE NOCASE"),
+        // Quanto mais perto do menor preço já visto, melhor o momento de comprar
+        "lowest_ratio" => Ok("localized_price IS NULL OR IFNULL(lowest_price, 0) <= 0, localized_price / lowest_price, name COLLATE NOCASE"),
+        "priority" => Ok("IFNULL(priority, 0) DESC, sort_position IS NULL, sort_position, added_at DESC"),
+        other => Err(format!("Ordenação desconhecida: {}", other)),
+    }
+}
+
+fn query_wishlist(
+    conn: &Connection,
+    archived: bool,
+    sort_by: Option<&str>,
+) -> Result<Vec<WishlistGame>, String> {
+    let (filter, order) = if archived {
+        ("archived_at IS NOT NULL", "archived_at DESC")
+    } else {
+        ("archived_at IS NULL", wishlist_order(sort_by)?)
+    };
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM wishlist WHERE {} ORDER BY {}",
+            WISHLIST_COLUMNS, filter, order
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let games = stmt
+        .query_map([], map_wishlist_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(games)
+}
+
+/// `sort_by`: "manual" (padrão), "added", "discount", "price", "lowest_ratio" ou "priority"
 #[tauri::command]
-pub fn get_wishlist(state: State<AppState>) -> Result<Vec<WishlistGame>, String> {
+pub fn get_wishlist(
+    state: State<AppState>,
+    sort_by: Option<String>,
+) -> Result<Vec<WishlistGame>, String> {
     let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    query_wishlist(&conn, false, sort_by.as_deref())
+}
 
-    let mut stmt = conn
-        .prepare("SELECT id, name, cover_url, store_url, current_price, lowest_price, on_sale, localized_price, localized_currency, steam_app_id, added_at FROM wishlist ORDER BY added_at DESC")
+/// Atualiza os dados do usuário de um item: prioridade, notas e tags
+#[tauri::command]
+pub fn update_wishlist_item(
+    state: State<AppState>,
+    id: String,
+    priority: Option<i32>,
+    notes: Option<String>,
+    tags: Vec<String>,
+) -> Result<(), String> {
+    if let Some(p) = priority {
+        if !(constants::MIN_WISHLIST_PRIORITY..=constants::MAX_WISHLIST_PRIORITY).contains(&p) {
+            return Err(format!(
+                "Prioridade deve estar entre {} e {}",
+                constants::MIN_WISHLIST_PRIORITY,
+                constants::MAX_WISHLIST_PRIORITY
+            ));
+        }
+    }
+
+    let notes = notes.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    if notes.as_ref().is_some_and(|n| n.len() > constants::MAX_NOTES_LENGTH) {
+        return Err(format!(
+            "Notas muito longas (máximo {} caracteres)",
+            constants::MAX_NOTES_LENGTH
+        ));
+    }
+
+    let mut clean_tags: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_string();
+        if tag.is_empty() || clean_tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            continue;
+        }
+        if tag.contains(',') {
+            return Err(format!("Tag inválida (não use vírgulas): {}", tag));
+        }
+        if tag.len() > constants::MAX_TAG_LENGTH {
+            return Err(format!(
+                "Tag muito longa (máximo {} caracteres): {}",
+                constants::MAX_TAG_LENGTH,
+                tag
+            ));
+        }
+        clean_tags.push(tag);
+    }
+    if clean_tags.len() > constants::MAX_TAGS_PER_ITEM {
+        return Err(format!(
+            "Muitas tags (máximo {} por item)",
+            constants::MAX_TAGS_PER_ITEM
+        ));
+    }
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    let rows = conn
+        .execute(
+            "UPDATE wishlist SET priority = ?1, notes = ?2, tags = ?3 WHERE id = ?4",
+            params![priority, notes, join_tags(&clean_tags), id],
+        )
         .map_err(|e| e.to_string())?;
 
-    let games_iter = stmt
-        .query_map([], |row| {
-            Ok(WishlistGame {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                cover_url: row.get(2)?,
-                store_url: row.get(3)?,
-                current_price: row.get(4)?,
-                lowest_price: row.get(5)?,
-                on_sale: row.get(6)?,
-                localized_price: row.get(7)?,
-                localized_currency: row.get(8)?,
-                steam_app_id: row.get(9)?,
-                added_at: row.get(10)?,
-            })
+    if rows == 0 {
+        return Err("Item não encontrado na lista de desejos".to_string());
+    }
+
+    Ok(())
+}
+
+/// Define a ordem manual: `ids` na ordem desejada (o primeiro fica no topo).
+/// Itens ativos fora da lista vão para o final, mantendo a ordem relativa.
+#[tauri::command]
+pub fn reorder_wishlist(state: State<AppState>, ids: Vec<String>) -> Result<(), String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    let current: Vec<String> = query_wishlist(&conn, false, Some("manual"))?
+        .into_iter()
+        .map(|item| item.id)
+        .collect();
+
+    let ordered: Vec<&String> = ids
+        .iter()
+        .filter(|id| current.contains(id))
+        .chain(current.iter().filter(|id| !ids.contains(id)))
+        .collect();
+
+    database::in_transaction(&conn, |conn| {
+        journal::journaled(conn, "reorder_wishlist", None, |conn| {
+            for (position, id) in ordered.iter().enumerate() {
+                conn.execute(
+                    "UPDATE wishlist SET sort_position = ?1 WHERE id = ?2",
+                    params![position as i32 + 1, id],
+                )
+                .map_err(|e| format!("Erro ao reordenar lista de desejos: {}", e))?;
+            }
+            Ok(())
         })
-        .map_err(|e| e.to_string())?;
+    })
+}
+
+/// Itens comprados (arquivados), do mais recente para o mais antigo
+#[tauri::command]
+pub fn get_archived_wishlist(state: State<AppState>) -> Result<Vec<WishlistGame>, String> {
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    query_wishlist(&conn, true, None)
+}
+
+/// Arquiva o item e registra o jogo da biblioteca criado a partir dele
+fn archive_wishlist_item(conn: &Connection, wishlist_id: &str, game_id: &str) -> rusqlite::Result<usize> {
+    conn.execute(
+        "UPDATE wishlist SET archived_at = CURRENT_TIMESTAMP, purchased_game_id = ?1
+         WHERE id = ?2 AND archived_at IS NULL",
+        params![game_id, wishlist_id],
+    )
+}
+
+/// Arquiva os itens ativos cujo AppID já está na biblioteca (ex: comprados fora do app).
+/// O preço visto na wishlist vira o preço pago da cópia, se ainda não houver um.
+pub(crate) fn archive_owned_wishlist_items(conn: &Connection) -> rusqlite::Result<usize> {
+    let candidates: Vec<(String, i32)> = conn
+        .prepare("SELECT id, steam_app_id FROM wishlist WHERE archived_at IS NULL AND steam_app_id IS NOT NULL")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
 
-    let mut games = Vec::new();
-    for game in games_iter {
-        games.push(game.map_err(|e| e.to_string())?);
+    let mut archived = 0;
+    for (wishlist_id, app_id) in candidates {
+        let app_id = app_id.to_string();
+        let Some(game_id) =
+            database::owned_game_id(conn, constants::DEFAULT_PLATFORM_STEAM, &app_id)?
+        else {
+            continue;
+        };
+
+        purchases::fill_purchase_from_wishlist(conn, &game_id, &wishlist_id)?;
+        archived += archive_wishlist_item(conn, &wishlist_id, &game_id)?;
     }
 
-    Ok(games)
+    Ok(archived)
+}
+
+/// Marca um item da lista de desejos como comprado: cria (ou reaproveita) o jogo na biblioteca,
+/// registra a cópia com o preço pago e arquiva o item, tudo em uma única transação.
+/// Sem `price_paid`, usa o último preço visto na wishlist.
+#[tauri::command]
+pub fn purchase_wishlist_item(
+    state: State<AppState>,
+    id: String,
+    platform: Option<String>,
+    price_paid: Option<f64>,
+    currency: Option<String>,
+    purchase_date: Option<String>,
+) -> Result<String, String> {
+    if let Some(price) = price_paid {
+        if !price.is_finite() || price < 0.0 {
+            return Err("Preço pago não pode ser negativo".to_string());
+        }
+    }
+    if let Some(date) = &purchase_date {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| "Data de compra inválida (use AAAA-MM-DD)".to_string())?;
+    }
+    let currency = match currency.as_deref().filter(|c| !c.trim().is_empty()) {
+        Some(code) => Some(normalize_currency(code)?),
+        None => None,
+    };
+    let platform = platform
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| constants::DEFAULT_PLATFORM_STEAM.to_string());
+    if platform.len() > constants::MAX_PLATFORM_LENGTH {
+        return Err(format!(
+            "Plataforma muito longa (máximo {} caracteres)",
+            constants::MAX_PLATFORM_LENGTH
+        ));
+    }
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    let item = conn
+        .query_row(
+            &format!("SELECT {} FROM wishlist WHERE id = ?1", WISHLIST_COLUMNS),
+            params![id],
+            map_wishlist_row,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or("Item não encontrado na lista de desejos")?;
+
+    if item.archived_at.is_some() {
+        return Err("Este item já foi marcado como comprado".to_string());
+    }
+
+    let result = database::in_transaction(&conn, |conn| {
+        journal::journaled(conn, "purchase_wishlist_item", Some(&item.name), |conn| {
+            save_purchase(
+                conn,
+                &item,
+                &platform,
+                price_paid,
+                currency.as_deref(),
+                purchase_date.as_deref(),
+            )
+            .map_err(|e| e.to_string())
+        })
+    });
+
+    match result {
+        Ok(game_id) => {
+            info!("Wishlist: '{}' comprado e movido para a biblioteca ({})", item.name, game_id);
+            Ok(format!("{} foi adicionado à biblioteca!", item.name))
+        }
+        Err(e) => {
+            error!("Erro ao converter item da wishlist {}: {}", item.id, e);
+            Err(format!("Erro ao mover jogo para a biblioteca: {}", e))
+        }
+    }
+}
+
+/// Cria (ou reaproveita) o jogo do item comprado, registra a cópia e arquiva o item.
+/// Retorna o ID do jogo na biblioteca.
+fn save_purchase(
+    conn: &Connection,
+    item: &WishlistGame,
+    platform: &str,
+    price_paid: Option<f64>,
+    currency: Option<&str>,
+    purchase_date: Option<&str>,
+) -> rusqlite::Result<String> {
+    let is_steam = platform == constants::DEFAULT_PLATFORM_STEAM;
+    let app_id = item.steam_app_id.map(|a| a.to_string());
+    let external_id = if is_steam { app_id.clone() } else { None };
+
+    // Reaproveita o jogo se a cópia Steam já estiver na biblioteca (ex: após mesclagem)
+    let existing = match &external_id {
+        Some(ext) => database::owned_game_id(conn, platform, ext)?,
+        None => None,
+    };
+    let game_id = existing.unwrap_or_else(|| {
+        external_id
+            .clone()
+            .unwrap_or_else(|| format!("wishlist:{}", item.id))
+    });
+
+    let cover_url = match &app_id {
+        Some(app) => Some(format!(
+            "{}/steam/apps/{}/library_600x900.jpg",
+            constants::STEAM_CDN_URL,
+            app
+        )),
+        None => item.cover_url.clone(),
+    };
+
+    conn.execute(
+        "INSERT OR IGNORE INTO games (id, name, genre, platform, cover_url, playtime, rating)
+         VALUES (?1, ?2, ?3, ?4, ?5, 0, NULL)",
+        params![
+            game_id,
+            item.name,
+            constants::DEFAULT_GENRE,
+            platform,
+            cover_url
+        ],
+    )?;
+
+    database::record_ownership(conn, &game_id, Some(platform), external_id.as_deref())?;
+
+    if price_paid.is_some() {
+        conn.execute(
+            "UPDATE game_ownership
+                SET price_paid = ?1, currency = ?2, purchase_date = COALESCE(?3, purchase_date, date('now'))
+              WHERE game_id = ?4 AND platform = ?5",
+            params![price_paid, currency, purchase_date, game_id, platform],
+        )?;
+    } else {
+        purchases::fill_purchase_from_wishlist(conn, &game_id, &item.id)?;
+        if purchase_date.is_some() {
+            conn.execute(
+                "UPDATE game_ownership SET purchase_date = ?1 WHERE game_id = ?2 AND platform = ?3",
+                params![purchase_date, game_id, platform],
+            )?;
+        }
+    }
+
+    archive_wishlist_item(conn, &item.id, &game_id)?;
+
+    Ok(game_id)
 }
 
 #[tauri::command]
@@ -96,7 +482,7 @@ pub fn check_wishlist_status(state: State<AppState>, id: String) -> Result<bool,
 
     let count: i32 = conn
         .query_row(
-            "SELECT COUNT(1) FROM wishlist WHERE id = ?1",
+            "SELECT COUNT(1) FROM wishlist WHERE id = ?1 AND archived_at IS NULL",
             params![id],
             |row| row.get(0),
         )
@@ -105,13 +491,127 @@ pub fn check_wishlist_status(state: State<AppState>, id: String) -> Result<bool,
     Ok(count > 0)
 }
 
+/// Grava a situação de lançamento e indica se o item foi lançado ou teve a data alterada
+fn update_release_info(
+    conn: &Connection,
+    id: &str,
+    name: &str,
+    status: &steam::StoreStatus,
+) -> rusqlite::Result<Option<WishlistReleaseEvent>> {
+    let previous: Option<(bool, Option<String>, Option<String>)> = conn
+        .query_row(
+            "SELECT IFNULL(coming_soon, 0), release_date_text, release_date FROM wishlist WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+    let Some((was_coming_soon, previous_text, previous_date)) = previous else {
+        return Ok(None);
+    };
+
+    let release_date = status.release_date.map(|d| d.format("%Y-%m-%d").to_string());
+    conn.execute(
+        "UPDATE wishlist SET coming_soon = ?1, release_date_text = ?2, release_date = ?3 WHERE id = ?4",
+        params![status.coming_soon, status.release_text, release_date, id],
+    )?;
+
+    let kind = if was_coming_soon && !status.coming_soon {
+        "released"
+    } else if status.coming_soon
+        && previous_text.is_some()
+        && status.release_text.is_some()
+        && previous_text != status.release_text
+    {
+        "date_changed"
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(WishlistReleaseEvent {
+        id: id.to_string(),
+        name: name.to_string(),
+        kind: kind.to_string(),
+        previous_date: previous_date.or(previous_text),
+        release_date: release_date.or_else(|| status.release_text.clone()),
+    }))
+}
+
+/// Grava preço e situação de lançamento consultados na loja
+fn save_store_status(
+    conn: &Connection,
+    id: &str,
+    name: &str,
+    app_id: i32,
+    status: &steam::StoreStatus,
+) -> rusqlite::Result<Option<WishlistReleaseEvent>> {
+    // URL da loja Steam para o botão "Ir para Loja"
+    let store_url = format!("https://store.steampowered.com/app/{}/", app_id);
+
+    // Atualiza BRL (jogos não lançados costumam vir sem preço)
+    if let Some(price) = &status.price {
+        let on_sale = price.discount_percent > 0;
+        conn.execute(
+            "UPDATE wishlist
+                SET localized_price = ?1, localized_currency = ?2,
+                    on_sale = ?3, store_url = ?4, discount_percent = ?6,
+                    lowest_price = MIN(IFNULL(lowest_price, 9999), ?1)
+                WHERE id = ?5",
+            params![
+                price.final_price,
+                price.currency,
+                on_sale,
+                store_url,
+                id,
+                price.discount_percent
+            ],
+        )?;
+    }
+
+    update_release_info(conn, id, name, status)
+}
+
+/// Itens ativos com lançamento no mês informado ("AAAA-MM"; padrão: mês atual)
 #[tauri::command]
-pub async fn refresh_prices(state: State<'_, AppState>) -> Result<String, String> {
+pub fn get_wishlist_releases(
+    state: State<AppState>,
+    month: Option<String>,
+) -> Result<Vec<WishlistGame>, String> {
+    let month = match month.filter(|m| !m.trim().is_empty()) {
+        Some(m) => {
+            chrono::NaiveDate::parse_from_str(&format!("{}-01", m.trim()), "%Y-%m-%d")
+                .map_err(|_| "Mês inválido (use AAAA-MM)".to_string())?;
+            m.trim().to_string()
+        }
+        None => chrono::Local::now().format("%Y-%m").to_string(),
+    };
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM wishlist
+             WHERE archived_at IS NULL AND substr(release_date, 1, 7) = ?1
+             ORDER BY release_date, name COLLATE NOCASE",
+            WISHLIST_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let games = stmt
+        .query_map(params![month], map_wishlist_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(games)
+}
+
+#[tauri::command]
+pub async fn refresh_prices(app: AppHandle, state: State<'_, AppState>) -> Result<String, String> {
     // Busca dados básicos do banco
     let games: Vec<(String, Option<i32>, String)> = {
         let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
         let mut stmt = conn
-            .prepare("SELECT id, steam_app_id, name FROM wishlist")
+            .prepare("SELECT id, steam_app_id, name FROM wishlist WHERE archived_at IS NULL")
             .map_err(|e| e.to_string())?;
 
         let rows = stmt
@@ -124,6 +624,7 @@ pub async fn refresh_prices(state: State<'_, AppState>) -> Result<String, String
 
     let total = games.len();
     let mut updated_count = 0;
+    let mut failed_count = 0;
 
     for (id, steam_app_id, name) in games {
         let mut current_app_id = steam_app_id;
@@ -135,34 +636,40 @@ pub async fn refresh_prices(state: State<'_, AppState>) -> Result<String, String
                 if let Some(first) = results.first() {
                     current_app_id = Some(first.id as i32);
                     let conn = state.db.lock().map_err(|_| "Falha mutex")?;
-                    let _ = conn.execute(
+                    if let Err(e) = conn.execute(
                         "UPDATE wishlist SET steam_app_id = ?1 WHERE id = ?2",
                         params![current_app_id, &id],
-                    );
+                    ) {
+                        warn!("Erro ao salvar AppID de '{}': {}", name, e);
+                    }
                 }
             }
         }
 
-        // 2. Busca Preço na Steam
+        // 2. Busca preço e data de lançamento na Steam
         if let Some(app_id_val) = current_app_id {
-            match steam::fetch_price(app_id_val as u32).await {
-                Ok(Some(price)) => {
-                    let conn = state.db.lock().map_err(|_| "Falha mutex")?;
-                    let on_sale = price.discount_percent > 0;
-
-                    // URL da loja Steam para o botão "Ir para Loja"
-                    let store_url = format!("https://store.steampowered.com/app/{}/", app_id_val);
-
-                    // Atualiza BRL
-                    let _ = conn.execute(
-                        "UPDATE wishlist
-                            SET localized_price = ?1, localized_currency = ?2,
-                                on_sale = ?3, store_url = ?4,
-                                lowest_price = MIN(IFNULL(lowest_price, 9999), ?1)
-                            WHERE id = ?5",
-                        params![price.final_price, price.currency, on_sale, store_url, id],
-                    );
-                    updated_count += 1;
+            match steam::fetch_store_status(app_id_val as u32).await {
+                Ok(Some(status)) => {
+                    let saved = {
+                        let conn = state.db.lock().map_err(|_| "Falha mutex")?;
+                        save_store_status(&conn, &id, &name, app_id_val, &status)
+                    };
+
+                    match saved {
+                        Ok(event) => {
+                            if let Some(event) = event {
+                                info!("Wishlist: {} ({})", event.name, event.kind);
+                                if let Err(e) = app.emit(constants::EVENT_WISHLIST_RELEASE, &event) {
+                                    warn!("Falha ao emitir evento de lançamento: {}", e);
+                                }
+                            }
+                            updated_count += 1;
+                        }
+                        Err(e) => {
+                            warn!("Erro ao salvar preço de '{}': {}", name, e);
+                            failed_count += 1;
+                        }
+                    }
                 }
                 Ok(None) => {
                     // Opcional: Aqui você poderia implementar o fallback para USD
@@ -177,5 +684,407 @@ pub async fn refresh_prices(state: State<'_, AppState>) -> Result<String, String
         sleep(Duration::from_millis(500)).await;
     }
 
+    if failed_count > 0 {
+        return Ok(format!(
+            "Preços atualizados: {}/{} ({} não puderam ser salvos, veja o log)",
+            updated_count, total, failed_count
+        ));
+    }
+
     Ok(format!("Preços atualizados: {}/{}", updated_count, total))
 }
+
+#[derive(Serialize)]
+pub struct WishlistImportSummary {
+    pub total: i32,
+    pub added: i32,
+    pub updated: i32,
+    pub removed: i32,
+    /// Itens que já estavam na biblioteca e foram arquivados como comprados
+    pub archived: i32,
+    /// Itens adicionados com nome provisório porque a loja não informou o nome
+    pub unnamed: i32,
+    pub message: String,
+}
+
+/// Importa a lista de desejos pública do `steam_id` configurado, preservando a ordem da Steam.
+/// Com `remove_missing`, itens importados da Steam que saíram de lá são removidos
+/// (itens adicionados manualmente nunca são removidos).
+#[tauri::command]
+pub async fn import_steam_wishlist(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    remove_missing: bool,
+) -> Result<WishlistImportSummary, String> {
+    let steam_id = storage::get_secret(&app, "steam_id")?;
+    if steam_id.trim().is_empty() {
+        return Err("Configure o Steam ID nas configurações antes de importar".to_string());
+    }
+
+    import_wishlist(
+        &state,
+        &steam::SteamEndpoints::default(),
+        steam_id.trim(),
+        remove_missing,
+    )
+    .await
+}
+
+/// Nome provisório de um app cujo nome a loja não informou; trocado na próxima importação
+fn placeholder_name(app_id: u32) -> String {
+    format!("App {}", app_id)
+}
+
+async fn import_wishlist(
+    state: &AppState,
+    endpoints: &steam::SteamEndpoints,
+    steam_id: &str,
+    remove_missing: bool,
+) -> Result<WishlistImportSummary, String> {
+    let items = steam::fetch_wishlist(endpoints, steam_id).await?;
+    info!("Wishlist Steam: {} itens encontrados", items.len());
+
+    // AppIDs já conhecidos localmente (ativos ou arquivados) -> ID e nome do item
+    let known: HashMap<i32, (String, String)> = {
+        let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+        let mut stmt = conn
+            .prepare("SELECT steam_app_id, id, name FROM wishlist WHERE steam_app_id IS NOT NULL")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+
+    // Só os itens novos (ou com nome provisório) precisam do nome: a API devolve só o AppID
+    let needs_name = |appid: u32| match known.get(&(appid as i32)) {
+        Some((_, name)) => *name == placeholder_name(appid),
+        None => true,
+    };
+    // A mesma consulta traz o tipo do app (DLC, demo...) e o jogo base
+    let mut basics: HashMap<u32, steam::AppBasics> = HashMap::new();
+    for item in items.iter().filter(|i| needs_name(i.appid)) {
+        match steam::fetch_app_basics(endpoints, item.appid).await {
+            Ok(Some(found)) => {
+                basics.insert(item.appid, found);
+            }
+            Ok(None) => warn!("App {} da wishlist não existe mais na loja", item.appid),
+            Err(e) => warn!("Erro ao buscar nome do app {}: {}", item.appid, e),
+        }
+        sleep(Duration::from_millis(constants::STEAM_RATE_LIMIT_MS)).await;
+    }
+
+    let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+
+    // Itens removidos por não estarem mais na Steam vão para a lixeira
+    let (added, updated, removed, archived, unnamed) = database::in_transaction(&conn, |conn| {
+        journal::journaled(conn, "import", Some("Steam (lista de desejos)"), |conn| {
+            save_steam_wishlist(conn, &items, &known, &basics, remove_missing).map_err(|e| {
+                error!("Erro ao importar wishlist Steam: {}", e);
+                format!("Erro ao salvar a lista de desejos: {}", e)
+            })
+        })
+    })?;
+
+    let message = if items.is_empty() {
+        "A lista de desejos da Steam está vazia. Nenhum item foi removido.".to_string()
+    } else if unnamed > 0 {
+        format!(
+            "Lista de desejos importada! {} novos ({} sem nome na loja), {} atualizados, {} removidos.",
+            added, unnamed, updated, removed
+        )
+    } else {
+        format!(
+            "Lista de desejos importada! {} novos, {} atualizados, {} removidos.",
+            added, updated, removed
+        )
+    };
+
+    Ok(WishlistImportSummary {
+        total: items.len() as i32,
+        added,
+        updated,
+        removed,
+        archived,
+        unnamed,
+        message,
+    })
+}
+
+/// Grava os itens da lista de desejos da Steam na ordem de lá.
+/// Retorna os itens novos, atualizados, removidos, arquivados e sem nome na loja.
+fn save_steam_wishlist(
+    conn: &Connection,
+    items: &[steam::SteamWishlistItem],
+    known: &HashMap<i32, (String, String)>,
+    basics: &HashMap<u32, steam::AppBasics>,
+    remove_missing: bool,
+) -> rusqlite::Result<(i32, i32, i32, i32, i32)> {
+    let (mut added, mut updated, mut removed, mut unnamed) = (0, 0, 0, 0);
+
+    for (position, item) in items.iter().enumerate() {
+        let app_id = item.appid as i32;
+        let priority = position as i32 + 1;
+        let found = basics.get(&item.appid);
+        let name = found.and_then(|b| b.name.clone());
+        let app_type = found.and_then(|b| b.app_type.as_ref());
+        let kind = app_type.map(|t| t.app_type.clone());
+        let parent_app_id = app_type.and_then(|t| t.parent_app_id).map(|id| id as i64);
+
+        if let Some((id, _)) = known.get(&app_id) {
+            // Itens já comprados (arquivados) continuam arquivados
+            updated += conn.execute(
+                "UPDATE wishlist SET steam_priority = ?1, name = COALESCE(?3, name),
+                        app_type = COALESCE(?4, app_type), parent_app_id = COALESCE(?5, parent_app_id)
+                 WHERE id = ?2 AND archived_at IS NULL",
+                params![priority, id, name, kind, parent_app_id],
+            )? as i32;
+            continue;
+        }
+
+        // Sem nome da loja, o item entra com um nome provisório em vez de ser perdido
+        let name = match name {
+            Some(name) => name,
+            None => {
+                unnamed += 1;
+                placeholder_name(item.appid)
+            }
+        };
+
+        let added_at = chrono::DateTime::from_timestamp(item.date_added, 0)
+            .filter(|_| item.date_added > 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string());
+
+        added += conn.execute(
+            "INSERT OR IGNORE INTO wishlist (id, name, cover_url, store_url, steam_app_id, added_at, source, steam_priority, app_type, parent_app_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, COALESCE(?6, CURRENT_TIMESTAMP), 'steam', ?7, ?8, ?9)",
+            params![
+                app_id.to_string(),
+                name,
+                format!("{}/steam/apps/{}/header.jpg", constants::STEAM_CDN_URL, app_id),
+                format!("{}/app/{}/", constants::STEAM_STORE_URL, app_id),
+                app_id,
+                added_at,
+                priority,
+                kind,
+                parent_app_id
+            ],
+        )? as i32;
+    }
+
+    // Uma lista vazia nunca apaga nada: é mais provável um problema na Steam do que o
+    // usuário ter esvaziado a lista
+    if remove_missing && !items.is_empty() {
+        let on_steam: Vec<i32> = items.iter().map(|i| i.appid as i32).collect();
+        let imported: Vec<(String, i32)> = conn
+            .prepare(
+                "SELECT id, steam_app_id FROM wishlist
+                 WHERE source = 'steam' AND archived_at IS NULL AND steam_app_id IS NOT NULL",
+            )?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (id, app_id) in imported {
+            if !on_steam.contains(&app_id) {
+                removed += conn.execute("DELETE FROM wishlist WHERE id = ?1", params![id])? as i32;
+            }
+        }
+    }
+
+    let archived = archive_owned_wishlist_items(conn)? as i32;
+
+    Ok((added, updated, removed, archived, unnamed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{memory_db, serve_http};
+    use std::sync::Mutex;
+
+    /// Steam falsa: lista de desejos fixa e nomes só para alguns apps
+    fn fake_steam(wishlist: &'static str) -> steam::SteamEndpoints {
+        let base = serve_http(move |request| {
+            assert!(request.method == "GET" && request.body.is_empty());
+            if request.path.starts_with("/IWishlistService/GetWishlist/v1/") {
+                return (200, wishlist.to_string());
+            }
+            let app_id = request
+                .path
+                .split("appids=")
+                .nth(1)
+                .and_then(|rest| rest.split('&').next())
+                .unwrap_or_default()
+                .to_string();
+            match app_id.as_str() {
+                "10" => (200, r#"{"10": {"success": true, "data": {"name": "Counter-Strike"}}}"#.to_string()),
+                "20" => (
+                    200,
+                    r#"{"20": {"success": true, "data": {"name": "Team Fortress", "type": "DLC", "fullgame": {"appid": "10"}}}}"#
+                        .to_string(),
+                ),
+                "30" => (200, r#"{"30": {"success": false}}"#.to_string()),
+                _ => (500, "erro".to_string()),
+            }
+        });
+
+        steam::SteamEndpoints {
+            web_api: base.clone(),
+            store: base,
+        }
+    }
+
+    fn state_with_items(items: &[(&str, i32, &str)]) -> AppState {
+        let conn = memory_db();
+        for (id, app_id, source) in items {
+            conn.execute(
+                "INSERT INTO wishlist (id, name, steam_app_id, source) VALUES (?1, ?1, ?2, ?3)",
+                params![id, app_id, source],
+            )
+            .unwrap();
+        }
+        AppState { db: Mutex::new(conn) }
+    }
+
+    fn wishlist_names(state: &AppState) -> Vec<String> {
+        let conn = state.db.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT name FROM wishlist ORDER BY id").unwrap();
+        let names = stmt
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        names
+    }
+
+    #[tokio::test]
+    async fn imports_in_order_and_keeps_unnamed_items() {
+        let endpoints = fake_steam(
+            r#"{"response": {"items": [
+                {"appid": 20, "priority": 2, "date_added": 1700000000},
+                {"appid": 10, "priority": 1, "date_added": 1700000000},
+                {"appid": 30, "priority": 0, "date_added": 1690000000},
+                {"appid": 40, "priority": 0, "date_added": 1710000000}
+            ]}}"#,
+        );
+        let state = state_with_items(&[("old", 99, "steam"), ("manual", 98, "manual")]);
+
+        let summary = import_wishlist(&state, &endpoints, "123", true).await.unwrap();
+
+        assert_eq!(summary.total, 4);
+        assert_eq!(summary.added, 4);
+        assert_eq!(summary.unnamed, 2);
+        assert_eq!(summary.removed, 1);
+        assert_eq!(
+            wishlist_names(&state),
+            vec!["Counter-Strike", "Team Fortress", "App 30", "App 40", "manual"]
+        );
+
+        let conn = state.db.lock().unwrap();
+        let priority: i32 = conn
+            .query_row("SELECT steam_priority FROM wishlist WHERE id = '10'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(priority, 1);
+
+        let (app_type, parent): (String, i64) = conn
+            .query_row("SELECT app_type, parent_app_id FROM wishlist WHERE id = '20'", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((app_type.as_str(), parent), ("dlc", 10));
+    }
+
+    #[tokio::test]
+    async fn private_profile_is_an_error_and_removes_nothing() {
+        let endpoints = fake_steam(r#"{"response": {}}"#);
+        let state = state_with_items(&[("10", 10, "steam")]);
+
+        assert!(import_wishlist(&state, &endpoints, "123", true).await.is_err());
+        assert_eq!(wishlist_names(&state), vec!["10"]);
+    }
+
+    #[tokio::test]
+    async fn empty_wishlist_skips_the_removal_pass() {
+        let endpoints = fake_steam(r#"{"response": {"items": []}}"#);
+        let state = state_with_items(&[("10", 10, "steam")]);
+
+        let summary = import_wishlist(&state, &endpoints, "123", true).await.unwrap();
+
+        assert_eq!(summary.removed, 0);
+        assert_eq!(wishlist_names(&state), vec!["10"]);
+    }
+
+    #[tokio::test]
+    async fn placeholder_names_are_replaced_later() {
+        let endpoints = fake_steam(r#"{"response": {"items": [{"appid": 10}]}}"#);
+        let state = state_with_items(&[("10", 10, "steam")]);
+        state
+            .db
+            .lock()
+            .unwrap()
+            .execute("UPDATE wishlist SET name = 'App 10'", [])
+            .unwrap();
+
+        import_wishlist(&state, &endpoints, "123", false).await.unwrap();
+
+        assert_eq!(wishlist_names(&state), vec!["Counter-Strike"]);
+    }
+
+    fn status(coming_soon: bool, text: &str) -> steam::StoreStatus {
+        steam::StoreStatus {
+            price: Some(steam::SteamPrice {
+                currency: "BRL".to_string(),
+                final_price: 49.9,
+                discount_percent: 10,
+            }),
+            coming_soon,
+            release_text: Some(text.to_string()),
+            release_date: steam::parse_store_date(text),
+        }
+    }
+
+    #[test]
+    fn store_status_saves_price_and_reports_release() {
+        let state = state_with_items(&[("10", 10, "steam")]);
+        let conn = state.db.lock().unwrap();
+
+        let event = save_store_status(&conn, "10", "Hades II", 10, &status(true, "Q1 2025")).unwrap();
+        assert!(event.is_none());
+
+        let event = save_store_status(&conn, "10", "Hades II", 10, &status(true, "14 Nov, 2025"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.kind, "date_changed");
+
+        let event = save_store_status(&conn, "10", "Hades II", 10, &status(false, "14 Nov, 2025"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.kind, "released");
+
+        let (price, on_sale): (f64, bool) = conn
+            .query_row(
+                "SELECT localized_price, on_sale FROM wishlist WHERE id = '10'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((price, on_sale), (49.9, true));
+    }
+
+    #[test]
+    fn store_status_write_errors_are_returned() {
+        let state = state_with_items(&[("10", 10, "steam")]);
+        let conn = state.db.lock().unwrap();
+        conn.execute_batch(
+            "CREATE TRIGGER block_price BEFORE UPDATE OF localized_price ON wishlist
+             BEGIN SELECT RAISE(ABORT, 'bloqueado'); END;",
+        )
+        .unwrap();
+
+        assert!(save_store_status(&conn, "10", "Hades II", 10, &status(false, "14 Nov, 2025")).is_err());
+    }
+}
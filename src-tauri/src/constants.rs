@@ -6,14 +6,24 @@ pub const STEAM_CDN_URL: &str = "https://cdn.cloudflare.steamstatic.com";
 pub const STEAM_STORE_API_URL: &str = "https://store.steampowered.com/api/appdetails";
 #[allow(dead_code)]
 pub const STEAM_PLAYER_API_URL: &str = "http://api.steampowered.com/IPlayerService/GetOwnedGames/v0001";
-#[allow(dead_code)]
 pub const RAWG_API_URL: &str = "https://api.rawg.io/api/games";
+pub const STEAM_WEB_API_URL: &str = "https://api.steampowered.com";
+pub const STEAM_STORE_URL: &str = "https://store.steampowered.com";
 
 // Valores padrão
 pub const DEFAULT_GENRE: &str = "Desconhecido";
-#[allow(dead_code)]
 pub const DEFAULT_PLATFORM_MANUAL: &str = "Manual";
 pub const DEFAULT_PLATFORM_STEAM: &str = "Steam";
+pub const PLATFORM_EPIC: &str = "Epic Games";
+pub const PLATFORM_GOG: &str = "GOG";
+pub const PLATFORM_AMAZON: &str = "Amazon";
+pub const PLATFORM_LUTRIS: &str = "Lutris";
+
+// Tipos de app da Steam (campo "type" do appdetails)
+pub const STEAM_APP_TYPE_DLC: &str = "dlc";
+// Tipos ignorados pela importação quando o usuário pede para pular não-jogos
+pub const SKIPPED_STEAM_APP_TYPES: &[&str] =
+    &["music", "application", "tool", "video", "demo", "advertising", "series", "episode"];
 
 // Limites de validação
 pub const MAX_NAME_LENGTH: usize = 200;
@@ -24,14 +34,117 @@ pub const MAX_PLAYTIME: i32 = 1_000_000;
 pub const MIN_RATING: i32 = 1;
 pub const MAX_RATING: i32 = 5;
 
+// Lista de desejos
+pub const MIN_WISHLIST_PRIORITY: i32 = 1;
+pub const MAX_WISHLIST_PRIORITY: i32 = 3;
+pub const MAX_NOTES_LENGTH: usize = 2000;
+pub const MAX_TAG_LENGTH: usize = 50;
+pub const MAX_TAGS_PER_ITEM: usize = 20;
+
+// Eventos emitidos para o frontend
+pub const EVENT_WISHLIST_RELEASE: &str = "wishlist-release";
+pub const EVENT_BACKUP_PROGRESS: &str = "backup-progress";
+pub const EVENT_STEAM_CLASSIFY_PROGRESS: &str = "steam-classify-progress";
+
 // Rate limiting
 pub const STEAM_RATE_LIMIT_MS: u64 = 500;
-#[allow(dead_code)]
 pub const RAWG_PAGE_SIZE: u32 = 20;
+// Pontuação mínima (0 a 1) para aceitar automaticamente um resultado da busca da RAWG
+pub const MIN_RAWG_MATCH_SCORE: f64 = 0.75;
+// Dias até tentar de novo um jogo que nenhuma fonte de metadados encontrou
+pub const METADATA_MISS_RETRY_DAYS: u32 = 7;
 
 // Configuração de banco de dados
-#[allow(dead_code)]
 pub const DB_FILENAME_LIBRARY: &str = "library.db";
 pub const DB_FILENAME_SECRETS: &str = "secrets.db";
 #[allow(dead_code)]
 pub const DB_JOURNAL_MODE: &str = "WAL";
+
+// Backup v2 (arquivo zip com manifesto e checksums)
+pub const BACKUP_FORMAT_NAME: &str = "playlite-backup";
+pub const BACKUP_FORMAT_VERSION: u32 = 2;
+pub const BACKUP_MANIFEST_FILE: &str = "manifest.json";
+// Incrementar quando uma migração mudar o significado de colunas existentes
+pub const DB_SCHEMA_VERSION: u32 = 1;
+// Entrada com as chaves de API, presente só em backups protegidos por senha
+pub const BACKUP_SECRETS_FILE: &str = "secrets.json";
+pub const MIN_BACKUP_PASSPHRASE_LEN: usize = 8;
+// Linhas processadas entre dois eventos de progresso do backup
+pub const BACKUP_PROGRESS_INTERVAL: usize = 1000;
+
+// Chaves de configurações não sensíveis (tabela app_settings)
+pub const SETTING_STEAM_ROOT: &str = "steam_root";
+pub const SETTING_BASE_CURRENCY: &str = "base_currency";
+pub const SETTING_METADATA_PRIORITY: &str = "metadata_priority";
+pub const SETTING_COVER_CACHE_MAX_MB: &str = "cover_cache_max_mb";
+pub const SETTING_SNAPSHOT_INTERVAL_HOURS: &str = "snapshot_interval_hours";
+pub const SETTING_SNAPSHOT_KEEP_DAILY: &str = "snapshot_keep_daily";
+pub const SETTING_SNAPSHOT_KEEP_WEEKLY: &str = "snapshot_keep_weekly";
+pub const SETTING_SYNC_DEVICE_ID: &str = "sync_device_id";
+pub const SETTING_SYNC_TRANSPORT: &str = "sync_transport";
+pub const SETTING_SYNC_FOLDER: &str = "sync_folder";
+pub const SETTING_SYNC_SERVER_URL: &str = "sync_server_url";
+pub const SETTING_SYNC_LAST_PUSH: &str = "sync_last_push";
+pub const SETTING_SYNC_CURSOR: &str = "sync_cursor";
+pub const SETTING_SYNC_LAST_RUN: &str = "sync_last_run";
+pub const SETTING_JOURNAL_RETENTION_DAYS: &str = "journal_retention_days";
+// Configurações desta máquina (caminhos, cópias automáticas, diário e sincronização): ficam
+// fora do backup e a restauração não as altera
+pub const LOCAL_SETTINGS: &[&str] = &[
+    SETTING_STEAM_ROOT,
+    SETTING_SNAPSHOT_INTERVAL_HOURS,
+    SETTING_SNAPSHOT_KEEP_DAILY,
+    SETTING_SNAPSHOT_KEEP_WEEKLY,
+    SETTING_SYNC_DEVICE_ID,
+    SETTING_SYNC_TRANSPORT,
+    SETTING_SYNC_FOLDER,
+    SETTING_SYNC_SERVER_URL,
+    SETTING_SYNC_LAST_PUSH,
+    SETTING_SYNC_CURSOR,
+    SETTING_SYNC_LAST_RUN,
+    SETTING_JOURNAL_RETENTION_DAYS,
+];
+
+// Cache local de capas (pasta dentro do app_data_dir)
+pub const COVER_CACHE_DIR: &str = "covers";
+pub const DEFAULT_COVER_CACHE_MAX_MB: u64 = 500;
+pub const COVER_THUMB_WIDTH: u32 = 200;
+pub const COVER_THUMB_HEIGHT: u32 = 300;
+
+// Cópias automáticas do banco (pasta dentro do app_data_dir)
+pub const SNAPSHOT_DIR: &str = "snapshots";
+pub const DEFAULT_SNAPSHOT_INTERVAL_HOURS: u64 = 24;
+pub const DEFAULT_SNAPSHOT_KEEP_DAILY: usize = 7;
+pub const DEFAULT_SNAPSHOT_KEEP_WEEKLY: usize = 4;
+// Cópias mais recentes mantidas além das diárias e semanais
+pub const SNAPSHOT_KEEP_RECENT: usize = 3;
+// Frequência com que o agendador confere se já passou o intervalo
+pub const SNAPSHOT_CHECK_INTERVAL_SECS: u64 = 600;
+
+// Sincronização entre dispositivos (pasta compartilhada ou servidor HTTP próprio)
+pub const SYNC_FORMAT_NAME: &str = "playlite-sync";
+pub const SYNC_FORMAT_VERSION: u32 = 1;
+pub const SYNC_TRANSPORT_FOLDER: &str = "folder";
+pub const SYNC_TRANSPORT_HTTP: &str = "http";
+// Subpasta criada dentro da pasta compartilhada escolhida pelo usuário
+pub const SYNC_FOLDER_DIR: &str = "playlite-sync";
+pub const SYNC_MAX_CHANGES_PER_SET: usize = 5000;
+// Chave do token do servidor no armazenamento seguro
+pub const SECRET_SYNC_TOKEN: &str = "sync_token";
+
+// Diário de alterações e lixeira (entradas mais antigas que a retenção são apagadas)
+pub const DEFAULT_JOURNAL_RETENTION_DAYS: u32 = 30;
+pub const JOURNAL_PURGE_INTERVAL_SECS: u64 = 3600;
+// Operação dos lotes criados pelo próprio desfazer, que não entram na pilha de desfazer
+pub const JOURNAL_OP_UNDO: &str = "undo";
+
+// Artes enviadas pelo usuário (pasta dentro do app_data_dir)
+pub const ARTWORK_DIR: &str = "artwork";
+pub const ARTWORK_KIND_COVER: &str = "cover";
+pub const ARTWORK_KIND_HERO: &str = "hero";
+pub const ARTWORK_KIND_LOGO: &str = "logo";
+pub const MAX_ARTWORK_BYTES: u64 = 20 * 1024 * 1024;
+pub const MAX_ARTWORK_DIMENSION: u32 = 8192;
+
+// Moeda usada quando nenhuma moeda base foi configurada (preços da Steam vêm em BRL)
+pub const DEFAULT_BASE_CURRENCY: &str = "BRL";
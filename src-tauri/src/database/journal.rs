@@ -0,0 +1,580 @@
+// Diário de alterações: gatilhos guardam a imagem de cada linha antes e depois de toda alteração
+// nas tabelas da biblioteca. Operações com várias linhas abrem um lote, desfeito de uma vez;
+// as exclusões de jogos e itens da lista de desejos formam a lixeira.
+
+use crate::constants;
+use crate::database;
+use crate::models::{JournalEntry, TrashItem, UndoResult};
+use crate::services::covers;
+use rusqlite::{params, Connection, OptionalExtension, ToSql};
+use serde_json::Value;
+use tracing::warn;
+
+/// Tabelas registradas no diário. Caches, configurações, controle de sincronização e os dados de
+/// instalação (refeitos a cada varredura do disco) ficam de fora.
+pub const JOURNALED_TABLES: &[&str] = &[
+    "games",
+    "wishlist",
+    "game_ownership",
+    "game_external_ids",
+    "game_metadata",
+    "metadata_sources",
+    "game_artwork",
+];
+
+/// Tabelas cujas exclusões aparecem na lixeira
+pub const TRASH_TABLES: &[&str] = &["games", "wishlist"];
+
+/// Recria os gatilhos do diário com as colunas atuais de cada tabela (depois das migrações)
+pub fn create_triggers(conn: &Connection) -> Result<(), String> {
+    for table in JOURNALED_TABLES {
+        let columns = database::table_columns(conn, table)?;
+        let keys = database::primary_key_columns(conn, table)?;
+
+        let image = |row: &str| {
+            let pairs = columns
+                .iter()
+                .map(|c| format!("'{c}', {row}.\"{c}\""))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("json_object({pairs})")
+        };
+        let key = |row: &str| {
+            let values = keys
+                .iter()
+                .map(|k| format!("{row}.\"{k}\""))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("json_array({values})")
+        };
+        // Atualizações que só mexem em `updated_at` (feitas pelos gatilhos de data) não entram
+        let changed = columns
+            .iter()
+            .filter(|c| c.as_str() != "updated_at")
+            .map(|c| format!("NEW.\"{c}\" IS NOT OLD.\"{c}\""))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+
+        for (event, operation, row_key, before, after, when) in [
+            ("INSERT", "insert", key("NEW"), "NULL".to_string(), image("NEW"), String::new()),
+            ("UPDATE", "update", key("NEW"), image("OLD"), image("NEW"), format!("WHEN {changed}")),
+            ("DELETE", "delete", key("OLD"), image("OLD"), "NULL".to_string(), String::new()),
+        ] {
+            conn.execute(
+                &format!("DROP TRIGGER IF EXISTS trg_{table}_journal_{operation}"),
+                [],
+            )
+            .map_err(|e| e.to_string())?;
+            conn.execute(
+                &format!(
+                    "CREATE TRIGGER trg_{table}_journal_{operation} AFTER {event} ON {table}
+                     FOR EACH ROW {when}
+                     BEGIN
+                         INSERT INTO change_journal (batch_id, table_name, operation, row_key, before_row, after_row)
+                         VALUES ((SELECT MAX(id) FROM journal_batches WHERE open = 1),
+                                 '{table}', '{operation}', {row_key}, {before}, {after});
+                     END"
+                ),
+                [],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Fecha lotes deixados abertos por uma execução interrompida
+pub fn close_open_batches(conn: &Connection) -> Result<(), String> {
+    conn.execute("UPDATE journal_batches SET open = 0 WHERE open = 1", [])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Abre um lote: as alterações seguintes ficam nele até `end_batch`.
+/// Dentro de uma transação, um ROLLBACK descarta o lote junto com as alterações.
+pub fn begin_batch(
+    conn: &Connection,
+    operation: &str,
+    description: Option<&str>,
+) -> Result<i64, String> {
+    conn.execute(
+        "INSERT INTO journal_batches (operation, description) VALUES (?1, ?2)",
+        params![operation, description],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Fecha o lote; lotes sem nenhuma alteração são descartados
+pub fn end_batch(conn: &Connection, batch_id: i64) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM journal_batches
+         WHERE id = ?1 AND NOT EXISTS (SELECT 1 FROM change_journal WHERE batch_id = ?1)",
+        params![batch_id],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE journal_batches SET open = 0 WHERE id = ?1",
+        params![batch_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Executa `work` com as alterações agrupadas em um lote
+pub fn journaled<T, F>(
+    conn: &Connection,
+    operation: &str,
+    description: Option<&str>,
+    work: F,
+) -> Result<T, String>
+where
+    F: FnOnce(&Connection) -> Result<T, String>,
+{
+    let batch_id = begin_batch(conn, operation, description)?;
+    let result = work(conn);
+    end_batch(conn, batch_id)?;
+    result
+}
+
+const ENTRY_QUERY: &str = "SELECT e.id, e.batch_id, b.operation, b.description, e.table_name,
+        e.operation, e.row_key, e.before_row, e.after_row, e.created_at, e.undone
+     FROM change_journal e
+     LEFT JOIN journal_batches b ON b.id = e.batch_id";
+
+fn parse_json(text: Option<String>) -> Option<Value> {
+    text.and_then(|t| serde_json::from_str(&t).ok())
+}
+
+fn map_entry(row: &rusqlite::Row) -> rusqlite::Result<JournalEntry> {
+    Ok(JournalEntry {
+        id: row.get(0)?,
+        batch_id: row.get(1)?,
+        batch_operation: row.get(2)?,
+        batch_description: row.get(3)?,
+        table_name: row.get(4)?,
+        operation: row.get(5)?,
+        row_key: parse_json(row.get(6)?).unwrap_or(Value::Null),
+        before: parse_json(row.get(7)?),
+        after: parse_json(row.get(8)?),
+        created_at: row.get(9)?,
+        undone: row.get(10)?,
+    })
+}
+
+fn load_entry(conn: &Connection, id: i64) -> Result<JournalEntry, String> {
+    conn.prepare_cached(&format!("{ENTRY_QUERY} WHERE e.id = ?1"))
+        .map_err(|e| e.to_string())?
+        .query_row(params![id], map_entry)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Entrada {} não encontrada no diário", id))
+}
+
+/// Entradas mais recentes primeiro
+pub fn list_entries(conn: &Connection, limit: usize, offset: usize) -> Result<Vec<JournalEntry>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{ENTRY_QUERY} ORDER BY e.id DESC LIMIT ?1 OFFSET ?2"))
+        .map_err(|e| e.to_string())?;
+
+    let entries = stmt
+        .query_map(params![limit as i64, offset as i64], map_entry)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(entries)
+}
+
+/// Percorre o diário inteiro em ordem cronológica, sem carregá-lo em memória
+pub fn for_each_entry<F>(conn: &Connection, mut on_entry: F) -> Result<usize, String>
+where
+    F: FnMut(JournalEntry) -> Result<(), String>,
+{
+    let mut stmt = conn
+        .prepare(&format!("{ENTRY_QUERY} ORDER BY e.id"))
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+
+    let mut count = 0;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        on_entry(map_entry(row).map_err(|e| e.to_string())?)?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+fn key_condition(keys: &[String], first: usize) -> String {
+    keys.iter()
+        .enumerate()
+        .map(|(i, k)| format!("\"{}\" IS ?{}", k, first + i))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// Imagem atual da linha, no mesmo formato das imagens do diário
+fn current_image(conn: &Connection, table: &str, key: &[Value]) -> Result<Option<Value>, String> {
+    let columns = database::table_columns(conn, table)?;
+    let keys = database::primary_key_columns(conn, table)?;
+    let pairs = columns
+        .iter()
+        .map(|c| format!("'{c}', \"{c}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let text: Option<String> = conn
+        .prepare_cached(&format!(
+            "SELECT json_object({}) FROM \"{}\" WHERE {}",
+            pairs,
+            table,
+            key_condition(&keys, 1)
+        ))
+        .map_err(|e| e.to_string())?
+        .query_row(
+            rusqlite::params_from_iter(key.iter().map(database::json_to_sql)),
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    Ok(parse_json(text))
+}
+
+/// Compara as colunas da imagem do diário com a linha atual (`updated_at` não conta)
+fn same_content(expected: &Value, current: &Value) -> bool {
+    let (Some(expected), Some(current)) = (expected.as_object(), current.as_object()) else {
+        return false;
+    };
+    expected
+        .iter()
+        .filter(|(column, _)| column.as_str() != "updated_at")
+        .all(|(column, value)| current.get(column) == Some(value))
+}
+
+/// Grava a imagem na tabela: INSERT, ou UPDATE da linha com a chave `key`.
+/// `updated_at` fica de fora para os gatilhos marcarem a reversão como alteração nova.
+fn write_image(conn: &Connection, table: &str, image: &Value, key: Option<&[Value]>) -> Result<(), String> {
+    let existing = database::table_columns(conn, table)?;
+    let Some(image) = image.as_object() else {
+        return Err(format!("Imagem inválida no diário para '{}'", table));
+    };
+    let (columns, mut values): (Vec<&String>, Vec<_>) = image
+        .iter()
+        .filter(|(column, _)| column.as_str() != "updated_at" && existing.contains(column))
+        .map(|(column, value)| (column, database::json_to_sql(value)))
+        .unzip();
+
+    let sql = match key {
+        None => format!(
+            "INSERT INTO \"{}\" ({}) VALUES ({})",
+            table,
+            columns
+                .iter()
+                .map(|c| format!("\"{}\"", c))
+                .collect::<Vec<_>>()
+                .join(", "),
+            (1..=columns.len())
+                .map(|i| format!("?{}", i))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Some(key) => {
+            let keys = database::primary_key_columns(conn, table)?;
+            values.extend(key.iter().map(database::json_to_sql));
+            format!(
+                "UPDATE \"{}\" SET {} WHERE {}",
+                table,
+                columns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| format!("\"{}\" = ?{}", c, i + 1))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                key_condition(&keys, columns.len() + 1)
+            )
+        }
+    };
+
+    conn.prepare_cached(&sql)
+        .map_err(|e| e.to_string())?
+        .execute(rusqlite::params_from_iter(values))
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Reverte uma entrada. Retorna false, sem alterar nada, quando a linha mudou depois dela.
+fn revert_entry(conn: &Connection, entry: &JournalEntry) -> Result<bool, String> {
+    let key = entry.row_key.as_array().cloned().unwrap_or_default();
+    let current = current_image(conn, &entry.table_name, &key)?;
+
+    match (
+        entry.operation.as_str(),
+        &entry.before,
+        &entry.after,
+        &current,
+    ) {
+        ("insert", _, Some(after), Some(current)) if same_content(after, current) => {
+            let keys = database::primary_key_columns(conn, &entry.table_name)?;
+            conn.prepare_cached(&format!(
+                "DELETE FROM \"{}\" WHERE {}",
+                entry.table_name,
+                key_condition(&keys, 1)
+            ))
+            .map_err(|e| e.to_string())?
+            .execute(rusqlite::params_from_iter(key.iter().map(database::json_to_sql)))
+            .map_err(|e| e.to_string())?;
+        }
+        ("update", Some(before), Some(after), Some(current)) if same_content(after, current) => {
+            write_image(conn, &entry.table_name, before, Some(&key))?;
+        }
+        ("delete", Some(before), _, None) => {
+            write_image(conn, &entry.table_name, before, None)?;
+        }
+        _ => return Ok(false),
+    }
+
+    conn.execute(
+        "UPDATE change_journal SET undone = 1 WHERE id = ?1",
+        params![entry.id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Desfaz o último lote (ou a última alteração avulsa) ainda não desfeito.
+/// Deve rodar em uma transação: se alguma linha mudou depois, nada é desfeito.
+pub fn undo_last(conn: &Connection) -> Result<UndoResult, String> {
+    let last: Option<i64> = conn
+        .query_row(
+            "SELECT e.id FROM change_journal e
+             LEFT JOIN journal_batches b ON b.id = e.batch_id
+             WHERE e.undone = 0 AND b.operation IS NOT ?1
+             ORDER BY e.id DESC LIMIT 1",
+            params![constants::JOURNAL_OP_UNDO],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let entry = load_entry(conn, last.ok_or("Nada para desfazer")?)?;
+
+    let ids: Vec<i64> = match entry.batch_id {
+        Some(batch_id) => {
+            let mut stmt = conn
+                .prepare("SELECT id FROM change_journal WHERE batch_id = ?1 AND undone = 0 ORDER BY id DESC")
+                .map_err(|e| e.to_string())?;
+            let ids = stmt
+                .query_map(params![batch_id], |row| row.get(0))
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            ids
+        }
+        None => vec![entry.id],
+    };
+
+    let operation = entry
+        .batch_operation
+        .clone()
+        .unwrap_or_else(|| entry.operation.clone());
+    let description = entry
+        .batch_description
+        .clone()
+        .or_else(|| Some(format!("{} {}", entry.table_name, entry.row_key)));
+
+    journaled(conn, constants::JOURNAL_OP_UNDO, description.as_deref(), |conn| {
+        for id in &ids {
+            let entry = load_entry(conn, *id)?;
+            if !revert_entry(conn, &entry)? {
+                return Err(format!(
+                    "Não é possível desfazer: o registro {} de '{}' foi alterado depois",
+                    entry.row_key, entry.table_name
+                ));
+            }
+        }
+        Ok(())
+    })?;
+
+    Ok(UndoResult {
+        operation,
+        description,
+        changes: ids.len(),
+    })
+}
+
+/// Jogos e itens da lista de desejos excluídos que ainda não voltaram, mais recentes primeiro
+pub fn list_trash(conn: &Connection) -> Result<Vec<TrashItem>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.id, e.table_name, json_extract(e.row_key, '$[0]'),
+                    json_extract(e.before_row, '$.name'), e.created_at, b.operation
+             FROM change_journal e
+             LEFT JOIN journal_batches b ON b.id = e.batch_id
+             WHERE e.operation = 'delete' AND e.undone = 0
+               AND e.table_name IN ('games', 'wishlist')
+               AND e.id = (SELECT MAX(x.id) FROM change_journal x
+                           WHERE x.table_name = e.table_name AND x.row_key = e.row_key)
+               AND NOT EXISTS (SELECT 1 FROM games g
+                               WHERE e.table_name = 'games' AND g.id = json_extract(e.row_key, '$[0]'))
+               AND NOT EXISTS (SELECT 1 FROM wishlist w
+                               WHERE e.table_name = 'wishlist' AND w.id = json_extract(e.row_key, '$[0]'))
+             ORDER BY e.id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let items = stmt
+        .query_map([], |row| {
+            Ok(TrashItem {
+                entry_id: row.get(0)?,
+                table_name: row.get(1)?,
+                item_id: row.get(2)?,
+                name: row.get(3)?,
+                deleted_at: row.get(4)?,
+                operation: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(items)
+}
+
+/// Restaura um item da lixeira. Para jogos, volta também o que foi apagado junto com ele
+/// (cópias, IDs externos, metadados, artes) e as DLCs desaninhadas. Retorna as linhas restauradas.
+pub fn restore_from_trash(conn: &Connection, entry_id: i64) -> Result<usize, String> {
+    let entry = load_entry(conn, entry_id)?;
+    if entry.operation != "delete"
+        || entry.undone
+        || !TRASH_TABLES.contains(&entry.table_name.as_str())
+    {
+        return Err("Item não está na lixeira".to_string());
+    }
+    let item_id = entry.row_key[0].as_str().unwrap_or_default().to_string();
+    let name = entry
+        .before
+        .as_ref()
+        .and_then(|b| b.get("name"))
+        .and_then(Value::as_str)
+        .map(String::from);
+
+    journaled(conn, "restore_from_trash", name.as_deref(), |conn| {
+        if !revert_entry(conn, &entry)? {
+            return Err("Já existe um item com o mesmo ID".to_string());
+        }
+        let mut restored = 1;
+
+        let Some(batch_id) = entry.batch_id.filter(|_| entry.table_name == "games") else {
+            return Ok(restored);
+        };
+        let mut stmt = conn
+            .prepare(
+                "SELECT id FROM change_journal
+                 WHERE batch_id = ?1 AND undone = 0
+                   AND ((operation = 'delete' AND table_name != 'games'
+                         AND json_extract(before_row, '$.game_id') = ?2)
+                     OR (operation = 'update' AND table_name = 'games'
+                         AND json_extract(before_row, '$.parent_game_id') = ?2))
+                 ORDER BY id DESC",
+            )
+            .map_err(|e| e.to_string())?;
+        let related = stmt
+            .query_map(params![batch_id, item_id], |row| row.get::<_, i64>(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        for id in related {
+            let entry = load_entry(conn, id)?;
+            if revert_entry(conn, &entry)? {
+                restored += 1;
+            } else {
+                warn!(
+                    "Registro {} de '{}' mudou depois da exclusão e não foi restaurado",
+                    entry.row_key, entry.table_name
+                );
+            }
+        }
+
+        Ok(restored)
+    })
+}
+
+/// Dias que as entradas (e os itens da lixeira) ficam no diário
+pub fn retention_days(conn: &Connection) -> u32 {
+    database::get_setting(conn, constants::SETTING_JOURNAL_RETENTION_DAYS)
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(constants::DEFAULT_JOURNAL_RETENTION_DAYS)
+}
+
+/// Apaga as entradas que atendem `filter` e os arquivos das artes excluídas nelas que
+/// nenhuma arte atual usa mais
+fn purge(conn: &Connection, filter: &str, filter_params: &[&dyn ToSql]) -> Result<usize, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT json_extract(before_row, '$.file_path'), json_extract(before_row, '$.thumb_path')
+             FROM change_journal
+             WHERE table_name = 'game_artwork' AND operation = 'delete' AND undone = 0 AND ({filter})"
+        ))
+        .map_err(|e| e.to_string())?;
+    let deleted_art = stmt
+        .query_map(filter_params, |row| {
+            Ok([row.get::<_, Option<String>>(0)?, row.get::<_, Option<String>>(1)?])
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut files = Vec::new();
+    for path in deleted_art.into_iter().flatten().flatten() {
+        let in_use: bool = conn
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM game_artwork WHERE file_path = ?1 OR thumb_path = ?1)",
+                params![path],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if !in_use {
+            files.push(Some(path));
+        }
+    }
+
+    let removed = conn
+        .execute(&format!("DELETE FROM change_journal WHERE {filter}"), filter_params)
+        .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM journal_batches
+         WHERE open = 0 AND NOT EXISTS (SELECT 1 FROM change_journal e WHERE e.batch_id = journal_batches.id)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    covers::remove_files(&files);
+    Ok(removed)
+}
+
+/// Apaga as entradas mais antigas que a retenção. Lotes saem inteiros, para o desfazer
+/// nunca encontrar um lote pela metade.
+pub fn purge_expired(conn: &Connection, retention_days: u32) -> Result<usize, String> {
+    let cutoff = format!("-{} days", retention_days);
+    purge(
+        conn,
+        "(batch_id IS NULL AND created_at < datetime('now', ?1))
+         OR batch_id IN (SELECT id FROM journal_batches WHERE open = 0 AND created_at < datetime('now', ?1))",
+        &[&cutoff],
+    )
+}
+
+/// Esvazia a lixeira: as operações que excluíram linhas deixam o diário e não podem mais
+/// ser desfeitas
+pub fn empty_trash(conn: &Connection) -> Result<usize, String> {
+    purge(
+        conn,
+        "(batch_id IS NULL AND operation = 'delete' AND undone = 0)
+         OR batch_id IN (SELECT batch_id FROM change_journal WHERE operation = 'delete' AND undone = 0)",
+        &[],
+    )
+}
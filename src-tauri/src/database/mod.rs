@@ -1,16 +1,63 @@
-use rusqlite::Connection;
+use crate::constants;
+use crate::models::Game;
+use base64::Engine;
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::collections::HashMap;
 use std::sync::Mutex;
 use tauri::State;
 
+pub mod journal;
+
 // Define o estado global da aplicação
 pub struct AppState {
     pub db: Mutex<Connection>,
 }
 
+/// Tabelas editadas pelo usuário que ganham a coluna `updated_at`, mantida por gatilhos
+const TOUCHED_TABLES: &[&str] = &["games", "wishlist", "game_ownership", "app_settings"];
+
+/// Tabelas da biblioteca que vão para o backup. Ficam de fora o estado desta máquina
+/// (instalações, histórico de mesclagens, exclusões para a sincronização, buscas sem
+/// resultado) e o diário; em `app_settings`, só as configurações fora de `LOCAL_SETTINGS`.
+pub const BACKUP_TABLES: &[&str] = &[
+    "games",
+    "wishlist",
+    "game_ownership",
+    "game_external_ids",
+    "game_metadata",
+    "metadata_sources",
+    "game_artwork",
+    "cover_cache",
+    "currency_rates",
+    "app_settings",
+];
+
+/// Tabelas sincronizadas entre dispositivos e as colunas que identificam a linha em qualquer um
+/// deles (o ID autoincremento das cópias muda de um banco para outro)
+pub const SYNCED_TABLES: &[(&str, &[&str])] = &[
+    ("games", &["id"]),
+    ("wishlist", &["id"]),
+    ("game_ownership", &["game_id", "platform", "external_id"]),
+];
+
 // Inicializa o banco de dados e cria as tabelas
 #[tauri::command]
 pub fn init_db(state: State<AppState>) -> Result<String, String> {
     let conn = state.db.lock().map_err(|_| "Falha ao bloquear mutex")?;
+    create_schema(&conn)?;
+
+    // Entradas do diário (e itens da lixeira) mais antigas que a retenção
+    let purged = journal::purge_expired(&conn, journal::retention_days(&conn))?;
+    if purged > 0 {
+        tracing::info!("Diário de alterações: {} entradas expiradas removidas", purged);
+    }
+
+    Ok("Banco inicializado com sucesso!".to_string())
+}
+
+/// Cria as tabelas, aplica as migrações de colunas e os índices
+pub fn create_schema(conn: &Connection) -> Result<(), String> {
 
     // === TABELAS BÁSICAS ===
 
@@ -49,6 +96,304 @@ pub fn init_db(state: State<AppState>) -> Result<String, String> {
     )
     .map_err(|e| e.to_string())?;
 
+    // Tabela de instalações locais (preenchida pelo scanner da Steam)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS game_installs (
+            game_id TEXT PRIMARY KEY,
+            install_path TEXT NOT NULL,
+            size_on_disk INTEGER DEFAULT 0,
+            last_updated TEXT,
+            scanned_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // IDs externos por fonte (Lutris, Heroic...), evitam duplicatas em re-importações
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS game_external_ids (
+            source TEXT NOT NULL,
+            external_id TEXT NOT NULL,
+            game_id TEXT NOT NULL,
+            PRIMARY KEY (source, external_id)
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Configurações não sensíveis da aplicação (chave/valor)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS app_settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Lojas/plataformas em que cada jogo lógico é possuído: uma linha por cópia, gravada ao
+    // adicionar, importar e mesclar jogos
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS game_ownership (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id TEXT NOT NULL,
+            platform TEXT NOT NULL,
+            external_id TEXT
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Histórico de mesclagens, com o estado anterior em JSON para permitir desfazer
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS game_merges (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            primary_id TEXT NOT NULL,
+            snapshot TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            undone BOOLEAN DEFAULT 0
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Cotações para converter gastos na moeda base (configuradas localmente, sem internet)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS currency_rates (
+            currency TEXT PRIMARY KEY,
+            rate REAL NOT NULL,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Metadados da RAWG por jogo da biblioteca (listas guardadas como arrays JSON)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS game_metadata (
+            game_id TEXT PRIMARY KEY,
+            rawg_id INTEGER,
+            description TEXT,
+            website TEXT,
+            metacritic INTEGER,
+            release_date TEXT,
+            background_image TEXT,
+            developers TEXT,
+            publishers TEXT,
+            tags TEXT,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_metadata_metacritic ON game_metadata(metacritic)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Último registro de cada fonte de metadados (Steam, RAWG, substituições locais),
+    // usado para mesclar novamente sem refazer as requisições
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS metadata_sources (
+            game_id TEXT NOT NULL,
+            provider TEXT NOT NULL,
+            external_id TEXT,
+            data TEXT NOT NULL,
+            fetched_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (game_id, provider)
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Jogos que nenhuma fonte de metadados encontrou, para o enriquecimento não repetir a busca
+    // a cada execução (ver `METADATA_MISS_RETRY_DAYS`)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS metadata_misses (
+            game_id TEXT PRIMARY KEY,
+            missed_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Capas baixadas para o cache local (status 'missing' quando nenhuma URL funcionou)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS cover_cache (
+            game_id TEXT PRIMARY KEY,
+            source_url TEXT,
+            file_path TEXT,
+            thumb_path TEXT,
+            size_bytes INTEGER NOT NULL DEFAULT 0,
+            status TEXT NOT NULL DEFAULT 'ok',
+            fetched_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Artes enviadas pelo usuário: capa, banner (hero) e logo
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS game_artwork (
+            game_id TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            file_path TEXT NOT NULL,
+            thumb_path TEXT,
+            width INTEGER NOT NULL,
+            height INTEGER NOT NULL,
+            size_bytes INTEGER NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (game_id, kind)
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Exclusões das tabelas sincronizadas, para propagá-las aos outros dispositivos.
+    // `row_key` é a chave da linha como lista JSON (ex.: ["steam_570"])
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_tombstones (
+            table_name TEXT NOT NULL,
+            row_key TEXT NOT NULL,
+            deleted_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (table_name, row_key)
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Diário de alterações: imagens JSON da linha antes/depois, gravadas pelos gatilhos
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS change_journal (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            batch_id INTEGER,
+            table_name TEXT NOT NULL,
+            operation TEXT NOT NULL,
+            row_key TEXT NOT NULL,
+            before_row TEXT,
+            after_row TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            undone BOOLEAN NOT NULL DEFAULT 0
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Lotes do diário: operações com várias linhas (exclusão de jogo, importação, restauração...)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS journal_batches (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            operation TEXT NOT NULL,
+            description TEXT,
+            open BOOLEAN NOT NULL DEFAULT 1,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // === MIGRAÇÕES DE COLUNAS ===
+
+    add_column_if_missing(conn, "games", "installed", "BOOLEAN DEFAULT 0")?;
+    add_column_if_missing(conn, "game_metadata", "sources", "TEXT")?;
+    add_column_if_missing(conn, "games", "last_played", "TEXT")?;
+    add_column_if_missing(conn, "games", "rawg_id", "INTEGER")?;
+    add_column_if_missing(conn, "game_ownership", "purchase_date", "TEXT")?;
+    add_column_if_missing(conn, "game_ownership", "price_paid", "REAL")?;
+    add_column_if_missing(conn, "game_ownership", "edition", "TEXT")?;
+    add_column_if_missing(conn, "game_ownership", "currency", "TEXT")?;
+    add_column_if_missing(conn, "wishlist", "archived_at", "DATETIME")?;
+    add_column_if_missing(conn, "wishlist", "purchased_game_id", "TEXT")?;
+    add_column_if_missing(conn, "wishlist", "source", "TEXT")?;
+    add_column_if_missing(conn, "wishlist", "steam_priority", "INTEGER")?;
+    add_column_if_missing(conn, "wishlist", "priority", "INTEGER")?;
+    add_column_if_missing(conn, "wishlist", "notes", "TEXT")?;
+    add_column_if_missing(conn, "wishlist", "tags", "TEXT")?;
+    add_column_if_missing(conn, "wishlist", "sort_position", "INTEGER")?;
+    add_column_if_missing(conn, "wishlist", "discount_percent", "INTEGER")?;
+    add_column_if_missing(conn, "wishlist", "release_date", "TEXT")?;
+    add_column_if_missing(conn, "wishlist", "release_date_text", "TEXT")?;
+    add_column_if_missing(conn, "wishlist", "coming_soon", "BOOLEAN DEFAULT 0")?;
+    add_column_if_missing(conn, "games", "app_type", "TEXT")?;
+    add_column_if_missing(conn, "games", "parent_game_id", "TEXT")?;
+    add_column_if_missing(conn, "games", "parent_app_id", "INTEGER")?;
+    add_column_if_missing(conn, "wishlist", "app_type", "TEXT")?;
+    add_column_if_missing(conn, "wishlist", "parent_app_id", "INTEGER")?;
+    for table in TOUCHED_TABLES {
+        add_column_if_missing(conn, table, "updated_at", "DATETIME")?;
+    }
+
+    // === GATILHOS ===
+
+    // Data da última alteração de cada linha (a restauração por mesclagem mantém a mais recente).
+    // Valores informados explicitamente, como os vindos de um backup, são preservados.
+    for table in TOUCHED_TABLES {
+        conn.execute(
+            &format!(
+                "CREATE TRIGGER IF NOT EXISTS trg_{table}_inserted AFTER INSERT ON {table}
+                 FOR EACH ROW WHEN NEW.updated_at IS NULL
+                 BEGIN
+                     UPDATE {table} SET updated_at = CURRENT_TIMESTAMP WHERE rowid = NEW.rowid;
+                 END"
+            ),
+            [],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute(
+            &format!(
+                "CREATE TRIGGER IF NOT EXISTS trg_{table}_updated AFTER UPDATE ON {table}
+                 FOR EACH ROW WHEN NEW.updated_at IS OLD.updated_at
+                 BEGIN
+                     UPDATE {table} SET updated_at = CURRENT_TIMESTAMP WHERE rowid = NEW.rowid;
+                 END"
+            ),
+            [],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    // Exclusões viram lápides; uma linha recriada com a mesma chave apaga a lápide
+    for (table, keys) in SYNCED_TABLES {
+        let key = |row: &str| {
+            keys.iter()
+                .map(|k| format!("{row}.{k}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        conn.execute(
+            &format!(
+                "CREATE TRIGGER IF NOT EXISTS trg_{table}_deleted AFTER DELETE ON {table}
+                 FOR EACH ROW
+                 BEGIN
+                     INSERT OR REPLACE INTO sync_tombstones (table_name, row_key, deleted_at)
+                     VALUES ('{table}', json_array({}), CURRENT_TIMESTAMP);
+                 END",
+                key("OLD")
+            ),
+            [],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute(
+            &format!(
+                "CREATE TRIGGER IF NOT EXISTS trg_{table}_revived AFTER INSERT ON {table}
+                 FOR EACH ROW
+                 BEGIN
+                     DELETE FROM sync_tombstones
+                     WHERE table_name = '{table}' AND row_key = json_array({});
+                 END",
+                key("NEW")
+            ),
+            [],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    // Diário de alterações (recriados com as colunas atuais depois das migrações)
+    journal::create_triggers(conn)?;
+    journal::close_open_batches(conn)?;
+
     // === ÍNDICES OTIMIZADOS ===
 
     // Índice para filtro de favoritos
@@ -72,6 +417,22 @@ pub fn init_db(state: State<AppState>) -> Result<String, String> {
     )
     .map_err(|e| e.to_string())?;
 
+    // Índice para buscar os IDs externos de um jogo
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_external_game ON game_external_ids(game_id)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Uma mesma cópia (plataforma + ID externo) só é registrada uma vez por jogo. IFNULL: para o
+    // SQLite, NULLs são sempre distintos e cópias manuais (sem ID externo) se repetiriam.
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_ownership_unique
+         ON game_ownership(game_id, platform, IFNULL(external_id, ''))",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
     // Índice para ordenação por data de adição na wishlist
     conn.execute(
         "CREATE INDEX IF NOT EXISTS idx_wishlist_added ON wishlist(added_at)",
@@ -79,5 +440,562 @@ pub fn init_db(state: State<AppState>) -> Result<String, String> {
     )
     .map_err(|e| e.to_string())?;
 
-    Ok("Banco inicializado com sucesso!".to_string())
+    // Índice para listar as cópias de um jogo
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_ownership_game ON game_ownership(game_id)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Índices do diário: histórico de uma linha e lote aberto (consultado pelos gatilhos)
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_journal_row ON change_journal(table_name, row_key)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_journal_batch ON change_journal(batch_id)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_journal_batches_open ON journal_batches(open, id)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Jogos anteriores à tabela de posse ganham uma cópia com a plataforma atual
+    journal::journaled(conn, "backfill_ownership", None, |conn| {
+        backfill_ownership(conn).map_err(|e| e.to_string())
+    })?;
+
+    Ok(())
+}
+
+/// Cria uma linha em `game_ownership` para cada jogo que ainda não possui nenhuma
+pub fn backfill_ownership(conn: &Connection) -> rusqlite::Result<usize> {
+    conn.execute(
+        "INSERT INTO game_ownership (game_id, platform, external_id)
+         SELECT g.id, COALESCE(NULLIF(TRIM(g.platform), ''), ?1),
+                CASE WHEN g.platform = ?2 THEN g.id END
+         FROM games g
+         WHERE NOT EXISTS (SELECT 1 FROM game_ownership o WHERE o.game_id = g.id)",
+        params![
+            constants::DEFAULT_PLATFORM_MANUAL,
+            constants::DEFAULT_PLATFORM_STEAM
+        ],
+    )
+}
+
+/// Registra que o jogo é possuído na plataforma, se ainda não houver cópia nela.
+/// Retorna o ID da linha criada.
+pub fn record_ownership(
+    conn: &Connection,
+    game_id: &str,
+    platform: Option<&str>,
+    external_id: Option<&str>,
+) -> rusqlite::Result<Option<i64>> {
+    let platform = platform
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .unwrap_or(constants::DEFAULT_PLATFORM_MANUAL);
+
+    let inserted = conn.execute(
+        "INSERT INTO game_ownership (game_id, platform, external_id)
+         SELECT ?1, ?2, ?3
+         WHERE NOT EXISTS (SELECT 1 FROM game_ownership WHERE game_id = ?1 AND platform = ?2)",
+        params![game_id, platform, external_id],
+    )?;
+    if inserted > 0 {
+        return Ok(Some(conn.last_insert_rowid()));
+    }
+
+    // Cópia já existente sem ID externo (ex: criada pelo preenchimento inicial)
+    if external_id.is_some() {
+        conn.execute(
+            "UPDATE OR IGNORE game_ownership SET external_id = ?3
+             WHERE game_id = ?1 AND platform = ?2 AND external_id IS NULL",
+            params![game_id, platform, external_id],
+        )?;
+    }
+
+    Ok(None)
+}
+
+/// Jogo lógico que já possui a cópia (plataforma + ID externo), se houver.
+/// Após uma mesclagem, o AppID da Steam passa a pertencer ao jogo principal.
+pub fn owned_game_id(
+    conn: &Connection,
+    platform: &str,
+    external_id: &str,
+) -> rusqlite::Result<Option<String>> {
+    conn.query_row(
+        "SELECT o.game_id FROM game_ownership o JOIN games g ON g.id = o.game_id
+         WHERE o.platform = ?1 AND o.external_id = ?2
+         LIMIT 1",
+        params![platform, external_id],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Preenche `Game::platforms` com as plataformas de `game_ownership`
+pub fn attach_platforms(conn: &Connection, games: &mut [Game]) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare("SELECT game_id, platform FROM game_ownership ORDER BY id")
+        .map_err(|e| e.to_string())?;
+
+    let mut by_game: HashMap<String, Vec<String>> = HashMap::new();
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?;
+    for row in rows {
+        let (game_id, platform) = row.map_err(|e| e.to_string())?;
+        let platforms = by_game.entry(game_id).or_default();
+        if !platforms.contains(&platform) {
+            platforms.push(platform);
+        }
+    }
+
+    for game in games.iter_mut() {
+        game.platforms = by_game
+            .remove(&game.id)
+            .unwrap_or_else(|| game.platform.clone().into_iter().collect());
+    }
+
+    Ok(())
+}
+
+/// Preenche os caminhos locais da capa e da miniatura (cache ou artes do usuário),
+/// do banner e do logo
+pub fn attach_cached_covers(conn: &Connection, games: &mut [Game]) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare("SELECT game_id, file_path, thumb_path FROM cover_cache WHERE status = 'ok'")
+        .map_err(|e| e.to_string())?;
+
+    let mut by_game: HashMap<String, (Option<String>, Option<String>)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    for game in games.iter_mut() {
+        if let Some((cover, thumb)) = by_game.remove(&game.id) {
+            game.local_cover = cover;
+            game.local_thumb = thumb;
+        }
+    }
+
+    // Artes enviadas pelo usuário têm prioridade sobre as capas baixadas
+    let mut stmt = conn
+        .prepare("SELECT game_id, kind, file_path, thumb_path FROM game_artwork")
+        .map_err(|e| e.to_string())?;
+    let artwork = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut by_game: HashMap<String, Vec<(String, String, Option<String>)>> = HashMap::new();
+    for (game_id, kind, path, thumb) in artwork {
+        by_game.entry(game_id).or_default().push((kind, path, thumb));
+    }
+
+    for game in games.iter_mut() {
+        for (kind, path, thumb) in by_game.remove(&game.id).unwrap_or_default() {
+            match kind.as_str() {
+                constants::ARTWORK_KIND_COVER => {
+                    game.local_thumb = thumb.or_else(|| Some(path.clone()));
+                    game.local_cover = Some(path);
+                }
+                constants::ARTWORK_KIND_HERO => game.hero_path = Some(path),
+                constants::ARTWORK_KIND_LOGO => game.logo_path = Some(path),
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Colunas da tabela `games` na ordem esperada por `map_game_row`
+pub const GAME_COLUMNS: &str =
+    "id, name, genre, platform, cover_url, playtime, rating, favorite, installed, last_played, app_type, parent_game_id, rawg_id, parent_app_id";
+
+/// Filtro SQL para ignorar DLCs, trilhas sonoras, ferramentas etc. (tipo desconhecido conta como jogo)
+pub const GAMES_ONLY_FILTER: &str = "(app_type IS NULL OR app_type = 'game')";
+
+/// Converte uma linha selecionada com `GAME_COLUMNS` em `Game`
+pub fn map_game_row(row: &Row) -> rusqlite::Result<Game> {
+    Ok(Game {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        genre: row.get(2)?,
+        platform: row.get(3)?,
+        cover_url: row.get(4)?,
+        playtime: row.get(5)?,
+        rating: row.get(6)?,
+        favorite: row.get(7)?,
+        installed: row.get::<_, Option<bool>>(8)?.unwrap_or(false),
+        last_played: row.get(9)?,
+        app_type: row.get(10)?,
+        parent_game_id: row.get(11)?,
+        rawg_id: row.get(12)?,
+        parent_app_id: row.get(13)?,
+        platforms: Vec::new(),
+        local_cover: None,
+        local_thumb: None,
+        hero_path: None,
+        logo_path: None,
+    })
+}
+
+/// Adiciona uma coluna a uma tabela existente caso ela ainda não exista
+/// (bancos criados por versões anteriores não possuem as colunas novas)
+pub fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({})", table))
+        .map_err(|e| e.to_string())?;
+
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(|e| e.to_string())?
+        .filter_map(Result::ok)
+        .any(|name| name.eq_ignore_ascii_case(column));
+
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Lê uma configuração não sensível da tabela `app_settings`
+pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT value FROM app_settings WHERE key = ?1",
+        params![key],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Grava (ou remove, se vazio) uma configuração não sensível
+pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    if value.trim().is_empty() {
+        conn.execute("DELETE FROM app_settings WHERE key = ?1", params![key])
+            .map_err(|e| e.to_string())?;
+    } else {
+        conn.execute(
+            "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+            params![key, value.trim()],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Executa `work` em uma transação (IMMEDIATE), desfeita se retornar erro
+pub fn in_transaction<T, F>(conn: &Connection, work: F) -> Result<T, String>
+where
+    F: FnOnce(&Connection) -> Result<T, String>,
+{
+    conn.execute("BEGIN IMMEDIATE TRANSACTION", [])
+        .map_err(|e| e.to_string())?;
+
+    match work(conn) {
+        Ok(value) => {
+            conn.execute("COMMIT", []).map_err(|e| e.to_string())?;
+            Ok(value)
+        }
+        Err(e) => {
+            let _ = conn.execute("ROLLBACK", []);
+            Err(e)
+        }
+    }
+}
+
+/// Tabelas do usuário no banco, em ordem alfabética. Ficam de fora as internas do SQLite e o
+/// diário de alterações, que descreve a história deste banco e não vai para o backup.
+pub fn list_tables(conn: &Connection) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT name FROM sqlite_master
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+               AND name NOT IN ('change_journal', 'journal_batches')
+             ORDER BY name",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let tables = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(tables)
+}
+
+/// Colunas da tabela, na ordem do esquema
+pub fn table_columns(conn: &Connection, table: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info(\"{}\")", table))
+        .map_err(|e| e.to_string())?;
+
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(columns)
+}
+
+pub fn sql_to_json(value: ValueRef) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => serde_json::Number::from_f64(f)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned().into(),
+        ValueRef::Blob(b) => base64::engine::general_purpose::STANDARD.encode(b).into(),
+    }
+}
+
+pub fn json_to_sql(value: &serde_json::Value) -> SqlValue {
+    match value {
+        serde_json::Value::Null => SqlValue::Null,
+        serde_json::Value::Bool(b) => SqlValue::Integer(*b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => SqlValue::Text(s.clone()),
+        other => SqlValue::Text(other.to_string()),
+    }
+}
+
+fn column_list(columns: &[String]) -> String {
+    columns
+        .iter()
+        .map(|c| format!("\"{}\"", c))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Percorre as linhas da tabela como valores JSON, alinhados com `columns`, sem carregá-las
+/// todas em memória. Retorna quantas linhas foram lidas.
+pub fn for_each_row<F>(
+    conn: &Connection,
+    table: &str,
+    columns: &[String],
+    mut on_row: F,
+) -> Result<usize, String>
+where
+    F: FnMut(Vec<serde_json::Value>) -> Result<(), String>,
+{
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM \"{}\"", column_list(columns), table))
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+
+    let mut count = 0;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let values = (0..columns.len())
+            .map(|i| row.get_ref(i).map(sql_to_json))
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| e.to_string())?;
+        on_row(values)?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+pub fn count_rows(conn: &Connection, table: &str) -> Result<usize, String> {
+    conn.query_row(&format!("SELECT COUNT(*) FROM \"{}\"", table), [], |row| {
+        row.get::<_, i64>(0)
+    })
+    .map(|count| count as usize)
+    .map_err(|e| e.to_string())
+}
+
+/// Colunas da chave primária, na ordem da chave
+pub fn primary_key_columns(conn: &Connection, table: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info(\"{}\")", table))
+        .map_err(|e| e.to_string())?;
+
+    let mut keys = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(5)?, row.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|(pk, _)| *pk > 0)
+        .collect::<Vec<_>>();
+    keys.sort();
+
+    Ok(keys.into_iter().map(|(_, name)| name).collect())
+}
+
+/// Busca a linha local com a mesma chave de uma linha vinda de fora (ex.: de um backup).
+/// A comparação usa `IS`, então chaves com NULL também casam.
+pub struct RowLookup<'c> {
+    stmt: rusqlite::Statement<'c>,
+    /// Índice de cada coluna da chave nas linhas de fora
+    key_indexes: Vec<Option<usize>>,
+    width: usize,
+}
+
+impl<'c> RowLookup<'c> {
+    pub fn new(
+        conn: &'c Connection,
+        table: &str,
+        columns: &[String],
+        key_columns: &[String],
+        row_columns: &[String],
+    ) -> Result<Self, String> {
+        let condition = key_columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| format!("\"{}\" IS ?{}", c, i + 1))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM \"{}\" WHERE {} LIMIT 1",
+                column_list(columns),
+                table,
+                condition
+            ))
+            .map_err(|e| e.to_string())?;
+
+        Ok(RowLookup {
+            stmt,
+            key_indexes: key_columns
+                .iter()
+                .map(|k| row_columns.iter().position(|c| c == k))
+                .collect(),
+            width: columns.len(),
+        })
+    }
+
+    pub fn find(&mut self, row: &[serde_json::Value]) -> Result<Option<Vec<serde_json::Value>>, String> {
+        let key: Vec<SqlValue> = self
+            .key_indexes
+            .iter()
+            .map(|i| i.and_then(|i| row.get(i)).map(json_to_sql).unwrap_or(SqlValue::Null))
+            .collect();
+        let width = self.width;
+
+        self.stmt
+            .query_row(rusqlite::params_from_iter(key), |row| {
+                (0..width)
+                    .map(|i| row.get_ref(i).map(sql_to_json))
+                    .collect::<rusqlite::Result<Vec<_>>>()
+            })
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Grava linhas com INSERT OR REPLACE ou INSERT OR IGNORE, com as instruções preparadas uma vez.
+/// Colunas que não existem mais no esquema atual são ignoradas; as novas ficam com o valor padrão.
+pub struct RowWriter<'c> {
+    insert: rusqlite::Statement<'c>,
+    replace: rusqlite::Statement<'c>,
+    kept: Vec<usize>,
+}
+
+impl<'c> RowWriter<'c> {
+    pub fn new(conn: &'c Connection, table: &str, columns: &[String]) -> Result<Self, String> {
+        let current = table_columns(conn, table)?;
+        let kept: Vec<usize> = (0..columns.len())
+            .filter(|&i| current.contains(&columns[i]))
+            .collect();
+        if kept.is_empty() {
+            return Err(format!("Tabela '{}' sem colunas em comum com o backup", table));
+        }
+
+        let names: Vec<String> = kept.iter().map(|&i| columns[i].clone()).collect();
+        let placeholders = (1..=kept.len())
+            .map(|i| format!("?{}", i))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let prepare = |verb: &str| {
+            conn.prepare(&format!(
+                "INSERT OR {} INTO \"{}\" ({}) VALUES ({})",
+                verb,
+                table,
+                column_list(&names),
+                placeholders
+            ))
+            .map_err(|e| e.to_string())
+        };
+
+        Ok(RowWriter {
+            insert: prepare("IGNORE")?,
+            replace: prepare("REPLACE")?,
+            kept,
+        })
+    }
+
+    pub fn write(&mut self, row: &[serde_json::Value], replace: bool) -> Result<(), String> {
+        let values: Vec<SqlValue> = self
+            .kept
+            .iter()
+            .map(|&i| row.get(i).map(json_to_sql).unwrap_or(SqlValue::Null))
+            .collect();
+        let stmt = if replace {
+            &mut self.replace
+        } else {
+            &mut self.insert
+        };
+        stmt.execute(rusqlite::params_from_iter(values))
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::memory_db;
+
+    #[test]
+    fn copies_without_external_id_are_unique_too() {
+        let conn = memory_db();
+        conn.execute("INSERT INTO games (id, name) VALUES ('g', 'Hades')", [])
+            .unwrap();
+
+        let insert = "INSERT OR IGNORE INTO game_ownership (game_id, platform, external_id) VALUES (?1, ?2, ?3)";
+        let added = |platform: &str, external_id: Option<&str>| {
+            conn.execute(insert, rusqlite::params!["g", platform, external_id])
+                .unwrap()
+        };
+        assert_eq!(added("Manual", None), 1);
+        assert_eq!(added("Manual", None), 0);
+        assert_eq!(added("Steam", Some("1145360")), 1);
+        assert_eq!(added("Steam", Some("1145360")), 0);
+        assert_eq!(added("Steam", None), 1);
+    }
 }
@@ -5,6 +5,8 @@ mod models;
 mod security;
 mod services;
 mod storage;
+#[cfg(test)]
+mod test_utils;
 mod utils;
 
 use crate::utils::logger;
@@ -49,7 +51,7 @@ pub fn run() {
 
             std::fs::create_dir_all(&app_data_dir).expect("Falha ao criar diretório de dados");
 
-            let db_path = app_data_dir.join("library.db");
+            let db_path = app_data_dir.join(constants::DB_FILENAME_LIBRARY);
 
             let conn =
                 Connection::open(&db_path).expect(&format!("Erro ao abrir banco em {:?}", db_path));
@@ -60,6 +62,13 @@ pub fn run() {
                 db: Mutex::new(conn),
             });
 
+            // Cópia automática do banco na abertura e no intervalo configurado
+            commands::snapshots::run_startup(app_handle);
+            commands::snapshots::start_scheduler(app_handle.clone());
+
+            // Limpeza periódica do diário de alterações e da lixeira
+            commands::journal::start_purge_scheduler(app_handle.clone());
+
             Ok(())
         })
         // Registra todos os comandos chamando a partir dos módulos
@@ -72,6 +81,33 @@ pub fn run() {
             commands::games::toggle_favorite,
             commands::games::delete_game,
             commands::games::update_game,
+            commands::games::set_game_parent,
+            // Comandos do Cache de Capas
+            commands::covers::cache_covers,
+            commands::covers::redownload_all_covers,
+            commands::covers::get_cover_cache_stats,
+            commands::covers::set_cover_cache_limit,
+            commands::covers::clear_cover_cache,
+            // Comandos de Artes Personalizadas
+            commands::artwork::import_game_artwork,
+            commands::artwork::get_game_artwork,
+            commands::artwork::remove_game_artwork,
+            // Comandos de Duplicatas
+            commands::duplicates::find_duplicate_games,
+            commands::duplicates::merge_games,
+            commands::duplicates::undo_merge,
+            commands::duplicates::get_merge_history,
+            // Comandos de Posse (cópias por plataforma)
+            commands::ownership::get_game_ownership,
+            commands::ownership::add_game_ownership,
+            commands::ownership::update_game_ownership,
+            commands::ownership::remove_game_ownership,
+            // Comandos de Gastos (compras e cotações)
+            commands::purchases::get_spending_report,
+            commands::purchases::get_currency_rates,
+            commands::purchases::set_currency_rate,
+            commands::purchases::delete_currency_rate,
+            commands::purchases::set_base_currency,
             // Comandos da Lista de Desejos
             commands::wishlist::search_wishlist_game,
             commands::wishlist::add_to_wishlist,
@@ -79,12 +115,34 @@ pub fn run() {
             commands::wishlist::remove_from_wishlist,
             commands::wishlist::check_wishlist_status,
             commands::wishlist::refresh_prices,
+            commands::wishlist::purchase_wishlist_item,
+            commands::wishlist::get_archived_wishlist,
+            commands::wishlist::import_steam_wishlist,
+            commands::wishlist::update_wishlist_item,
+            commands::wishlist::reorder_wishlist,
+            commands::wishlist::get_wishlist_releases,
             // Comandos de Integração (Steam/RAWG)
             commands::integrations::import_steam_library,
+            commands::integrations::scan_local_steam_library,
+            commands::integrations::classify_steam_apps,
             commands::integrations::enrich_library,
             commands::integrations::get_trending_games,
             commands::integrations::get_upcoming_games,
             commands::integrations::fetch_game_details,
+            commands::integrations::search_rawg_matches,
+            commands::integrations::set_game_rawg_id,
+            commands::metadata::get_game_metadata,
+            commands::metadata::get_metadata_sources,
+            commands::metadata::set_metadata_override,
+            commands::metadata::get_metadata_priority,
+            commands::metadata::set_metadata_priority,
+            commands::metadata::get_metadata_developers,
+            // Comandos de Importação (launchers locais)
+            commands::imports::import_launcher_library,
+            commands::imports::import_playnite_library,
+            commands::imports::list_import_presets,
+            commands::imports::preview_file_import,
+            commands::imports::import_file_library,
             // Comandos de Configuração (Secrets)
             commands::settings::set_secret,
             commands::settings::get_secret,
@@ -92,9 +150,32 @@ pub fn run() {
             commands::settings::list_secrets,
             commands::settings::get_secrets,
             commands::settings::set_secrets,
+            commands::settings::get_app_setting,
+            commands::settings::set_app_setting,
             // Comandos de Backup e Restauração
             commands::backup::export_database,
             commands::backup::import_database,
+            commands::backup::preview_import,
+            // Comandos de Cópias Automáticas do Banco
+            commands::snapshots::list_snapshots,
+            commands::snapshots::create_snapshot,
+            commands::snapshots::restore_snapshot,
+            commands::snapshots::check_database_health,
+            commands::snapshots::get_snapshot_settings,
+            commands::snapshots::set_snapshot_settings,
+            // Comandos de Sincronização entre Dispositivos
+            commands::sync::get_sync_settings,
+            commands::sync::set_sync_settings,
+            commands::sync::sync_now,
+            // Comandos do Diário de Alterações e Lixeira
+            commands::journal::get_journal,
+            commands::journal::undo_last,
+            commands::journal::list_trash,
+            commands::journal::restore_from_trash,
+            commands::journal::empty_trash,
+            commands::journal::export_journal,
+            commands::journal::get_journal_settings,
+            commands::journal::set_journal_settings,
             // Comando de Recomendação
             commands::recommendations::get_user_profile
         ])
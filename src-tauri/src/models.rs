@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Game {
@@ -10,6 +11,109 @@ pub struct Game {
     pub playtime: i32,
     pub rating: Option<i32>,
     pub favorite: bool,
+    #[serde(default)]
+    pub installed: bool,
+    #[serde(default)]
+    pub last_played: Option<String>,
+    /// Tipo do app na Steam ("game", "dlc", "music"...); None se desconhecido
+    #[serde(default)]
+    pub app_type: Option<String>,
+    /// Jogo base, para DLCs aninhadas
+    #[serde(default)]
+    pub parent_game_id: Option<String>,
+    /// AppID Steam do jogo base, guardado para vincular a DLC quando o jogo base chegar
+    #[serde(default)]
+    pub parent_app_id: Option<i64>,
+    /// ID do jogo na RAWG (resolvido pela busca ou escolhido pelo usuário)
+    #[serde(default)]
+    pub rawg_id: Option<i32>,
+    /// Todas as plataformas/lojas em que o jogo é possuído (ver `GameOwnership`)
+    #[serde(default)]
+    pub platforms: Vec<String>,
+    /// Capa e miniatura no cache local (ver `cover_cache`)
+    #[serde(default)]
+    pub local_cover: Option<String>,
+    #[serde(default)]
+    pub local_thumb: Option<String>,
+    /// Banner (hero) e logo enviados pelo usuário (ver `game_artwork`)
+    #[serde(default)]
+    pub hero_path: Option<String>,
+    #[serde(default)]
+    pub logo_path: Option<String>,
+}
+
+/// Arte enviada pelo usuário para um jogo
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameArtwork {
+    pub game_id: String,
+    /// "cover", "hero" ou "logo"
+    pub kind: String,
+    pub file_path: String,
+    pub thumb_path: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    pub size_bytes: u64,
+    #[serde(default)]
+    pub created_at: Option<String>,
+}
+
+/// Uma cópia de um jogo em uma plataforma ou loja
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameOwnership {
+    #[serde(default)]
+    pub id: i64,
+    pub game_id: String,
+    pub platform: String,
+    pub external_id: Option<String>,
+    pub purchase_date: Option<String>,
+    pub price_paid: Option<f64>,
+    /// Código ISO da moeda do preço pago (None = moeda base)
+    #[serde(default)]
+    pub currency: Option<String>,
+    pub edition: Option<String>,
+}
+
+/// Dados editáveis de uma cópia, recebidos do frontend
+#[derive(Debug, Deserialize)]
+pub struct OwnershipInput {
+    pub platform: String,
+    pub external_id: Option<String>,
+    pub purchase_date: Option<String>,
+    pub price_paid: Option<f64>,
+    pub currency: Option<String>,
+    pub edition: Option<String>,
+}
+
+/// Cotação de uma moeda em relação à moeda base (1 unidade = `rate` na moeda base)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CurrencyRate {
+    pub currency: String,
+    pub rate: f64,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+/// Metadados da RAWG salvos para um jogo da biblioteca
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameMetadata {
+    pub game_id: String,
+    pub rawg_id: Option<i32>,
+    pub description: Option<String>,
+    pub website: Option<String>,
+    pub metacritic: Option<i32>,
+    pub release_date: Option<String>,
+    pub background_image: Option<String>,
+    #[serde(default)]
+    pub developers: Vec<String>,
+    #[serde(default)]
+    pub publishers: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Fonte de cada campo preenchido (campo -> "local", "steam", "rawg")
+    #[serde(default)]
+    pub sources: BTreeMap<String, String>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -25,6 +129,108 @@ pub struct WishlistGame {
     pub localized_currency: Option<String>,
     pub steam_app_id: Option<i32>,
     pub added_at: Option<String>,
+    /// Preenchido quando o item foi comprado e saiu da lista ativa
+    #[serde(default)]
+    pub archived_at: Option<String>,
+    #[serde(default)]
+    pub purchased_game_id: Option<String>,
+    /// "steam" quando o item veio da importação da wishlist da Steam
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Posição do item na wishlist da Steam (1 = topo)
+    #[serde(default)]
+    pub steam_priority: Option<i32>,
+    /// Prioridade do usuário (1 = baixa, 2 = média, 3 = alta)
+    #[serde(default)]
+    pub priority: Option<i32>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Posição na ordenação manual (1 = topo)
+    #[serde(default)]
+    pub sort_position: Option<i32>,
+    #[serde(default)]
+    pub discount_percent: Option<i32>,
+    /// Data de lançamento exata (AAAA-MM-DD), quando conhecida
+    #[serde(default)]
+    pub release_date: Option<String>,
+    /// Texto da loja, também para datas vagas ("Q1 2025")
+    #[serde(default)]
+    pub release_date_text: Option<String>,
+    #[serde(default)]
+    pub coming_soon: bool,
+    #[serde(default)]
+    pub app_type: Option<String>,
+    #[serde(default)]
+    pub parent_app_id: Option<i32>,
+}
+
+/// Evento emitido quando um item da wishlist é lançado ou tem a data alterada
+#[derive(Debug, Serialize, Clone)]
+pub struct WishlistReleaseEvent {
+    pub id: String,
+    pub name: String,
+    /// "released" ou "date_changed"
+    pub kind: String,
+    pub previous_date: Option<String>,
+    pub release_date: Option<String>,
+}
+
+/// Progresso de exportação, pré-visualização ou restauração de backup, por tabela
+#[derive(Debug, Serialize, Clone)]
+pub struct BackupProgress {
+    /// "export", "preview" ou "import"
+    pub operation: String,
+    pub table: String,
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Progresso da consulta do tipo dos apps Steam (um app por vez, limitado pela loja)
+#[derive(Debug, Serialize, Clone)]
+pub struct SteamClassifyProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Linha do diário de alterações, com as imagens da linha antes e depois
+#[derive(Debug, Serialize, Clone)]
+pub struct JournalEntry {
+    pub id: i64,
+    pub batch_id: Option<i64>,
+    /// Operação que agrupou a alteração ("delete_game", "import", "restore", "sync"...)
+    pub batch_operation: Option<String>,
+    pub batch_description: Option<String>,
+    pub table_name: String,
+    /// "insert", "update" ou "delete"
+    pub operation: String,
+    pub row_key: serde_json::Value,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub created_at: String,
+    pub undone: bool,
+}
+
+/// Jogo ou item da lista de desejos excluído que ainda pode ser restaurado
+#[derive(Debug, Serialize, Clone)]
+pub struct TrashItem {
+    /// Entrada do diário com a exclusão (usada para restaurar)
+    pub entry_id: i64,
+    /// "games" ou "wishlist"
+    pub table_name: String,
+    pub item_id: String,
+    pub name: Option<String>,
+    pub deleted_at: String,
+    pub operation: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct UndoResult {
+    pub operation: String,
+    pub description: Option<String>,
+    /// Linhas revertidas
+    pub changes: usize,
 }
 
 // Enum de erros personalizados para melhor diagnóstico
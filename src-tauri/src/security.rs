@@ -1,12 +1,17 @@
 use aes_gcm::{
-    aead::{Aead, KeyInit},
+    aead::{
+        stream::{EncryptorBE32, NewStream, StreamBE32, StreamPrimitive},
+        Aead, KeyInit, Payload,
+    },
     Aes256Gcm, Nonce,
 };
+use argon2::{Algorithm, Argon2, Params, Version};
 use base64::Engine;
 use once_cell::sync::OnceCell;
 use rand::RngCore;
 use sha2::{Digest, Sha256};
 use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
 use tauri::{AppHandle, Manager};
 use tauri_plugin_machine_uid::MachineUidExt;
 
@@ -110,3 +115,402 @@ pub fn decrypt(data: &str) -> Result<String, String> {
 
     String::from_utf8(plain).map_err(|e| e.to_string())
 }
+
+// === CRIPTOGRAFIA POR SENHA (backups portáveis entre máquinas) ===
+//
+// Formato: MAGIC | versão (1) | m_cost, t_cost, p_cost (u32 LE) | salt (16) | nonce (7)
+//          | verificador (16) | blocos AES-256-GCM (STREAM, 64 KiB de texto cada)
+// O cabeçalho inteiro entra como dado associado de cada bloco, então qualquer alteração nele
+// invalida as tags. O último bloco é marcado na construção STREAM: um arquivo truncado falha.
+// O verificador (derivado da chave) separa senha incorreta de arquivo adulterado.
+
+const PASSPHRASE_MAGIC: &[u8; 8] = b"PLBKENC\0";
+const PASSPHRASE_FORMAT_VERSION: u8 = 1;
+const PASSPHRASE_SALT_LEN: usize = 16;
+// 12 bytes do nonce do AES-GCM menos os 5 usados pelo contador de blocos
+const PASSPHRASE_NONCE_LEN: usize = 7;
+const PASSPHRASE_VERIFIER_LEN: usize = 16;
+const PASSPHRASE_HEADER_LEN: usize =
+    8 + 1 + 12 + PASSPHRASE_SALT_LEN + PASSPHRASE_NONCE_LEN + PASSPHRASE_VERIFIER_LEN;
+const PASSPHRASE_SALT_START: usize = 21;
+const PASSPHRASE_NONCE_START: usize = PASSPHRASE_SALT_START + PASSPHRASE_SALT_LEN;
+const PASSPHRASE_VERIFIER_START: usize = PASSPHRASE_NONCE_START + PASSPHRASE_NONCE_LEN;
+const PASSPHRASE_CHUNK_LEN: usize = 64 * 1024;
+const PASSPHRASE_TAG_LEN: usize = 16;
+
+// Argon2id com os parâmetros recomendados pelo OWASP (19 MiB, 2 iterações)
+const ARGON2_M_COST: u32 = 19 * 1024;
+const ARGON2_T_COST: u32 = 2;
+const ARGON2_P_COST: u32 = 1;
+// Quanto o cabeçalho pode pedir além dos parâmetros de exportação antes de ser rejeitado
+const ARGON2_MAX_FACTOR: u32 = 4;
+
+fn derive_passphrase_key(
+    passphrase: &str,
+    salt: &[u8],
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+) -> Result<[u8; 32], String> {
+    let params = Params::new(m_cost, t_cost, p_cost, Some(32))
+        .map_err(|e| format!("Parâmetros de criptografia inválidos: {}", e))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Erro ao derivar chave: {}", e))?;
+    Ok(key)
+}
+
+fn passphrase_verifier(key: &[u8; 32]) -> [u8; PASSPHRASE_VERIFIER_LEN] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"playlite-backup-verifier");
+    hasher.update(key);
+    let digest = hasher.finalize();
+    let mut verifier = [0u8; PASSPHRASE_VERIFIER_LEN];
+    verifier.copy_from_slice(&digest[..PASSPHRASE_VERIFIER_LEN]);
+    verifier
+}
+
+/// Lê até encher o buffer ou o fluxo acabar; retorna quantos bytes foram lidos
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize, String> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    Ok(filled)
+}
+
+/// Indica se os dados foram gerados por `PassphraseWriter`
+pub fn is_passphrase_encrypted(data: &[u8]) -> bool {
+    data.starts_with(PASSPHRASE_MAGIC)
+}
+
+fn tampered() -> String {
+    "Arquivo de backup adulterado: a verificação de integridade falhou".to_string()
+}
+
+/// Encripta o que é escrito com uma chave derivada da senha (Argon2id), independente da
+/// máquina. Só um bloco fica em memória por vez; `finish` grava o último bloco.
+pub struct PassphraseWriter<W: Write> {
+    inner: W,
+    header: Vec<u8>,
+    encryptor: EncryptorBE32<Aes256Gcm>,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> PassphraseWriter<W> {
+    pub fn new(mut inner: W, passphrase: &str) -> Result<Self, String> {
+        let mut salt = [0u8; PASSPHRASE_SALT_LEN];
+        let mut nonce_bytes = [0u8; PASSPHRASE_NONCE_LEN];
+        rand::rng().fill_bytes(&mut salt);
+        rand::rng().fill_bytes(&mut nonce_bytes);
+
+        let key =
+            derive_passphrase_key(passphrase, &salt, ARGON2_M_COST, ARGON2_T_COST, ARGON2_P_COST)?;
+
+        let mut header = Vec::with_capacity(PASSPHRASE_HEADER_LEN);
+        header.extend_from_slice(PASSPHRASE_MAGIC);
+        header.push(PASSPHRASE_FORMAT_VERSION);
+        header.extend_from_slice(&ARGON2_M_COST.to_le_bytes());
+        header.extend_from_slice(&ARGON2_T_COST.to_le_bytes());
+        header.extend_from_slice(&ARGON2_P_COST.to_le_bytes());
+        header.extend_from_slice(&salt);
+        header.extend_from_slice(&nonce_bytes);
+        header.extend_from_slice(&passphrase_verifier(&key));
+        inner.write_all(&header).map_err(|e| e.to_string())?;
+
+        let cipher = Aes256Gcm::new_from_slice(&key).map_err(|e| e.to_string())?;
+        Ok(PassphraseWriter {
+            inner,
+            header,
+            encryptor: EncryptorBE32::from_aead(cipher, nonce_bytes.as_slice().into()),
+            buf: Vec::with_capacity(PASSPHRASE_CHUNK_LEN),
+        })
+    }
+
+    /// Grava o bloco cheio; só é chamado quando chega mais texto, porque o último bloco
+    /// precisa ser marcado como tal
+    fn write_chunk(&mut self) -> std::io::Result<()> {
+        let chunk = self
+            .encryptor
+            .encrypt_next(Payload {
+                msg: &self.buf,
+                aad: &self.header,
+            })
+            .map_err(|_| std::io::Error::other("Erro ao encriptar backup"))?;
+        self.buf.clear();
+        self.inner.write_all(&chunk)
+    }
+
+    /// Grava o último bloco (menor que um bloco cheio, possivelmente vazio) e devolve o destino
+    pub fn finish(mut self) -> Result<W, String> {
+        if self.buf.len() == PASSPHRASE_CHUNK_LEN {
+            self.write_chunk().map_err(|e| e.to_string())?;
+        }
+        let chunk = self
+            .encryptor
+            .encrypt_last(Payload {
+                msg: &self.buf,
+                aad: &self.header,
+            })
+            .map_err(|_| "Erro ao encriptar backup".to_string())?;
+        self.inner.write_all(&chunk).map_err(|e| e.to_string())?;
+        self.inner.flush().map_err(|e| e.to_string())?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for PassphraseWriter<W> {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        if self.buf.len() == PASSPHRASE_CHUNK_LEN {
+            self.write_chunk()?;
+        }
+        let taken = data.len().min(PASSPHRASE_CHUNK_LEN - self.buf.len());
+        self.buf.extend_from_slice(&data[..taken]);
+        Ok(taken)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Chave de um arquivo de `PassphraseWriter`, derivada uma vez para abrir quantos leitores
+/// forem necessários
+#[derive(Clone)]
+pub struct PassphraseKey {
+    header: [u8; PASSPHRASE_HEADER_LEN],
+    cipher: Aes256Gcm,
+}
+
+impl PassphraseKey {
+    /// Lê o cabeçalho e deriva a chave, diferenciando senha incorreta de arquivo adulterado
+    pub fn read<R: Read>(reader: &mut R, passphrase: &str) -> Result<Self, String> {
+        let mut header = [0u8; PASSPHRASE_HEADER_LEN];
+        if read_full(reader, &mut header)? < PASSPHRASE_HEADER_LEN
+            || !is_passphrase_encrypted(&header)
+        {
+            return Err(
+                "Arquivo de backup inválido: cabeçalho de criptografia ausente ou incompleto".into(),
+            );
+        }
+        if header[8] != PASSPHRASE_FORMAT_VERSION {
+            return Err(format!("Versão de backup incompatível: criptografia v{}", header[8]));
+        }
+
+        let read_u32 = |offset: usize| {
+            u32::from_le_bytes([
+                header[offset],
+                header[offset + 1],
+                header[offset + 2],
+                header[offset + 3],
+            ])
+        };
+        let (m_cost, t_cost, p_cost) = (read_u32(9), read_u32(13), read_u32(17));
+
+        // Parâmetros adulterados não podem travar o aplicativo com uma derivação gigante
+        if m_cost > ARGON2_M_COST * ARGON2_MAX_FACTOR
+            || t_cost > ARGON2_T_COST * ARGON2_MAX_FACTOR
+            || p_cost > ARGON2_P_COST * ARGON2_MAX_FACTOR
+        {
+            return Err("Arquivo de backup adulterado: parâmetros de criptografia inválidos".into());
+        }
+
+        let salt = &header[PASSPHRASE_SALT_START..PASSPHRASE_NONCE_START];
+        let key = derive_passphrase_key(passphrase, salt, m_cost, t_cost, p_cost)?;
+        if passphrase_verifier(&key) != header[PASSPHRASE_VERIFIER_START..] {
+            return Err("Senha do backup incorreta".into());
+        }
+
+        Ok(PassphraseKey {
+            header,
+            cipher: Aes256Gcm::new_from_slice(&key).map_err(|e| e.to_string())?,
+        })
+    }
+}
+
+/// Leitura com busca de um arquivo de `PassphraseWriter`: cada bloco tem o próprio nonce
+/// (contador da construção STREAM), então só o bloco lido é decifrado e nada vai em claro
+/// para o disco. Um bloco adulterado, fora de ordem ou um arquivo truncado geram erro na leitura.
+pub struct PassphraseReader<R: Read + Seek> {
+    inner: R,
+    key: PassphraseKey,
+    stream: StreamBE32<Aes256Gcm>,
+    /// Número de blocos e tamanho encriptado do último
+    chunks: u64,
+    last_chunk_len: usize,
+    len: u64,
+    position: u64,
+    /// Bloco decifrado atual
+    current: Option<u64>,
+    buf: Vec<u8>,
+}
+
+impl<R: Read + Seek> PassphraseReader<R> {
+    pub fn new(mut inner: R, key: &PassphraseKey) -> Result<Self, String> {
+        let size = inner.seek(SeekFrom::End(0)).map_err(|e| e.to_string())?;
+        let body = size
+            .checked_sub(PASSPHRASE_HEADER_LEN as u64)
+            .ok_or_else(tampered)?;
+        let full = (PASSPHRASE_CHUNK_LEN + PASSPHRASE_TAG_LEN) as u64;
+        // O último bloco é sempre menor que um cheio e tem pelo menos a tag; um arquivo
+        // truncado no limite de um bloco fica sem ele
+        let last_chunk_len = (body % full) as usize;
+        if last_chunk_len < PASSPHRASE_TAG_LEN {
+            return Err(tampered());
+        }
+        let chunks = body / full + 1;
+        if chunks > u32::MAX as u64 {
+            return Err(tampered());
+        }
+
+        let nonce = &key.header[PASSPHRASE_NONCE_START..PASSPHRASE_VERIFIER_START];
+        let mut reader = PassphraseReader {
+            inner,
+            key: key.clone(),
+            stream: StreamBE32::from_aead(key.cipher.clone(), nonce.into()),
+            chunks,
+            last_chunk_len,
+            len: (chunks - 1) * PASSPHRASE_CHUNK_LEN as u64
+                + (last_chunk_len - PASSPHRASE_TAG_LEN) as u64,
+            position: 0,
+            current: None,
+            buf: Vec::with_capacity(PASSPHRASE_CHUNK_LEN + PASSPHRASE_TAG_LEN),
+        };
+        // Confere o último bloco já na abertura: um arquivo truncado falha aqui
+        reader.load_chunk(chunks - 1).map_err(|e| e.to_string())?;
+        Ok(reader)
+    }
+
+    fn load_chunk(&mut self, index: u64) -> std::io::Result<()> {
+        if self.current == Some(index) {
+            return Ok(());
+        }
+        self.current = None;
+
+        let last = index == self.chunks - 1;
+        let full = PASSPHRASE_CHUNK_LEN + PASSPHRASE_TAG_LEN;
+        let len = if last { self.last_chunk_len } else { full };
+        self.inner
+            .seek(SeekFrom::Start(PASSPHRASE_HEADER_LEN as u64 + index * full as u64))?;
+        self.buf.resize(len, 0);
+        self.inner.read_exact(&mut self.buf)?;
+
+        self.stream
+            .decrypt_in_place(index as u32, last, &self.key.header, &mut self.buf)
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, tampered()))?;
+        self.current = Some(index);
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> Read for PassphraseReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        if self.position >= self.len || out.is_empty() {
+            return Ok(0);
+        }
+        let index = self.position / PASSPHRASE_CHUNK_LEN as u64;
+        self.load_chunk(index)?;
+
+        let offset = (self.position % PASSPHRASE_CHUNK_LEN as u64) as usize;
+        let read = out.len().min(self.buf.len() - offset);
+        out[..read].copy_from_slice(&self.buf[offset..offset + read]);
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: Read + Seek> Seek for PassphraseReader<R> {
+    fn seek(&mut self, target: SeekFrom) -> std::io::Result<u64> {
+        let position = match target {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(delta) => self.len.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+        };
+        self.position = position.ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "Posição inválida no backup")
+        })?;
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Um bloco cheio e um pedaço, para exercitar mais de um bloco da construção STREAM
+    fn sample() -> Vec<u8> {
+        (0..PASSPHRASE_CHUNK_LEN + 1000).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn encrypted(plain: &[u8]) -> Vec<u8> {
+        let mut writer = PassphraseWriter::new(Vec::new(), "correta").unwrap();
+        writer.write_all(plain).unwrap();
+        writer.finish().unwrap()
+    }
+
+    fn decrypted(data: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+        let mut source = std::io::Cursor::new(data);
+        let key = PassphraseKey::read(&mut source, passphrase)?;
+        let mut reader = PassphraseReader::new(source, &key)?;
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).map_err(|e| e.to_string())?;
+        Ok(out)
+    }
+
+    #[test]
+    fn round_trip_and_wrong_passphrase() {
+        let plain = sample();
+        let data = encrypted(&plain);
+        assert!(is_passphrase_encrypted(&data));
+        assert_eq!(decrypted(&data, "correta").unwrap(), plain);
+        assert_eq!(decrypted(&data, "errada").unwrap_err(), "Senha do backup incorreta");
+    }
+
+    #[test]
+    fn blocks_are_read_in_any_order() {
+        // Texto que termina no limite de um bloco: o último bloco fica vazio
+        let plain: Vec<u8> = (0..PASSPHRASE_CHUNK_LEN * 2).map(|i| (i % 251) as u8).collect();
+        let data = encrypted(&plain);
+        assert_eq!(decrypted(&data, "correta").unwrap(), plain);
+
+        let mut source = std::io::Cursor::new(&data);
+        let key = PassphraseKey::read(&mut source, "correta").unwrap();
+        let mut reader = PassphraseReader::new(source, &key).unwrap();
+        assert_eq!(reader.seek(SeekFrom::End(0)).unwrap(), plain.len() as u64);
+
+        let mut tail = [0u8; 10];
+        reader.seek(SeekFrom::End(-10)).unwrap();
+        reader.read_exact(&mut tail).unwrap();
+        assert_eq!(tail, plain[plain.len() - 10..]);
+
+        let mut across = [0u8; 100];
+        reader.seek(SeekFrom::Start(PASSPHRASE_CHUNK_LEN as u64 - 50)).unwrap();
+        reader.read_exact(&mut across).unwrap();
+        assert_eq!(across, plain[PASSPHRASE_CHUNK_LEN - 50..PASSPHRASE_CHUNK_LEN + 50]);
+    }
+
+    #[test]
+    fn tampering_and_truncation_are_detected() {
+        let data = encrypted(&sample());
+
+        let mut flipped = data.clone();
+        flipped[PASSPHRASE_HEADER_LEN + 10] ^= 0x01;
+        assert!(decrypted(&flipped, "correta").unwrap_err().contains("adulterado"));
+
+        // Sem o último bloco o fluxo termina exatamente no limite de um bloco cheio
+        let boundary = PASSPHRASE_HEADER_LEN + PASSPHRASE_CHUNK_LEN + PASSPHRASE_TAG_LEN;
+        assert!(decrypted(&data[..boundary], "correta").unwrap_err().contains("adulterado"));
+        assert!(decrypted(&data[..PASSPHRASE_HEADER_LEN], "correta").is_err());
+
+        // Um custo de memória muito acima do exportado é recusado antes da derivação
+        let mut greedy = data.clone();
+        greedy[9..13].copy_from_slice(&(ARGON2_M_COST * ARGON2_MAX_FACTOR + 1).to_le_bytes());
+        assert!(decrypted(&greedy, "correta").unwrap_err().contains("parâmetros"));
+    }
+}
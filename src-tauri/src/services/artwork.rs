@@ -0,0 +1,185 @@
+// Validação e armazenamento das artes enviadas pelo usuário (capa, banner e logo)
+
+use crate::constants;
+use crate::services::covers;
+use image::{DynamicImage, GenericImageView, ImageFormat};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Arte validada e gravada na pasta de artes
+pub struct StoredArtwork {
+    pub file_path: PathBuf,
+    /// Só capas geram miniatura
+    pub thumb_path: Option<PathBuf>,
+    pub width: u32,
+    pub height: u32,
+    pub size_bytes: u64,
+}
+
+/// Imagem que passou por `validate`, já decodificada (a miniatura reaproveita a decodificação)
+pub struct ValidatedImage {
+    pub format: ImageFormat,
+    pub width: u32,
+    pub height: u32,
+    image: DynamicImage,
+}
+
+/// Tipos de arte aceitos
+pub fn is_valid_kind(kind: &str) -> bool {
+    matches!(
+        kind,
+        constants::ARTWORK_KIND_COVER | constants::ARTWORK_KIND_HERO | constants::ARTWORK_KIND_LOGO
+    )
+}
+
+/// Dimensões mínimas (largura, altura) de cada tipo de arte
+fn min_dimensions(kind: &str) -> (u32, u32) {
+    match kind {
+        constants::ARTWORK_KIND_COVER => (150, 200),
+        constants::ARTWORK_KIND_HERO => (960, 310),
+        _ => (100, 40),
+    }
+}
+
+/// Confere formato (PNG, JPEG ou WebP), tamanho do arquivo e dimensões da imagem
+pub fn validate(kind: &str, bytes: &[u8]) -> Result<ValidatedImage, String> {
+    if !is_valid_kind(kind) {
+        return Err(format!("Tipo de arte inválido: {}", kind));
+    }
+    if bytes.len() as u64 > constants::MAX_ARTWORK_BYTES {
+        return Err(format!(
+            "Imagem muito grande (máximo de {} MB)",
+            constants::MAX_ARTWORK_BYTES / 1024 / 1024
+        ));
+    }
+
+    let format = image::guess_format(bytes).map_err(|_| "Arquivo não é uma imagem".to_string())?;
+    if !matches!(
+        format,
+        ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::WebP
+    ) {
+        return Err("Formato não suportado (use PNG, JPEG ou WebP)".to_string());
+    }
+
+    let img = image::load_from_memory_with_format(bytes, format)
+        .map_err(|e| format!("Imagem inválida: {}", e))?;
+    let (width, height) = img.dimensions();
+
+    let (min_w, min_h) = min_dimensions(kind);
+    if width < min_w || height < min_h {
+        return Err(format!(
+            "Imagem pequena demais para {}: {}x{} (mínimo {}x{})",
+            kind, width, height, min_w, min_h
+        ));
+    }
+    if width > constants::MAX_ARTWORK_DIMENSION || height > constants::MAX_ARTWORK_DIMENSION {
+        return Err(format!(
+            "Imagem grande demais: {}x{} (máximo {} px por lado)",
+            width,
+            height,
+            constants::MAX_ARTWORK_DIMENSION
+        ));
+    }
+
+    Ok(ValidatedImage {
+        format,
+        width,
+        height,
+        image: img,
+    })
+}
+
+/// Grava a arte já validada como `{chave}_{tipo}_{momento}.{ext}`; capas também ganham
+/// miniatura. O nome nunca se repete: a arte substituída continua no disco enquanto sua
+/// exclusão puder ser desfeita.
+pub fn store_artwork(
+    dir: &Path,
+    game_id: &str,
+    kind: &str,
+    bytes: &[u8],
+    validated: &ValidatedImage,
+) -> Result<StoredArtwork, String> {
+    let extension = validated
+        .format
+        .extensions_str()
+        .first()
+        .copied()
+        .unwrap_or("img");
+    let stem = format!(
+        "{}_{}_{}",
+        covers::cache_key(game_id),
+        kind,
+        chrono::Utc::now().timestamp_micros()
+    );
+
+    fs::create_dir_all(dir).map_err(|e| format!("Erro ao criar pasta de artes: {}", e))?;
+
+    let file_path = dir.join(format!("{}.{}", stem, extension));
+    fs::write(&file_path, bytes).map_err(|e| format!("Erro ao salvar arte: {}", e))?;
+
+    let mut size_bytes = bytes.len() as u64;
+    let thumb_path = if kind == constants::ARTWORK_KIND_COVER {
+        let path = dir.join(format!("{}_thumb.jpg", stem));
+        covers::write_thumbnail(&validated.image, &path)?;
+        size_bytes += fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        Some(path)
+    } else {
+        None
+    };
+
+    Ok(StoredArtwork {
+        file_path,
+        thumb_path,
+        width: validated.width,
+        height: validated.height,
+        size_bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_dir;
+    use image::{Rgb, RgbImage};
+    use std::io::Cursor;
+
+    fn encoded(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        RgbImage::from_pixel(width, height, Rgb([20, 120, 200]))
+            .write_to(&mut Cursor::new(&mut bytes), format)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn validates_kind_format_and_dimensions() {
+        let cover = encoded(300, 450, ImageFormat::Png);
+        let validated = validate(constants::ARTWORK_KIND_COVER, &cover).unwrap();
+        assert_eq!((validated.format, validated.width, validated.height), (ImageFormat::Png, 300, 450));
+
+        assert!(validate("poster", &cover).is_err());
+        assert!(validate(constants::ARTWORK_KIND_HERO, &cover).is_err());
+        assert!(validate(constants::ARTWORK_KIND_COVER, b"GIF89a\x01\x00\x01\x00").is_err());
+        assert!(validate(constants::ARTWORK_KIND_COVER, &cover[..cover.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn only_covers_get_a_thumbnail() {
+        let dir = temp_dir("artwork");
+
+        let cover = encoded(300, 450, ImageFormat::Jpeg);
+        let validated = validate(constants::ARTWORK_KIND_COVER, &cover).unwrap();
+        let stored = store_artwork(&dir, "g", constants::ARTWORK_KIND_COVER, &cover, &validated).unwrap();
+        assert!(stored.file_path.exists());
+        assert!(stored.thumb_path.as_ref().is_some_and(|p| p.exists()));
+        assert_eq!((stored.width, stored.height), (300, 450));
+
+        let logo = encoded(200, 80, ImageFormat::Png);
+        let validated = validate(constants::ARTWORK_KIND_LOGO, &logo).unwrap();
+        let stored = store_artwork(&dir, "g", constants::ARTWORK_KIND_LOGO, &logo, &validated).unwrap();
+        assert!(stored.thumb_path.is_none());
+        assert_eq!(stored.size_bytes, logo.len() as u64);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,418 @@
+// Arquivo de backup v2: zip com manifesto, tabelas em JSON e imagens, com checksum de cada entrada.
+// Tudo é gravado e lido em fluxo, para a memória não crescer com o tamanho da biblioteca.
+
+use crate::constants;
+use serde::de::{DeserializeSeed, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Tabela exportada em `tables/{name}.json` (lista de linhas alinhadas com `columns`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableInfo {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub rows: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileInfo {
+    pub sha256: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format: String,
+    pub version: u32,
+    pub schema_version: u32,
+    pub app_version: String,
+    pub created_at: String,
+    pub tables: Vec<TableInfo>,
+    /// Checksum de cada entrada do arquivo (exceto o próprio manifesto)
+    #[serde(default)]
+    pub files: BTreeMap<String, FileInfo>,
+}
+
+impl BackupManifest {
+    pub fn new(app_version: &str) -> Self {
+        BackupManifest {
+            format: constants::BACKUP_FORMAT_NAME.to_string(),
+            version: constants::BACKUP_FORMAT_VERSION,
+            schema_version: constants::DB_SCHEMA_VERSION,
+            app_version: app_version.to_string(),
+            created_at: chrono::Local::now().to_rfc3339(),
+            tables: Vec::new(),
+            files: BTreeMap::new(),
+        }
+    }
+}
+
+/// Caminho da tabela dentro do arquivo
+pub fn table_entry(name: &str) -> String {
+    format!("tables/{}.json", name)
+}
+
+/// Backups v2 são zip; os v1 são JSON puro
+pub fn is_archive(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"PK\x03\x04")
+}
+
+/// Repassa a escrita calculando o SHA-256 e o tamanho do que passou
+pub struct HashingWriter<W: Write> {
+    inner: W,
+    hasher: Sha256,
+    size: u64,
+}
+
+impl<W: Write> HashingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        HashingWriter {
+            inner,
+            hasher: Sha256::new(),
+            size: 0,
+        }
+    }
+
+    pub fn finish(self) -> FileInfo {
+        FileInfo {
+            sha256: self
+                .hasher
+                .finalize()
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect(),
+            size: self.size,
+        }
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Destino do zip que não aceita busca (ex.: a criptografia em blocos). O zip só volta atrás
+/// para completar o cabeçalho da entrada anterior, então só o trecho a partir da penúltima
+/// entrada fica em memória; o que vem antes já seguiu para o destino.
+pub struct TailWriter<W: Write> {
+    inner: W,
+    /// Bytes já enviados ao destino: nada antes deles pode ser reescrito
+    sealed: u64,
+    tail: Vec<u8>,
+    position: u64,
+    /// Entradas abertas pelo `ArchiveWriter`; cada nova libera o trecho anterior à penúltima
+    entries: Arc<AtomicUsize>,
+    seen_entries: usize,
+    last_entry_start: u64,
+}
+
+impl<W: Write> TailWriter<W> {
+    fn end(&self) -> u64 {
+        self.sealed + self.tail.len() as u64
+    }
+
+    /// Registra o início da entrada aberta desde a última escrita e envia ao destino tudo o
+    /// que vem antes da entrada anterior a ela
+    fn seal_finished_entries(&mut self) -> io::Result<()> {
+        let entries = self.entries.load(Ordering::Relaxed);
+        if entries == self.seen_entries {
+            return Ok(());
+        }
+        self.seen_entries = entries;
+
+        let end = self.end();
+        let previous_start = std::mem::replace(&mut self.last_entry_start, end);
+        let sealable = (previous_start - self.sealed) as usize;
+        self.inner.write_all(&self.tail[..sealable])?;
+        self.tail.drain(..sealable);
+        self.sealed = previous_start;
+        Ok(())
+    }
+
+    /// Envia o restante ao destino e o devolve
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.inner.write_all(&self.tail)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for TailWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.seal_finished_entries()?;
+
+        let offset = (self.position - self.sealed) as usize;
+        let overwritten = buf.len().min(self.tail.len() - offset);
+        self.tail[offset..offset + overwritten].copy_from_slice(&buf[..overwritten]);
+        self.tail.extend_from_slice(&buf[overwritten..]);
+        self.position += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<W: Write> Seek for TailWriter<W> {
+    fn seek(&mut self, target: SeekFrom) -> io::Result<u64> {
+        let position = match target {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(delta) => self.end().checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+        };
+        match position {
+            Some(position) if position >= self.sealed && position <= self.end() => {
+                self.position = position;
+                Ok(position)
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Posição já gravada no destino do backup",
+            )),
+        }
+    }
+}
+
+/// Grava o zip entrada por entrada; o manifesto, com os checksums, é a última
+pub struct ArchiveWriter<W: Write + Seek> {
+    zip: ZipWriter<W>,
+    manifest: BackupManifest,
+    /// Entradas abertas, acompanhadas pelo `TailWriter` quando o destino não aceita busca
+    entries: Arc<AtomicUsize>,
+}
+
+impl ArchiveWriter<File> {
+    pub fn create(path: &Path, manifest: BackupManifest) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("Erro ao salvar arquivo: {}", e))?;
+        Ok(ArchiveWriter {
+            zip: ZipWriter::new(file),
+            manifest,
+            entries: Arc::default(),
+        })
+    }
+}
+
+impl<W: Write> ArchiveWriter<TailWriter<W>> {
+    /// Zip gravado em fluxo em um destino sem busca
+    pub fn streaming(inner: W, manifest: BackupManifest) -> Self {
+        let entries = Arc::new(AtomicUsize::new(0));
+        let tail = TailWriter {
+            inner,
+            sealed: 0,
+            tail: Vec::new(),
+            position: 0,
+            entries: entries.clone(),
+            seen_entries: 0,
+            last_entry_start: 0,
+        };
+        ArchiveWriter {
+            zip: ZipWriter::new(tail),
+            manifest,
+            entries,
+        }
+    }
+}
+
+impl<W: Write + Seek> ArchiveWriter<W> {
+    fn start_entry(&mut self, name: &str, options: SimpleFileOptions) -> Result<(), String> {
+        self.entries.fetch_add(1, Ordering::Relaxed);
+        self.zip
+            .start_file(name, options)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Abre a entrada e entrega o escritor a `write`. Imagens já são comprimidas e vão sem compressão.
+    pub fn write_entry<F>(&mut self, name: &str, compress: bool, write: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut dyn Write) -> Result<(), String>,
+    {
+        let method = if compress {
+            CompressionMethod::Deflated
+        } else {
+            CompressionMethod::Stored
+        };
+        // Entradas acima de 4 GB precisam de ZIP64
+        let options = SimpleFileOptions::default()
+            .compression_method(method)
+            .large_file(true);
+        self.start_entry(name, options)?;
+
+        let mut writer = HashingWriter::new(&mut self.zip);
+        write(&mut writer)?;
+        self.manifest.files.insert(name.to_string(), writer.finish());
+        Ok(())
+    }
+
+    /// Indica se a entrada já foi gravada
+    pub fn contains(&self, name: &str) -> bool {
+        self.manifest.files.contains_key(name)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &String> {
+        self.manifest.files.keys()
+    }
+
+    pub fn add_table(&mut self, table: TableInfo) {
+        self.manifest.tables.push(table);
+    }
+
+    /// Grava o manifesto e fecha o zip, devolvendo o destino
+    pub fn finish(mut self) -> Result<(BackupManifest, W), String> {
+        let manifest_json = serde_json::to_vec_pretty(&self.manifest).map_err(|e| e.to_string())?;
+        self.start_entry(
+            constants::BACKUP_MANIFEST_FILE,
+            SimpleFileOptions::default().compression_method(CompressionMethod::Deflated),
+        )?;
+        self.zip
+            .write_all(&manifest_json)
+            .map_err(|e| e.to_string())?;
+        let inner = self.zip.finish().map_err(|e| e.to_string())?;
+        Ok((self.manifest, inner))
+    }
+}
+
+/// Origem do zip: o arquivo em disco ou o conteúdo decifrado de um backup encriptado
+pub trait ArchiveSource: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ArchiveSource for T {}
+
+pub type ArchiveInput = Box<dyn ArchiveSource>;
+
+/// Abre o arquivo conferindo formato, versão e o checksum de cada entrada listada no manifesto
+pub fn open_archive(
+    input: ArchiveInput,
+) -> Result<(BackupManifest, ZipArchive<ArchiveInput>), String> {
+    let mut zip =
+        ZipArchive::new(input).map_err(|e| format!("Arquivo de backup inválido: {}", e))?;
+
+    let manifest: BackupManifest = {
+        let entry = zip
+            .by_name(constants::BACKUP_MANIFEST_FILE)
+            .map_err(|_| "Arquivo de backup inválido: manifesto ausente".to_string())?;
+        serde_json::from_reader(entry)
+            .map_err(|e| format!("Arquivo de backup inválido: manifesto ilegível ({})", e))?
+    };
+
+    if manifest.format != constants::BACKUP_FORMAT_NAME {
+        return Err("Arquivo de backup inválido: formato desconhecido".to_string());
+    }
+    if manifest.version > constants::BACKUP_FORMAT_VERSION
+        || manifest.schema_version > constants::DB_SCHEMA_VERSION
+    {
+        return Err(format!(
+            "Versão de backup incompatível: {} (esquema {})",
+            manifest.version, manifest.schema_version
+        ));
+    }
+
+    for (name, expected) in &manifest.files {
+        let mut entry = zip
+            .by_name(name)
+            .map_err(|_| format!("Arquivo de backup inválido: '{}' ausente", name))?;
+        let mut hasher = HashingWriter::new(io::sink());
+        io::copy(&mut entry, &mut hasher).map_err(|e| e.to_string())?;
+        let actual = hasher.finish();
+
+        if actual.size != expected.size || actual.sha256 != expected.sha256 {
+            return Err(format!(
+                "Arquivo de backup inválido: checksum de '{}' não confere",
+                name
+            ));
+        }
+    }
+
+    Ok((manifest, zip))
+}
+
+/// Segundo leitor do mesmo arquivo, para copiar imagens enquanto uma tabela é lida
+pub fn reopen_archive(input: ArchiveInput) -> Result<ZipArchive<ArchiveInput>, String> {
+    ZipArchive::new(input).map_err(|e| format!("Arquivo de backup inválido: {}", e))
+}
+
+/// Lê inteira uma entrada pequena (ex.: chaves de API); None quando ela não existe
+pub fn read_entry<R: Read + io::Seek>(
+    zip: &mut ZipArchive<R>,
+    name: &str,
+) -> Result<Option<Vec<u8>>, String> {
+    let mut entry = match zip.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut content = Vec::new();
+    entry
+        .read_to_end(&mut content)
+        .map_err(|e| e.to_string())?;
+    Ok(Some(content))
+}
+
+struct RowVisitor<'f, F> {
+    on_row: &'f mut F,
+    count: usize,
+}
+
+impl<'de, F> Visitor<'de> for RowVisitor<'_, F>
+where
+    F: FnMut(Vec<serde_json::Value>) -> Result<(), String>,
+{
+    type Value = usize;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("uma lista de linhas")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<usize, A::Error> {
+        while let Some(row) = seq.next_element::<Vec<serde_json::Value>>()? {
+            (self.on_row)(row).map_err(serde::de::Error::custom)?;
+            self.count += 1;
+        }
+        Ok(self.count)
+    }
+}
+
+impl<'de, F> DeserializeSeed<'de> for RowVisitor<'_, F>
+where
+    F: FnMut(Vec<serde_json::Value>) -> Result<(), String>,
+{
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+/// Percorre a lista de linhas de uma tabela sem carregá-la inteira; retorna quantas foram lidas
+pub fn for_each_row<R, F>(reader: R, mut on_row: F) -> Result<usize, String>
+where
+    R: Read,
+    F: FnMut(Vec<serde_json::Value>) -> Result<(), String>,
+{
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+    let visitor = RowVisitor {
+        on_row: &mut on_row,
+        count: 0,
+    };
+    let count = visitor
+        .deserialize(&mut deserializer)
+        .map_err(|e| format!("Arquivo de backup inválido: {}", e))?;
+    deserializer.end().map_err(|e| e.to_string())?;
+    Ok(count)
+}
@@ -0,0 +1,238 @@
+// Download de capas com alternativas e geração de miniaturas
+
+use crate::constants;
+use crate::utils::http_client::HTTP_CLIENT;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Artes da Steam tentadas em ordem quando a capa vertical não existe
+const STEAM_ART_PATHS: &[&str] = &[
+    "library_600x900.jpg",
+    "library_600x900_2x.jpg",
+    "header.jpg",
+    "capsule_616x353.jpg",
+    "capsule_231x87.jpg",
+];
+
+// Formatos habilitados na crate `image`; outros são detectados mas não decodificam
+const SUPPORTED_FORMATS: &[ImageFormat] = &[ImageFormat::Jpeg, ImageFormat::Png, ImageFormat::WebP];
+
+/// Formato da imagem, se for um dos que conseguimos decodificar
+pub fn supported_format(bytes: &[u8]) -> Option<ImageFormat> {
+    image::guess_format(bytes)
+        .ok()
+        .filter(|format| SUPPORTED_FORMATS.contains(format))
+}
+
+/// Capa baixada e gravada no cache
+pub struct StoredCover {
+    pub source_url: String,
+    pub file_path: PathBuf,
+    pub thumb_path: PathBuf,
+    pub size_bytes: u64,
+}
+
+/// URLs a tentar, na ordem: capa cadastrada, artes alternativas da Steam e fundo da RAWG
+pub fn candidate_urls(
+    cover_url: Option<&str>,
+    steam_app_id: Option<u32>,
+    background_image: Option<&str>,
+) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    let mut push = |url: String| {
+        if !url.trim().is_empty() && !urls.contains(&url) {
+            urls.push(url);
+        }
+    };
+
+    if let Some(url) = cover_url.filter(|u| u.starts_with("http")) {
+        push(url.to_string());
+    }
+    if let Some(app_id) = steam_app_id {
+        for path in STEAM_ART_PATHS {
+            push(format!(
+                "{}/steam/apps/{}/{}",
+                constants::STEAM_CDN_URL,
+                app_id,
+                path
+            ));
+        }
+    }
+    if let Some(url) = background_image {
+        push(url.to_string());
+    }
+
+    urls
+}
+
+/// Baixa a primeira URL que responder com uma imagem em formato suportado.
+/// Ok(None) quando todas respondem com erro HTTP (404, 403...) ou com outro conteúdo;
+/// Err só quando alguma falhou na rede e nenhuma outra serviu.
+pub async fn download_first(urls: &[String]) -> Result<Option<(String, Vec<u8>)>, String> {
+    let mut last_error = None;
+
+    for url in urls {
+        let res = match HTTP_CLIENT.get(url).send().await {
+            Ok(res) => res,
+            Err(e) => {
+                last_error = Some(e.to_string());
+                continue;
+            }
+        };
+
+        if !res.status().is_success() {
+            // Arte inexistente ou bloqueada: a resposta veio, só não há imagem nessa URL
+            continue;
+        }
+
+        let bytes = match res.bytes().await {
+            Ok(bytes) => bytes.to_vec(),
+            Err(e) => {
+                last_error = Some(e.to_string());
+                continue;
+            }
+        };
+
+        if supported_format(&bytes).is_some() {
+            return Ok(Some((url.clone(), bytes)));
+        }
+    }
+
+    match last_error {
+        Some(e) => Err(e),
+        None => Ok(None),
+    }
+}
+
+/// Nome de arquivo estável para o jogo (IDs podem conter caracteres inválidos em caminhos)
+pub fn cache_key(game_id: &str) -> String {
+    let digest = Sha256::digest(game_id.as_bytes());
+    digest.iter().take(12).map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Grava a imagem original e uma miniatura JPEG de tamanho fixo. Decodifica e redimensiona:
+/// em código assíncrono, chamar dentro de `spawn_blocking`.
+pub fn store_cover(
+    dir: &Path,
+    game_id: &str,
+    source_url: &str,
+    bytes: &[u8],
+) -> Result<StoredCover, String> {
+    let format = supported_format(bytes).ok_or("Formato de imagem não suportado")?;
+    let extension = format.extensions_str().first().copied().unwrap_or("img");
+    let key = cache_key(game_id);
+
+    fs::create_dir_all(dir).map_err(|e| format!("Erro ao criar pasta de capas: {}", e))?;
+
+    let img = image::load_from_memory_with_format(bytes, format)
+        .map_err(|e| format!("Imagem inválida: {}", e))?;
+
+    let file_path = dir.join(format!("{}.{}", key, extension));
+    let thumb_path = dir.join(format!("{}_thumb.jpg", key));
+
+    fs::write(&file_path, bytes).map_err(|e| format!("Erro ao salvar capa: {}", e))?;
+    write_thumbnail(&img, &thumb_path)?;
+
+    let thumb_size = fs::metadata(&thumb_path).map(|m| m.len()).unwrap_or(0);
+
+    Ok(StoredCover {
+        source_url: source_url.to_string(),
+        file_path,
+        thumb_path,
+        size_bytes: bytes.len() as u64 + thumb_size,
+    })
+}
+
+/// Grava a miniatura JPEG de tamanho fixo usada nas grades da biblioteca
+pub fn write_thumbnail(img: &DynamicImage, path: &Path) -> Result<(), String> {
+    img.resize_to_fill(
+        constants::COVER_THUMB_WIDTH,
+        constants::COVER_THUMB_HEIGHT,
+        FilterType::Triangle,
+    )
+    .to_rgb8()
+    .save_with_format(path, ImageFormat::Jpeg)
+    .map_err(|e| format!("Erro ao salvar miniatura: {}", e))
+}
+
+/// Remove os arquivos de uma capa (ignora arquivos que já não existem)
+pub fn remove_files(paths: &[Option<String>]) {
+    for path in paths.iter().flatten() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{serve_http_bytes, temp_dir};
+    use image::{Rgb, RgbImage};
+    use std::io::Cursor;
+
+    fn png() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        RgbImage::from_pixel(4, 6, Rgb([200, 40, 40]))
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    fn fake_cdn() -> String {
+        serve_http_bytes(|request| match request.path.as_str() {
+            "/ok.png" => (200, png()),
+            "/forbidden.jpg" => (403, b"negado".to_vec()),
+            "/gone.jpg" => (404, Vec::new()),
+            "/anim.gif" => (200, b"GIF89a\x01\x00\x01\x00\x00\x00\x00;".to_vec()),
+            "/page.html" => (200, b"<html></html>".to_vec()),
+            _ => (500, Vec::new()),
+        })
+    }
+
+    fn urls(base: &str, paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|p| format!("{}{}", base, p)).collect()
+    }
+
+    #[tokio::test]
+    async fn http_errors_and_unsupported_images_are_missing() {
+        let base = fake_cdn();
+
+        let found = download_first(&urls(&base, &["/forbidden.jpg", "/anim.gif", "/page.html", "/ok.png"]))
+            .await
+            .unwrap();
+        assert_eq!(found.map(|(url, _)| url), Some(format!("{}/ok.png", base)));
+
+        let missing = download_first(&urls(&base, &["/forbidden.jpg", "/gone.jpg", "/anim.gif"]))
+            .await
+            .unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[tokio::test]
+    async fn network_failures_are_errors() {
+        // Porta fechada: a conexão falha antes de qualquer resposta
+        let closed = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}/ok.png", listener.local_addr().unwrap())
+        };
+        assert!(download_first(&[closed]).await.is_err());
+    }
+
+    #[test]
+    fn stores_supported_images_and_rejects_the_rest() {
+        let dir = temp_dir("covers");
+
+        let stored = store_cover(&dir, "steam:1", "https://cdn/ok.png", &png()).unwrap();
+        assert!(stored.file_path.extension().is_some_and(|e| e == "png"));
+        let thumb = image::open(&stored.thumb_path).unwrap();
+        assert_eq!(
+            (thumb.width(), thumb.height()),
+            (constants::COVER_THUMB_WIDTH, constants::COVER_THUMB_HEIGHT)
+        );
+
+        assert!(store_cover(&dir, "steam:2", "https://cdn/anim.gif", b"GIF89a\x01\x00").is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
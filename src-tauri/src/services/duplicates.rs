@@ -0,0 +1,309 @@
+use crate::constants;
+use crate::models::Game;
+use crate::utils::text;
+use serde::Serialize;
+use std::collections::HashMap;
+
+// Confiança de cada tipo de evidência
+const CONFIDENCE_EXTERNAL_ID: f32 = 1.0;
+const CONFIDENCE_RAWG_ID: f32 = 0.95;
+const CONFIDENCE_SAME_NAME: f32 = 0.9;
+const CONFIDENCE_SAME_TITLE: f32 = 0.75;
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateCluster {
+    pub games: Vec<Game>,
+    /// Confiança da ligação mais fraca do grupo (0.0 a 1.0)
+    pub confidence: f32,
+    pub reasons: Vec<String>,
+    /// Jogo sugerido para manter (mais tempo jogado / metadados mais completos)
+    pub suggested_primary: String,
+}
+
+/// Chave de ID externo comparável entre fontes diferentes.
+/// Ex: Heroic ("gog:123") e GOG Galaxy ("123") apontam para o mesmo produto.
+pub fn canonical_external_key(source: &str, external_id: &str) -> String {
+    match source {
+        "heroic" => external_id.to_string(),
+        "gog" => format!("gog:{}", external_id),
+        other => format!("{}:{}", other, external_id),
+    }
+}
+
+/// Jogos importados da Steam usam o AppID como ID da linha
+fn steam_key(game: &Game) -> Option<String> {
+    let is_steam = game.platform.as_deref() == Some(constants::DEFAULT_PLATFORM_STEAM);
+    if is_steam && game.id.parse::<u32>().is_ok() {
+        Some(format!("steam:{}", game.id))
+    } else {
+        None
+    }
+}
+
+struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+        }
+    }
+
+    fn find(&mut self, x: usize) -> usize {
+        if self.parent[x] != x {
+            let root = self.find(self.parent[x]);
+            self.parent[x] = root;
+        }
+        self.parent[x]
+    }
+
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra == rb {
+            return false;
+        }
+        self.parent[rb] = ra;
+        true
+    }
+}
+
+fn completeness(game: &Game) -> (i32, bool, bool, bool) {
+    let known_genre = game
+        .genre
+        .as_deref()
+        .map(|g| !g.is_empty() && g != constants::DEFAULT_GENRE)
+        .unwrap_or(false);
+    (
+        game.playtime,
+        game.rating.is_some(),
+        known_genre,
+        game.cover_url.is_some(),
+    )
+}
+
+/// Agrupa jogos que provavelmente são o mesmo título.
+/// `external_ids`: (game_id, source, external_id); `rawg_ids`: game_id -> ID na RAWG.
+pub fn find_clusters(
+    games: Vec<Game>,
+    external_ids: &[(String, String, String)],
+    rawg_ids: &HashMap<String, i32>,
+) -> Vec<DuplicateCluster> {
+    let index: HashMap<&str, usize> = games
+        .iter()
+        .enumerate()
+        .map(|(i, g)| (g.id.as_str(), i))
+        .collect();
+
+    // Agrupa os índices por cada tipo de chave, já com a confiança e o motivo
+    let mut buckets: HashMap<String, (f32, String, Vec<usize>)> = HashMap::new();
+    let mut add = |key: String, confidence: f32, reason: String, idx: usize| {
+        let entry = buckets
+            .entry(key)
+            .or_insert_with(|| (confidence, reason, Vec::new()));
+        if !entry.2.contains(&idx) {
+            entry.2.push(idx);
+        }
+    };
+
+    for (game_id, source, external_id) in external_ids {
+        if let Some(&idx) = index.get(game_id.as_str()) {
+            let key = canonical_external_key(source, external_id);
+            add(
+                format!("ext:{}", key),
+                CONFIDENCE_EXTERNAL_ID,
+                format!("Mesmo ID externo ({})", key),
+                idx,
+            );
+        }
+    }
+
+    for (idx, game) in games.iter().enumerate() {
+        if let Some(key) = steam_key(game) {
+            add(
+                format!("ext:{}", key),
+                CONFIDENCE_EXTERNAL_ID,
+                format!("Mesmo ID externo ({})", key),
+                idx,
+            );
+        }
+
+        if let Some(rawg_id) = rawg_ids.get(&game.id) {
+            add(
+                format!("rawg:{}", rawg_id),
+                CONFIDENCE_RAWG_ID,
+                format!("Mesmo jogo na RAWG (#{})", rawg_id),
+                idx,
+            );
+        }
+
+        let name = text::normalize_name(&game.name);
+        if !name.is_empty() {
+            add(
+                format!("name:{}", name),
+                CONFIDENCE_SAME_NAME,
+                format!("Mesmo nome ({})", name),
+                idx,
+            );
+        }
+
+        // Sem o sufixo de edição ("Hades" x "Hades GOTY"); nomes iguais já foram ligados acima
+        let title = text::normalize_title(&game.name);
+        if !title.is_empty() {
+            add(
+                format!("title:{}", title),
+                CONFIDENCE_SAME_TITLE,
+                format!("Mesmo título sem edição ({})", title),
+                idx,
+            );
+        }
+    }
+
+    // Liga os grupos da evidência mais forte para a mais fraca
+    let mut edges: Vec<(f32, String, Vec<usize>)> = buckets
+        .into_values()
+        .filter(|(_, _, members)| members.len() > 1)
+        .collect();
+    edges.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut uf = UnionFind::new(games.len());
+    let mut weakest: HashMap<usize, f32> = HashMap::new();
+    let mut reasons: HashMap<usize, Vec<String>> = HashMap::new();
+
+    for (confidence, reason, members) in edges {
+        for &other in &members[1..] {
+            let (ra, rb) = (uf.find(members[0]), uf.find(other));
+            if !uf.union(ra, rb) {
+                continue;
+            }
+
+            // `ra` continua sendo a raiz: acumula nela a confiança mais fraca e os motivos
+            let lowest = confidence
+                .min(weakest.remove(&ra).unwrap_or(1.0))
+                .min(weakest.remove(&rb).unwrap_or(1.0));
+            weakest.insert(ra, lowest);
+
+            let moved = reasons.remove(&rb).unwrap_or_default();
+            let entry = reasons.entry(ra).or_default();
+            for r in moved {
+                if !entry.contains(&r) {
+                    entry.push(r);
+                }
+            }
+            if !entry.contains(&reason) {
+                entry.push(reason.clone());
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..games.len() {
+        let root = uf.find(i);
+        groups.entry(root).or_default().push(i);
+    }
+
+    let mut slots: Vec<Option<Game>> = games.into_iter().map(Some).collect();
+    let mut clusters: Vec<DuplicateCluster> = groups
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|(root, members)| {
+            let cluster_games: Vec<Game> = members
+                .iter()
+                .filter_map(|&i| slots[i].take())
+                .collect();
+            let suggested_primary = cluster_games
+                .iter()
+                .max_by_key(|g| completeness(g))
+                .map(|g| g.id.clone())
+                .unwrap_or_default();
+
+            DuplicateCluster {
+                games: cluster_games,
+                confidence: *weakest.get(&root).unwrap_or(&1.0),
+                reasons: reasons.remove(&root).unwrap_or_default(),
+                suggested_primary,
+            }
+        })
+        .collect();
+
+    clusters.sort_by(|a, b| {
+        b.confidence
+            .partial_cmp(&a.confidence)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn game(id: &str, name: &str, platform: &str, playtime: i32) -> Game {
+        serde_json::from_value(json!({
+            "id": id, "name": name, "genre": null, "platform": platform, "cover_url": null,
+            "playtime": playtime, "rating": null, "favorite": false
+        }))
+        .unwrap()
+    }
+
+    fn ids(cluster: &DuplicateCluster) -> Vec<&str> {
+        let mut ids: Vec<&str> = cluster.games.iter().map(|g| g.id.as_str()).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn groups_by_strongest_evidence_and_keeps_weakest_link() {
+        let games = vec![
+            game("1145360", "Hades", "Steam", 40),
+            game("epic:hades", "HADES™", "Epic Games", 2),
+            game("gog:hades", "Hades - Game of the Year Edition", "GOG", 0),
+            game("heroic:gog:1", "Unreal Tournament 2004", "GOG", 0),
+            game("gog:1", "UT2004", "GOG", 5),
+            game("celeste", "Celeste", "Manual", 1),
+            game("portal", "Portal", "Steam", 3),
+            game("portal2", "Portal 2", "Steam", 3),
+        ];
+        let external_ids = vec![
+            ("heroic:gog:1".to_string(), "heroic".to_string(), "gog:1".to_string()),
+            ("gog:1".to_string(), "gog".to_string(), "1".to_string()),
+        ];
+        let rawg_ids = HashMap::from([("celeste".to_string(), 42)]);
+
+        let clusters = find_clusters(games, &external_ids, &rawg_ids);
+        assert_eq!(clusters.len(), 2, "Celeste, Portal e Portal 2 ficam sozinhos");
+
+        // Mesmo produto GOG pelo Heroic e pelo Galaxy, com nomes diferentes
+        assert_eq!(ids(&clusters[0]), vec!["gog:1", "heroic:gog:1"]);
+        assert_eq!(clusters[0].confidence, CONFIDENCE_EXTERNAL_ID);
+
+        // Nome igual liga Steam e Epic; o título sem edição traz o GOG com confiança menor
+        let hades = &clusters[1];
+        assert_eq!(ids(hades), vec!["1145360", "epic:hades", "gog:hades"]);
+        assert_eq!(hades.confidence, CONFIDENCE_SAME_TITLE);
+        assert_eq!(hades.suggested_primary, "1145360");
+        assert!(hades.reasons.iter().any(|r| r == "Mesmo nome (hades)"));
+        assert!(hades.reasons.iter().any(|r| r == "Mesmo título sem edição (hades)"));
+    }
+
+    #[test]
+    fn steam_app_ids_and_rawg_ids_link_games() {
+        let games = vec![
+            game("570", "Dota 2", "Steam", 100),
+            game("dota", "Defense of the Ancients 2", "Manual", 0),
+            game("witcher", "The Witcher 3", "GOG", 0),
+            game("w3", "Witcher III", "Manual", 0),
+        ];
+        let external_ids = vec![("dota".to_string(), "steam".to_string(), "570".to_string())];
+        let rawg_ids = HashMap::from([("witcher".to_string(), 3328), ("w3".to_string(), 3328)]);
+
+        let clusters = find_clusters(games, &external_ids, &rawg_ids);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(ids(&clusters[0]), vec!["570", "dota"]);
+        assert_eq!(clusters[1].confidence, CONFIDENCE_RAWG_ID);
+        assert_eq!(ids(&clusters[1]), vec!["w3", "witcher"]);
+    }
+}
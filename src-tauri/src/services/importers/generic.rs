@@ -0,0 +1,394 @@
+use super::ImportedGame;
+use crate::constants;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Unidade do tempo jogado na planilha de origem
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaytimeUnit {
+    #[default]
+    Hours,
+    Minutes,
+    Seconds,
+}
+
+/// Mapeamento "coluna do arquivo -> campo do jogo" informado pelo usuário (ou por um preset)
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ColumnMapping {
+    pub name: String,
+    pub genre: Option<String>,
+    pub platform: Option<String>,
+    pub cover_url: Option<String>,
+    pub playtime: Option<String>,
+    pub rating: Option<String>,
+    pub favorite: Option<String>,
+    /// Coluna com um ID estável na origem; sem ela o nome normalizado é usado
+    pub external_id: Option<String>,
+    #[serde(default)]
+    pub playtime_unit: PlaytimeUnit,
+    /// Nota máxima da escala de origem (ex: 10 ou 100), convertida para MIN_RATING..MAX_RATING
+    pub rating_scale: Option<f64>,
+}
+
+/// Presets para exportações conhecidas
+pub fn preset(name: &str) -> Option<ColumnMapping> {
+    let col = |c: &str| Some(c.to_string());
+
+    match name.to_lowercase().as_str() {
+        "backloggd" => Some(ColumnMapping {
+            name: "Game".to_string(),
+            platform: col("Platform"),
+            genre: col("Genres"),
+            rating: col("Rating"),
+            rating_scale: Some(5.0),
+            ..Default::default()
+        }),
+        "hltb" | "howlongtobeat" => Some(ColumnMapping {
+            name: "Title".to_string(),
+            platform: col("Platform"),
+            playtime: col("Progress"),
+            rating: col("Review"),
+            rating_scale: Some(100.0),
+            ..Default::default()
+        }),
+        "grouvee" => Some(ColumnMapping {
+            name: "name".to_string(),
+            platform: col("platforms"),
+            genre: col("genres"),
+            rating: col("rating"),
+            external_id: col("giantbomb_id"),
+            rating_scale: Some(5.0),
+            ..Default::default()
+        }),
+        "playnite" => Some(ColumnMapping {
+            name: "Name".to_string(),
+            platform: col("Platforms"),
+            genre: col("Genres"),
+            cover_url: col("CoverImage"),
+            playtime: col("Playtime"),
+            rating: col("UserScore"),
+            favorite: col("Favorite"),
+            external_id: col("Id"),
+            // Playnite guarda o tempo jogado em segundos e a nota de 0 a 100
+            playtime_unit: PlaytimeUnit::Seconds,
+            rating_scale: Some(100.0),
+        }),
+        _ => None,
+    }
+}
+
+pub fn preset_names() -> Vec<&'static str> {
+    vec!["backloggd", "hltb", "grouvee", "playnite"]
+}
+
+/// Uma linha do arquivo já convertida para texto, indexada pelo nome da coluna
+pub type Record = HashMap<String, String>;
+
+pub fn parse_csv(content: &str) -> Result<Vec<Record>, String> {
+    // Planilhas exportadas no Excel costumam vir com BOM
+    let content = content.trim_start_matches('\u{feff}');
+
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Cabeçalho do CSV inválido: {}", e))?
+        .clone();
+
+    let mut records = Vec::new();
+    for (i, row) in reader.records().enumerate() {
+        let row = row.map_err(|e| format!("Linha {} do CSV inválida: {}", i + 2, e))?;
+        let record = headers
+            .iter()
+            .zip(row.iter())
+            .map(|(h, v)| (h.to_string(), v.to_string()))
+            .collect();
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+/// Converte um valor JSON em texto (listas viram "a, b"; objetos usam o campo "name")
+fn json_to_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Array(items) => {
+            let parts: Vec<String> = items.iter().filter_map(json_to_text).collect();
+            Some(parts.join(", "))
+        }
+        Value::Object(obj) => obj.get("name").or_else(|| obj.get("Name")).and_then(json_to_text),
+    }
+}
+
+/// Aceita uma lista de objetos ou um objeto com a lista em "games"/"items"/"library"
+pub fn parse_json(content: &str) -> Result<Vec<Record>, String> {
+    let root: Value =
+        serde_json::from_str(content).map_err(|e| format!("JSON inválido: {}", e))?;
+
+    let items = match &root {
+        Value::Array(items) => items,
+        Value::Object(obj) => ["games", "items", "library"]
+            .iter()
+            .find_map(|k| obj.get(*k).and_then(|v| v.as_array()))
+            .ok_or("JSON sem lista de jogos (games/items/library)")?,
+        _ => return Err("JSON deve conter uma lista de jogos".to_string()),
+    };
+
+    Ok(items
+        .iter()
+        .filter_map(|item| item.as_object())
+        .map(|obj| {
+            obj.iter()
+                .filter_map(|(k, v)| json_to_text(v).map(|text| (k.clone(), text)))
+                .collect()
+        })
+        .collect())
+}
+
+/// Interpreta durações como "12.5", "01:30:00", "12h 30m" na unidade informada e devolve horas
+pub fn parse_playtime(raw: &str, unit: PlaytimeUnit) -> Result<i32, String> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Ok(0);
+    }
+
+    let hours = if raw.contains(':') {
+        // HH:MM[:SS]
+        let parts: Vec<f64> = raw
+            .split(':')
+            .map(|p| p.trim().parse::<f64>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("Tempo jogado inválido: '{}'", raw))?;
+        parts
+            .iter()
+            .zip([1.0, 60.0, 3600.0])
+            .map(|(v, div)| v / div)
+            .sum()
+    } else if raw.chars().any(char::is_alphabetic) {
+        // "12h 30m", "1h30m", "45m"
+        let invalid = || format!("Tempo jogado inválido: '{}'", raw);
+        let mut total = 0.0;
+        let mut number = String::new();
+        for c in raw.chars().filter(|c| !c.is_whitespace()) {
+            if c.is_ascii_digit() || c == '.' || c == ',' {
+                number.push(c);
+                continue;
+            }
+            let value: f64 = number.replace(',', ".").parse().map_err(|_| invalid())?;
+            number.clear();
+            total += match c.to_ascii_lowercase() {
+                'h' => value,
+                'm' => value / 60.0,
+                's' => value / 3600.0,
+                _ => return Err(invalid()),
+            };
+        }
+        // Número sem unidade no fim ("12h 30")
+        if !number.is_empty() {
+            return Err(invalid());
+        }
+        total
+    } else {
+        let value: f64 = raw
+            .replace(',', ".")
+            .parse()
+            .map_err(|_| format!("Tempo jogado inválido: '{}'", raw))?;
+        match unit {
+            PlaytimeUnit::Hours => value,
+            PlaytimeUnit::Minutes => value / 60.0,
+            PlaytimeUnit::Seconds => value / 3600.0,
+        }
+    };
+
+    Ok(hours.round() as i32)
+}
+
+/// Converte uma nota da escala de origem para MIN_RATING..MAX_RATING.
+/// Nota zero é tratada como "sem avaliação".
+pub fn parse_rating(raw: &str, scale: Option<f64>) -> Result<Option<i32>, String> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Ok(None);
+    }
+
+    let value: f64 = raw
+        .replace(',', ".")
+        .parse()
+        .map_err(|_| format!("Avaliação inválida: '{}'", raw))?;
+
+    if value <= 0.0 {
+        return Ok(None);
+    }
+
+    let scaled = match scale {
+        Some(max) if max > 0.0 => {
+            let ratio = (value / max).min(1.0);
+            (ratio * constants::MAX_RATING as f64)
+                .round()
+                .max(constants::MIN_RATING as f64)
+        }
+        _ => value.round(),
+    };
+
+    Ok(Some(scaled as i32))
+}
+
+fn parse_bool(raw: &str) -> bool {
+    matches!(
+        raw.trim().to_lowercase().as_str(),
+        "1" | "true" | "yes" | "y" | "sim" | "x"
+    )
+}
+
+/// Normaliza um nome para servir de ID quando a origem não tem ID próprio
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') && !slug.is_empty() {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Aplica o mapeamento a uma linha. Erros de conversão viram mensagens para o relatório.
+pub fn map_record(record: &Record, mapping: &ColumnMapping) -> Result<ImportedGame, String> {
+    let get = |column: &Option<String>| {
+        column
+            .as_ref()
+            .and_then(|c| record.get(c))
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    let name = record
+        .get(&mapping.name)
+        .map(|v| v.trim().to_string())
+        .unwrap_or_default();
+
+    let playtime = match get(&mapping.playtime) {
+        Some(raw) => parse_playtime(&raw, mapping.playtime_unit)?,
+        None => 0,
+    };
+
+    let rating = match get(&mapping.rating) {
+        Some(raw) => parse_rating(&raw, mapping.rating_scale)?,
+        None => None,
+    };
+
+    // Sem coluna de ID, um nome só com símbolos ("!!!") não identifica o jogo
+    let external_id = get(&mapping.external_id).unwrap_or_else(|| slugify(&name));
+    if external_id.is_empty() && !name.is_empty() {
+        return Err(format!("Nome sem letras ou números para identificar o jogo: '{}'", name));
+    }
+
+    Ok(ImportedGame {
+        external_id,
+        platform: get(&mapping.platform).unwrap_or_else(|| constants::DEFAULT_PLATFORM_MANUAL.to_string()),
+        genre: get(&mapping.genre),
+        // Só URLs: exportações como a do Playnite trazem caminhos locais relativos
+        cover_url: get(&mapping.cover_url).filter(|url| url.starts_with("http")),
+        favorite: get(&mapping.favorite).map(|v| parse_bool(&v)).unwrap_or(false),
+        name,
+        playtime,
+        rating,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(fields: &[(&str, &str)]) -> Record {
+        fields
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_playtime_formats() {
+        assert_eq!(parse_playtime("", PlaytimeUnit::Hours), Ok(0));
+        assert_eq!(parse_playtime("12,4", PlaytimeUnit::Hours), Ok(12));
+        assert_eq!(parse_playtime("90", PlaytimeUnit::Minutes), Ok(2));
+        assert_eq!(parse_playtime("36000", PlaytimeUnit::Seconds), Ok(10));
+        assert_eq!(parse_playtime("01:30:00", PlaytimeUnit::Seconds), Ok(2));
+        assert_eq!(parse_playtime("12h 30m", PlaytimeUnit::Hours), Ok(13));
+        assert_eq!(parse_playtime("1h30m", PlaytimeUnit::Hours), Ok(2));
+        assert_eq!(parse_playtime("45m", PlaytimeUnit::Hours), Ok(1));
+        for invalid in ["abc", "12h 30", "1x", "h", "1:xx"] {
+            assert!(parse_playtime(invalid, PlaytimeUnit::Hours).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn parses_rating_on_the_source_scale() {
+        assert_eq!(parse_rating("", Some(5.0)), Ok(None));
+        assert_eq!(parse_rating("0", Some(5.0)), Ok(None));
+        assert_eq!(parse_rating("4", None), Ok(Some(4)));
+        assert_eq!(parse_rating("80", Some(100.0)), Ok(Some(4)));
+        assert_eq!(parse_rating("2,5", Some(5.0)), Ok(Some(3)));
+        // Acima da escala vira a nota máxima; muito baixa vira a mínima
+        assert_eq!(parse_rating("150", Some(100.0)), Ok(Some(constants::MAX_RATING)));
+        assert_eq!(parse_rating("1", Some(100.0)), Ok(Some(constants::MIN_RATING)));
+        assert!(parse_rating("ótimo", None).is_err());
+    }
+
+    #[test]
+    fn maps_a_playnite_row() {
+        let mapping = preset("playnite").unwrap();
+        let game = map_record(
+            &record(&[
+                ("Name", " Hades "),
+                ("Platforms", "PC (Windows)"),
+                ("Genres", "Roguelike, Action"),
+                ("CoverImage", "a1b2/cover.jpg"),
+                ("Playtime", "7200"),
+                ("UserScore", "90"),
+                ("Favorite", "True"),
+                ("Id", "8f3c"),
+            ]),
+            &mapping,
+        )
+        .unwrap();
+
+        assert_eq!(game.name, "Hades");
+        assert_eq!(game.external_id, "8f3c");
+        assert_eq!(game.platform, "PC (Windows)");
+        assert_eq!(game.genre.as_deref(), Some("Roguelike, Action"));
+        // Caminho local da capa é descartado
+        assert_eq!(game.cover_url, None);
+        assert_eq!((game.playtime, game.rating, game.favorite), (2, Some(5), true));
+
+        let game = map_record(
+            &record(&[("Name", "Hades"), ("CoverImage", "https://img/hades.jpg")]),
+            &mapping,
+        )
+        .unwrap();
+        assert_eq!(game.cover_url.as_deref(), Some("https://img/hades.jpg"));
+    }
+
+    #[test]
+    fn maps_a_row_without_id_column() {
+        let mapping = preset("backloggd").unwrap();
+        let game = map_record(&record(&[("Game", "Half-Life 2: Episode One")]), &mapping).unwrap();
+        assert_eq!(game.external_id, "half-life-2-episode-one");
+        assert_eq!(game.platform, constants::DEFAULT_PLATFORM_MANUAL);
+        assert_eq!((game.playtime, game.rating), (0, None));
+
+        assert!(map_record(&record(&[("Game", "!!!")]), &mapping).is_err());
+        assert!(map_record(&record(&[("Game", "Hades"), ("Rating", "muito")]), &mapping).is_err());
+    }
+}
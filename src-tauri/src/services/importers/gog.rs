@@ -0,0 +1,375 @@
+use super::{minutes_to_hours, ImportedGame, LibraryImporter};
+use crate::constants;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Caminho padrão do banco do GOG Galaxy 2.0 no Windows
+const GALAXY_DB_WINDOWS: &str = r"C:\ProgramData\GOG.com\Galaxy\storage\galaxy-2.0.db";
+
+/// Importa jogos GOG a partir do banco local do GOG Galaxy 2.0 (`galaxy-2.0.db`)
+/// ou de um JSON exportado da conta GOG
+pub struct GogImporter {
+    pub path: PathBuf,
+}
+
+impl GogImporter {
+    pub fn new(path: Option<String>) -> Result<Self, String> {
+        let path = match path.filter(|p| !p.trim().is_empty()) {
+            Some(p) => PathBuf::from(p.trim()),
+            None => {
+                let default = PathBuf::from(GALAXY_DB_WINDOWS);
+                if !default.exists() {
+                    return Err("Banco do GOG Galaxy não encontrado. Informe o caminho do galaxy-2.0.db ou do JSON exportado.".to_string());
+                }
+                default
+            }
+        };
+
+        Ok(Self { path })
+    }
+
+    fn is_json(&self) -> bool {
+        self.path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("json"))
+            .unwrap_or(false)
+    }
+}
+
+impl LibraryImporter for GogImporter {
+    fn source(&self) -> &'static str {
+        "gog"
+    }
+
+    fn load(&self) -> Result<Vec<ImportedGame>, String> {
+        if self.is_json() {
+            load_account_export(&self.path)
+        } else {
+            load_galaxy_db(&self.path)
+        }
+    }
+}
+
+/// Lê o valor JSON de um "game piece" do Galaxy (title, originalImages, meta...) por releaseKey
+fn load_game_pieces(conn: &Connection, piece_type: &str) -> Result<HashMap<String, Value>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT gp.releaseKey, gp.value FROM GamePieces gp
+             JOIN GamePieceTypes t ON t.id = gp.gamePieceTypeId
+             WHERE t.type = ?1 AND gp.releaseKey LIKE 'gog_%'",
+        )
+        .map_err(|e| format!("Banco do GOG Galaxy inválido: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![piece_type], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut pieces = HashMap::new();
+    for row in rows {
+        let (key, value) = row.map_err(|e| e.to_string())?;
+        if let Ok(json) = serde_json::from_str::<Value>(&value) {
+            pieces.insert(key, json);
+        }
+    }
+
+    Ok(pieces)
+}
+
+fn table_exists(conn: &Connection, table: &str) -> bool {
+    conn.query_row(
+        "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        params![table],
+        |_| Ok(()),
+    )
+    .optional()
+    .map(|r| r.is_some())
+    .unwrap_or(false)
+}
+
+fn load_galaxy_db(path: &Path) -> Result<Vec<ImportedGame>, String> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Erro ao abrir {:?}: {}", path, e))?;
+
+    if !table_exists(&conn, "LibraryReleases") {
+        return Err("Arquivo não parece ser um banco do GOG Galaxy 2.0".to_string());
+    }
+
+    // Produtos da conta GOG (o Galaxy também guarda jogos de outras integrações)
+    let mut stmt = conn
+        .prepare("SELECT DISTINCT releaseKey FROM LibraryReleases WHERE releaseKey LIKE 'gog_%'")
+        .map_err(|e| e.to_string())?;
+    let release_keys: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let titles = load_game_pieces(&conn, "title")?;
+    let images = load_game_pieces(&conn, "originalImages")?;
+    let meta = load_game_pieces(&conn, "meta")?;
+
+    let mut minutes: HashMap<String, i64> = HashMap::new();
+    if table_exists(&conn, "GameTimes") {
+        let mut stmt = conn
+            .prepare("SELECT releaseKey, SUM(minutesInGame) FROM GameTimes GROUP BY releaseKey")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?)))
+            .map_err(|e| e.to_string())?;
+        for row in rows.flatten() {
+            minutes.insert(row.0, row.1.unwrap_or(0));
+        }
+    }
+
+    let mut last_played: HashMap<String, String> = HashMap::new();
+    if table_exists(&conn, "LastPlayedDates") {
+        let mut stmt = conn
+            .prepare("SELECT gameReleaseKey, MAX(lastPlayedDate) FROM LastPlayedDates GROUP BY gameReleaseKey")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)))
+            .map_err(|e| e.to_string())?;
+        for (key, date) in rows.flatten() {
+            if let Some(date) = date {
+                last_played.insert(key, date);
+            }
+        }
+    }
+
+    let mut installed: Vec<String> = Vec::new();
+    if table_exists(&conn, "InstalledBaseProducts") {
+        let mut stmt = conn
+            .prepare("SELECT productId FROM InstalledBaseProducts")
+            .map_err(|e| e.to_string())?;
+        installed = stmt
+            .query_map([], |row| row.get::<_, i64>(0))
+            .map_err(|e| e.to_string())?
+            .flatten()
+            .map(|id| id.to_string())
+            .collect();
+    }
+
+    let mut games = Vec::new();
+
+    for key in release_keys {
+        let product_id = key.trim_start_matches("gog_").to_string();
+
+        let Some(name) = titles
+            .get(&key)
+            .and_then(|t| t.get("title"))
+            .and_then(|t| t.as_str())
+            .map(|t| t.to_string())
+        else {
+            continue;
+        };
+
+        let cover_url = images
+            .get(&key)
+            .and_then(|i| i.get("verticalCover").or_else(|| i.get("background")))
+            .and_then(|u| u.as_str())
+            // O Galaxy guarda as URLs com um marcador de tamanho a ser substituído
+            .map(|u| u.replace("_{formatter}", "").replace("{formatter}", ""));
+
+        let genre = meta
+            .get(&key)
+            .and_then(|m| m.get("genres"))
+            .and_then(|g| g.as_array())
+            .map(|genres| {
+                genres
+                    .iter()
+                    .filter_map(|g| g.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .filter(|g| !g.is_empty());
+
+        games.push(ImportedGame {
+            playtime: minutes_to_hours(*minutes.get(&key).unwrap_or(&0) as f64),
+            last_played: last_played.get(&key).cloned(),
+            installed: installed.contains(&product_id),
+            external_id: product_id,
+            name,
+            platform: constants::PLATFORM_GOG.to_string(),
+            genre,
+            cover_url,
+            rating: None,
+            favorite: false,
+        });
+    }
+
+    Ok(games)
+}
+
+/// Busca o primeiro campo existente entre vários nomes possíveis
+fn field<'a>(item: &'a Value, names: &[&str]) -> Option<&'a Value> {
+    names.iter().find_map(|n| item.get(*n)).filter(|v| !v.is_null())
+}
+
+/// Lê um JSON exportado da conta GOG. Aceita uma lista de produtos ou um objeto
+/// com a lista em "products", "games" ou "owned".
+fn load_account_export(path: &Path) -> Result<Vec<ImportedGame>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Erro ao ler {:?}: {}", path, e))?;
+    let root: Value =
+        serde_json::from_str(&content).map_err(|_| "JSON de exportação do GOG inválido".to_string())?;
+
+    let items = match &root {
+        Value::Array(items) => items,
+        Value::Object(_) => field(&root, &["products", "games", "owned"])
+            .and_then(|v| v.as_array())
+            .ok_or("JSON do GOG sem lista de produtos (products/games/owned)")?,
+        _ => return Err("JSON de exportação do GOG inválido".to_string()),
+    };
+
+    let mut games = Vec::new();
+
+    for item in items {
+        let product_id = match field(item, &["id", "productId", "product_id"]) {
+            Some(Value::Number(n)) => n.to_string(),
+            Some(Value::String(s)) => s.clone(),
+            _ => continue,
+        };
+
+        let Some(name) = field(item, &["title", "name"]).and_then(|v| v.as_str()) else {
+            continue;
+        };
+
+        let minutes = field(item, &["minutesInGame", "playtime", "minutes_played"])
+            .and_then(|v| v.as_f64())
+            .unwrap_or(0.0);
+
+        games.push(ImportedGame {
+            external_id: product_id,
+            name: name.to_string(),
+            platform: constants::PLATFORM_GOG.to_string(),
+            genre: None,
+            rating: None,
+            favorite: false,
+            cover_url: field(item, &["image", "cover", "coverUrl"])
+                .and_then(|v| v.as_str())
+                .filter(|u| u.starts_with("http"))
+                .map(|u| u.to_string()),
+            playtime: minutes_to_hours(minutes),
+            installed: false,
+            last_played: field(item, &["lastSession", "last_session", "lastPlayed"])
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+        });
+    }
+
+    Ok(games)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_dir;
+
+    /// Banco com o subconjunto de tabelas do `galaxy-2.0.db` lido pelo importador
+    fn fixture_galaxy_db() -> PathBuf {
+        let path = temp_dir("gog_galaxy").join("galaxy-2.0.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE LibraryReleases (id INTEGER PRIMARY KEY, releaseKey TEXT, userId INTEGER);
+            CREATE TABLE GamePieceTypes (id INTEGER PRIMARY KEY, type TEXT);
+            CREATE TABLE GamePieces (releaseKey TEXT, gamePieceTypeId INTEGER, userId INTEGER, value TEXT);
+            CREATE TABLE GameTimes (releaseKey TEXT, userId INTEGER, minutesInGame INTEGER);
+            CREATE TABLE LastPlayedDates (gameReleaseKey TEXT, userId INTEGER, lastPlayedDate TEXT);
+            CREATE TABLE InstalledBaseProducts (productId INTEGER, installationPath TEXT);
+
+            INSERT INTO LibraryReleases (releaseKey, userId) VALUES
+                ('gog_1207658924', 1), ('gog_1207658924', 1), ('gog_1495134320', 1),
+                ('gog_1111111111', 1), ('steam_570', 1);
+            INSERT INTO GamePieceTypes VALUES (1, 'title'), (2, 'originalImages'), (3, 'meta');
+            INSERT INTO GamePieces VALUES
+                ('gog_1207658924', 1, 1, '{"title":"Unreal Tournament 2004"}'),
+                ('gog_1207658924', 2, 1, '{"verticalCover":"https://images.gog.com/ut_{formatter}.jpg"}'),
+                ('gog_1207658924', 3, 1, '{"genres":["Shooter","Action"]}'),
+                ('gog_1495134320', 1, 1, '{"title":"The Witcher 3: Wild Hunt"}'),
+                ('gog_1495134320', 3, 1, '{"genres":[]}'),
+                ('steam_570', 1, 1, '{"title":"Dota 2"}');
+            INSERT INTO GameTimes VALUES
+                ('gog_1495134320', 1, 3000), ('gog_1495134320', 2, 60);
+            INSERT INTO LastPlayedDates VALUES
+                ('gog_1495134320', 1, '2024-03-01T10:00:00Z'),
+                ('gog_1495134320', 1, '2024-05-01T10:00:00Z');
+            INSERT INTO InstalledBaseProducts VALUES (1495134320, 'C:\Games\Witcher 3');
+            "#,
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn loads_galaxy_database() {
+        let path = fixture_galaxy_db();
+        let importer = GogImporter::new(Some(path.to_string_lossy().to_string())).unwrap();
+        let mut games = importer.load().unwrap();
+        games.sort_by(|a, b| a.external_id.cmp(&b.external_id));
+
+        // Produtos de outras integrações e sem título ficam de fora
+        assert_eq!(games.len(), 2);
+
+        let ut = &games[0];
+        assert_eq!(ut.external_id, "1207658924");
+        assert_eq!(ut.cover_url.as_deref(), Some("https://images.gog.com/ut.jpg"));
+        assert_eq!(ut.genre.as_deref(), Some("Shooter, Action"));
+        assert!(!ut.installed);
+
+        let witcher = &games[1];
+        assert_eq!(witcher.name, "The Witcher 3: Wild Hunt");
+        assert_eq!(witcher.genre, None);
+        assert_eq!(witcher.playtime, 51);
+        assert_eq!(witcher.last_played.as_deref(), Some("2024-05-01T10:00:00Z"));
+        assert!(witcher.installed);
+    }
+
+    #[test]
+    fn rejects_other_databases() {
+        let path = temp_dir("gog_other").join("other.db");
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch("CREATE TABLE games (id INTEGER);")
+            .unwrap();
+
+        let importer = GogImporter::new(Some(path.to_string_lossy().to_string())).unwrap();
+        assert!(importer.load().is_err());
+    }
+
+    #[test]
+    fn loads_account_export() {
+        let path = temp_dir("gog_export").join("gog.json");
+        fs::write(
+            &path,
+            r#"{"products": [
+                {"id": 1207658924, "title": "Unreal Tournament 2004", "image": "https://images.gog.com/ut.jpg", "minutesInGame": 125},
+                {"productId": "1495134320", "name": "The Witcher 3", "lastSession": "2024-05-01"},
+                {"title": "Sem ID"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let games = GogImporter::new(Some(path.to_string_lossy().to_string()))
+            .unwrap()
+            .load()
+            .unwrap();
+
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].external_id, "1207658924");
+        assert_eq!(games[0].playtime, 2);
+        assert_eq!(games[1].external_id, "1495134320");
+        assert_eq!(games[1].last_played.as_deref(), Some("2024-05-01"));
+
+        fs::write(&path, "{\"owned\": 3}").unwrap();
+        assert!(GogImporter::new(Some(path.to_string_lossy().to_string()))
+            .unwrap()
+            .load()
+            .is_err());
+    }
+}
@@ -0,0 +1,234 @@
+use super::{minutes_to_hours, ImportedGame, LibraryImporter};
+use crate::constants;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Deserialize)]
+struct HeroicGame {
+    app_name: String,
+    title: String,
+    #[serde(default)]
+    art_cover: Option<String>,
+    #[serde(default)]
+    art_square: Option<String>,
+    #[serde(default)]
+    is_installed: bool,
+    #[serde(default)]
+    is_dlc: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct HeroicLibraryFile {
+    // Epic e Amazon usam "library", GOG usa "games"
+    #[serde(default, alias = "games")]
+    library: Vec<HeroicGame>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HeroicTimestamp {
+    #[serde(default, rename = "totalPlayed")]
+    total_played: Option<f64>,
+    #[serde(default, rename = "lastPlayed")]
+    last_played: Option<String>,
+}
+
+/// Loja de origem dentro do Heroic e os arquivos de cache correspondentes
+struct HeroicStore {
+    key: &'static str,
+    platform: &'static str,
+    files: &'static [&'static str],
+}
+
+const STORES: &[HeroicStore] = &[
+    HeroicStore {
+        key: "epic",
+        platform: constants::PLATFORM_EPIC,
+        files: &["store_cache/legendary_library.json", "lib-cache/library.json"],
+    },
+    HeroicStore {
+        key: "gog",
+        platform: constants::PLATFORM_GOG,
+        files: &["store_cache/gog_library.json", "gog_store/library.json"],
+    },
+    HeroicStore {
+        key: "amazon",
+        platform: constants::PLATFORM_AMAZON,
+        files: &["store_cache/nile_library.json"],
+    },
+];
+
+/// Importa as bibliotecas Epic/GOG/Amazon a partir dos caches JSON do Heroic
+pub struct HeroicImporter {
+    pub config_dir: PathBuf,
+}
+
+impl HeroicImporter {
+    pub fn new(config_dir: Option<String>) -> Result<Self, String> {
+        let config_dir = match config_dir.filter(|p| !p.trim().is_empty()) {
+            Some(p) => PathBuf::from(p.trim()),
+            None => Self::default_paths()
+                .into_iter()
+                .find(|p| p.is_dir())
+                .ok_or("Pasta de configuração do Heroic não encontrada. Informe o caminho manualmente.")?,
+        };
+
+        Ok(Self { config_dir })
+    }
+
+    fn default_paths() -> Vec<PathBuf> {
+        match std::env::var_os("HOME").map(PathBuf::from) {
+            Some(home) => vec![
+                home.join(".config/heroic"),
+                // Instalação via Flatpak
+                home.join(".var/app/com.heroicgameslauncher.hgl/config/heroic"),
+            ],
+            None => Vec::new(),
+        }
+    }
+
+    /// Tempo jogado (em minutos) e última sessão por app_name, registrados pelo próprio Heroic
+    fn load_playtimes(&self) -> HashMap<String, HeroicTimestamp> {
+        let path = self.config_dir.join("store").join("timestamp.json");
+        fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn load_store(&self, store: &HeroicStore) -> Result<Vec<HeroicGame>, String> {
+        // Usa o primeiro arquivo existente (o Heroic mudou a estrutura entre versões)
+        let Some(path) = store
+            .files
+            .iter()
+            .map(|f| self.config_dir.join(f))
+            .find(|p| p.exists())
+        else {
+            return Ok(Vec::new());
+        };
+
+        parse_library_file(&path)
+    }
+}
+
+fn parse_library_file(path: &Path) -> Result<Vec<HeroicGame>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Erro ao ler {:?}: {}", path, e))?;
+    let file: HeroicLibraryFile = serde_json::from_str(&content)
+        .map_err(|e| format!("Cache do Heroic inválido {:?}: {}", path, e))?;
+    Ok(file.library)
+}
+
+impl LibraryImporter for HeroicImporter {
+    fn source(&self) -> &'static str {
+        "heroic"
+    }
+
+    fn load(&self) -> Result<Vec<ImportedGame>, String> {
+        let playtimes = self.load_playtimes();
+        let mut games = Vec::new();
+
+        for store in STORES {
+            for game in self.load_store(store)? {
+                if game.is_dlc {
+                    continue;
+                }
+
+                let cover_url = game
+                    .art_square
+                    .or(game.art_cover)
+                    .filter(|url| url.starts_with("http"));
+
+                let timestamps = playtimes.get(&game.app_name);
+
+                games.push(ImportedGame {
+                    // app_name só é único dentro da loja
+                    external_id: format!("{}:{}", store.key, game.app_name),
+                    playtime: timestamps
+                        .and_then(|t| t.total_played)
+                        .map(minutes_to_hours)
+                        .unwrap_or(0),
+                    last_played: timestamps.and_then(|t| t.last_played.clone()),
+                    name: game.title,
+                    platform: store.platform.to_string(),
+                    genre: None,
+                    rating: None,
+                    favorite: false,
+                    cover_url,
+                    installed: game.is_installed,
+                });
+            }
+        }
+
+        Ok(games)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_dir;
+
+    fn fixture_config(name: &str) -> PathBuf {
+        let dir = temp_dir(name);
+        fs::create_dir_all(dir.join("store_cache")).unwrap();
+        fs::create_dir_all(dir.join("store")).unwrap();
+        fs::write(
+            dir.join("store_cache/legendary_library.json"),
+            include_str!("../../../tests/fixtures/heroic/legendary_library.json"),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("store_cache/gog_library.json"),
+            include_str!("../../../tests/fixtures/heroic/gog_library.json"),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("store/timestamp.json"),
+            include_str!("../../../tests/fixtures/heroic/timestamp.json"),
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn loads_every_store_and_skips_dlcs() {
+        let dir = fixture_config("heroic_full");
+        let games = HeroicImporter::new(Some(dir.to_string_lossy().to_string()))
+            .unwrap()
+            .load()
+            .unwrap();
+
+        let ids: Vec<&str> = games.iter().map(|g| g.external_id.as_str()).collect();
+        assert_eq!(ids, vec!["epic:Fortnite", "epic:Hades", "gog:1207658924"]);
+
+        let fortnite = &games[0];
+        assert_eq!(fortnite.platform, constants::PLATFORM_EPIC);
+        assert_eq!(fortnite.playtime, 13);
+        assert_eq!(fortnite.last_played.as_deref(), Some("2024-10-01T21:30:00.000Z"));
+        assert_eq!(
+            fortnite.cover_url.as_deref(),
+            Some("https://cdn1.epicgames.com/fortnite_square.jpg")
+        );
+        assert!(fortnite.installed);
+
+        // Arte só em cache local não serve como capa
+        assert_eq!(games[1].cover_url, None);
+        assert_eq!(games[1].playtime, 0);
+
+        assert_eq!(games[2].platform, constants::PLATFORM_GOG);
+        assert_eq!(games[2].playtime, 0);
+    }
+
+    #[test]
+    fn missing_stores_are_empty_and_invalid_cache_fails() {
+        let dir = temp_dir("heroic_partial");
+        let importer = HeroicImporter::new(Some(dir.to_string_lossy().to_string())).unwrap();
+        assert!(importer.load().unwrap().is_empty());
+
+        fs::create_dir_all(dir.join("store_cache")).unwrap();
+        fs::write(dir.join("store_cache/nile_library.json"), "{ \"library\": [").unwrap();
+        assert!(importer.load().is_err());
+    }
+}
@@ -0,0 +1,199 @@
+use super::{unix_to_rfc3339, ImportedGame, LibraryImporter};
+use crate::constants;
+use rusqlite::{Connection, OpenFlags};
+use std::path::PathBuf;
+
+/// Importa a biblioteca do Lutris a partir do banco `pga.db`
+pub struct LutrisImporter {
+    pub db_path: PathBuf,
+}
+
+impl LutrisImporter {
+    pub fn new(db_path: Option<String>) -> Result<Self, String> {
+        let db_path = match db_path.filter(|p| !p.trim().is_empty()) {
+            Some(p) => PathBuf::from(p.trim()),
+            None => Self::default_paths()
+                .into_iter()
+                .find(|p| p.exists())
+                .ok_or("Banco do Lutris (pga.db) não encontrado. Informe o caminho manualmente.")?,
+        };
+
+        Ok(Self { db_path })
+    }
+
+    fn default_paths() -> Vec<PathBuf> {
+        match std::env::var_os("HOME").map(PathBuf::from) {
+            Some(home) => vec![
+                home.join(".local/share/lutris/pga.db"),
+                // Instalação via Flatpak
+                home.join(".var/app/net.lutris.Lutris/data/lutris/pga.db"),
+            ],
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Mapeia o serviço de origem do Lutris para o valor de `platform` usado na biblioteca
+fn platform_for_service(service: Option<&str>) -> String {
+    match service.unwrap_or_default() {
+        "steam" => constants::DEFAULT_PLATFORM_STEAM,
+        "gog" => constants::PLATFORM_GOG,
+        "egs" => constants::PLATFORM_EPIC,
+        "amazon" => constants::PLATFORM_AMAZON,
+        "humblebundle" => "Humble Bundle",
+        "itchio" => "itch.io",
+        "ea_app" | "origin" => "EA App",
+        "ubisoft" => "Ubisoft Connect",
+        _ => constants::PLATFORM_LUTRIS,
+    }
+    .to_string()
+}
+
+impl LibraryImporter for LutrisImporter {
+    fn source(&self) -> &'static str {
+        "lutris"
+    }
+
+    fn load(&self) -> Result<Vec<ImportedGame>, String> {
+        let conn = Connection::open_with_flags(&self.db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| format!("Erro ao abrir {:?}: {}", self.db_path, e))?;
+
+        // Versões antigas do Lutris não possuem todas as colunas
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(games)")
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| row.get::<_, String>(1))?
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|e| format!("Banco do Lutris inválido: {}", e))?;
+
+        if columns.is_empty() {
+            return Err("Banco do Lutris sem a tabela 'games'".to_string());
+        }
+
+        let col = |name: &str| {
+            if columns.iter().any(|c| c == name) {
+                name.to_string()
+            } else {
+                "NULL".to_string()
+            }
+        };
+
+        let sql = format!(
+            "SELECT id, name, {}, {}, {}, {}, {} FROM games WHERE name IS NOT NULL",
+            col("slug"),
+            col("service"),
+            col("playtime"),
+            col("installed"),
+            col("lastplayed"),
+        );
+
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let games = stmt
+            .query_map([], |row| {
+                let id: i64 = row.get(0)?;
+                let name: String = row.get(1)?;
+                let slug: Option<String> = row.get(2)?;
+                let service: Option<String> = row.get(3)?;
+                // Lutris guarda o tempo jogado em horas (REAL)
+                let playtime: Option<f64> = row.get(4)?;
+                let installed: Option<bool> = row.get(5)?;
+                let lastplayed: Option<i64> = row.get(6)?;
+
+                Ok(ImportedGame {
+                    external_id: slug.filter(|s| !s.is_empty()).unwrap_or_else(|| id.to_string()),
+                    name,
+                    platform: platform_for_service(service.as_deref()),
+                    genre: None,
+                    rating: None,
+                    favorite: false,
+                    cover_url: None,
+                    playtime: playtime.unwrap_or(0.0).round().max(0.0) as i32,
+                    installed: installed.unwrap_or(false),
+                    last_played: lastplayed.and_then(unix_to_rfc3339),
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        Ok(games)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_dir;
+
+    fn fixture_db(name: &str, schema: &str, rows: &str) -> PathBuf {
+        let path = temp_dir(name).join("pga.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(schema).unwrap();
+        conn.execute_batch(rows).unwrap();
+        path
+    }
+
+    #[test]
+    fn loads_current_schema() {
+        let path = fixture_db(
+            "lutris_current",
+            "CREATE TABLE games (id INTEGER PRIMARY KEY, name TEXT, slug TEXT, runner TEXT,
+                service TEXT, playtime REAL, installed INTEGER, lastplayed INTEGER);",
+            "INSERT INTO games VALUES (1, 'Celeste', 'celeste', 'linux', 'humblebundle', 12.6, 1, 1700000000);
+             INSERT INTO games VALUES (2, 'Portal 2', 'portal-2', 'steam', 'steam', NULL, 0, 0);
+             INSERT INTO games VALUES (3, 'Doom', '', 'wine', NULL, 1.2, NULL, NULL);
+             INSERT INTO games VALUES (4, NULL, 'sem-nome', 'wine', NULL, 0, 0, 0);",
+        );
+
+        let games = LutrisImporter::new(Some(path.to_string_lossy().to_string()))
+            .unwrap()
+            .load()
+            .unwrap();
+
+        assert_eq!(games.len(), 3);
+
+        let celeste = &games[0];
+        assert_eq!(celeste.external_id, "celeste");
+        assert_eq!(celeste.platform, "Humble Bundle");
+        assert_eq!(celeste.playtime, 13);
+        assert!(celeste.installed);
+        assert!(celeste.last_played.as_deref().unwrap().starts_with("2023-11-14"));
+
+        assert_eq!(games[1].platform, constants::DEFAULT_PLATFORM_STEAM);
+        assert_eq!(games[1].last_played, None);
+
+        // Sem slug, o ID interno do Lutris vira o ID externo
+        assert_eq!(games[2].external_id, "3");
+        assert_eq!(games[2].platform, constants::PLATFORM_LUTRIS);
+    }
+
+    #[test]
+    fn loads_old_schema_without_optional_columns() {
+        let path = fixture_db(
+            "lutris_old",
+            "CREATE TABLE games (id INTEGER PRIMARY KEY, name TEXT, slug TEXT, runner TEXT);",
+            "INSERT INTO games VALUES (1, 'Quake', 'quake', 'linux');",
+        );
+
+        let games = LutrisImporter::new(Some(path.to_string_lossy().to_string()))
+            .unwrap()
+            .load()
+            .unwrap();
+
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].playtime, 0);
+        assert!(!games[0].installed);
+    }
+
+    #[test]
+    fn rejects_database_without_games_table() {
+        let path = fixture_db("lutris_empty", "CREATE TABLE other (id INTEGER);", "");
+
+        let result = LutrisImporter::new(Some(path.to_string_lossy().to_string()))
+            .unwrap()
+            .load();
+
+        assert!(result.is_err());
+    }
+}
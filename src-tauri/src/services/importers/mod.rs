@@ -0,0 +1,69 @@
+pub mod generic;
+pub mod gog;
+pub mod heroic;
+pub mod lutris;
+pub mod playnite;
+
+use serde::Serialize;
+
+/// Jogo lido de uma fonte externa (launcher, exportação etc.), antes de virar linha em `games`
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ImportedGame {
+    /// Identificador estável dentro da fonte (slug do Lutris, app_name da Epic...)
+    pub external_id: String,
+    pub name: String,
+    pub platform: String,
+    pub genre: Option<String>,
+    pub cover_url: Option<String>,
+    /// Tempo jogado em horas (mesma unidade da tabela `games`)
+    pub playtime: i32,
+    pub rating: Option<i32>,
+    pub favorite: bool,
+    pub installed: bool,
+    /// Data da última sessão (RFC 3339), quando a fonte informa
+    pub last_played: Option<String>,
+}
+
+/// Contrato comum a todos os importadores de bibliotecas locais
+pub trait LibraryImporter {
+    /// Nome da fonte, usado como prefixo do ID e na tabela `game_external_ids`
+    fn source(&self) -> &'static str;
+
+    /// Lê a fonte e devolve os jogos encontrados
+    fn load(&self) -> Result<Vec<ImportedGame>, String>;
+}
+
+/// Fonte e ID externo gravados em `game_external_ids`. Produtos GOG lidos pelo Heroic
+/// ("gog:<product_id>") usam o mesmo par do importador do GOG, para não duplicar o jogo.
+pub fn external_key<'a>(source: &'a str, external_id: &'a str) -> (&'a str, &'a str) {
+    match (source, external_id.strip_prefix("gog:")) {
+        ("heroic", Some(product_id)) => ("gog", product_id),
+        _ => (source, external_id),
+    }
+}
+
+/// AppID de um jogo da Steam lido por outro launcher ("steam:<appid>", como no Playnite).
+/// Esses jogos usam a cópia e o ID de linha do importador da Steam, para não duplicar o jogo.
+pub fn steam_app_id(external_id: &str) -> Option<&str> {
+    external_id
+        .strip_prefix("steam:")
+        .filter(|id| id.parse::<u32>().is_ok())
+}
+
+/// ID da linha em `games` para um jogo importado (determinístico para evitar duplicatas)
+pub fn game_id_for(source: &str, external_id: &str) -> String {
+    format!("{}:{}", source, external_id)
+}
+
+/// Converte um timestamp Unix (segundos) em data RFC 3339
+pub fn unix_to_rfc3339(ts: i64) -> Option<String> {
+    if ts <= 0 {
+        return None;
+    }
+    chrono::DateTime::from_timestamp(ts, 0).map(|dt| dt.to_rfc3339())
+}
+
+/// Converte minutos em horas arredondadas, como no import da Steam
+pub fn minutes_to_hours(minutes: f64) -> i32 {
+    (minutes / 60.0).round().max(0.0) as i32
+}
@@ -0,0 +1,390 @@
+use super::generic::parse_rating;
+use super::{steam_app_id, ImportedGame, LibraryImporter};
+use crate::constants;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+// Campos do Playnite lidos por `map_game`; os demais entram no relatório do que ficou de fora
+const MAPPED_FIELDS: &[&str] = &[
+    "Id",
+    "Name",
+    "Source",
+    "SourceId",
+    "Platforms",
+    "PlatformIds",
+    "Genres",
+    "GenreIds",
+    "Tags",
+    "TagIds",
+    "Playtime",
+    "UserScore",
+    "Favorite",
+    "IsInstalled",
+    "LastActivity",
+];
+
+const EXAMPLES_PER_FIELD: usize = 3;
+
+/// Campo da origem que não foi importado, com quantos jogos o possuíam
+#[derive(Debug, Serialize)]
+pub struct UnmappedField {
+    pub field: String,
+    pub games: i32,
+    pub examples: Vec<String>,
+}
+
+/// Importa uma biblioteca do Playnite exportada em JSON.
+/// Aceita uma lista de jogos ou um objeto com "Games" e, opcionalmente, as coleções
+/// de referência ("Sources", "Platforms", "Genres", "Tags", "CompletionStatuses")
+/// para resolver campos que vêm apenas como IDs.
+pub struct PlayniteImporter {
+    pub path: PathBuf,
+}
+
+impl PlayniteImporter {
+    pub fn new(path: String) -> Result<Self, String> {
+        let path = PathBuf::from(path.trim());
+
+        let is_litedb = path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("db"))
+            .unwrap_or(false);
+        if is_litedb {
+            return Err("O banco LiteDB do Playnite não pode ser lido diretamente. Exporte a biblioteca em JSON pelo Playnite.".to_string());
+        }
+
+        Ok(Self { path })
+    }
+
+    /// Lê o arquivo e devolve os jogos mapeados junto com o relatório do que ficou de fora
+    pub fn load_with_report(&self) -> Result<(Vec<ImportedGame>, Vec<UnmappedField>), String> {
+        let content = fs::read_to_string(&self.path)
+            .map_err(|e| format!("Erro ao ler {:?}: {}", self.path, e))?;
+        let root: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("JSON do Playnite inválido: {}", e))?;
+
+        let (items, lookups) = match &root {
+            Value::Array(items) => (items.clone(), Lookups::default()),
+            Value::Object(obj) => {
+                let items = obj
+                    .get("Games")
+                    .and_then(|v| v.as_array())
+                    .cloned()
+                    .ok_or("JSON do Playnite sem a coleção 'Games'")?;
+                (items, Lookups::from_root(obj))
+            }
+            _ => return Err("JSON do Playnite inválido".to_string()),
+        };
+
+        let mut games = Vec::new();
+        let mut unmapped: BTreeMap<&str, (i32, Vec<String>)> = BTreeMap::new();
+
+        for item in items.iter().filter_map(|i| i.as_object()) {
+            let Some(game) = map_game(item, &lookups) else {
+                continue;
+            };
+
+            for (field, value) in item {
+                if is_mapped(field, &game) {
+                    continue;
+                }
+                if let Some(text) = lookups.describe(field, value) {
+                    let entry = unmapped.entry(field.as_str()).or_insert((0, Vec::new()));
+                    entry.0 += 1;
+                    if entry.1.len() < EXAMPLES_PER_FIELD {
+                        entry.1.push(format!("{}: {}", game.name, text));
+                    }
+                }
+            }
+
+            games.push(game);
+        }
+
+        let report = unmapped
+            .into_iter()
+            .map(|(field, (games, examples))| UnmappedField {
+                field: field.to_string(),
+                games,
+                examples,
+            })
+            .collect();
+
+        Ok((games, report))
+    }
+}
+
+impl LibraryImporter for PlayniteImporter {
+    fn source(&self) -> &'static str {
+        "playnite"
+    }
+
+    fn load(&self) -> Result<Vec<ImportedGame>, String> {
+        self.load_with_report().map(|(games, _)| games)
+    }
+}
+
+/// Tabelas de referência (ID -> nome) presentes em exportações completas
+#[derive(Default)]
+struct Lookups {
+    by_collection: HashMap<&'static str, HashMap<String, String>>,
+}
+
+impl Lookups {
+    fn from_root(root: &Map<String, Value>) -> Self {
+        let mut by_collection = HashMap::new();
+
+        for collection in ["Sources", "Platforms", "Genres", "Tags", "CompletionStatuses"] {
+            let names: HashMap<String, String> = root
+                .get(collection)
+                .and_then(|v| v.as_array())
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|i| {
+                            let id = i.get("Id")?.as_str()?;
+                            let name = i.get("Name")?.as_str()?;
+                            Some((id.to_string(), name.to_string()))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            by_collection.insert(collection, names);
+        }
+
+        Self { by_collection }
+    }
+
+    /// Nomes de um campo de referência: aceita objetos {Id, Name}, listas deles,
+    /// nomes diretos ou IDs resolvidos pela coleção correspondente
+    fn names(&self, collection: &str, value: Option<&Value>) -> Vec<String> {
+        let resolve = |v: &Value| -> Option<String> {
+            match v {
+                Value::Object(obj) => obj.get("Name").and_then(|n| n.as_str()).map(|s| s.to_string()),
+                Value::String(s) => Some(
+                    self.by_collection
+                        .get(collection)
+                        .and_then(|names| names.get(s))
+                        .cloned()
+                        .unwrap_or_else(|| s.clone()),
+                ),
+                _ => None,
+            }
+        };
+
+        match value {
+            Some(Value::Array(items)) => items.iter().filter_map(resolve).collect(),
+            Some(v) => resolve(v).into_iter().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Texto legível de um campo não mapeado (ou None se estiver vazio)
+    fn describe(&self, field: &str, value: &Value) -> Option<String> {
+        match value {
+            Value::Null => None,
+            Value::Bool(false) => None,
+            Value::String(s) if s.trim().is_empty() => None,
+            Value::Array(items) if items.is_empty() => None,
+            Value::Number(n) if n.as_f64() == Some(0.0) => None,
+            _ if field == "CompletionStatus" || field == "CompletionStatusId" => self
+                .names("CompletionStatuses", Some(value))
+                .into_iter()
+                .next(),
+            Value::Array(_) | Value::Object(_) => {
+                let names = self.names(field, Some(value));
+                if names.is_empty() {
+                    Some(value.to_string())
+                } else {
+                    Some(names.join(", "))
+                }
+            }
+            Value::String(s) => Some(s.chars().take(80).collect()),
+            other => Some(other.to_string()),
+        }
+    }
+}
+
+/// Mapeia a loja de origem do Playnite para o valor de `platform` da biblioteca
+fn platform_for_source(source: &str) -> String {
+    let lower = source.to_lowercase();
+    if lower.contains("steam") {
+        constants::DEFAULT_PLATFORM_STEAM.to_string()
+    } else if lower.contains("epic") {
+        constants::PLATFORM_EPIC.to_string()
+    } else if lower.contains("gog") {
+        constants::PLATFORM_GOG.to_string()
+    } else if lower.contains("amazon") {
+        constants::PLATFORM_AMAZON.to_string()
+    } else {
+        source.to_string()
+    }
+}
+
+/// Junta gêneros e tags sem repetir, respeitando o limite de tamanho do campo
+fn merge_genres(genres: Vec<String>, tags: Vec<String>) -> Option<String> {
+    let mut merged: Vec<String> = Vec::new();
+    for name in genres.into_iter().chain(tags) {
+        let name = name.trim().to_string();
+        if name.is_empty() || merged.iter().any(|m| m.eq_ignore_ascii_case(&name)) {
+            continue;
+        }
+        let candidate_len = merged.iter().map(|m| m.len() + 2).sum::<usize>() + name.len();
+        if candidate_len > constants::MAX_GENRE_LENGTH {
+            break;
+        }
+        merged.push(name);
+    }
+
+    if merged.is_empty() {
+        None
+    } else {
+        Some(merged.join(", "))
+    }
+}
+
+/// O campo foi aproveitado na importação deste jogo? `GameId` só é usado em jogos da Steam
+/// e `CoverImage` só quando é uma URL.
+fn is_mapped(field: &str, game: &ImportedGame) -> bool {
+    match field {
+        "GameId" => steam_app_id(&game.external_id).is_some(),
+        "CoverImage" => game.cover_url.is_some(),
+        _ => MAPPED_FIELDS.contains(&field),
+    }
+}
+
+fn map_game(item: &Map<String, Value>, lookups: &Lookups) -> Option<ImportedGame> {
+    let name = item.get("Name")?.as_str()?.trim().to_string();
+    if name.is_empty() {
+        return None;
+    }
+
+    let source = lookups
+        .names("Sources", item.get("Source").or_else(|| item.get("SourceId")))
+        .into_iter()
+        .find(|s| !s.is_empty() && s != "00000000-0000-0000-0000-000000000000");
+
+    // Na biblioteca Steam, `GameId` é o AppID: o jogo é a mesma cópia do importador da Steam
+    let steam_app = item
+        .get("GameId")
+        .and_then(|v| v.as_str())
+        .filter(|id| id.parse::<u32>().is_ok())
+        .filter(|_| source.as_deref().is_some_and(|s| s.to_lowercase().contains("steam")));
+
+    let external_id = match steam_app {
+        Some(app_id) => format!("steam:{}", app_id),
+        None => item
+            .get("Id")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| super::generic::slugify(&name)),
+    };
+
+    let platform = match source {
+        Some(source) => platform_for_source(&source),
+        None => lookups
+            .names("Platforms", item.get("Platforms").or_else(|| item.get("PlatformIds")))
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| "Playnite".to_string()),
+    };
+
+    let genre = merge_genres(
+        lookups.names("Genres", item.get("Genres").or_else(|| item.get("GenreIds"))),
+        lookups.names("Tags", item.get("Tags").or_else(|| item.get("TagIds"))),
+    );
+
+    // Playnite guarda o tempo jogado em segundos e a nota do usuário de 0 a 100
+    let seconds = item.get("Playtime").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let rating = item
+        .get("UserScore")
+        .and_then(|v| v.as_f64())
+        .and_then(|score| parse_rating(&score.to_string(), Some(100.0)).ok())
+        .flatten();
+
+    let cover_url = item
+        .get("CoverImage")
+        .and_then(|v| v.as_str())
+        .filter(|u| u.starts_with("http"))
+        .map(|u| u.to_string());
+
+    Some(ImportedGame {
+        external_id,
+        name,
+        platform,
+        genre,
+        cover_url,
+        playtime: (seconds / 3600.0).round().max(0.0) as i32,
+        rating,
+        favorite: item.get("Favorite").and_then(|v| v.as_bool()).unwrap_or(false),
+        installed: item.get("IsInstalled").and_then(|v| v.as_bool()).unwrap_or(false),
+        last_played: item
+            .get("LastActivity")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_dir;
+
+    fn load_fixture() -> (Vec<ImportedGame>, Vec<UnmappedField>) {
+        let dir = temp_dir("playnite");
+        let path = dir.join("library.json");
+        fs::write(&path, include_str!("../../../tests/fixtures/playnite/library.json")).unwrap();
+        let loaded = PlayniteImporter::new(path.to_string_lossy().to_string())
+            .unwrap()
+            .load_with_report()
+            .unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        loaded
+    }
+
+    #[test]
+    fn maps_games_and_resolves_reference_ids() {
+        let (games, _) = load_fixture();
+        assert_eq!(games.len(), 2, "jogo sem nome é ignorado");
+
+        // Jogo da biblioteca Steam: chaveado pelo AppID
+        let portal = &games[0];
+        assert_eq!(portal.external_id, "steam:620");
+        assert_eq!(portal.platform, constants::DEFAULT_PLATFORM_STEAM);
+        assert_eq!(portal.genre.as_deref(), Some("Puzzle, Co-op"));
+        assert_eq!((portal.playtime, portal.rating), (15, Some(5)));
+        assert!(portal.favorite && portal.installed);
+        assert_eq!(portal.last_played.as_deref(), Some("2024-05-01T20:15:00Z"));
+        assert_eq!(portal.cover_url, None);
+
+        let disco = &games[1];
+        assert_eq!(disco.external_id, "7d2f0b11-2a44-4c4e-8a77-5f0e1c9b2b02");
+        assert_eq!(disco.platform, constants::PLATFORM_GOG);
+        assert_eq!(disco.cover_url.as_deref(), Some("https://images.gog.com/disco.jpg"));
+        assert_eq!((disco.playtime, disco.rating), (0, None));
+    }
+
+    #[test]
+    fn reports_every_field_left_out() {
+        let (_, report) = load_fixture();
+        let fields: Vec<(&str, i32)> = report.iter().map(|f| (f.field.as_str(), f.games)).collect();
+        // GameId fica de fora só fora da Steam; a capa, só quando é caminho local.
+        // Campos vazios ou falsos não entram.
+        assert_eq!(
+            fields,
+            vec![
+                ("CompletionStatusId", 1),
+                ("CoverImage", 1),
+                ("GameId", 1),
+                ("Notes", 1),
+                ("PluginId", 1)
+            ]
+        );
+
+        let status = &report[0];
+        assert_eq!(status.examples, vec!["Portal 2: Completed"]);
+        assert_eq!(report[2].examples, vec!["Disco Elysium: 1771589310"]);
+    }
+}
@@ -0,0 +1,376 @@
+// Provedores de metadados e mesclagem campo a campo por prioridade de fonte
+
+use crate::services::{rawg, steam};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+
+pub const PROVIDER_LOCAL: &str = "local";
+pub const PROVIDER_STEAM: &str = "steam";
+pub const PROVIDER_RAWG: &str = "rawg";
+
+/// Provedores conhecidos, na ordem padrão de prioridade
+pub const PROVIDERS: &[&str] = &[PROVIDER_LOCAL, PROVIDER_RAWG, PROVIDER_STEAM];
+
+/// Campos que podem vir de qualquer provedor
+pub const FIELDS: &[&str] = &[
+    "genre",
+    "cover_url",
+    "description",
+    "website",
+    "metacritic",
+    "release_date",
+    "background_image",
+    "developers",
+    "publishers",
+    "tags",
+];
+
+/// Registro parcial de metadados: None (ou lista vazia) significa que a fonte não tem o campo
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetadataFields {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub genre: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub website: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metacritic: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background_image: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub developers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub publishers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+impl MetadataFields {
+    pub fn has(&self, field: &str) -> bool {
+        match field {
+            "genre" => self.genre.is_some(),
+            "cover_url" => self.cover_url.is_some(),
+            "description" => self.description.is_some(),
+            "website" => self.website.is_some(),
+            "metacritic" => self.metacritic.is_some(),
+            "release_date" => self.release_date.is_some(),
+            "background_image" => self.background_image.is_some(),
+            "developers" => !self.developers.is_empty(),
+            "publishers" => !self.publishers.is_empty(),
+            "tags" => !self.tags.is_empty(),
+            _ => false,
+        }
+    }
+
+    fn copy_field(&mut self, from: &MetadataFields, field: &str) {
+        match field {
+            "genre" => self.genre = from.genre.clone(),
+            "cover_url" => self.cover_url = from.cover_url.clone(),
+            "description" => self.description = from.description.clone(),
+            "website" => self.website = from.website.clone(),
+            "metacritic" => self.metacritic = from.metacritic,
+            "release_date" => self.release_date = from.release_date.clone(),
+            "background_image" => self.background_image = from.background_image.clone(),
+            "developers" => self.developers = from.developers.clone(),
+            "publishers" => self.publishers = from.publishers.clone(),
+            "tags" => self.tags = from.tags.clone(),
+            _ => {}
+        }
+    }
+
+    /// Define um campo a partir de texto (substituições manuais). Listas são separadas por vírgula;
+    /// `None` ou texto vazio remove o campo.
+    pub fn set_from_text(&mut self, field: &str, value: Option<&str>) -> Result<(), String> {
+        let text = non_empty(value.map(str::to_string));
+        match field {
+            "genre" => self.genre = text,
+            "cover_url" => self.cover_url = text,
+            "description" => self.description = text,
+            "website" => self.website = text,
+            "metacritic" => {
+                self.metacritic = match text {
+                    Some(t) => {
+                        let score: i32 = t
+                            .parse()
+                            .map_err(|_| "Metacritic deve ser um número".to_string())?;
+                        if !(0..=100).contains(&score) {
+                            return Err("Metacritic deve estar entre 0 e 100".to_string());
+                        }
+                        Some(score)
+                    }
+                    None => None,
+                }
+            }
+            "release_date" => {
+                if let Some(date) = &text {
+                    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                        .map_err(|_| "Data de lançamento inválida (use AAAA-MM-DD)".to_string())?;
+                }
+                self.release_date = text;
+            }
+            "background_image" => self.background_image = text,
+            "developers" => self.developers = text.as_deref().map(parse_list).unwrap_or_default(),
+            "publishers" => self.publishers = text.as_deref().map(parse_list).unwrap_or_default(),
+            "tags" => self.tags = text.as_deref().map(parse_list).unwrap_or_default(),
+            other => return Err(format!("Campo de metadados desconhecido: '{}'", other)),
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !FIELDS.iter().any(|field| self.has(field))
+    }
+}
+
+/// Jogo da biblioteca a ser enriquecido, com os IDs conhecidos em cada fonte
+#[derive(Debug, Clone)]
+pub struct GameRef {
+    pub id: String,
+    pub name: String,
+    pub platform: Option<String>,
+    pub steam_app_id: Option<u32>,
+    pub rawg_id: Option<i32>,
+    /// Ano de lançamento de outra fonte, usado para desempatar a busca na RAWG
+    pub release_year: Option<i32>,
+}
+
+/// Resultado de um provedor: os campos encontrados e o ID do jogo na fonte
+#[derive(Debug, Clone)]
+pub struct ProviderRecord {
+    pub external_id: Option<String>,
+    pub fields: MetadataFields,
+}
+
+pub type ProviderFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Option<ProviderRecord>, String>> + Send + 'a>>;
+
+/// Fonte de metadados. `fetch` retorna None quando a fonte não conhece o jogo.
+pub trait MetadataProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn fetch<'a>(&'a self, game: &'a GameRef) -> ProviderFuture<'a>;
+}
+
+/// Página da loja Steam (apenas jogos com AppID conhecido)
+pub struct SteamStoreProvider;
+
+impl MetadataProvider for SteamStoreProvider {
+    fn name(&self) -> &'static str {
+        PROVIDER_STEAM
+    }
+
+    fn fetch<'a>(&'a self, game: &'a GameRef) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let Some(app_id) = game.steam_app_id else {
+                return Ok(None);
+            };
+            let Some(data) = steam::fetch_store_details(app_id).await? else {
+                return Ok(None);
+            };
+
+            let fields = MetadataFields {
+                genre: data
+                    .genres
+                    .as_ref()
+                    .and_then(|g| g.first())
+                    .map(|g| g.description.clone()),
+                cover_url: non_empty(data.header_image),
+                description: non_empty(data.short_description),
+                website: non_empty(data.website),
+                metacritic: data.metacritic.map(|m| m.score),
+                release_date: data
+                    .release_date
+                    .as_ref()
+                    .and_then(|r| steam::parse_store_date(&r.date))
+                    .map(|d| d.format("%Y-%m-%d").to_string()),
+                background_image: non_empty(data.background),
+                developers: data.developers.unwrap_or_default(),
+                publishers: data.publishers.unwrap_or_default(),
+                tags: Vec::new(),
+            };
+
+            Ok(Some(ProviderRecord {
+                external_id: Some(app_id.to_string()),
+                fields,
+            }))
+        })
+    }
+}
+
+/// RAWG: usa o ID já associado ao jogo ou resolve pela busca
+pub struct RawgProvider {
+    pub api_key: String,
+}
+
+impl MetadataProvider for RawgProvider {
+    fn name(&self) -> &'static str {
+        PROVIDER_RAWG
+    }
+
+    fn fetch<'a>(&'a self, game: &'a GameRef) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let rawg_id = match game.rawg_id {
+                Some(id) => id,
+                None => {
+                    let hints = rawg::MatchHints {
+                        year: game.release_year,
+                        platform: game.platform.clone(),
+                    };
+                    match rawg::find_best_match(&self.api_key, &game.name, &hints).await? {
+                        Some(best) => best.id,
+                        None => return Ok(None),
+                    }
+                }
+            };
+
+            let details = rawg::fetch_game_details(&self.api_key, rawg_id).await?;
+
+            let fields = MetadataFields {
+                genre: None,
+                cover_url: None,
+                description: non_empty(Some(details.description_raw)),
+                website: non_empty(Some(details.website)),
+                metacritic: details.metacritic,
+                release_date: details.released,
+                background_image: details.background_image,
+                developers: details.developers.into_iter().map(|d| d.name).collect(),
+                publishers: details.publishers.into_iter().map(|p| p.name).collect(),
+                tags: details.tags.into_iter().map(|t| t.name).collect(),
+            };
+
+            Ok(Some(ProviderRecord {
+                external_id: Some(rawg_id.to_string()),
+                fields,
+            }))
+        })
+    }
+}
+
+/// Substituições manuais do usuário, carregadas do banco antes do enriquecimento
+pub struct LocalOverrideProvider {
+    pub overrides: BTreeMap<String, MetadataFields>,
+}
+
+impl MetadataProvider for LocalOverrideProvider {
+    fn name(&self) -> &'static str {
+        PROVIDER_LOCAL
+    }
+
+    fn fetch<'a>(&'a self, game: &'a GameRef) -> ProviderFuture<'a> {
+        let record = self.overrides.get(&game.id).map(|fields| ProviderRecord {
+            external_id: None,
+            fields: fields.clone(),
+        });
+        Box::pin(async move { Ok(record) })
+    }
+}
+
+/// Ordem de prioridade das fontes: uma ordem padrão e, opcionalmente, uma por campo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergePolicy {
+    pub default_order: Vec<String>,
+    #[serde(default)]
+    pub fields: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for MergePolicy {
+    fn default() -> Self {
+        let order = |providers: &[&str]| providers.iter().map(|p| p.to_string()).collect();
+        MergePolicy {
+            default_order: order(PROVIDERS),
+            // Gênero e descrição da Steam vêm em português
+            fields: BTreeMap::from([
+                ("genre".to_string(), order(&[PROVIDER_LOCAL, PROVIDER_STEAM, PROVIDER_RAWG])),
+                (
+                    "description".to_string(),
+                    order(&[PROVIDER_LOCAL, PROVIDER_STEAM, PROVIDER_RAWG]),
+                ),
+            ]),
+        }
+    }
+}
+
+impl MergePolicy {
+    /// Valida nomes de campos e provedores
+    pub fn validate(&self) -> Result<(), String> {
+        let check = |order: &Vec<String>| {
+            order.iter().try_for_each(|p| {
+                if PROVIDERS.contains(&p.as_str()) {
+                    Ok(())
+                } else {
+                    Err(format!("Fonte de metadados desconhecida: '{}'", p))
+                }
+            })
+        };
+
+        check(&self.default_order)?;
+        for (field, order) in &self.fields {
+            if !FIELDS.contains(&field.as_str()) {
+                return Err(format!("Campo de metadados desconhecido: '{}'", field));
+            }
+            check(order)?;
+        }
+        Ok(())
+    }
+
+    /// Ordem das fontes para o campo; fontes não listadas entram no fim, na ordem padrão
+    fn order_for(&self, field: &str) -> Vec<&str> {
+        let mut order: Vec<&str> = self
+            .fields
+            .get(field)
+            .unwrap_or(&self.default_order)
+            .iter()
+            .map(String::as_str)
+            .collect();
+        for provider in self.default_order.iter().map(String::as_str).chain(PROVIDERS.iter().copied()) {
+            if !order.contains(&provider) {
+                order.push(provider);
+            }
+        }
+        order
+    }
+}
+
+/// Mescla os registros de cada fonte: cada campo vem da fonte de maior prioridade que o possui.
+/// Retorna também a origem (campo -> fonte) de cada campo preenchido.
+pub fn merge(
+    records: &BTreeMap<String, MetadataFields>,
+    policy: &MergePolicy,
+) -> (MetadataFields, BTreeMap<String, String>) {
+    let mut merged = MetadataFields::default();
+    let mut sources = BTreeMap::new();
+
+    for field in FIELDS {
+        let winner = policy
+            .order_for(field)
+            .into_iter()
+            .find(|provider| records.get(*provider).is_some_and(|r| r.has(field)));
+
+        if let Some(provider) = winner {
+            merged.copy_field(&records[provider], field);
+            sources.insert(field.to_string(), provider.to_string());
+        }
+    }
+
+    (merged, sources)
+}
@@ -1,3 +1,14 @@
+pub mod artwork;
+pub mod backup_archive;
+pub mod covers;
+pub mod duplicates;
+pub mod importers;
+pub mod metadata;
 pub mod rawg;
 pub mod recommendation;
+pub mod restore;
+pub mod snapshots;
+pub mod spending;
 pub mod steam;
+pub mod steam_local;
+pub mod sync;
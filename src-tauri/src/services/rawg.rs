@@ -1,4 +1,6 @@
+use crate::constants;
 use crate::utils::http_client::HTTP_CLIENT;
+use crate::utils::text;
 use chrono::Datelike;
 use serde::{Deserialize, Serialize};
 
@@ -30,11 +32,20 @@ pub struct RawgPublisher {
 pub struct GameDetails {
     pub id: i32,
     pub name: String,
+    #[serde(default)]
     pub description_raw: String,
     pub metacritic: Option<i32>,
+    #[serde(default)]
     pub website: String,
+    #[serde(default)]
+    pub released: Option<String>,
+    #[serde(default)]
+    pub background_image: Option<String>,
+    #[serde(default)]
     pub tags: Vec<RawgTag>,
+    #[serde(default)]
     pub developers: Vec<RawgDeveloper>,
+    #[serde(default)]
     pub publishers: Vec<RawgPublisher>,
 }
 
@@ -58,6 +69,171 @@ struct RawgResponse {
     results: Vec<RawgGame>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RawgPlatform {
+    pub name: String,
+    pub slug: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RawgPlatformEntry {
+    pub platform: RawgPlatform,
+}
+
+/// Resultado do endpoint de busca (`/games?search=`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RawgSearchResult {
+    pub id: i32,
+    pub name: String,
+    pub released: Option<String>,
+    pub background_image: Option<String>,
+    #[serde(default)]
+    pub platforms: Option<Vec<RawgPlatformEntry>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawgSearchResponse {
+    results: Vec<RawgSearchResult>,
+}
+
+/// Dicas usadas para desempatar resultados com nomes parecidos
+#[derive(Debug, Default)]
+pub struct MatchHints {
+    pub year: Option<i32>,
+    /// Plataforma/loja local ("Steam", "PlayStation 5"...)
+    pub platform: Option<String>,
+}
+
+/// Candidato da busca com a pontuação calculada por `score_candidate`
+#[derive(Debug, Clone, Serialize)]
+pub struct RawgMatch {
+    pub id: i32,
+    pub name: String,
+    pub released: Option<String>,
+    pub background_image: Option<String>,
+    pub platforms: Vec<String>,
+    pub score: f64,
+}
+
+/// Família de plataforma usada pela RAWG para uma plataforma/loja local
+fn platform_family(platform: &str) -> Option<&'static str> {
+    let p = platform.to_lowercase();
+    if p.contains("playstation") || p.starts_with("ps") {
+        Some("playstation")
+    } else if p.contains("xbox") {
+        Some("xbox")
+    } else if p.contains("switch") || p.contains("nintendo") {
+        Some("nintendo")
+    } else if ["steam", "epic", "gog", "amazon", "lutris", "heroic", "pc", "windows", "manual"]
+        .iter()
+        .any(|store| p.contains(store))
+    {
+        Some("pc")
+    } else {
+        None
+    }
+}
+
+/// Ano de uma data "AAAA-MM-DD" (formato da RAWG e dos metadados salvos)
+pub fn release_year(date: &str) -> Option<i32> {
+    date.get(0..4).and_then(|y| y.parse().ok())
+}
+
+/// Pontua um resultado da busca: similaridade do nome, ano de lançamento e plataforma
+pub fn score_candidate(query: &str, candidate: &RawgSearchResult, hints: &MatchHints) -> f64 {
+    let mut score = text::similarity(query, &candidate.name);
+
+    if let Some(year) = hints.year {
+        let released_year = candidate.released.as_deref().and_then(release_year);
+        score += match released_year {
+            Some(y) if y == year => 0.15,
+            Some(y) if (y - year).abs() == 1 => 0.05,
+            Some(_) => -0.1,
+            None => 0.0,
+        };
+    }
+
+    if let (Some(family), Some(platforms)) = (
+        hints.platform.as_deref().and_then(platform_family),
+        &candidate.platforms,
+    ) {
+        let available = platforms
+            .iter()
+            .any(|entry| entry.platform.slug.starts_with(family));
+        score += if available { 0.1 } else { -0.05 };
+    }
+
+    score
+}
+
+/// Ordena os resultados da busca do mais para o menos provável
+pub fn rank_candidates(
+    query: &str,
+    candidates: Vec<RawgSearchResult>,
+    hints: &MatchHints,
+) -> Vec<RawgMatch> {
+    let mut ranked: Vec<RawgMatch> = candidates
+        .into_iter()
+        .map(|c| RawgMatch {
+            score: score_candidate(query, &c, hints),
+            id: c.id,
+            name: c.name,
+            released: c.released,
+            background_image: c.background_image,
+            platforms: c
+                .platforms
+                .unwrap_or_default()
+                .into_iter()
+                .map(|entry| entry.platform.name)
+                .collect(),
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    ranked
+}
+
+/// Busca jogos pelo nome na RAWG
+pub async fn search_games(api_key: &str, query: &str) -> Result<Vec<RawgSearchResult>, String> {
+    let url = format!(
+        "{}?key={}&search={}&page_size={}",
+        constants::RAWG_API_URL,
+        api_key,
+        urlencoding::encode(query),
+        constants::RAWG_PAGE_SIZE
+    );
+
+    let res = HTTP_CLIENT
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !res.status().is_success() {
+        return Err(format!("Erro na busca RAWG: {}", res.status()));
+    }
+
+    let data: RawgSearchResponse = res.json().await.map_err(|e| e.to_string())?;
+    Ok(data.results)
+}
+
+/// Melhor resultado da busca, se a pontuação for suficiente para aceitar sem confirmação
+pub async fn find_best_match(
+    api_key: &str,
+    query: &str,
+    hints: &MatchHints,
+) -> Result<Option<RawgMatch>, String> {
+    let candidates = search_games(api_key, query).await?;
+    Ok(rank_candidates(query, candidates, hints)
+        .into_iter()
+        .next()
+        .filter(|best| best.score >= constants::MIN_RAWG_MATCH_SCORE))
+}
+
 // Busca os jogos mais populares do momento
 pub async fn fetch_trending_games(api_key: &str) -> Result<Vec<RawgGame>, String> {
     // Ordena por rating, filtrando datas recentes
@@ -84,17 +260,9 @@ pub async fn fetch_trending_games(api_key: &str) -> Result<Vec<RawgGame>, String
     Ok(data.results)
 }
 
-pub async fn fetch_game_details(api_key: &str, query: String) -> Result<GameDetails, String> {
-    // Transforma o nome em slug (Lógica de negócio)
-    let slug = query
-        .to_lowercase()
-        .replace(" ", "-")
-        .replace(":", "")
-        .replace("'", "")
-        .replace("&", "")
-        .replace(".", "");
-
-    let url = format!("https://api.rawg.io/api/games/{}?key={}", slug, api_key);
+/// Detalhes de um jogo pelo ID da RAWG
+pub async fn fetch_game_details(api_key: &str, rawg_id: i32) -> Result<GameDetails, String> {
+    let url = format!("{}/{}?key={}", constants::RAWG_API_URL, rawg_id, api_key);
 
     let res = HTTP_CLIENT
         .get(&url)
@@ -140,3 +308,51 @@ pub async fn fetch_upcoming_games(api_key: &str) -> Result<Vec<RawgGame>, String
     let data: RawgResponse = res.json().await.map_err(|e| e.to_string())?;
     Ok(data.results)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(id: i32, name: &str, released: &str, platform: &str) -> RawgSearchResult {
+        RawgSearchResult {
+            id,
+            name: name.to_string(),
+            released: Some(released.to_string()),
+            background_image: None,
+            platforms: Some(vec![RawgPlatformEntry {
+                platform: RawgPlatform {
+                    name: platform.to_string(),
+                    slug: platform.to_lowercase(),
+                },
+            }]),
+        }
+    }
+
+    #[test]
+    fn score_candidate_uses_year_and_platform_to_break_ties() {
+        let remake = candidate(1, "Doom", "2016-05-13", "PC");
+        let original = candidate(2, "DOOM", "1993-12-10", "PC");
+
+        let by_year = MatchHints { year: Some(2016), platform: None };
+        assert!(
+            score_candidate("Doom", &remake, &by_year)
+                > score_candidate("Doom", &original, &by_year)
+        );
+
+        let pc = candidate(3, "Hades", "2020-09-17", "PC");
+        let console = candidate(4, "Hades", "2020-09-17", "PlayStation5");
+        let on_steam = MatchHints { year: None, platform: Some("Steam".to_string()) };
+        assert!(
+            score_candidate("Hades", &pc, &on_steam)
+                > score_candidate("Hades", &console, &on_steam)
+        );
+
+        // Sem ano, o nome decide entre títulos parecidos
+        let sequel = candidate(5, "Mega Man 10", "2010-03-01", "PC");
+        let hints = MatchHints { year: None, platform: Some("Steam".to_string()) };
+        assert!(
+            score_candidate("Mega Man X", &sequel, &hints)
+                < score_candidate("Mega Man X", &candidate(6, "Mega Man X", "1993-12-17", "PC"), &hints)
+        );
+    }
+}
@@ -12,6 +12,8 @@ pub fn calculate_user_profile(games: &[Game]) -> UserProfile {
     let mut genre_scores: HashMap<String, (f32, i32)> = HashMap::new();
     let mut total_playtime = 0;
 
+    // Cada linha já é um jogo lógico: as cópias em outras plataformas ficam em
+    // `game_ownership` e jogos duplicados são unidos pela mesclagem, não pelo título
     for game in games {
         total_playtime += game.playtime;
 
@@ -76,3 +78,59 @@ pub fn calculate_user_profile(games: &[Game]) -> UserProfile {
 
 // Futuro: Função para cruzar perfil com novos jogos
 // pub fn rank_games(profile: &UserProfile, candidates: Vec<Game>) -> Vec<Game> { ... }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(id: &str, name: &str, genre: &str, playtime: i32) -> Game {
+        Game {
+            id: id.to_string(),
+            name: name.to_string(),
+            genre: Some(genre.to_string()),
+            platform: None,
+            cover_url: None,
+            playtime,
+            rating: None,
+            favorite: false,
+            installed: false,
+            last_played: None,
+            app_type: None,
+            parent_game_id: None,
+            parent_app_id: None,
+            rawg_id: None,
+            platforms: Vec::new(),
+            local_cover: None,
+            local_thumb: None,
+            hero_path: None,
+            logo_path: None,
+        }
+    }
+
+    #[test]
+    fn games_with_the_same_title_stay_separate() {
+        let games = vec![
+            game("doom_1993", "DOOM", "Shooter", 600),
+            game("doom_2016", "DOOM", "Shooter, Action", 1200),
+        ];
+
+        let profile = calculate_user_profile(&games);
+
+        assert_eq!(profile.total_games, 2);
+        assert_eq!(profile.total_playtime, 1800);
+        let shooter = profile.top_genres.iter().find(|g| g.name == "Shooter").unwrap();
+        assert_eq!(shooter.game_count, 2);
+    }
+
+    #[test]
+    fn a_merged_game_counts_once() {
+        // Hades na Steam e no Switch: uma linha, duas cópias em `game_ownership`
+        let mut hades = game("hades", "Hades", "Roguelike", 120);
+        hades.platforms = vec!["Steam".to_string(), "Nintendo Switch".to_string()];
+
+        let profile = calculate_user_profile(&[hades]);
+
+        assert_eq!(profile.total_games, 1);
+        assert_eq!(profile.top_genres[0].game_count, 1);
+    }
+}
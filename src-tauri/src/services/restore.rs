@@ -0,0 +1,188 @@
+// Planejamento da restauração de backups: compara as linhas do backup com as locais
+
+use chrono::{DateTime, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// Colunas usadas como data da última alteração, na ordem de preferência
+const TIMESTAMP_COLUMNS: &[&str] = &["updated_at", "fetched_at", "scanned_at", "created_at"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestoreMode {
+    /// Apaga as tabelas presentes no backup e carrega as linhas dele
+    Replace,
+    /// Adiciona linhas novas e atualiza só as que estão mais recentes no backup
+    Merge,
+    /// Apenas adiciona linhas que não existem localmente
+    Additive,
+    /// Grava todas as linhas do backup por cima das locais, sem apagar nada
+    Overwrite,
+}
+
+impl RestoreMode {
+    pub const ALL: [RestoreMode; 4] = [
+        RestoreMode::Replace,
+        RestoreMode::Merge,
+        RestoreMode::Additive,
+        RestoreMode::Overwrite,
+    ];
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "replace" => Ok(RestoreMode::Replace),
+            "merge" => Ok(RestoreMode::Merge),
+            "additive" => Ok(RestoreMode::Additive),
+            "overwrite" => Ok(RestoreMode::Overwrite),
+            _ => Err(format!("Modo de restauração inválido: {}", value)),
+        }
+    }
+}
+
+/// Contagem do que a restauração faria em uma tabela
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TableChanges {
+    pub table: String,
+    pub added: usize,
+    pub updated: usize,
+    pub deleted: usize,
+    /// Linhas iguais nos dois lados
+    pub unchanged: usize,
+    /// Linhas diferentes em que a versão local é mantida
+    pub skipped: usize,
+}
+
+/// Situação de uma linha do backup em relação à linha local de mesma chave
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowState {
+    New,
+    Unchanged,
+    /// `newer` indica que a data de alteração do backup é mais recente que a local
+    Differs { newer: bool },
+}
+
+/// O que fazer com a linha do backup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowAction {
+    /// Grava sem sobrescrever conflitos
+    Insert,
+    /// Substitui a linha local
+    Replace,
+    /// Mantém a linha local
+    Keep,
+}
+
+fn column_index(columns: &[String], name: &str) -> Option<usize> {
+    columns.iter().position(|c| c == name)
+}
+
+/// Aceita o formato do CURRENT_TIMESTAMP do SQLite e RFC 3339
+pub fn parse_timestamp(value: &Value) -> Option<NaiveDateTime> {
+    let text = value.as_str()?;
+    NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f"))
+        .ok()
+        .or_else(|| DateTime::parse_from_rfc3339(text).ok().map(|d| d.naive_utc()))
+}
+
+/// Sem data local, a linha do backup é considerada mais recente; sem data no backup, nunca
+fn is_newer(backup: Option<&Value>, local: Option<&Value>) -> bool {
+    match (
+        backup.and_then(parse_timestamp),
+        local.and_then(parse_timestamp),
+    ) {
+        (Some(b), Some(l)) => b > l,
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+/// Compara linhas do backup com as locais de uma tabela, uma por vez.
+/// `ignored_columns` não contam na comparação (ex.: caminhos de arquivos).
+pub struct RowComparer {
+    /// Colunas presentes nos dois lados: (índice no backup, índice local)
+    compared: Vec<(usize, usize)>,
+    timestamp: Option<(usize, usize)>,
+}
+
+impl RowComparer {
+    pub fn new(local_columns: &[String], backup_columns: &[String], ignored_columns: &[&str]) -> Self {
+        let compared = backup_columns
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| !ignored_columns.contains(&c.as_str()))
+            .filter_map(|(b, c)| column_index(local_columns, c).map(|l| (b, l)))
+            .collect();
+
+        let timestamp = TIMESTAMP_COLUMNS.iter().find_map(|c| {
+            Some((
+                column_index(backup_columns, c)?,
+                column_index(local_columns, c)?,
+            ))
+        });
+
+        RowComparer {
+            compared,
+            timestamp,
+        }
+    }
+
+    pub fn compare(&self, backup_row: &[Value], local_row: Option<&[Value]>) -> RowState {
+        let Some(local_row) = local_row else {
+            return RowState::New;
+        };
+
+        let differs = self.compared.iter().any(|&(b, l)| {
+            backup_row.get(b).unwrap_or(&Value::Null) != local_row.get(l).unwrap_or(&Value::Null)
+        });
+        if !differs {
+            return RowState::Unchanged;
+        }
+
+        RowState::Differs {
+            newer: self
+                .timestamp
+                .is_some_and(|(b, l)| is_newer(backup_row.get(b), local_row.get(l))),
+        }
+    }
+}
+
+/// Decide o que o modo faz com a linha. No modo replace a tabela é apagada antes,
+/// então até as linhas iguais precisam ser gravadas de novo.
+pub fn row_action(mode: RestoreMode, state: RowState) -> RowAction {
+    match (mode, state) {
+        (RestoreMode::Replace | RestoreMode::Overwrite, _) => RowAction::Replace,
+        (RestoreMode::Merge | RestoreMode::Additive, RowState::New) => RowAction::Insert,
+        (RestoreMode::Merge, RowState::Differs { newer: true }) => RowAction::Replace,
+        _ => RowAction::Keep,
+    }
+}
+
+impl TableChanges {
+    pub fn new(table: &str) -> Self {
+        TableChanges {
+            table: table.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Conta a linha e retorna a ação que o modo aplica a ela
+    pub fn record(&mut self, mode: RestoreMode, state: RowState) -> RowAction {
+        let action = row_action(mode, state);
+        match state {
+            RowState::New => self.added += 1,
+            RowState::Unchanged => self.unchanged += 1,
+            RowState::Differs { .. } if action == RowAction::Replace => self.updated += 1,
+            RowState::Differs { .. } => self.skipped += 1,
+        }
+        action
+    }
+
+    /// No modo replace, as linhas locais sem correspondente no backup são apagadas
+    pub fn finish(&mut self, mode: RestoreMode, local_rows: usize) {
+        if mode == RestoreMode::Replace {
+            let matched = self.updated + self.unchanged + self.skipped;
+            self.deleted = local_rows.saturating_sub(matched);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+// Cópias do banco pela API de backup online do SQLite, com rotação diária/semanal
+
+use crate::constants;
+use chrono::{Datelike, Local, NaiveDateTime};
+use rusqlite::{Connection, OpenFlags, MAIN_DB};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SNAPSHOT_PREFIX: &str = "library-";
+const SNAPSHOT_EXTENSION: &str = "db";
+const SNAPSHOT_TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotInfo {
+    pub file_name: String,
+    pub path: String,
+    pub created_at: NaiveDateTime,
+    pub size_bytes: u64,
+}
+
+fn parse_snapshot_name(file_name: &str) -> Option<NaiveDateTime> {
+    let stamp = file_name
+        .strip_prefix(SNAPSHOT_PREFIX)?
+        .strip_suffix(&format!(".{}", SNAPSHOT_EXTENSION))?;
+    NaiveDateTime::parse_from_str(stamp, SNAPSHOT_TIME_FORMAT).ok()
+}
+
+/// Cópias da pasta, da mais recente para a mais antiga
+pub fn list_snapshots(dir: &Path) -> Result<Vec<SnapshotInfo>, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Erro ao ler pasta de cópias: {}", e)),
+    };
+
+    let mut snapshots: Vec<SnapshotInfo> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let created_at = parse_snapshot_name(&file_name)?;
+            Some(SnapshotInfo {
+                path: entry.path().to_string_lossy().to_string(),
+                size_bytes: entry.metadata().map(|m| m.len()).unwrap_or(0),
+                file_name,
+                created_at,
+            })
+        })
+        .collect();
+
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+    Ok(snapshots)
+}
+
+/// Copia o banco aberto para um novo arquivo da pasta. A cópia é gravada em um arquivo
+/// temporário e renomeada no fim, para nunca deixar uma cópia pela metade.
+pub fn create_snapshot(conn: &Connection, dir: &Path) -> Result<SnapshotInfo, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Erro ao criar pasta de cópias: {}", e))?;
+
+    let now = Local::now().naive_local();
+    // Cópias no mesmo segundo (ex.: antes de uma restauração) não se sobrescrevem
+    let mut stamp = now;
+    let file_name = loop {
+        let name = format!(
+            "{}{}.{}",
+            SNAPSHOT_PREFIX,
+            stamp.format(SNAPSHOT_TIME_FORMAT),
+            SNAPSHOT_EXTENSION
+        );
+        if !dir.join(&name).exists() {
+            break name;
+        }
+        stamp += chrono::Duration::seconds(1);
+    };
+
+    let path = dir.join(&file_name);
+    let temp_path: PathBuf = dir.join(format!("{}.tmp", file_name));
+
+    conn.backup(MAIN_DB, &temp_path, None)
+        .map_err(|e| format!("Erro ao copiar banco: {}", e))?;
+    fs::rename(&temp_path, &path).map_err(|e| format!("Erro ao salvar cópia: {}", e))?;
+
+    Ok(SnapshotInfo {
+        path: path.to_string_lossy().to_string(),
+        size_bytes: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+        file_name,
+        created_at: parse_snapshot_name(&path.file_name().unwrap_or_default().to_string_lossy())
+            .unwrap_or(now),
+    })
+}
+
+/// `PRAGMA quick_check`: Ok(None) quando o banco está íntegro, senão a descrição do problema
+pub fn check_integrity(conn: &Connection) -> Result<Option<String>, String> {
+    let result: String = conn
+        .query_row("PRAGMA quick_check", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+
+    Ok(if result == "ok" { None } else { Some(result) })
+}
+
+/// Confere se a cópia abre e está íntegra, sem alterá-la
+pub fn verify_snapshot(path: &Path) -> Result<(), String> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Cópia ilegível: {}", e))?;
+    match check_integrity(&conn)? {
+        None => Ok(()),
+        Some(problem) => Err(format!("Cópia corrompida: {}", problem)),
+    }
+}
+
+/// Cópias a manter: as `SNAPSHOT_KEEP_RECENT` mais recentes (cópias do mesmo dia não se
+/// substituem), a mais recente de cada um dos últimos `keep_daily` dias com cópia e
+/// a mais recente de cada uma das últimas `keep_weekly` semanas com cópia
+pub fn select_to_keep(
+    snapshots: &[SnapshotInfo],
+    keep_daily: usize,
+    keep_weekly: usize,
+) -> HashSet<String> {
+    let mut keep = HashSet::new();
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+
+    // A lista já vem da mais recente para a mais antiga
+    for (index, snapshot) in snapshots.iter().enumerate() {
+        if index < constants::SNAPSHOT_KEEP_RECENT {
+            keep.insert(snapshot.file_name.clone());
+        }
+
+
+        let day = snapshot.created_at.date();
+        if days.len() < keep_daily && days.insert(day) {
+            keep.insert(snapshot.file_name.clone());
+        }
+
+        let week = day.iso_week();
+        if weeks.len() < keep_weekly && weeks.insert((week.year(), week.week())) {
+            keep.insert(snapshot.file_name.clone());
+        }
+    }
+
+    keep
+}
+
+/// Apaga as cópias fora da rotação, exceto as de `protected`, e retorna quantas foram removidas
+pub fn rotate(
+    dir: &Path,
+    keep_daily: usize,
+    keep_weekly: usize,
+    protected: &[&str],
+) -> Result<usize, String> {
+    let snapshots = list_snapshots(dir)?;
+    let mut keep = select_to_keep(&snapshots, keep_daily, keep_weekly);
+    keep.extend(protected.iter().map(|name| name.to_string()));
+
+    let mut removed = 0;
+    for snapshot in snapshots.iter().filter(|s| !keep.contains(&s.file_name)) {
+        if fs::remove_file(&snapshot.path).is_ok() {
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
+}
@@ -0,0 +1,174 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+// Agrupamento usado para compras sem data
+const NO_DATE_KEY: &str = "Sem data";
+
+/// Uma cópia com preço pago, junto com o tempo jogado do jogo lógico
+pub struct Purchase {
+    pub game_id: String,
+    pub name: String,
+    pub platform: String,
+    pub purchase_date: Option<String>,
+    pub price: f64,
+    pub currency: Option<String>,
+    /// Horas jogadas (tabela `games`)
+    pub playtime: i32,
+}
+
+/// Converte valores para a moeda base usando a tabela local de cotações
+pub struct Converter {
+    pub base: String,
+    /// moeda -> valor de 1 unidade na moeda base
+    pub rates: HashMap<String, f64>,
+}
+
+impl Converter {
+    /// Valor na moeda base, ou None se não houver cotação para a moeda
+    pub fn to_base(&self, amount: f64, currency: Option<&str>) -> Option<f64> {
+        match currency {
+            None => Some(amount),
+            Some(code) if code.eq_ignore_ascii_case(&self.base) => Some(amount),
+            Some(code) => self
+                .rates
+                .get(&code.to_uppercase())
+                .map(|rate| amount * rate),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpendingGroup {
+    pub key: String,
+    pub total: f64,
+    pub purchases: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GameCost {
+    pub game_id: String,
+    pub name: String,
+    pub spent: f64,
+    pub hours: i32,
+    /// None quando o jogo nunca foi jogado
+    pub cost_per_hour: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpendingReport {
+    pub base_currency: String,
+    pub total_spent: f64,
+    pub by_year: Vec<SpendingGroup>,
+    pub by_platform: Vec<SpendingGroup>,
+    /// Jogos jogados, do maior para o menor custo por hora
+    pub cost_per_hour: Vec<GameCost>,
+    pub average_cost_per_hour: Option<f64>,
+    pub never_played_spent: f64,
+    pub never_played: Vec<GameCost>,
+    /// Moedas sem cotação configurada (as compras nelas ficam fora dos totais)
+    pub missing_rates: Vec<String>,
+}
+
+fn round_money(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn into_groups(map: BTreeMap<String, (f64, i32)>) -> Vec<SpendingGroup> {
+    map.into_iter()
+        .map(|(key, (total, purchases))| SpendingGroup {
+            key,
+            total: round_money(total),
+            purchases,
+        })
+        .collect()
+}
+
+pub fn build_report(purchases: &[Purchase], converter: &Converter) -> SpendingReport {
+    let mut by_year: BTreeMap<String, (f64, i32)> = BTreeMap::new();
+    let mut by_platform: BTreeMap<String, (f64, i32)> = BTreeMap::new();
+    let mut by_game: HashMap<&str, (&str, f64, i32)> = HashMap::new();
+    let mut missing_rates: Vec<String> = Vec::new();
+    let mut total_spent = 0.0;
+
+    for purchase in purchases {
+        let Some(amount) = converter.to_base(purchase.price, purchase.currency.as_deref()) else {
+            if let Some(code) = &purchase.currency {
+                if !missing_rates.contains(code) {
+                    missing_rates.push(code.clone());
+                }
+            }
+            continue;
+        };
+
+        total_spent += amount;
+
+        let year = purchase
+            .purchase_date
+            .as_deref()
+            .and_then(|d| d.get(0..4))
+            .filter(|y| y.chars().all(|c| c.is_ascii_digit()))
+            .unwrap_or(NO_DATE_KEY)
+            .to_string();
+        let entry = by_year.entry(year).or_insert((0.0, 0));
+        entry.0 += amount;
+        entry.1 += 1;
+
+        let entry = by_platform
+            .entry(purchase.platform.clone())
+            .or_insert((0.0, 0));
+        entry.0 += amount;
+        entry.1 += 1;
+
+        let entry = by_game
+            .entry(&purchase.game_id)
+            .or_insert((&purchase.name, 0.0, purchase.playtime));
+        entry.1 += amount;
+    }
+
+    let mut cost_per_hour = Vec::new();
+    let mut never_played = Vec::new();
+    for (game_id, (name, spent, hours)) in by_game {
+        let cost = GameCost {
+            game_id: game_id.to_string(),
+            name: name.to_string(),
+            spent: round_money(spent),
+            hours,
+            cost_per_hour: (hours > 0).then(|| round_money(spent / hours as f64)),
+        };
+        if hours > 0 {
+            cost_per_hour.push(cost);
+        } else {
+            never_played.push(cost);
+        }
+    }
+
+    cost_per_hour.sort_by(|a, b| {
+        b.cost_per_hour
+            .partial_cmp(&a.cost_per_hour)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    never_played.sort_by(|a, b| {
+        b.spent
+            .partial_cmp(&a.spent)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let played_spent: f64 = cost_per_hour.iter().map(|c| c.spent).sum();
+    let played_hours: i32 = cost_per_hour.iter().map(|c| c.hours).sum();
+    let never_played_spent = never_played.iter().map(|c| c.spent).sum();
+
+    missing_rates.sort();
+
+    SpendingReport {
+        base_currency: converter.base.clone(),
+        total_spent: round_money(total_spent),
+        by_year: into_groups(by_year),
+        by_platform: into_groups(by_platform),
+        cost_per_hour,
+        average_cost_per_hour: (played_hours > 0)
+            .then(|| round_money(played_spent / played_hours as f64)),
+        never_played_spent: round_money(never_played_spent),
+        never_played,
+        missing_rates,
+    }
+}
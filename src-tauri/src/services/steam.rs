@@ -1,3 +1,4 @@
+use crate::constants;
 use crate::utils::http_client::HTTP_CLIENT;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -26,8 +27,15 @@ pub struct StoreGenre {
     pub description: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct StoreMetacritic {
+    pub score: i32,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct StoreReleaseDate {
+    #[serde(default)]
+    pub coming_soon: bool,
     pub date: String,
 }
 
@@ -42,9 +50,26 @@ pub struct StorePriceOverview {
     pub discount_percent: i32,
 }
 
+/// Jogo base de uma DLC, trilha sonora ou demo
+#[derive(Debug, Deserialize)]
+pub struct StoreFullGame {
+    /// A loja devolve o AppID como texto ("620")
+    pub appid: serde_json::Value,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct StoreGameDetails {
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub app_type: Option<String>,
+    pub fullgame: Option<StoreFullGame>,
     pub short_description: Option<String>,
+    pub header_image: Option<String>,
+    pub background: Option<String>,
+    pub website: Option<String>,
+    pub developers: Option<Vec<String>>,
+    pub publishers: Option<Vec<String>>,
+    pub metacritic: Option<StoreMetacritic>,
     pub genres: Option<Vec<StoreGenre>>,
     pub release_date: Option<StoreReleaseDate>,
     pub price_overview: Option<StorePriceOverview>,
@@ -56,13 +81,6 @@ pub struct StoreAppResponse {
     pub data: Option<StoreGameDetails>,
 }
 
-#[allow(dead_code)]
-pub struct ProcessedGameData {
-    pub genre: String,
-    pub description: String,
-    pub release_date: String,
-}
-
 #[derive(Debug)]
 pub struct SteamPrice {
     pub currency: String,
@@ -70,6 +88,31 @@ pub struct SteamPrice {
     pub discount_percent: i32,
 }
 
+/// Preço e situação de lançamento de um app, consultados juntos na atualização da wishlist
+#[derive(Debug)]
+pub struct StoreStatus {
+    pub price: Option<SteamPrice>,
+    pub coming_soon: bool,
+    /// Texto exibido pela loja ("14 Nov, 2024", "Q1 2025", "Coming soon"...)
+    pub release_text: Option<String>,
+    /// Data exata, quando o texto da loja permite
+    pub release_date: Option<chrono::NaiveDate>,
+}
+
+/// Tipo de um app na Steam ("game", "dlc", "music"...) e o jogo base, se houver
+#[derive(Debug, Clone)]
+pub struct AppTypeInfo {
+    pub app_type: String,
+    pub parent_app_id: Option<u32>,
+}
+
+/// Nome e tipo de um app, consultados juntos na importação da lista de desejos
+#[derive(Debug, Clone)]
+pub struct AppBasics {
+    pub name: Option<String>,
+    pub app_type: Option<AppTypeInfo>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct StoreSearchItem {
     pub id: u32,
@@ -77,6 +120,45 @@ pub struct StoreSearchItem {
     pub tiny_image: Option<String>,
 }
 
+/// Endereços base da Web API e da loja Steam (os testes apontam para um servidor local)
+#[derive(Debug, Clone)]
+pub struct SteamEndpoints {
+    pub web_api: String,
+    pub store: String,
+}
+
+impl Default for SteamEndpoints {
+    fn default() -> Self {
+        Self {
+            web_api: constants::STEAM_WEB_API_URL.to_string(),
+            store: constants::STEAM_STORE_URL.to_string(),
+        }
+    }
+}
+
+/// Item da lista de desejos pública de um usuário Steam
+#[derive(Debug, Deserialize, Clone)]
+pub struct SteamWishlistItem {
+    pub appid: u32,
+    /// Posição definida pelo usuário na Steam (0 = sem posição)
+    #[serde(default)]
+    pub priority: u32,
+    /// Unix timestamp de quando o jogo foi adicionado
+    #[serde(default)]
+    pub date_added: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct SteamWishlistData {
+    /// Ausente quando o perfil (ou a lista de desejos) é privado
+    items: Option<Vec<SteamWishlistItem>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SteamWishlistResponse {
+    response: SteamWishlistData,
+}
+
 #[derive(Debug, Deserialize)]
 struct StoreSearchResponse {
     #[allow(dead_code)]
@@ -128,57 +210,43 @@ pub async fn list_steam_games(api_key: &str, steam_id: &str) -> Result<Vec<Steam
     Ok(api_data.response.games)
 }
 
-pub async fn fetch_game_metadata(app_id: u32) -> Result<ProcessedGameData, String> {
+#[allow(dead_code)]
+pub async fn search_steam_app_id(game_name: &str) -> Result<Option<u32>, String> {
+    // API oficial de busca da loja Steam
     let url = format!(
-        "https://store.steampowered.com/api/appdetails?appids={}&l=brazilian",
-        app_id
+        "https://store.steampowered.com/api/storesearch/?term={}&l=english&cc=BR",
+        urlencoding::encode(game_name)
     );
 
-    let res: HashMap<String, StoreAppResponse> = HTTP_CLIENT
+    let res = HTTP_CLIENT
         .get(&url)
         .send()
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    if !res.status().is_success() {
+        return Ok(None);
+    }
+
+    let data: StoreSearchResponse = res
         .json()
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| format!("Erro ao decodificar JSON da busca: {}", e))?;
 
-    if let Some(entry) = res.get(&app_id.to_string()) {
-        if entry.success {
-            if let Some(data) = &entry.data {
-                // Pega o primeiro gênero da lista ou "Desconhecido"
-                let genre = data
-                    .genres
-                    .as_ref()
-                    .and_then(|g| g.first())
-                    .map(|g| g.description.clone())
-                    .unwrap_or("Desconhecido".to_string());
-
-                let description = data.short_description.clone().unwrap_or_default();
-                let release = data
-                    .release_date
-                    .as_ref()
-                    .map(|r| r.date.clone())
-                    .unwrap_or_default();
-
-                return Ok(ProcessedGameData {
-                    genre,
-                    description,
-                    release_date: release,
-                });
-            }
+    if let Some(items) = data.items {
+        if let Some(first) = items.first() {
+            return Ok(Some(first.id));
         }
     }
 
-    Err("Dados não encontrados".to_string())
+    Ok(None)
 }
 
-#[allow(dead_code)]
-pub async fn search_steam_app_id(game_name: &str) -> Result<Option<u32>, String> {
-    // API oficial de busca da loja Steam
+// Nova função que retorna LISTA de resultados para o Modal
+pub async fn search_store(query: &str) -> Result<Vec<StoreSearchItem>, String> {
     let url = format!(
         "https://store.steampowered.com/api/storesearch/?term={}&l=english&cc=BR",
-        urlencoding::encode(game_name)
+        urlencoding::encode(query)
     );
 
     let res = HTTP_CLIENT
@@ -188,30 +256,62 @@ pub async fn search_steam_app_id(game_name: &str) -> Result<Option<u32>, String>
         .map_err(|e| e.to_string())?;
 
     if !res.status().is_success() {
-        return Ok(None);
+        return Ok(Vec::new());
     }
 
-    let data: StoreSearchResponse = res
-        .json()
+    let data: StoreSearchResponse = res.json().await.map_err(|e| format!("Erro JSON: {}", e))?;
+
+    Ok(data.items.unwrap_or_default())
+}
+
+/// Lista de desejos pública do usuário, na ordem definida por ele na Steam.
+/// Perfis privados retornam erro, para nunca serem confundidos com uma lista vazia.
+pub async fn fetch_wishlist(
+    endpoints: &SteamEndpoints,
+    steam_id: &str,
+) -> Result<Vec<SteamWishlistItem>, String> {
+    let url = format!(
+        "{}/IWishlistService/GetWishlist/v1/?steamid={}",
+        endpoints.web_api,
+        urlencoding::encode(steam_id)
+    );
+
+    let res = HTTP_CLIENT
+        .get(&url)
+        .send()
         .await
-        .map_err(|e| format!("Erro ao decodificar JSON da busca: {}", e))?;
+        .map_err(|e| format!("Erro na requisição: {}", e))?;
 
-    if let Some(items) = data.items {
-        if let Some(first) = items.first() {
-            return Ok(Some(first.id));
-        }
+    if !res.status().is_success() {
+        return Err(format!("Erro na API Steam: Código {}", res.status()));
     }
 
-    Ok(None)
+    let data: SteamWishlistResponse = res
+        .json()
+        .await
+        .map_err(|e| format!("Erro ao ler JSON da wishlist Steam: {}", e))?;
+
+    let mut items = data.response.items.ok_or(
+        "A Steam não retornou a lista de desejos. O perfil e a lista de desejos precisam ser públicos.",
+    )?;
+
+    // Itens ordenados pelo usuário primeiro; os demais pela data de adição
+    items.sort_by_key(|i| (i.priority == 0, i.priority, i.date_added));
+
+    Ok(items)
 }
 
-pub async fn fetch_price(app_id: u32) -> Result<Option<SteamPrice>, String> {
+/// Nome, tipo e jogo base de um app em uma única consulta (None se o app não existir mais)
+pub async fn fetch_app_basics(
+    endpoints: &SteamEndpoints,
+    app_id: u32,
+) -> Result<Option<AppBasics>, String> {
     let url = format!(
-        "https://store.steampowered.com/api/appdetails?appids={}&cc=br&l=brazilian&filters=price_overview",
-        app_id
+        "{}/api/appdetails?appids={}&l=brazilian&filters=basic",
+        endpoints.store, app_id
     );
 
-    let res: HashMap<String, StoreAppResponse> = HTTP_CLIENT
+    let mut res: HashMap<String, StoreAppResponse> = HTTP_CLIENT
         .get(&url)
         .send()
         .await
@@ -220,41 +320,113 @@ pub async fn fetch_price(app_id: u32) -> Result<Option<SteamPrice>, String> {
         .await
         .map_err(|e| e.to_string())?;
 
-    if let Some(entry) = res.get(&app_id.to_string()) {
-        if entry.success {
-            if let Some(data) = &entry.data {
-                if let Some(overview) = &data.price_overview {
-                    return Ok(Some(SteamPrice {
-                        currency: overview.currency.clone(),
-                        final_price: overview.final_price as f64 / 100.0,
-                        discount_percent: overview.discount_percent,
-                    }));
-                }
-            }
-        }
-    }
+    let Some(data) = res
+        .remove(&app_id.to_string())
+        .filter(|entry| entry.success)
+        .and_then(|entry| entry.data)
+    else {
+        return Ok(None);
+    };
 
-    Ok(None)
+    Ok(Some(AppBasics {
+        app_type: app_type_info(&data),
+        name: data.name,
+    }))
 }
 
-// Nova função que retorna LISTA de resultados para o Modal
-pub async fn search_store(query: &str) -> Result<Vec<StoreSearchItem>, String> {
+/// Converte a data da loja (em inglês) para uma data exata.
+/// Datas vagas como "Q1 2025" ou "Coming soon" retornam None.
+pub fn parse_store_date(text: &str) -> Option<chrono::NaiveDate> {
+    const FORMATS: &[&str] = &["%d %b, %Y", "%b %d, %Y", "%d %B, %Y", "%B %d, %Y", "%Y-%m-%d"];
+
+    let text = text.trim();
+    FORMATS
+        .iter()
+        .find_map(|fmt| chrono::NaiveDate::parse_from_str(text, fmt).ok())
+}
+
+/// Preço (loja BR) e data de lançamento em uma única requisição
+pub async fn fetch_store_status(app_id: u32) -> Result<Option<StoreStatus>, String> {
     let url = format!(
-        "https://store.steampowered.com/api/storesearch/?term={}&l=english&cc=BR",
-        urlencoding::encode(query)
+        "https://store.steampowered.com/api/appdetails?appids={}&cc=br&l=english&filters=price_overview,release_date",
+        app_id
     );
 
-    let res = HTTP_CLIENT
+    let res: HashMap<String, StoreAppResponse> = HTTP_CLIENT
         .get(&url)
         .send()
         .await
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
         .map_err(|e| e.to_string())?;
 
-    if !res.status().is_success() {
-        return Ok(Vec::new());
-    }
+    let Some(data) = res
+        .get(&app_id.to_string())
+        .filter(|entry| entry.success)
+        .and_then(|entry| entry.data.as_ref())
+    else {
+        return Ok(None);
+    };
+
+    let price = data.price_overview.as_ref().map(|overview| SteamPrice {
+        currency: overview.currency.clone(),
+        final_price: overview.final_price as f64 / 100.0,
+        discount_percent: overview.discount_percent,
+    });
+
+    let release_text = data
+        .release_date
+        .as_ref()
+        .map(|r| r.date.trim().to_string())
+        .filter(|d| !d.is_empty());
+
+    Ok(Some(StoreStatus {
+        price,
+        coming_soon: data.release_date.as_ref().is_some_and(|r| r.coming_soon),
+        release_date: release_text.as_deref().and_then(parse_store_date),
+        release_text,
+    }))
+}
 
-    let data: StoreSearchResponse = res.json().await.map_err(|e| format!("Erro JSON: {}", e))?;
+/// Consulta o tipo do app e o jogo base (para DLCs, trilhas sonoras e demos)
+pub async fn fetch_app_type(app_id: u32) -> Result<Option<AppTypeInfo>, String> {
+    Ok(fetch_app_basics(&SteamEndpoints::default(), app_id)
+        .await?
+        .and_then(|basics| basics.app_type))
+}
 
-    Ok(data.items.unwrap_or_default())
+fn app_type_info(data: &StoreGameDetails) -> Option<AppTypeInfo> {
+    let parent_app_id = data.fullgame.as_ref().and_then(|full| match &full.appid {
+        serde_json::Value::String(s) => s.parse::<u32>().ok(),
+        serde_json::Value::Number(n) => n.as_u64().map(|n| n as u32),
+        _ => None,
+    });
+
+    data.app_type.as_ref().map(|app_type| AppTypeInfo {
+        app_type: app_type.to_lowercase(),
+        parent_app_id,
+    })
+}
+
+/// Dados da página da loja usados pelo enriquecimento de metadados
+pub async fn fetch_store_details(app_id: u32) -> Result<Option<StoreGameDetails>, String> {
+    let url = format!(
+        "https://store.steampowered.com/api/appdetails?appids={}&l=brazilian",
+        app_id
+    );
+
+    let mut res: HashMap<String, StoreAppResponse> = HTTP_CLIENT
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(res
+        .remove(&app_id.to_string())
+        .filter(|entry| entry.success)
+        .and_then(|entry| entry.data))
 }
@@ -0,0 +1,270 @@
+use crate::utils::vdf;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// AppIDs que aparecem como "instalados" mas não são jogos (redistribuíveis, runtimes, Proton)
+const IGNORED_APP_IDS: &[u32] = &[228980, 1070560, 1391110, 1628350, 1493710, 2180100];
+const IGNORED_NAME_PREFIXES: &[&str] = &["Proton ", "Steam Linux Runtime", "Steamworks Common"];
+
+#[derive(Debug, Serialize, Clone)]
+pub struct InstalledGame {
+    pub appid: u32,
+    pub name: String,
+    pub install_path: String,
+    pub size_on_disk: i64,
+    pub last_updated: Option<String>,
+}
+
+/// Caminhos padrão da instalação da Steam, em ordem de preferência
+pub fn default_steam_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+
+    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+        roots.push(home.join(".steam/steam"));
+        roots.push(home.join(".local/share/Steam"));
+        // Instalação via Flatpak
+        roots.push(home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"));
+        roots.push(home.join("Library/Application Support/Steam"));
+    }
+
+    roots.push(PathBuf::from(r"C:\Program Files (x86)\Steam"));
+
+    roots
+}
+
+/// Retorna a primeira raiz padrão que contém a pasta `steamapps`
+pub fn detect_steam_root() -> Option<PathBuf> {
+    default_steam_roots()
+        .into_iter()
+        .find(|root| root.join("steamapps").is_dir())
+}
+
+/// Lê `steamapps/libraryfolders.vdf` e devolve todas as bibliotecas conhecidas.
+/// A própria raiz da Steam é sempre considerada uma biblioteca.
+pub fn list_library_folders(steam_root: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut folders = vec![steam_root.to_path_buf()];
+
+    let vdf_path = steam_root.join("steamapps").join("libraryfolders.vdf");
+    if !vdf_path.exists() {
+        return Ok(folders);
+    }
+
+    let content = fs::read_to_string(&vdf_path)
+        .map_err(|e| format!("Erro ao ler {:?}: {}", vdf_path, e))?;
+    let root = vdf::parse(&content)?;
+
+    let libraries = root
+        .get("libraryfolders")
+        .ok_or("Arquivo libraryfolders.vdf sem a chave 'libraryfolders'")?;
+
+    for (key, value) in libraries.entries() {
+        // Apenas as entradas numéricas são bibliotecas ("0", "1", ...)
+        if key.parse::<u32>().is_err() {
+            continue;
+        }
+
+        // Formato novo: objeto com "path"; formato antigo: o valor é o próprio caminho
+        let path = match value {
+            vdf::VdfValue::Str(path) => Some(path.as_str()),
+            vdf::VdfValue::Obj(_) => value.get_str("path"),
+        };
+
+        if let Some(path) = path {
+            let path = PathBuf::from(path);
+            if !folders.iter().any(|f| same_path(f, &path)) {
+                folders.push(path);
+            }
+        }
+    }
+
+    Ok(folders)
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Converte um arquivo `appmanifest_*.acf` em um jogo instalado
+pub fn parse_app_manifest(content: &str, library: &Path) -> Result<Option<InstalledGame>, String> {
+    let root = vdf::parse(content)?;
+    let state = root
+        .get("AppState")
+        .ok_or("Manifesto sem a chave 'AppState'")?;
+
+    let appid = state
+        .get_str("appid")
+        .and_then(|id| id.parse::<u32>().ok())
+        .ok_or("Manifesto sem appid válido")?;
+
+    let name = state
+        .get_str("name")
+        .map(|n| n.to_string())
+        .unwrap_or_else(|| format!("App {}", appid));
+
+    if IGNORED_APP_IDS.contains(&appid)
+        || IGNORED_NAME_PREFIXES.iter().any(|p| name.starts_with(p))
+    {
+        return Ok(None);
+    }
+
+    let install_dir = state.get_str("installdir").unwrap_or_default();
+    let install_path = library.join("steamapps").join("common").join(install_dir);
+
+    let size_on_disk = state
+        .get_str("SizeOnDisk")
+        .and_then(|s| s.parse::<i64>().ok())
+        .unwrap_or(0);
+
+    let last_updated = state
+        .get_str("LastUpdated")
+        .and_then(|s| s.parse::<i64>().ok())
+        .filter(|ts| *ts > 0)
+        .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+        .map(|dt| dt.to_rfc3339());
+
+    Ok(Some(InstalledGame {
+        appid,
+        name,
+        install_path: install_path.to_string_lossy().to_string(),
+        size_on_disk,
+        last_updated,
+    }))
+}
+
+/// Varre todas as bibliotecas da instalação da Steam em busca de jogos instalados.
+/// Manifestos inválidos são ignorados e registrados no log.
+pub fn scan_installed_games(steam_root: &Path) -> Result<Vec<InstalledGame>, String> {
+    if !steam_root.join("steamapps").is_dir() {
+        return Err(format!(
+            "Pasta 'steamapps' não encontrada em {:?}. Verifique o caminho da Steam.",
+            steam_root
+        ));
+    }
+
+    let mut games: Vec<InstalledGame> = Vec::new();
+
+    for library in list_library_folders(steam_root)? {
+        let steamapps = library.join("steamapps");
+        let entries = match fs::read_dir(&steamapps) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!("Biblioteca Steam inacessível {:?}: {}", steamapps, e);
+                continue;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if !file_name.starts_with("appmanifest_") || !file_name.ends_with(".acf") {
+                continue;
+            }
+
+            let parsed = fs::read_to_string(entry.path())
+                .map_err(|e| e.to_string())
+                .and_then(|content| parse_app_manifest(&content, &library));
+
+            match parsed {
+                Ok(Some(game)) => {
+                    if !games.iter().any(|g| g.appid == game.appid) {
+                        games.push(game);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Manifesto ignorado {}: {}", file_name, e),
+            }
+        }
+    }
+
+    games.sort_by_key(|g| g.name.to_lowercase());
+
+    Ok(games)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_dir;
+
+    #[test]
+    fn parses_manifest_fixture() {
+        let library = Path::new("/mnt/games/SteamLibrary");
+        let game = parse_app_manifest(
+            include_str!("../../tests/fixtures/steam/appmanifest_570.acf"),
+            library,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(game.appid, 570);
+        assert_eq!(game.name, "Dota 2");
+        assert_eq!(game.size_on_disk, 38211233123);
+        assert_eq!(
+            PathBuf::from(&game.install_path),
+            library.join("steamapps").join("common").join("dota 2 beta")
+        );
+        assert!(game.last_updated.unwrap().starts_with("2024-10-15"));
+    }
+
+    #[test]
+    fn skips_redistributables() {
+        let parsed = parse_app_manifest(
+            include_str!("../../tests/fixtures/steam/appmanifest_228980.acf"),
+            Path::new("/tmp"),
+        )
+        .unwrap();
+
+        assert!(parsed.is_none());
+    }
+
+    #[test]
+    fn scans_every_library_and_ignores_broken_manifests() {
+        let root = temp_dir("steam_root");
+        let extra = temp_dir("steam_extra");
+        fs::create_dir_all(root.join("steamapps")).unwrap();
+        fs::create_dir_all(extra.join("steamapps")).unwrap();
+
+        // Caminhos das bibliotecas trocados pelos diretórios temporários
+        let folders = include_str!("../../tests/fixtures/steam/libraryfolders.vdf")
+            .replace("/home/user/.local/share/Steam", &root.to_string_lossy())
+            .replace("/mnt/games/SteamLibrary", &extra.to_string_lossy());
+        fs::write(root.join("steamapps/libraryfolders.vdf"), folders).unwrap();
+
+        fs::write(
+            root.join("steamapps/appmanifest_570.acf"),
+            include_str!("../../tests/fixtures/steam/appmanifest_570.acf"),
+        )
+        .unwrap();
+        fs::write(
+            root.join("steamapps/appmanifest_228980.acf"),
+            include_str!("../../tests/fixtures/steam/appmanifest_228980.acf"),
+        )
+        .unwrap();
+        fs::write(
+            extra.join("steamapps/appmanifest_1091500.acf"),
+            include_str!("../../tests/fixtures/steam/appmanifest_broken.acf"),
+        )
+        .unwrap();
+        fs::write(
+            extra.join("steamapps/appmanifest_400.acf"),
+            "\"AppState\" { \"appid\" \"400\" \"name\" \"Portal\" \"installdir\" \"Portal\" }",
+        )
+        .unwrap();
+
+        let libraries = list_library_folders(&root).unwrap();
+        assert_eq!(libraries.len(), 2);
+
+        let games = scan_installed_games(&root).unwrap();
+        let names: Vec<&str> = games.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, vec!["Dota 2", "Portal"]);
+        assert!(games[1].install_path.starts_with(&*extra.to_string_lossy()));
+
+        assert!(scan_installed_games(&extra.join("missing")).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+        let _ = fs::remove_dir_all(&extra);
+    }
+}
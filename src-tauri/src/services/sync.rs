@@ -0,0 +1,421 @@
+// Sincronização entre dispositivos: conjuntos de alterações, resolução de conflitos e transportes
+//
+// Cada dispositivo publica as linhas alteradas (com a data da alteração) e as lápides das linhas
+// excluídas desde o último envio, e aplica as dos outros. O conflito é resolvido linha a linha
+// pela alteração mais recente; empates são decididos pelo conteúdo, então todos os dispositivos
+// chegam ao mesmo resultado qualquer que seja a ordem em que recebem os conjuntos.
+
+use crate::constants;
+use crate::services::restore::parse_timestamp;
+use crate::utils::http_client::HTTP_CLIENT;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use tracing::warn;
+
+/// Colunas que só fazem sentido no próprio dispositivo e não são enviadas
+const LOCAL_ONLY_COLUMNS: &[(&str, &str)] = &[("games", "installed"), ("game_ownership", "id")];
+
+/// `updated_at` viaja separado, como a data da alteração
+pub fn is_synced_column(table: &str, column: &str) -> bool {
+    column != "updated_at" && !LOCAL_ONLY_COLUMNS.contains(&(table, column))
+}
+
+/// Alteração de uma linha: o conteúdo novo ou, em exclusões, só a chave
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Change {
+    pub table: String,
+    pub key: Vec<Value>,
+    pub modified_at: Option<String>,
+    /// None quando a linha foi excluída (lápide)
+    pub row: Option<BTreeMap<String, Value>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeSet {
+    pub format: String,
+    pub version: u32,
+    pub device_id: String,
+    pub created_at: String,
+    pub changes: Vec<Change>,
+}
+
+impl ChangeSet {
+    pub fn new(device_id: &str, changes: Vec<Change>) -> Self {
+        ChangeSet {
+            format: constants::SYNC_FORMAT_NAME.to_string(),
+            version: constants::SYNC_FORMAT_VERSION,
+            device_id: device_id.to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            changes,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.format != constants::SYNC_FORMAT_NAME {
+            return Err("Conjunto de alterações com formato desconhecido".to_string());
+        }
+        if self.version > constants::SYNC_FORMAT_VERSION {
+            return Err(format!(
+                "Conjunto de alterações de uma versão mais nova ({}): atualize o aplicativo",
+                self.version
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Chave da linha como texto, no mesmo formato do `json_array` dos gatilhos de exclusão
+pub fn row_key(key: &[Value]) -> String {
+    serde_json::to_string(key).unwrap_or_default()
+}
+
+/// Estado local da linha alterada
+pub enum LocalState {
+    Missing,
+    Row {
+        modified_at: Option<String>,
+        row: BTreeMap<String, Value>,
+    },
+    Deleted {
+        deleted_at: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// A alteração recebida vence e é aplicada
+    Apply,
+    /// A versão local vence
+    Keep,
+    /// Os dois lados já são iguais
+    Unchanged,
+}
+
+/// Ordem total das versões de uma linha: data da alteração, depois exclusão antes de conteúdo,
+/// depois o próprio conteúdo
+fn rank(
+    modified_at: Option<&str>,
+    row: Option<&BTreeMap<String, Value>>,
+) -> (Option<NaiveDateTime>, bool, String) {
+    (
+        modified_at.and_then(|t| parse_timestamp(&Value::from(t))),
+        row.is_none(),
+        row.map(|r| serde_json::to_string(r).unwrap_or_default())
+            .unwrap_or_default(),
+    )
+}
+
+/// Decide entre a alteração recebida e a versão local da linha
+pub fn resolve(change: &Change, local: &LocalState) -> Resolution {
+    let (local_modified, local_row) = match local {
+        LocalState::Missing => return Resolution::Apply,
+        LocalState::Row { modified_at, row } => {
+            // Compara só as colunas enviadas: versões diferentes do app podem ter colunas a mais
+            let shared: BTreeMap<String, Value> = match &change.row {
+                Some(remote) => remote
+                    .keys()
+                    .map(|k| (k.clone(), row.get(k).cloned().unwrap_or(Value::Null)))
+                    .collect(),
+                None => row.clone(),
+            };
+            if change.row.as_ref() == Some(&shared) {
+                return Resolution::Unchanged;
+            }
+            (modified_at.as_deref(), Some(shared))
+        }
+        LocalState::Deleted { deleted_at } => (Some(deleted_at.as_str()), None),
+    };
+
+    let remote = rank(change.modified_at.as_deref(), change.row.as_ref());
+    let local = rank(local_modified, local_row.as_ref());
+    match remote.cmp(&local) {
+        Ordering::Greater => Resolution::Apply,
+        Ordering::Equal => Resolution::Unchanged,
+        Ordering::Less => Resolution::Keep,
+    }
+}
+
+/// Posição já lida de cada fonte (por dispositivo na pasta, ou a sequência do servidor)
+pub type SyncCursor = BTreeMap<String, u64>;
+
+pub type SyncFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 'a>>;
+
+/// Destino onde os dispositivos publicam e buscam conjuntos de alterações
+pub trait SyncTransport: Send + Sync {
+    fn push<'a>(&'a self, set: &'a ChangeSet) -> SyncFuture<'a, ()>;
+    /// Conjuntos dos outros dispositivos posteriores ao cursor, e o cursor atualizado
+    fn pull<'a>(
+        &'a self,
+        device_id: &'a str,
+        cursor: &'a SyncCursor,
+    ) -> SyncFuture<'a, (Vec<ChangeSet>, SyncCursor)>;
+}
+
+/// Pasta compartilhada (Syncthing, NAS...): `playlite-sync/{dispositivo}/{sequência}.json`.
+/// Cada dispositivo só escreve na própria subpasta, então não há disputa por arquivos.
+pub struct FolderTransport {
+    pub root: PathBuf,
+}
+
+fn sequence_of(path: &std::path::Path) -> Option<u64> {
+    if path.extension().and_then(|e| e.to_str()) != Some("json") {
+        return None;
+    }
+    path.file_stem()?.to_str()?.parse().ok()
+}
+
+impl FolderTransport {
+    fn base(&self) -> PathBuf {
+        self.root.join(constants::SYNC_FOLDER_DIR)
+    }
+
+    /// Arquivos de conjunto da subpasta, em ordem de sequência
+    fn list_sets(dir: &std::path::Path) -> Result<Vec<(u64, PathBuf)>, String> {
+        let mut sets: Vec<(u64, PathBuf)> = fs::read_dir(dir)
+            .map_err(|e| format!("Erro ao ler pasta de sincronização: {}", e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter_map(|path| sequence_of(&path).map(|seq| (seq, path)))
+            .collect();
+        sets.sort();
+        Ok(sets)
+    }
+}
+
+impl SyncTransport for FolderTransport {
+    fn push<'a>(&'a self, set: &'a ChangeSet) -> SyncFuture<'a, ()> {
+        Box::pin(async move {
+            if !self.root.is_dir() {
+                return Err("Pasta de sincronização não encontrada".to_string());
+            }
+            let dir = self.base().join(&set.device_id);
+            fs::create_dir_all(&dir).map_err(|e| format!("Erro ao criar pasta: {}", e))?;
+
+            let next = Self::list_sets(&dir)?
+                .last()
+                .map(|(seq, _)| seq + 1)
+                .unwrap_or(1);
+            let target = dir.join(format!("{:012}.json", next));
+            // Grava com outro nome e renomeia: quem sincroniza a pasta nunca vê o arquivo pela metade
+            let partial = dir.join(format!("{:012}.json.partial", next));
+
+            let json = serde_json::to_vec(set).map_err(|e| e.to_string())?;
+            fs::write(&partial, json).map_err(|e| format!("Erro ao gravar alterações: {}", e))?;
+            fs::rename(&partial, &target).map_err(|e| format!("Erro ao gravar alterações: {}", e))
+        })
+    }
+
+    fn pull<'a>(
+        &'a self,
+        device_id: &'a str,
+        cursor: &'a SyncCursor,
+    ) -> SyncFuture<'a, (Vec<ChangeSet>, SyncCursor)> {
+        Box::pin(async move {
+            if !self.root.is_dir() {
+                return Err("Pasta de sincronização não encontrada".to_string());
+            }
+            let mut cursor = cursor.clone();
+            let mut sets = Vec::new();
+            let base = self.base();
+            if !base.is_dir() {
+                return Ok((sets, cursor));
+            }
+
+            for entry in fs::read_dir(&base).map_err(|e| e.to_string())?.flatten() {
+                let Some(device) = entry.file_name().to_str().map(String::from) else {
+                    continue;
+                };
+                if device == device_id || !entry.path().is_dir() {
+                    continue;
+                }
+
+                let seen = cursor.get(&device).copied().unwrap_or(0);
+                for (seq, path) in Self::list_sets(&entry.path())? {
+                    if seq <= seen {
+                        continue;
+                    }
+                    // Arquivo ilegível (cópia de conflito, sincronização incompleta) fica para depois
+                    let set = fs::read(&path)
+                        .map_err(|e| e.to_string())
+                        .and_then(|bytes| {
+                            serde_json::from_slice::<ChangeSet>(&bytes).map_err(|e| e.to_string())
+                        });
+                    match set {
+                        Ok(set) => {
+                            sets.push(set);
+                            cursor.insert(device.clone(), seq);
+                        }
+                        Err(e) => {
+                            warn!("Conjunto de alterações ignorado ({:?}): {}", path, e);
+                            break;
+                        }
+                    }
+                }
+            }
+
+            Ok((sets, cursor))
+        })
+    }
+}
+
+/// Servidor HTTP próprio. Protocolo (ver docs/sync.md):
+/// `POST {url}/changesets` com o conjunto em JSON e
+/// `GET {url}/changesets?after={n}&exclude={dispositivo}` retornando `{ cursor, changesets }`.
+pub struct HttpTransport {
+    pub url: String,
+    pub token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PullResponse {
+    cursor: u64,
+    changesets: Vec<ChangeSet>,
+}
+
+// Chave do cursor de servidores HTTP (a sequência é global no servidor)
+const SERVER_CURSOR: &str = "server";
+
+impl HttpTransport {
+    fn endpoint(&self) -> String {
+        format!("{}/changesets", self.url.trim_end_matches('/'))
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+impl SyncTransport for HttpTransport {
+    fn push<'a>(&'a self, set: &'a ChangeSet) -> SyncFuture<'a, ()> {
+        Box::pin(async move {
+            let res = self
+                .authorize(HTTP_CLIENT.post(self.endpoint()).json(set))
+                .send()
+                .await
+                .map_err(|e| format!("Erro ao enviar alterações: {}", e))?;
+            if !res.status().is_success() {
+                return Err(format!("Servidor de sincronização recusou o envio: {}", res.status()));
+            }
+            Ok(())
+        })
+    }
+
+    fn pull<'a>(
+        &'a self,
+        device_id: &'a str,
+        cursor: &'a SyncCursor,
+    ) -> SyncFuture<'a, (Vec<ChangeSet>, SyncCursor)> {
+        Box::pin(async move {
+            let after = cursor.get(SERVER_CURSOR).copied().unwrap_or(0);
+            let res = self
+                .authorize(HTTP_CLIENT.get(self.endpoint()).query(&[
+                    ("after", after.to_string()),
+                    ("exclude", device_id.to_string()),
+                ]))
+                .send()
+                .await
+                .map_err(|e| format!("Erro ao buscar alterações: {}", e))?;
+            if !res.status().is_success() {
+                return Err(format!("Servidor de sincronização recusou a busca: {}", res.status()));
+            }
+
+            let body: PullResponse = res
+                .json()
+                .await
+                .map_err(|e| format!("Resposta inválida do servidor de sincronização: {}", e))?;
+
+            let mut cursor = cursor.clone();
+            cursor.insert(SERVER_CURSOR.to_string(), body.cursor.max(after));
+            Ok((body.changesets, cursor))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn row(rating: i64) -> BTreeMap<String, Value> {
+        BTreeMap::from([
+            ("id".to_string(), json!("hades")),
+            ("rating".to_string(), json!(rating)),
+        ])
+    }
+
+    fn change(modified_at: &str, row: Option<BTreeMap<String, Value>>) -> Change {
+        Change {
+            table: "games".to_string(),
+            key: vec![json!("hades")],
+            modified_at: Some(modified_at.to_string()),
+            row,
+        }
+    }
+
+    fn local(modified_at: &str, row: BTreeMap<String, Value>) -> LocalState {
+        LocalState::Row {
+            modified_at: Some(modified_at.to_string()),
+            row,
+        }
+    }
+
+    #[test]
+    fn rank_orders_by_date_then_deletion_then_content() {
+        let older = rank(Some("2024-01-01 10:00:00"), Some(&row(5)));
+        let newer = rank(Some("2024-01-01 10:00:01"), Some(&row(1)));
+        assert!(newer > older);
+        // Sem data perde para qualquer data
+        assert!(rank(None, Some(&row(5))) < older);
+        // Mesma data: a exclusão vence o conteúdo, e entre conteúdos decide o próprio conteúdo
+        let deleted = rank(Some("2024-01-01 10:00:00"), None);
+        assert!(deleted > older);
+        assert!(rank(Some("2024-01-01 10:00:00"), Some(&row(4))) < older);
+        // Formatos de data diferentes para o mesmo instante empatam na data
+        assert_eq!(rank(Some("2024-01-01T10:00:00"), None), deleted);
+    }
+
+    #[test]
+    fn resolve_picks_the_latest_version() {
+        let t1 = "2024-01-01 10:00:00";
+        let t2 = "2024-01-02 10:00:00";
+
+        assert_eq!(resolve(&change(t1, Some(row(5))), &LocalState::Missing), Resolution::Apply);
+        assert_eq!(resolve(&change(t2, Some(row(5))), &local(t1, row(3))), Resolution::Apply);
+        assert_eq!(resolve(&change(t1, Some(row(5))), &local(t2, row(3))), Resolution::Keep);
+        assert_eq!(resolve(&change(t1, None), &local(t2, row(3))), Resolution::Keep);
+        assert_eq!(resolve(&change(t2, None), &local(t1, row(3))), Resolution::Apply);
+
+        let tombstone = |at: &str| LocalState::Deleted {
+            deleted_at: at.to_string(),
+        };
+        assert_eq!(resolve(&change(t2, Some(row(5))), &tombstone(t1)), Resolution::Apply);
+        assert_eq!(resolve(&change(t1, Some(row(5))), &tombstone(t2)), Resolution::Keep);
+        assert_eq!(resolve(&change(t1, None), &tombstone(t1)), Resolution::Unchanged);
+    }
+
+    #[test]
+    fn resolve_ignores_local_only_columns_and_converges_on_ties() {
+        let t = "2024-01-01 10:00:00";
+
+        // Coluna que só existe na versão local do aplicativo não conta como diferença
+        let mut wider = row(5);
+        wider.insert("extra".to_string(), json!("x"));
+        assert_eq!(resolve(&change("2020-01-01 00:00:00", Some(row(5))), &local(t, wider)), Resolution::Unchanged);
+
+        // Empate na data: cada lado chega à mesma versão vencedora
+        let here = resolve(&change(t, Some(row(5))), &local(t, row(3)));
+        let there = resolve(&change(t, Some(row(3))), &local(t, row(5)));
+        assert_ne!(here, there);
+        assert!(matches!(here, Resolution::Apply | Resolution::Keep));
+        assert!(matches!(there, Resolution::Apply | Resolution::Keep));
+    }
+}
@@ -75,5 +75,5 @@ pub fn delete_secret(app: &AppHandle, key_name: &str) -> Result<(), String> {
 
 /// Lista de chaves suportadas pela aplicação
 pub fn list_supported_keys() -> Vec<&'static str> {
-    vec!["steam_id", "steam_api_key", "rawg_api_key"]
+    vec!["steam_id", "steam_api_key", "rawg_api_key", "sync_token"]
 }
@@ -0,0 +1,158 @@
+// Utilitários compartilhados pelos testes
+
+use rusqlite::Connection;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fs;
+use std::path::PathBuf;
+
+/// Banco em memória com o esquema completo
+pub fn memory_db() -> Connection {
+    let conn = Connection::open_in_memory().expect("banco em memória");
+    crate::database::create_schema(&conn).expect("esquema");
+    conn
+}
+
+/// Alocador dos testes: conta, por thread, os bytes alocados pelo Rust (não pelo SQLite)
+struct TrackingAllocator;
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
+thread_local! {
+    static ALLOCATED: Cell<isize> = const { Cell::new(0) };
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+}
+
+fn track(delta: isize) {
+    // try_with: a thread pode estar sendo encerrada
+    let _ = ALLOCATED.try_with(|allocated| {
+        let now = allocated.get() + delta;
+        allocated.set(now);
+        let _ = PEAK.try_with(|peak| peak.set(peak.get().max(now)));
+    });
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            track(layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            track(layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        track(-(layout.size() as isize));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            track(new_size as isize - layout.size() as isize);
+        }
+        new_ptr
+    }
+}
+
+/// Executa `work` e retorna o pico de memória que ele alocou além do que já estava em uso
+/// na thread atual
+pub fn peak_allocation<T>(work: impl FnOnce() -> T) -> (T, usize) {
+    let baseline = ALLOCATED.with(Cell::get);
+    PEAK.with(|peak| peak.set(baseline));
+    let result = work();
+    let peak = PEAK.with(Cell::get);
+    (result, (peak - baseline).max(0) as usize)
+}
+
+/// Pasta temporária vazia, exclusiva do teste e do processo
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("playlite_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("pasta temporária");
+    dir
+}
+
+/// Requisição recebida pelo servidor HTTP de testes
+pub struct HttpRequest {
+    pub method: String,
+    /// Caminho com a query string ("/api/appdetails?appids=10")
+    pub path: String,
+    pub body: Vec<u8>,
+}
+
+/// Servidor HTTP local mínimo (uma conexão por requisição) que responde com `handler`.
+/// Retorna o endereço base ("http://127.0.0.1:porta").
+pub fn serve_http<F>(handler: F) -> String
+where
+    F: Fn(&HttpRequest) -> (u16, String) + Send + 'static,
+{
+    serve_http_bytes(move |request| {
+        let (status, body) = handler(request);
+        (status, body.into_bytes())
+    })
+}
+
+/// Igual a `serve_http`, com corpo binário na resposta (imagens, arquivos)
+pub fn serve_http_bytes<F>(handler: F) -> String
+where
+    F: Fn(&HttpRequest) -> (u16, Vec<u8>) + Send + 'static,
+{
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("porta local");
+    let address = format!("http://{}", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut request_line = String::new();
+            if reader.read_line(&mut request_line).is_err() {
+                continue;
+            }
+            let mut parts = request_line.split_whitespace();
+            let method = parts.next().unwrap_or_default().to_string();
+            let path = parts.next().unwrap_or_default().to_string();
+
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).is_err() || line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            if reader.read_exact(&mut body).is_err() {
+                continue;
+            }
+
+            let (status, response) = handler(&HttpRequest { method, path, body });
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                response.len()
+            );
+            let _ = stream.write_all(&response);
+        }
+    });
+
+    address
+}
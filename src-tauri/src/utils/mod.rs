@@ -1,2 +1,4 @@
 pub mod http_client;
 pub mod logger;
+pub mod vdf;
+pub mod text;
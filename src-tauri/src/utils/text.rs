@@ -0,0 +1,237 @@
+// Normalização de nomes de jogos para comparação entre fontes diferentes
+
+// Sufixos de edição que não mudam o jogo em si (comparados já normalizados, do mais longo ao mais curto)
+const EDITION_SUFFIXES: &[&str] = &[
+    "game of the year edition",
+    "game of the year",
+    "goty edition",
+    "goty",
+    "definitive edition",
+    "complete edition",
+    "deluxe edition",
+    "gold edition",
+    "ultimate edition",
+    "enhanced edition",
+    "special edition",
+    "standard edition",
+    "anniversary edition",
+    "collectors edition",
+    "directors cut",
+];
+
+/// Remove acentos das letras latinas mais comuns (á -> a, ç -> c...)
+pub fn fold_accents(input: &str) -> String {
+    input
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' | 'ä' | 'å' => 'a',
+            'Á' | 'À' | 'Â' | 'Ã' | 'Ä' | 'Å' => 'A',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'É' | 'È' | 'Ê' | 'Ë' => 'E',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'Í' | 'Ì' | 'Î' | 'Ï' => 'I',
+            'ó' | 'ò' | 'ô' | 'õ' | 'ö' | 'ø' => 'o',
+            'Ó' | 'Ò' | 'Ô' | 'Õ' | 'Ö' | 'Ø' => 'O',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'Ú' | 'Ù' | 'Û' | 'Ü' => 'U',
+            'ç' => 'c',
+            'Ç' => 'C',
+            'ñ' => 'n',
+            'Ñ' => 'N',
+            'ý' | 'ÿ' => 'y',
+            'Ý' => 'Y',
+            other => other,
+        })
+        .collect()
+}
+
+/// Minúsculas, sem acentos, sem símbolos de marca e sem pontuação, com espaços simples.
+/// Ex: "Assassin's Creed® Odyssey" -> "assassins creed odyssey"
+pub fn normalize_name(name: &str) -> String {
+    let folded = fold_accents(&name.to_lowercase());
+
+    let mut out = String::with_capacity(folded.len());
+    for c in folded.chars() {
+        match c {
+            '™' | '®' | '©' | '\'' | '’' => {}
+            '&' => out.push_str(" and "),
+            c if c.is_alphanumeric() => out.push(c),
+            _ => out.push(' '),
+        }
+    }
+
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Igual a `normalize_name`, removendo também sufixos de edição ("GOTY", "Definitive Edition"...)
+pub fn normalize_title(name: &str) -> String {
+    let mut normalized = normalize_name(name);
+
+    loop {
+        let before = normalized.len();
+        for suffix in EDITION_SUFFIXES {
+            if let Some(stripped) = normalized.strip_suffix(suffix) {
+                // O sufixo precisa ser uma palavra inteira ("goty", não "...goty")
+                if stripped.ends_with(' ') && !stripped.trim().is_empty() {
+                    normalized = stripped.trim_end().to_string();
+                    break;
+                }
+            }
+        }
+        if normalized.len() == before {
+            break;
+        }
+    }
+
+    normalized
+}
+
+/// Converte um token em numeral romano (até XX) para arábico ("ii" -> "2").
+/// "x" sozinho fica de fora: é letra em nomes demais ("Mega Man X", "Sonic X").
+fn roman_to_arabic(token: &str) -> Option<u32> {
+    const NUMERALS: &[&str] = &[
+        "i", "ii", "iii", "iv", "v", "vi", "vii", "viii", "ix", "x", "xi", "xii", "xiii", "xiv",
+        "xv", "xvi", "xvii", "xviii", "xix", "xx",
+    ];
+    if token == "x" {
+        return None;
+    }
+    NUMERALS
+        .iter()
+        .position(|n| *n == token)
+        .map(|i| i as u32 + 1)
+}
+
+/// Início do subtítulo ("Final Fantasy VII: Remake", "Hitman II - Gold"), se houver
+fn subtitle_start(name: &str) -> Option<usize> {
+    [":", " - ", " – ", " — "]
+        .iter()
+        .filter_map(|sep| name.find(sep))
+        .min()
+}
+
+/// Chave usada para comparar nomes de fontes diferentes: `normalize_title` com numerais
+/// romanos convertidos ("Hades II" e "Hades 2" -> "hades 2"). Só a palavra na posição de
+/// sequência é convertida: a última do título principal (antes do subtítulo) ou a última
+/// do nome, e nunca a primeira ("V Rising" e "Mega Man X" continuam como estão).
+pub fn match_key(name: &str) -> String {
+    let mut tokens: Vec<String> = normalize_title(name)
+        .split(' ')
+        .map(str::to_string)
+        .collect();
+
+    let main_len = subtitle_start(name)
+        .map(|end| normalize_name(&name[..end]).split_whitespace().count())
+        .unwrap_or(tokens.len())
+        .min(tokens.len());
+    let mut positions = vec![main_len.saturating_sub(1), tokens.len() - 1];
+    positions.dedup();
+
+    for position in positions.into_iter().filter(|&p| p > 0) {
+        if let Some(n) = roman_to_arabic(&tokens[position]) {
+            tokens[position] = n.to_string();
+        }
+    }
+
+    tokens.join(" ")
+}
+
+/// Similaridade entre 0 e 1 pela distância de edição (Levenshtein) entre as chaves dos nomes
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = match_key(a).chars().collect();
+    let b: Vec<char> = match_key(b).chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_name_folds_case_accents_and_symbols() {
+        assert_eq!(normalize_name("Assassin's Creed® Odyssey"), "assassins creed odyssey");
+        assert_eq!(normalize_name("  Pokémon:   Édition  "), "pokemon edition");
+        assert_eq!(normalize_name("Ratchet & Clank"), "ratchet and clank");
+        assert_eq!(normalize_name("HADES™"), "hades");
+        assert_eq!(normalize_name("!!!"), "");
+    }
+
+    #[test]
+    fn normalize_title_strips_only_whole_edition_suffixes() {
+        assert_eq!(normalize_title("Hades GOTY"), "hades");
+        assert_eq!(normalize_title("Hades - Game of the Year Edition"), "hades");
+        assert_eq!(normalize_title("Skyrim Special Edition"), "skyrim");
+        // Sufixos empilhados saem todos
+        assert_eq!(normalize_title("Fallout 4 GOTY Deluxe Edition"), "fallout 4");
+        // "goty" dentro de uma palavra ou no meio do nome fica
+        assert_eq!(normalize_title("Argoty"), "argoty");
+        assert_eq!(normalize_title("GOTY Quest"), "goty quest");
+        // Nome que é só sufixo não vira vazio
+        assert_eq!(normalize_title("Game of the Year Edition"), "game of the year edition");
+        assert_eq!(normalize_title("GOTY"), "goty");
+        assert_eq!(normalize_title("Definitive Edition GOTY"), "definitive edition");
+    }
+
+    #[test]
+    fn match_key_converts_only_sequel_numerals() {
+        let corpus = [
+            ("Hades II", "hades 2"),
+            ("Final Fantasy VII Remake", "final fantasy vii remake"),
+            ("Final Fantasy VII: Remake", "final fantasy 7 remake"),
+            ("Grand Theft Auto V", "grand theft auto 5"),
+            ("Hitman II - Gold Edition", "hitman 2"),
+            ("The Witcher III: Wild Hunt - Game of the Year Edition", "the witcher 3 wild hunt"),
+            ("Mega Man X", "mega man x"),
+            ("Mega Man X: Command Mission", "mega man x command mission"),
+            ("V Rising", "v rising"),
+            ("I Am Bread", "i am bread"),
+            ("Civilization VI", "civilization 6"),
+            ("Star Wars: Episode I Racer", "star wars episode i racer"),
+            ("Rocky", "rocky"),
+            ("X", "x"),
+            ("", ""),
+        ];
+
+        for (title, key) in corpus {
+            assert_eq!(match_key(title), key, "título: {title:?}");
+        }
+    }
+
+    #[test]
+    fn similarity_separates_lookalike_titles() {
+        let same = [
+            ("Hades II", "Hades 2"),
+            ("The Witcher 3: Wild Hunt", "The Witcher III: Wild Hunt – Complete Edition"),
+            ("Grand Theft Auto V", "Grand Theft Auto 5"),
+            ("Assassin's Creed® Odyssey", "Assassins Creed Odyssey"),
+            ("DOOM (2016)", "Doom 2016"),
+        ];
+        for (a, b) in same {
+            assert!(similarity(a, b) >= 0.75, "{a:?} ~ {b:?}: {}", similarity(a, b));
+        }
+
+        assert_eq!(similarity("Hades II", "Hades 2"), 1.0);
+        assert!(similarity("Mega Man X", "Mega Man 10") < 1.0);
+        assert!(similarity("Hades", "Hades II") < similarity("Hades II", "Hades 2"));
+        assert!(similarity("Portal", "Portal 2") < 1.0);
+        assert_eq!(similarity("", ""), 0.0);
+    }
+}
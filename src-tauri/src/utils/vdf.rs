@@ -0,0 +1,235 @@
+// Parser mínimo para o formato KeyValues (VDF/ACF) usado pelo cliente Steam
+// Ex: libraryfolders.vdf e appmanifest_*.acf
+
+#[derive(Debug, Clone)]
+pub enum VdfValue {
+    Str(String),
+    Obj(Vec<(String, VdfValue)>),
+}
+
+impl VdfValue {
+    /// Busca uma chave filha (case-insensitive, como o próprio cliente Steam faz)
+    pub fn get(&self, key: &str) -> Option<&VdfValue> {
+        match self {
+            VdfValue::Obj(entries) => entries
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v),
+            VdfValue::Str(_) => None,
+        }
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(|v| v.as_str())
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            VdfValue::Str(s) => Some(s),
+            VdfValue::Obj(_) => None,
+        }
+    }
+
+    pub fn entries(&self) -> &[(String, VdfValue)] {
+        match self {
+            VdfValue::Obj(entries) => entries,
+            VdfValue::Str(_) => &[],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Text(String),
+    Open,
+    Close,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '{' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            '}' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '/' => {
+                // Comentário de linha (// ...)
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                } else {
+                    return Err("Caractere '/' inesperado no arquivo VDF".to_string());
+                }
+            }
+            '[' => {
+                // Condicionais de plataforma ([$WIN32], [$LINUX]...) são ignoradas
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                }
+            }
+            '"' => {
+                chars.next();
+                let mut text = String::new();
+                let mut closed = false;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => match chars.next() {
+                            Some('n') => text.push('\n'),
+                            Some('t') => text.push('\t'),
+                            Some(other) => text.push(other),
+                            None => break,
+                        },
+                        '"' => {
+                            closed = true;
+                            break;
+                        }
+                        _ => text.push(c),
+                    }
+                }
+                if !closed {
+                    return Err("String não terminada no arquivo VDF".to_string());
+                }
+                tokens.push(Token::Text(text));
+            }
+            _ => {
+                // Token sem aspas: vai até o próximo espaço ou delimitador
+                let mut text = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '{' || c == '}' || c == '"' {
+                        break;
+                    }
+                    text.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Text(text));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_entries<I>(tokens: &mut I, nested: bool) -> Result<Vec<(String, VdfValue)>, String>
+where
+    I: Iterator<Item = Token>,
+{
+    let mut entries = Vec::new();
+
+    loop {
+        match tokens.next() {
+            Some(Token::Text(key)) => match tokens.next() {
+                Some(Token::Text(value)) => entries.push((key, VdfValue::Str(value))),
+                Some(Token::Open) => {
+                    let children = parse_entries(tokens, true)?;
+                    entries.push((key, VdfValue::Obj(children)));
+                }
+                _ => return Err(format!("Valor ausente para a chave '{}'", key)),
+            },
+            Some(Token::Close) if nested => return Ok(entries),
+            Some(Token::Close) => return Err("'}' sem '{' correspondente".to_string()),
+            Some(Token::Open) => return Err("'{' sem chave correspondente".to_string()),
+            None if nested => return Err("Bloco não fechado no arquivo VDF".to_string()),
+            None => return Ok(entries),
+        }
+    }
+}
+
+/// Converte o conteúdo de um arquivo VDF/ACF em uma árvore de valores.
+/// A raiz retornada é sempre um objeto com as chaves de nível superior.
+pub fn parse(input: &str) -> Result<VdfValue, String> {
+    let tokens = tokenize(input)?;
+    let mut iter = tokens.into_iter();
+    let entries = parse_entries(&mut iter, false)?;
+    Ok(VdfValue::Obj(entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIBRARY_FOLDERS: &str = include_str!("../../tests/fixtures/steam/libraryfolders.vdf");
+    const LEGACY_LIBRARY_FOLDERS: &str =
+        include_str!("../../tests/fixtures/steam/libraryfolders_legacy.vdf");
+    const APP_MANIFEST: &str = include_str!("../../tests/fixtures/steam/appmanifest_570.acf");
+    const BROKEN_MANIFEST: &str = include_str!("../../tests/fixtures/steam/appmanifest_broken.acf");
+
+    #[test]
+    fn parses_library_folders() {
+        let root = parse(LIBRARY_FOLDERS).unwrap();
+        let libraries = root.get("libraryfolders").unwrap();
+
+        assert_eq!(libraries.entries().len(), 2);
+        assert_eq!(
+            libraries.get("1").and_then(|l| l.get_str("path")),
+            Some("/mnt/games/SteamLibrary")
+        );
+        assert_eq!(
+            libraries
+                .get("0")
+                .and_then(|l| l.get("apps"))
+                .and_then(|apps| apps.get_str("570")),
+            Some("38211233123")
+        );
+    }
+
+    #[test]
+    fn keys_are_case_insensitive() {
+        let root = parse(LEGACY_LIBRARY_FOLDERS).unwrap();
+        let libraries = root.get("libraryfolders").unwrap();
+
+        // Barras invertidas escapadas viram uma só
+        assert_eq!(libraries.get_str("1"), Some(r"D:\SteamLibrary"));
+        assert_eq!(libraries.get_str("timenextstatsreport"), Some("1590000000"));
+    }
+
+    #[test]
+    fn parses_app_manifest() {
+        let root = parse(APP_MANIFEST).unwrap();
+        let state = root.get("AppState").unwrap();
+
+        assert_eq!(state.get_str("appid"), Some("570"));
+        assert_eq!(state.get_str("installdir"), Some("dota 2 beta"));
+        assert_eq!(
+            state
+                .get("InstalledDepots")
+                .and_then(|d| d.get("373301"))
+                .and_then(|d| d.get_str("size")),
+            Some("1290431829")
+        );
+        assert!(state.get_str("InstalledDepots").is_none());
+    }
+
+    #[test]
+    fn handles_unquoted_tokens_and_conditionals() {
+        let root = parse("root { key value [$WIN32] other \"x\" }").unwrap();
+        let obj = root.get("root").unwrap();
+
+        assert_eq!(obj.get_str("key"), Some("value"));
+        assert_eq!(obj.get_str("other"), Some("x"));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert!(parse(BROKEN_MANIFEST).is_err());
+        assert!(parse("\"a\" { \"b\" \"c\"").is_err());
+        assert!(parse("\"a\" \"b\" }").is_err());
+        assert!(parse("\"a\"").is_err());
+        assert!(parse("{ \"a\" \"b\" }").is_err());
+    }
+}
@@ -15,6 +15,7 @@ import {
 import { Game } from "../types";
 import { useGameDetails } from "../hooks/useGameDetails";
 import { cn } from "@/lib/utils";
+import { coverSrc } from "@/utils/covers";
 
 interface GameDetailsModalProps {
   game: Game | null;
@@ -35,6 +36,8 @@ export default function GameDetailsModal({
 
   if (!game) return null;
 
+  const cover = coverSrc(game);
+
   return (
     <Dialog open={isOpen} onOpenChange={onClose}>
       {/* Modal: Ocupa quase toda a tela em mobile (95vh) para dar espaço ao conteúdo */}
@@ -53,10 +56,10 @@ export default function GameDetailsModal({
         {/* HEADER (Banner) - Altura responsiva (h-48 mobile / h-64 desktop) */}
         <div className="relative shrink-0 h-48 md:h-64 w-full bg-muted overflow-hidden">
           {/* Background Image */}
-          {game.cover_url && (
+          {cover && (
             <div
               className="absolute inset-0 bg-cover bg-center blur-2xl opacity-50 scale-110"
-              style={{ backgroundImage: `url(${game.cover_url})` }}
+              style={{ backgroundImage: `url(${cover})` }}
             />
           )}
           <div className="absolute inset-0 bg-gradient-to-b from-black/20 via-transparent to-background" />
@@ -65,9 +68,9 @@ export default function GameDetailsModal({
           <div className="absolute bottom-0 left-0 w-full p-6 md:p-8 flex items-end gap-6 md:gap-8 z-10">
             {/* Capa (Box Art) - Escondida em mobile muito pequeno, visível em sm+ */}
             <div className="relative shrink-0 w-32 h-48 md:w-40 md:h-60 rounded-lg shadow-2xl overflow-hidden border-4 border-background -mb-12 shadow-black/50 hidden sm:block">
-              {game.cover_url ? (
+              {cover ? (
                 <img
-                  src={game.cover_url}
+                  src={cover}
                   alt={game.name}
                   className="w-full h-full object-cover"
                 />
@@ -188,7 +191,7 @@ export default function GameDetailsModal({
                         <Building2 size={14} /> Dev
                       </span>
                       <span className="font-medium truncate max-w-[120px] text-right text-xs md:text-sm">
-                        {details.developers[0].name}
+                        {details.developers[0]}
                       </span>
                     </div>
                   )}
@@ -204,11 +207,11 @@ export default function GameDetailsModal({
                   <div className="flex flex-wrap gap-1.5 md:gap-2">
                     {details.tags.slice(0, 8).map((tag) => (
                       <Badge
-                        key={tag.id}
+                        key={tag}
                         variant="secondary"
                         className="font-normal bg-secondary/50 hover:bg-secondary text-[10px] md:text-xs"
                       >
-                        {tag.name}
+                        {tag}
                       </Badge>
                     ))}
                   </div>
@@ -275,7 +278,7 @@ export default function GameDetailsModal({
                 </div>
               ) : details ? (
                 <div className="prose prose-sm md:prose-lg dark:prose-invert max-w-none text-foreground/80 leading-relaxed whitespace-pre-line">
-                  {details.description_raw ||
+                  {details.description ||
                     "Nenhuma descrição fornecida pelo desenvolvedor."}
                 </div>
               ) : (
@@ -9,6 +9,7 @@ import {
 import { Button } from "@/components/ui/button";
 import { Game } from "../types";
 import { launchGame } from "@/utils/launcher.ts";
+import { coverSrc } from "@/utils/covers";
 
 interface PlaylistItemProps {
   game: Game;
@@ -78,9 +79,9 @@ export default function PlaylistItem({
 
       {/* Capa Pequena */}
       <div className="relative h-16 w-12 shrink-0 rounded overflow-hidden bg-muted cursor-pointer group/img shadow-sm">
-        {game.cover_url ? (
+        {coverSrc(game, true) ? (
           <img
-            src={game.cover_url}
+            src={coverSrc(game, true) ?? undefined}
             alt=""
             className="h-full w-full object-cover"
             draggable={false}
@@ -16,6 +16,10 @@ export const ERROR_MESSAGES = {
     "Arquivo inválido. Selecione um backup válido do Playlite.",
   BACKUP_INCOMPATIBLE_VERSION:
     "Versão do backup não é compatível com esta versão do app.",
+  BACKUP_PASSWORD_REQUIRED: "Este backup é protegido por senha. Informe a senha.",
+  BACKUP_WRONG_PASSWORD: "Senha do backup incorreta.",
+  BACKUP_TAMPERED:
+    "O arquivo de backup foi alterado ou está danificado e não pode ser restaurado.",
 
   // Erros de sistema
   MUTEX_LOCK_ERROR: "Tente novamente em alguns segundos.",
@@ -76,6 +80,18 @@ export function parseBackupError(error: unknown): string {
     return ERROR_MESSAGES.BACKUP_INVALID_FILE;
   }
 
+  if (errorStr.includes("Backup protegido por senha")) {
+    return ERROR_MESSAGES.BACKUP_PASSWORD_REQUIRED;
+  }
+
+  if (errorStr.includes("Senha do backup incorreta")) {
+    return ERROR_MESSAGES.BACKUP_WRONG_PASSWORD;
+  }
+
+  if (errorStr.includes("Arquivo de backup adulterado")) {
+    return ERROR_MESSAGES.BACKUP_TAMPERED;
+  }
+
   if (errorStr.includes("Versão de backup incompatível")) {
     return ERROR_MESSAGES.BACKUP_INCOMPATIBLE_VERSION;
   }
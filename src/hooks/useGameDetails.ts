@@ -1,9 +1,9 @@
 import { useState, useEffect } from "react";
-import { Game, GameDetails, GamePlatformLink } from "../types";
+import { Game, GameMetadata, GamePlatformLink } from "../types";
 import { detailsService } from "../services/detailsService";
 
 export function useGameDetails(selectedGame: Game | null, allGames: Game[]) {
-  const [details, setDetails] = useState<GameDetails | null>(null);
+  const [details, setDetails] = useState<GameMetadata | null>(null);
   const [loading, setLoading] = useState(false);
   const [siblings, setSiblings] = useState<GamePlatformLink[]>([]);
 
@@ -24,11 +24,11 @@ export function useGameDetails(selectedGame: Game | null, allGames: Game[]) {
 
     setSiblings(related);
 
-    // Busca dados na nuvem
+    // Metadados salvos (buscados na RAWG apenas na primeira vez)
     const fetchRemote = async () => {
       setLoading(true);
       try {
-        const data = await detailsService.getGameDetails(selectedGame.name);
+        const data = await detailsService.getGameMetadata(selectedGame.id);
         setDetails(data);
       } catch (err) {
         console.error(err);
@@ -1,6 +1,7 @@
 import { useState, useEffect, useCallback } from "react";
 import { Game } from "../types";
 import { librariesService } from "../services/librariesService.ts";
+import { snapshotsService } from "../services/snapshotsService.ts";
 
 export function useLibraries() {
   const [games, setGames] = useState<Game[]>([]);
@@ -20,6 +21,19 @@ export function useLibraries() {
   useEffect(() => {
     const init = async () => {
       try {
+        // Banco corrompido: oferece a cópia íntegra mais recente antes de continuar
+        const health = await snapshotsService.checkHealth();
+        const snapshot = health.latest_good_snapshot;
+        if (
+          !health.ok &&
+          snapshot &&
+          confirm(
+            `O banco de dados está corrompido. Restaurar a cópia de ${new Date(snapshot.created_at).toLocaleString()}?`
+          )
+        ) {
+          await snapshotsService.restore(snapshot.file_name);
+        }
+
         await librariesService.initDb();
         await refreshGames();
       } catch (error) {
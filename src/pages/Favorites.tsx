@@ -16,6 +16,7 @@ import {
   DropdownMenuTrigger,
 } from "@/components/ui/dropdown-menu";
 import { launchGame } from "../utils/launcher";
+import { coverSrc } from "../utils/covers";
 // Importar Hooks
 import { usePlaylist } from "../hooks/usePlaylist";
 import { toast } from "sonner";
@@ -83,7 +84,7 @@ export default function Favorites({
               <div key={game.id} className="relative group">
                 <StandardGameCard
                   title={game.name}
-                  coverUrl={game.cover_url}
+                  coverUrl={coverSrc(game, true)}
                   subtitle={game.genre || "Sem gênero"}
                   rating={game.rating || undefined}
                   onClick={() => actions.onGameClick(game)}
@@ -18,6 +18,7 @@ import { useHome } from "../hooks/useHome";
 import { Button } from "@/components/ui/button";
 import { openExternalLink } from "../utils/navigation";
 import { launchGame } from "../utils/launcher";
+import { coverSrc } from "../utils/covers";
 import { formatTime } from "../utils/formatTime";
 import { Game, RawgGame, UserProfile } from "../types";
 import StandardGameCard from "@/components/StandardGameCard";
@@ -220,7 +221,7 @@ export default function Home({
                 <StandardGameCard
                   key={game.id}
                   title={game.name}
-                  coverUrl={game.cover_url}
+                  coverUrl={coverSrc(game, true)}
                   subtitle={`${formatTime(game.playtime)} jogadas`}
                   onClick={() => onGameClick(game)}
                   // Ação de Play no Hover
@@ -264,7 +265,7 @@ export default function Home({
                 <StandardGameCard
                   key={game.id}
                   title={game.name}
-                  coverUrl={game.cover_url}
+                  coverUrl={coverSrc(game, true)}
                   subtitle={game.genre?.split(",")[0]}
                   badge="Recomendado"
                   onClick={() => launchGame(game)}
@@ -17,6 +17,7 @@ import {
   DropdownMenuTrigger,
 } from "@/components/ui/dropdown-menu";
 import { launchGame } from "../utils/launcher";
+import { coverSrc } from "../utils/covers";
 import { usePlaylist } from "../hooks/usePlaylist";
 import { toast } from "sonner";
 import { ActionButton } from "@/components/ActionButton.tsx";
@@ -80,7 +81,7 @@ export default function Libraries({
               <div key={game.id} className="relative group">
                 <StandardGameCard
                   title={game.name}
-                  coverUrl={game.cover_url}
+                  coverUrl={coverSrc(game, true)}
                   subtitle={game.genre || "Sem gênero"}
                   rating={game.rating || undefined}
                   onClick={() => actions.onGameClick(game)}
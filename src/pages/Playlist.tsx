@@ -6,6 +6,7 @@ import PlaylistItem from "../components/PlaylistItem";
 import StandardGameCard from "@/components/StandardGameCard";
 import { Button } from "@/components/ui/button";
 import { launchGame } from "../utils/launcher";
+import { coverSrc } from "../utils/covers";
 import { toast } from "sonner";
 import {
   DragDropContext,
@@ -154,7 +155,7 @@ export default function Playlist({
               <div key={game.id} className="relative group">
                 <StandardGameCard
                   title={game.name}
-                  coverUrl={game.cover_url}
+                  coverUrl={coverSrc(game, true)}
                   className="text-xs"
                   actions={
                     <Button
@@ -0,0 +1,17 @@
+import { invoke } from "@tauri-apps/api/core";
+import { ArtworkKind, GameArtwork } from "../types";
+
+export const artworkService = {
+  // Copia uma imagem local como capa, banner ou logo do jogo
+  importArtwork: async (gameId: string, kind: ArtworkKind, filePath: string): Promise<GameArtwork> => {
+    return await invoke<GameArtwork>("import_game_artwork", { gameId, kind, filePath });
+  },
+
+  getArtwork: async (gameId: string): Promise<GameArtwork[]> => {
+    return await invoke<GameArtwork[]>("get_game_artwork", { gameId });
+  },
+
+  removeArtwork: async (gameId: string, kind: ArtworkKind): Promise<void> => {
+    return await invoke("remove_game_artwork", { gameId, kind });
+  },
+};
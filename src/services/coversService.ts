@@ -0,0 +1,25 @@
+import { invoke } from "@tauri-apps/api/core";
+import { CoverCacheStats, CoverCacheSummary } from "../types";
+
+export const coversService = {
+  // Baixa as capas que ainda não estão no cache (opcionalmente só de alguns jogos)
+  cacheCovers: async (gameIds?: string[]): Promise<CoverCacheSummary> => {
+    return await invoke<CoverCacheSummary>("cache_covers", { gameIds });
+  },
+
+  redownloadAll: async (): Promise<CoverCacheSummary> => {
+    return await invoke<CoverCacheSummary>("redownload_all_covers");
+  },
+
+  getStats: async (): Promise<CoverCacheStats> => {
+    return await invoke<CoverCacheStats>("get_cover_cache_stats");
+  },
+
+  setLimit: async (maxMb: number): Promise<number> => {
+    return await invoke<number>("set_cover_cache_limit", { maxMb });
+  },
+
+  clear: async (): Promise<number> => {
+    return await invoke<number>("clear_cover_cache");
+  },
+};
@@ -1,16 +1,37 @@
 import { invoke } from "@tauri-apps/api/core";
-import { GameDetails } from "../types";
+import { GameDetails, GameMetadata, RawgMatch } from "../types";
 
 export const detailsService = {
-  // Busca detalhes extras na API (RAWG) baseado no nome
-  getGameDetails: async (gameName: string): Promise<GameDetails | null> => {
+  // Busca detalhes extras na API (RAWG); com o ID do jogo, a associação encontrada é salva
+  getGameDetails: async (gameName: string, gameId?: string): Promise<GameDetails | null> => {
     try {
       return await invoke<GameDetails>("fetch_game_details", {
         query: gameName,
+        gameId,
       });
     } catch (error) {
       console.error("Erro ao buscar detalhes:", error);
       return null;
     }
   },
+
+  // Metadados salvos do jogo (busca na RAWG apenas na primeira vez ou com refresh)
+  getGameMetadata: async (gameId: string, refresh = false): Promise<GameMetadata | null> => {
+    try {
+      return await invoke<GameMetadata | null>("get_game_metadata", { gameId, refresh });
+    } catch (error) {
+      console.error("Erro ao buscar metadados:", error);
+      return null;
+    }
+  },
+
+  // Candidatos da RAWG para corrigir a associação manualmente (com o ID do jogo, o ano
+  // de lançamento já conhecido ajuda a ordenar)
+  searchMatches: async (query: string, platform?: string, gameId?: string): Promise<RawgMatch[]> => {
+    return await invoke<RawgMatch[]>("search_rawg_matches", { query, platform, gameId });
+  },
+
+  setRawgId: async (gameId: string, rawgId: number | null): Promise<void> => {
+    await invoke("set_game_rawg_id", { gameId, rawgId });
+  },
 };
@@ -0,0 +1,38 @@
+import { invoke } from "@tauri-apps/api/core";
+import { JournalEntry, JournalSettings, TrashItem, UndoResult } from "../types";
+
+export const journalService = {
+  // Mais recentes primeiro
+  getEntries: async (limit?: number, offset?: number): Promise<JournalEntry[]> => {
+    return await invoke<JournalEntry[]>("get_journal", { limit, offset });
+  },
+
+  // Desfaz a última operação (uma importação ou restauração inteira, por exemplo)
+  undoLast: async (): Promise<UndoResult> => {
+    return await invoke<UndoResult>("undo_last");
+  },
+
+  listTrash: async (): Promise<TrashItem[]> => {
+    return await invoke<TrashItem[]>("list_trash");
+  },
+
+  restoreFromTrash: async (entryId: number): Promise<number> => {
+    return await invoke<number>("restore_from_trash", { entryId });
+  },
+
+  emptyTrash: async (): Promise<number> => {
+    return await invoke<number>("empty_trash");
+  },
+
+  exportJournal: async (filePath: string): Promise<number> => {
+    return await invoke<number>("export_journal", { filePath });
+  },
+
+  getSettings: async (): Promise<JournalSettings> => {
+    return await invoke<JournalSettings>("get_journal_settings");
+  },
+
+  setSettings: async (settings: JournalSettings): Promise<void> => {
+    return await invoke("set_journal_settings", { settings });
+  },
+};
@@ -1,13 +1,17 @@
 import { invoke } from "@tauri-apps/api/core";
-import { Game } from "../types";
+import { Game, LibraryFilters } from "../types";
 
 export const librariesService = {
   initDb: async (): Promise<void> => {
     await invoke("init_db");
   },
 
-  getGames: async (): Promise<Game[]> => {
-    return await invoke<Game[]>("get_games");
+  getGames: async (filters: LibraryFilters = {}): Promise<Game[]> => {
+    return await invoke<Game[]>("get_games", { ...filters });
+  },
+
+  getDevelopers: async (): Promise<string[]> => {
+    return await invoke<string[]>("get_metadata_developers");
   },
 
   addGame: async (game: {
@@ -1,6 +1,6 @@
 import {invoke} from "@tauri-apps/api/core";
 import {open, save} from "@tauri-apps/plugin-dialog";
-import {KeysBatch, ImportSummary} from "../types";
+import {KeysBatch, ImportSummary, RestoreMode, RestorePreview} from "../types";
 import {ERROR_MESSAGES, parseBackupError} from "@/constants/errorMessages.ts";
 
 export const settingsService = {
@@ -27,13 +27,14 @@ export const settingsService = {
         return await invoke<ImportSummary>("enrich_library");
     },
 
-    exportDatabase: async (): Promise<string> => {
+    // Com senha, o arquivo é encriptado e pode incluir as chaves de API
+    exportDatabase: async (options: {passphrase?: string; includeSecrets?: boolean} = {}): Promise<string> => {
         try {
             const filePath = await save({
-                defaultPath: `playlite-backup-${new Date().toISOString().split('T')[0]}.json`,
+                defaultPath: `playlite-backup-${new Date().toISOString().split('T')[0]}.zip`,
                 filters: [{
-                    name: 'JSON',
-                    extensions: ['json']
+                    name: 'Backup',
+                    extensions: ['zip']
                 }]
             });
 
@@ -41,7 +42,11 @@ export const settingsService = {
                 throw new Error(ERROR_MESSAGES.CANCELLED);
             }
 
-            await invoke("export_database", {filePath});
+            await invoke("export_database", {
+                filePath,
+                passphrase: options.passphrase || null,
+                includeSecrets: options.includeSecrets ?? false,
+            });
             return "Backup exportado com sucesso!";
         } catch (error: any) {
             if (error.message === ERROR_MESSAGES.CANCELLED) {
@@ -58,13 +63,15 @@ export const settingsService = {
         }
     },
 
-    importDatabase: async (): Promise<string> => {
+    // Sem modo, o backup é gravado por cima dos dados locais ("overwrite")
+    importDatabase: async (mode?: RestoreMode, passphrase?: string): Promise<string> => {
         try {
             const selected = await open({
                 multiple: false,
+                // .zip (v2) ou .json (backups antigos, v1)
                 filters: [{
-                    name: 'JSON',
-                    extensions: ['json']
+                    name: 'Backup',
+                    extensions: ['zip', 'json']
                 }]
             });
 
@@ -73,7 +80,7 @@ export const settingsService = {
             }
 
             const filePath = selected as string;
-            return await invoke<string>("import_database", {filePath});
+            return await invoke<string>("import_database", {filePath, mode, passphrase: passphrase || null});
         } catch (error: any) {
             if (error.message === ERROR_MESSAGES.CANCELLED) {
                 throw new Error(ERROR_MESSAGES.CANCELLED);
@@ -88,4 +95,9 @@ export const settingsService = {
             throw new Error(friendlyError);
         }
     },
+
+    // O que cada modo de restauração faria com o arquivo, sem gravar nada
+    previewImport: async (filePath: string, passphrase?: string): Promise<RestorePreview> => {
+        return await invoke<RestorePreview>("preview_import", {filePath, passphrase: passphrase || null});
+    },
 };
\ No newline at end of file
@@ -0,0 +1,29 @@
+import { invoke } from "@tauri-apps/api/core";
+import { DatabaseHealth, SnapshotInfo, SnapshotSettings } from "../types";
+
+export const snapshotsService = {
+  list: async (): Promise<SnapshotInfo[]> => {
+    return await invoke<SnapshotInfo[]>("list_snapshots");
+  },
+
+  create: async (): Promise<SnapshotInfo> => {
+    return await invoke<SnapshotInfo>("create_snapshot");
+  },
+
+  // Substitui o banco atual pela cópia (o atual é copiado antes)
+  restore: async (fileName: string): Promise<string> => {
+    return await invoke<string>("restore_snapshot", { fileName });
+  },
+
+  checkHealth: async (): Promise<DatabaseHealth> => {
+    return await invoke<DatabaseHealth>("check_database_health");
+  },
+
+  getSettings: async (): Promise<SnapshotSettings> => {
+    return await invoke<SnapshotSettings>("get_snapshot_settings");
+  },
+
+  setSettings: async (settings: SnapshotSettings): Promise<void> => {
+    return await invoke("set_snapshot_settings", { settings });
+  },
+};
@@ -0,0 +1,27 @@
+import { invoke } from "@tauri-apps/api/core";
+import { SyncReport, SyncSettings, SyncTransport } from "../types";
+
+export const syncService = {
+  getSettings: async (): Promise<SyncSettings> => {
+    return await invoke<SyncSettings>("get_sync_settings");
+  },
+
+  // transport null desativa; token vazio remove o token salvo, undefined mantém
+  setSettings: async (
+    transport: SyncTransport | null,
+    folderPath?: string,
+    serverUrl?: string,
+    token?: string,
+  ): Promise<SyncSettings> => {
+    return await invoke<SyncSettings>("set_sync_settings", {
+      transport,
+      folderPath,
+      serverUrl,
+      token,
+    });
+  },
+
+  syncNow: async (): Promise<SyncReport> => {
+    return await invoke<SyncReport>("sync_now");
+  },
+};
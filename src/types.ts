@@ -7,6 +7,79 @@ export interface Game {
   playtime: number;
   rating?: number;
   favorite: boolean;
+  installed?: boolean;
+  last_played?: string | null;
+  /** Tipo do app na Steam ("game", "dlc", "music"...) */
+  app_type?: string | null;
+  /** Jogo base quando o item é uma DLC */
+  parent_game_id?: string | null;
+  /** AppID Steam do jogo base (DLCs cujo jogo base ainda não está na biblioteca) */
+  parent_app_id?: number | null;
+  /** ID do jogo na RAWG */
+  rawg_id?: number | null;
+  platforms?: string[];
+  /** Caminhos da capa e da miniatura no cache local */
+  local_cover?: string | null;
+  local_thumb?: string | null;
+  /** Banner e logo enviados pelo usuário */
+  hero_path?: string | null;
+  logo_path?: string | null;
+}
+
+export type ArtworkKind = "cover" | "hero" | "logo";
+
+export interface GameArtwork {
+  game_id: string;
+  kind: ArtworkKind;
+  file_path: string;
+  thumb_path?: string | null;
+  width: number;
+  height: number;
+  size_bytes: number;
+  created_at?: string | null;
+}
+
+export interface GameOwnership {
+  id: number;
+  game_id: string;
+  platform: string;
+  external_id?: string | null;
+  purchase_date?: string | null;
+  price_paid?: number | null;
+  currency?: string | null;
+  edition?: string | null;
+}
+
+export interface CurrencyRate {
+  currency: string;
+  rate: number;
+  updated_at?: string | null;
+}
+
+export interface SpendingGroup {
+  key: string;
+  total: number;
+  purchases: number;
+}
+
+export interface GameCost {
+  game_id: string;
+  name: string;
+  spent: number;
+  hours: number;
+  cost_per_hour: number | null;
+}
+
+export interface SpendingReport {
+  base_currency: string;
+  total_spent: number;
+  by_year: SpendingGroup[];
+  by_platform: SpendingGroup[];
+  cost_per_hour: GameCost[];
+  average_cost_per_hour: number | null;
+  never_played_spent: number;
+  never_played: GameCost[];
+  missing_rates: string[];
 }
 
 export interface GameActions {
@@ -37,6 +110,54 @@ export interface WishlistGame {
   localized_currency: string | null;
   steam_app_id: number | null;
   added_at: string;
+  archived_at?: string | null;
+  purchased_game_id?: string | null;
+  source?: string | null;
+  steam_priority?: number | null;
+  priority?: number | null;
+  notes?: string | null;
+  tags?: string[];
+  sort_position?: number | null;
+  discount_percent?: number | null;
+  release_date?: string | null;
+  release_date_text?: string | null;
+  coming_soon?: boolean;
+  app_type?: string | null;
+  parent_app_id?: number | null;
+}
+
+export interface WishlistReleaseEvent {
+  id: string;
+  name: string;
+  kind: "released" | "date_changed";
+  previous_date: string | null;
+  release_date: string | null;
+}
+
+export type WishlistSort =
+  | "manual"
+  | "added"
+  | "discount"
+  | "price"
+  | "lowest_ratio"
+  | "priority";
+
+export interface WishlistImportSummary {
+  total: number;
+  added: number;
+  updated: number;
+  removed: number;
+  archived: number;
+  unnamed: number;
+  message: string;
+}
+
+export interface ClassifySummary {
+  games_classified: number;
+  wishlist_classified: number;
+  dlcs_linked: number;
+  errors: number;
+  message: string;
 }
 
 export interface KeysBatch {
@@ -45,6 +166,119 @@ export interface KeysBatch {
   rawg_api_key: string;
 }
 
+export interface SnapshotInfo {
+  file_name: string;
+  path: string;
+  created_at: string;
+  size_bytes: number;
+}
+
+export interface SnapshotSettings {
+  interval_hours: number;
+  keep_daily: number;
+  keep_weekly: number;
+}
+
+export type SyncTransport = "folder" | "http";
+
+export interface SyncSettings {
+  device_id: string;
+  transport?: SyncTransport | null;
+  folder_path?: string | null;
+  server_url?: string | null;
+  has_token: boolean;
+  last_sync?: string | null;
+}
+
+export interface SyncReport {
+  pushed: number;
+  received: number;
+  applied: number;
+  deleted: number;
+  kept_local: number;
+  conflicts: number;
+}
+
+export type JournalOperation = "insert" | "update" | "delete";
+
+export interface JournalEntry {
+  id: number;
+  batch_id?: number | null;
+  batch_operation?: string | null;
+  batch_description?: string | null;
+  table_name: string;
+  operation: JournalOperation;
+  row_key: unknown[];
+  before?: Record<string, unknown> | null;
+  after?: Record<string, unknown> | null;
+  created_at: string;
+  undone: boolean;
+}
+
+export interface TrashItem {
+  entry_id: number;
+  table_name: "games" | "wishlist";
+  item_id: string;
+  name?: string | null;
+  deleted_at: string;
+  operation?: string | null;
+}
+
+export interface UndoResult {
+  operation: string;
+  description?: string | null;
+  changes: number;
+}
+
+export interface JournalSettings {
+  retention_days: number;
+}
+
+export interface DatabaseHealth {
+  ok: boolean;
+  problem?: string | null;
+  latest_good_snapshot?: SnapshotInfo | null;
+}
+
+export type RestoreMode = "replace" | "merge" | "additive" | "overwrite";
+
+export interface TableChanges {
+  table: string;
+  added: number;
+  updated: number;
+  deleted: number;
+  unchanged: number;
+  skipped: number;
+}
+
+export interface RestorePreview {
+  version: number;
+  created_at: string;
+  /** Chaves de API incluídas (só em backups com senha) */
+  secrets: string[];
+  modes: {
+    mode: RestoreMode;
+    added: number;
+    updated: number;
+    deleted: number;
+    tables: TableChanges[];
+  }[];
+}
+
+/** Payload do evento `backup-progress` */
+export interface BackupProgress {
+  operation: "export" | "preview" | "import";
+  table: string;
+  done: number;
+  total: number;
+}
+
+/** Payload do evento `steam-classify-progress` */
+export interface SteamClassifyProgress {
+  done: number;
+  total: number;
+}
+
 export interface ImportSummary {
   success_count: number;
   error_count: number;
@@ -62,6 +296,73 @@ export interface GameDetails {
   publishers: { name: string }[];
 }
 
+export interface LibraryFilters {
+  gamesOnly?: boolean;
+  developer?: string;
+  publisher?: string;
+  minMetacritic?: number;
+}
+
+export interface GameMetadata {
+  game_id: string;
+  rawg_id?: number | null;
+  description?: string | null;
+  website?: string | null;
+  metacritic?: number | null;
+  release_date?: string | null;
+  background_image?: string | null;
+  developers: string[];
+  publishers: string[];
+  tags: string[];
+  /** Fonte de cada campo: "local", "steam" ou "rawg" */
+  sources: Record<string, string>;
+  updated_at?: string | null;
+}
+
+export type MetadataField =
+  | "genre"
+  | "cover_url"
+  | "description"
+  | "website"
+  | "metacritic"
+  | "release_date"
+  | "background_image"
+  | "developers"
+  | "publishers"
+  | "tags";
+
+export interface MetadataPriority {
+  default_order: string[];
+  fields: Partial<Record<MetadataField, string[]>>;
+}
+
+export interface RawgMatch {
+  id: number;
+  name: string;
+  released?: string | null;
+  background_image?: string | null;
+  platforms: string[];
+  /** Pontuação da busca (nome, ano e plataforma) */
+  score: number;
+}
+
+export interface CoverCacheSummary {
+  downloaded: number;
+  missing: number;
+  failed: number;
+  evicted: number;
+  total_bytes: number;
+  errors: string[];
+}
+
+export interface CoverCacheStats {
+  cached: number;
+  missing: number;
+  total_bytes: number;
+  max_bytes: number;
+  directory: string;
+}
+
 export interface GamePlatformLink {
   id: string;
   platform: string;
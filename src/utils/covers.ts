@@ -0,0 +1,19 @@
+import { convertFileSrc } from "@tauri-apps/api/core";
+import { Game } from "../types";
+
+/**
+ * Capa a exibir: a arte do usuário ou a versão do cache local quando existir, senão a URL remota.
+ * `thumb` usa a miniatura (grades e listas).
+ */
+export const coverSrc = (game: Game, thumb = false): string | null => {
+  const local = thumb ? game.local_thumb || game.local_cover : game.local_cover;
+  if (local) return convertFileSrc(local);
+  return game.cover_url || null;
+};
+
+/** Banner (hero) e logo enviados pelo usuário, quando existirem */
+export const heroSrc = (game: Game): string | null =>
+  game.hero_path ? convertFileSrc(game.hero_path) : null;
+
+export const logoSrc = (game: Game): string | null =>
+  game.logo_path ? convertFileSrc(game.logo_path) : null;